@@ -0,0 +1,139 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxSet;
+use zen::commands::{self, CommandContext};
+use zen::document::Document;
+use zen::editor::Position;
+use zen::mode::EditorMode;
+use zen::row::Row;
+use zen::terminal::ColorSupport;
+
+const TERMINAL_HEIGHT: usize = 60;
+
+const RUST_SNIPPET: &[&str] = &[
+    "/// Adds two numbers together.",
+    "pub fn add(left: usize, right: usize) -> usize {",
+    "    let result = left.saturating_add(right); // Never overflows",
+    "    println!(\"{} + {} = {}\", left, right, result);",
+    "    result",
+    "}",
+    "",
+];
+
+// Writes a generated Rust file with the given amount of lines to the temp directory.
+fn fixture(name: &str, lines: usize) -> PathBuf {
+    let path = env::temp_dir().join(name);
+    let contents: Vec<&str> = RUST_SNIPPET.iter().copied().cycle().take(lines).collect();
+    fs::write(&path, contents.join("\n")).expect("Failed to write benchmark fixture");
+    path
+}
+
+fn open(path: &Path) -> Document {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("Failed to build runtime");
+    runtime
+        .block_on(Document::open(path.to_str().unwrap()))
+        .expect("Failed to open benchmark fixture")
+}
+
+fn bench_highlight_large_file(c: &mut Criterion) {
+    let mut document = open(&fixture("zen_bench_highlight.rs", 10_000));
+
+    // Unchanged views aren't highlighted again, so scroll by a row each time to highlight the whole view.
+    let mut top = 0;
+    c.bench_function("bench_highlight_large_file", |b| {
+        b.iter(|| {
+            top = 1 - top;
+            document.highlight(
+                black_box(top..top + TERMINAL_HEIGHT),
+                ColorSupport::TrueColor,
+            )
+        });
+    });
+}
+
+fn bench_row_render(c: &mut Criterion) {
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let theme_set = ThemeSet::load_defaults();
+    let syntax = syntax_set.find_syntax_by_extension("rs").unwrap();
+    let mut highlighter = HighlightLines::new(syntax, &theme_set.themes["base16-ocean.dark"]);
+
+    let mut row = Row::from(RUST_SNIPPET[3]);
+    row.highlight(&syntax_set, &mut highlighter, ColorSupport::TrueColor)
+        .unwrap();
+
+    c.bench_function("bench_row_render", |b| b.iter(|| black_box(row.render())));
+}
+
+fn bench_cursor_move_down_large(c: &mut Criterion) {
+    let mut document = open(&fixture("zen_bench_cursor.rs", 10_000));
+    let mut ctx = CommandContext {
+        document: &mut document,
+        cursor_position: Position::default(),
+        view_height: TERMINAL_HEIGHT,
+        view_top: 0,
+        text_width: 80,
+        mode: EditorMode::Normal,
+    };
+
+    c.bench_function("bench_cursor_move_down_large", |b| {
+        b.iter(|| {
+            ctx.cursor_position = Position::default();
+            for _ in 0..10_000 {
+                ctx.cursor_position = commands::cursor::move_down(black_box(&ctx));
+            }
+        });
+    });
+}
+
+fn bench_document_insert_middle(c: &mut Criterion) {
+    let mut document = open(&fixture("zen_bench_insert.rs", 100_000));
+    let middle = Position {
+        x: 4,
+        y: document.len() / 2,
+    };
+
+    // Deleting the character again keeps the row from growing across iterations.
+    c.bench_function("bench_document_insert_middle", |b| {
+        b.iter(|| {
+            document.insert(black_box(&middle), black_box('x'));
+            document.delete(&middle);
+        });
+    });
+}
+
+// Inserts into a single 100k character row at positions spread by a fixed-seed LCG,
+// so every run edits the same places.
+fn bench_row_insert_long_line(c: &mut Criterion) {
+    let line = "abcdefghij".repeat(10_000);
+
+    c.bench_function("bench_row_insert_long_line", |b| {
+        b.iter(|| {
+            let mut row = Row::from(line.as_str());
+            let mut seed: u64 = 42;
+            for _ in 0..1_000 {
+                seed = seed
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                let at = (seed >> 33) as usize % (row.len() + 1);
+                row.insert(black_box(at), black_box('x'));
+            }
+            row
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_highlight_large_file,
+    bench_row_render,
+    bench_cursor_move_down_large,
+    bench_document_insert_middle,
+    bench_row_insert_long_line
+);
+criterion_main!(benches);
@@ -0,0 +1,96 @@
+//! Applies arbitrary sequences of edits to a `Document` and checks its invariants.
+//!
+//! Run with `cargo fuzz run document` from the repository root.
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use std::env;
+use std::process;
+use unicode_segmentation::UnicodeSegmentation;
+use zen::document::Document;
+use zen::editor::Position;
+
+#[derive(Arbitrary, Debug)]
+enum DocumentOperation {
+    Insert { x: u8, y: u8, c: char },
+    InsertNewline { x: u8, y: u8 },
+    Delete { x: u8, y: u8 },
+    Save,
+}
+
+fn position(x: u8, y: u8) -> Position {
+    Position {
+        x: usize::from(x),
+        y: usize::from(y),
+    }
+}
+
+fn contents(document: &Document) -> Vec<String> {
+    (0..document.len())
+        .filter_map(|y| document.row(y))
+        .map(|row| row.string.clone())
+        .collect()
+}
+
+fn check_rows(document: &Document) {
+    for y in 0..document.len() {
+        let row = document.row(y).expect("Row within len() is missing");
+        assert_eq!(
+            row.len(),
+            row.string.graphemes(true).count(),
+            "Row {} length is out of sync with its string {:?}",
+            y,
+            row.string
+        );
+    }
+}
+
+fuzz_target!(|operations: Vec<DocumentOperation>| {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("Failed to build runtime");
+    let path = env::temp_dir().join(format!("zen_fuzz_document_{}.txt", process::id()));
+    let mut document = Document::default();
+    document.file_name = Some(path.to_str().unwrap().to_string());
+
+    for operation in operations {
+        let len = document.len();
+
+        match operation {
+            DocumentOperation::Insert { x, y, c } => {
+                let at = position(x, y);
+                document.insert(&at, c);
+                if at.y <= len {
+                    assert!(document.is_dirty(), "Insert at {:?} didn't mark the document dirty", (x, y));
+                }
+            }
+            DocumentOperation::InsertNewline { x, y } => {
+                let at = position(x, y);
+                document.insert_newline(&at);
+                if at.y <= len {
+                    assert!(document.is_dirty(), "Newline at {:?} didn't mark the document dirty", (x, y));
+                }
+            }
+            DocumentOperation::Delete { x, y } => {
+                let at = position(x, y);
+                document.delete(&at);
+                if at.y < len {
+                    assert!(document.is_dirty(), "Delete at {:?} didn't mark the document dirty", (x, y));
+                }
+                assert!(document.len() <= len, "Delete grew the document");
+            }
+            DocumentOperation::Save => {
+                runtime
+                    .block_on(document.save())
+                    .expect("Failed to save the fuzzed document");
+                let reopened = runtime
+                    .block_on(Document::open(path.to_str().unwrap()))
+                    .expect("Failed to reopen");
+                assert_eq!(contents(&reopened), contents(&document), "Save + open changed the content");
+            }
+        }
+
+        check_rows(&document);
+    }
+});
@@ -0,0 +1,32 @@
+use crate::search::is_word_char;
+
+use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
+
+// The expansion of the word the text before the cursor ends in, along with how many graphemes
+// that word is, if it's an abbreviation in the table. It's called as a non-word character is
+// typed, and only the whole word counts: `theremin` isn't `the` followed by more.
+pub fn expand<'a>(
+    before_cursor: &str,
+    table: &'a HashMap<String, String>,
+) -> Option<(usize, &'a str)> {
+    let graphemes: Vec<(usize, &str)> = before_cursor.grapheme_indices(true).collect();
+    let len = graphemes
+        .iter()
+        .rev()
+        .take_while(|(_, grapheme)| grapheme.chars().next().is_some_and(is_word_char))
+        .count();
+    let (start, _) = graphemes.get(graphemes.len().checked_sub(len)?)?;
+    let expansion = table.get(&before_cursor[*start..])?;
+    Some((len, expansion.as_str()))
+}
+
+// The abbreviations as `:iabbrev` lists them, sorted by word.
+pub fn list(table: &HashMap<String, String>) -> Vec<String> {
+    let mut entries: Vec<(&String, &String)> = table.iter().collect();
+    entries.sort();
+    entries
+        .into_iter()
+        .map(|(word, expansion)| format!("i  {:<12} {}", word, expansion))
+        .collect()
+}
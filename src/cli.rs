@@ -0,0 +1,102 @@
+/// Usage text printed by `--help`.
+pub const USAGE: &str = "\
+Usage: zen [OPTIONS] [FILE]
+
+Options:
+  -h, --help       Print this help message and exit
+  -V, --version    Print the version and exit
+  -R, --readonly   Open the file read-only
+  -l, --line N     Open the file with the cursor on line N
+      --pipe CMD   Run the Ex command CMD on stdin and print the result to stdout;
+                   repeat it to run several commands in order
+";
+
+/// Options gathered from the command line.
+#[derive(Default)]
+pub struct LaunchOptions {
+    // File to open, if one was given.
+    pub file_name: Option<String>,
+
+    // Prevents the document from being edited or saved.
+    pub readonly: bool,
+
+    // 1-based line the cursor should start on.
+    pub line: Option<usize>,
+}
+
+/// What the binary should do after parsing its arguments.
+pub enum Action {
+    Run(LaunchOptions),
+    // Ex commands to run over stdin, in order, for `--pipe`.
+    Pipe(Vec<String>),
+    Help,
+    Version,
+}
+
+// Parses the arguments following the program name.
+// Help and version short-circuit so they never touch the terminal.
+pub fn parse<I>(arguments: I) -> Result<Action, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = LaunchOptions::default();
+    let mut arguments = arguments.into_iter();
+    let mut only_files = false;
+    let mut pipe = Vec::new();
+
+    while let Some(argument) = arguments.next() {
+        if only_files || !argument.starts_with('-') || argument == "-" {
+            if options.file_name.is_none() {
+                options.file_name = Some(argument);
+            }
+            continue;
+        }
+
+        match argument.as_str() {
+            "--" => only_files = true,
+            "-h" | "--help" => return Ok(Action::Help),
+            "-V" | "--version" => return Ok(Action::Version),
+            "-R" | "--readonly" => options.readonly = true,
+            "-l" | "--line" => {
+                let value = arguments
+                    .next()
+                    .ok_or_else(|| format!("'{}' requires a line number", argument))?;
+                options.line = Some(parse_line(&value)?);
+            }
+            "--pipe" => {
+                let command = arguments
+                    .next()
+                    .ok_or_else(|| format!("'{}' requires a command", argument))?;
+                pipe.push(command);
+            }
+            _ => {
+                if let Some(value) = argument.strip_prefix("--line=") {
+                    options.line = Some(parse_line(value)?);
+                } else if let Some(command) = argument.strip_prefix("--pipe=") {
+                    pipe.push(command.to_string());
+                } else {
+                    return Err(format!("unknown option '{}'", argument));
+                }
+            }
+        }
+    }
+
+    if pipe.is_empty() {
+        return Ok(Action::Run(options));
+    }
+    // Pipe mode reads stdin, so there's no file to open.
+    match options.file_name {
+        Some(file_name) => Err(format!(
+            "'--pipe' reads stdin, but a file was given: '{}'",
+            file_name
+        )),
+        None => Ok(Action::Pipe(pipe)),
+    }
+}
+
+fn parse_line(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(line) if line > 0 => Ok(line),
+        _ => Err(format!("invalid line number '{}'", value)),
+    }
+}
@@ -0,0 +1,33 @@
+use std::process::Stdio;
+use tokio::process::Command;
+
+// Tools that print the system clipboard, tried in order: Wayland, X11, then macOS.
+const PASTE_COMMANDS: [&[&str]; 4] = [
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-out"],
+    &["xsel", "--clipboard", "--output"],
+    &["pbpaste"],
+];
+
+// The text on the system clipboard, from the first of the tools that's installed and succeeds.
+// None when none does, e.g. over SSH without a display.
+pub async fn paste() -> Option<String> {
+    for command in PASTE_COMMANDS {
+        let Some((program, args)) = command.split_first() else {
+            continue;
+        };
+        let output = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await;
+        if let Ok(output) = output {
+            if output.status.success() {
+                return Some(String::from_utf8_lossy(&output.stdout).into_owned());
+            }
+        }
+    }
+    None
+}
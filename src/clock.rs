@@ -0,0 +1,48 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Source of the current time, so time-based behavior (like status message expiry)
+/// can be driven deterministically in tests.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// The real clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time.
+#[derive(Clone)]
+pub struct MockClock {
+    now: Rc<Cell<Instant>>,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            now: Rc::new(Cell::new(Instant::now())),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
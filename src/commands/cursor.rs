@@ -1,52 +1,63 @@
-use crate::editor::{Editor, Position};
+use super::CommandContext;
+use crate::document::Document;
+use crate::editor::{Position, SearchDirection};
+use crate::mode::EditorMode;
+use crate::motion::Motion;
+use crate::row::Row;
 
 use std::cmp;
 
-pub fn move_up(editor: &mut Editor) {
-    let Position { mut y, mut x } = get_cursor_position(editor);
+pub fn move_up(ctx: &CommandContext) -> Position {
+    let Position { mut y, mut x } = get_cursor_position(ctx);
 
     y = y.saturating_sub(1);
 
-    editor.cursor_position = Position { x, y }
+    Position { x, y }
 }
 
-pub fn move_down(editor: &mut Editor) {
-    let Position { mut y, mut x } = get_cursor_position(editor);
-    let height = editor.document.len();
+pub fn move_down(ctx: &CommandContext) -> Position {
+    let Position { mut y, mut x } = get_cursor_position(ctx);
+    let height = ctx.document.len();
 
     if y < height {
         y = y.saturating_add(1);
     }
 
-    editor.cursor_position = Position { x, y }
+    Position { x, y }
 }
 
-pub fn move_left(editor: &mut Editor) {
-    let Position { mut y, mut x } = get_cursor_position(editor);
+pub fn move_left(ctx: &CommandContext) -> Position {
+    let Position { mut y, mut x } = get_cursor_position(ctx);
+
+    // Hex dumps move a byte at a time.
+    if let Some(view) = ctx.document.hex() {
+        return view.step(&Position { x, y }, SearchDirection::Backward);
+    }
 
     if x > 0 {
         x -= 1;
     } else if y > 0 {
         y -= 1;
-        if let Some(row) = editor.document.row(y) {
+        if let Some(row) = ctx.document.row(y) {
             x = row.len();
         } else {
             x = 0;
         }
     }
 
-    editor.cursor_position = Position { x, y }
+    Position { x, y }
 }
 
-pub fn move_right(editor: &mut Editor) {
-    let Position { mut y, mut x } = get_cursor_position(editor);
+pub fn move_right(ctx: &CommandContext) -> Position {
+    let Position { mut y, mut x } = get_cursor_position(ctx);
 
-    let height = editor.document.len();
-    let mut width = if let Some(row) = editor.document.row(y) {
-        row.len()
-    } else {
-        0
-    };
+    if let Some(view) = ctx.document.hex() {
+        return view.step(&Position { x, y }, SearchDirection::Forward);
+    }
+
+    let height = ctx.document.len();
+    // The last column the mode lets the cursor take on the row.
+    let width = clamp(ctx.document, &Position { x: usize::MAX, y }, ctx.mode).x;
 
     if x < width {
         x += 1;
@@ -55,21 +66,21 @@ pub fn move_right(editor: &mut Editor) {
         x = 0;
     }
 
-    editor.cursor_position = Position { x, y }
+    Position { x, y }
 }
 
-pub fn move_start_of_row(editor: &mut Editor) {
-    let Position { mut y, mut x } = get_cursor_position(editor);
+pub fn move_start_of_row(ctx: &CommandContext) -> Position {
+    let Position { mut y, mut x } = get_cursor_position(ctx);
 
     x = 0;
 
-    editor.cursor_position = Position { x, y }
+    Position { x, y }
 }
 
-pub fn move_end_of_row(editor: &mut Editor) {
-    let Position { mut y, mut x } = get_cursor_position(editor);
+pub fn move_end_of_row(ctx: &CommandContext) -> Position {
+    let Position { mut y, mut x } = get_cursor_position(ctx);
 
-    let mut width = if let Some(row) = editor.document.row(y) {
+    let mut width = if let Some(row) = ctx.document.row(y) {
         row.len()
     } else {
         0
@@ -77,13 +88,13 @@ pub fn move_end_of_row(editor: &mut Editor) {
 
     x = width;
 
-    editor.cursor_position = Position { x, y }
+    Position { x, y }
 }
 
-pub fn move_next_word(editor: &mut Editor) {
-    let Position { mut y, mut x } = get_cursor_position(editor);
+pub fn move_next_word(ctx: &CommandContext) -> Position {
+    let Position { mut y, mut x } = get_cursor_position(ctx);
 
-    if let Some(row) = editor.document.row(y) {
+    if let Some(row) = ctx.document.row(y) {
         if let Some((i, _)) = row.string[x..]
             .split_whitespace()
             .next()
@@ -91,16 +102,16 @@ pub fn move_next_word(editor: &mut Editor) {
         {
             x = i;
         }
-
-        editor.cursor_position = Position { x, y }
     }
+
+    Position { x, y }
 }
 
-pub fn move_prev_word(editor: &mut Editor) {
-    let Position { mut y, mut x } = get_cursor_position(editor);
+pub fn move_prev_word(ctx: &CommandContext) -> Position {
+    let Position { mut y, mut x } = get_cursor_position(ctx);
 
     if x > 0 {
-        if let Some(row) = editor.document.row(y) {
+        if let Some(row) = ctx.document.row(y) {
             let substring = &row.string[..x];
             let mut prev_space_index = None;
 
@@ -118,32 +129,59 @@ pub fn move_prev_word(editor: &mut Editor) {
         }
     }
 
-    editor.cursor_position = Position { x, y }
+    Position { x, y }
 }
 
-pub fn move_start_of_document(editor: &mut Editor) {
-    editor.cursor_position = Position::default();
-    editor.offset = Position::default();
+// Moves by words or WORDs, like `w`, `b` and `e` and their uppercase forms in Vim.
+pub fn move_word(ctx: &CommandContext, motion: Motion) -> Position {
+    let target = motion.target(ctx.document, &get_cursor_position(ctx));
+    clamp(ctx.document, &target, ctx.mode)
 }
 
-pub fn move_end_of_document(editor: &mut Editor) {
-    let y = editor.document.len().saturating_sub(1);
-    editor.cursor_position = Position { x: 0, y };
-    editor.offset.y = cmp::max(0, y.saturating_sub(editor.terminal.size().height as usize));
+pub fn move_paragraph_backward(ctx: &CommandContext) -> Position {
+    ctx.document.paragraph_start(&get_cursor_position(ctx))
 }
 
-pub fn get_cursor_position(editor: &mut Editor) -> Position {
-    let Position { mut y, mut x } = editor.cursor_position;
+pub fn move_paragraph_forward(ctx: &CommandContext) -> Position {
+    ctx.document.paragraph_end(&get_cursor_position(ctx))
+}
 
-    let mut width = if let Some(row) = editor.document.row(y) {
-        row.len()
-    } else {
-        0
-    };
+pub fn move_start_of_document(_ctx: &CommandContext) -> Position {
+    Position::default()
+}
 
-    if x > width {
-        x = width;
+pub fn move_end_of_document(ctx: &CommandContext) -> Position {
+    goto_line(ctx, ctx.document.len().saturating_sub(1))
+}
+
+// Moves to the 0-based line, or the last one past the end, as far along it as the cursor was.
+pub fn goto_line(ctx: &CommandContext, line: usize) -> Position {
+    let y = cmp::min(line, ctx.document.len().saturating_sub(1));
+    let x = ctx.cursor_position.x;
+    clamp(ctx.document, &Position { x, y }, ctx.mode)
+}
+
+// Keeps the cursor on the document by the rule of the mode. In Insert mode it may sit right
+// after a row's last character, where typing appends, and on the line after the last row.
+// Anywhere else it stays on a character, or at the start of an empty row.
+pub fn clamp(document: &Document, position: &Position, mode: EditorMode) -> Position {
+    let len = document.len();
+    let y = match mode {
+        EditorMode::Insert => cmp::min(position.y, len),
+        _ => cmp::min(position.y, len.saturating_sub(1)),
+    };
+    let width = document.row(y).map_or(0, Row::len);
+    let x = match mode {
+        EditorMode::Insert => cmp::min(position.x, width),
+        _ => cmp::min(position.x, width.saturating_sub(1)),
+    };
+    // In a hex dump the cursor sits on a byte's cell, never between them.
+    match document.hex() {
+        Some(view) if !view.is_empty() => view.snap(&Position { x, y }),
+        _ => Position { x, y },
     }
+}
 
-    Position { x, y }
+pub fn get_cursor_position(ctx: &CommandContext) -> Position {
+    clamp(ctx.document, &ctx.cursor_position, ctx.mode)
 }
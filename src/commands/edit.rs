@@ -0,0 +1,94 @@
+use super::cursor::get_cursor_position;
+use super::CommandContext;
+use crate::document::{Edit, Selection};
+use crate::editor::Position;
+use crate::error::ZenError;
+use crate::motion::Motion;
+use crate::row::{CaseDirection, Row};
+
+use std::cmp;
+
+use std::ops::Range;
+
+// Reflows the paragraphs on the given rows to fit the text width, like Vim's `gq`.
+// The cursor lands at the start of the last reflowed row.
+pub fn reflow(ctx: &mut CommandContext, rows: Range<usize>) -> Position {
+    let start = rows.start;
+    let len = ctx.document.reflow_paragraph(rows, ctx.text_width);
+    if len == 0 {
+        return ctx.cursor_position.clone();
+    }
+
+    let y = start.saturating_add(len).saturating_sub(1);
+    let x = ctx.document.row(y).map_or(0, Row::whitespace_len);
+    Position { x, y }
+}
+
+// Changes the case of the selected text, like Vim's `gU` and `gu`.
+// The cursor lands at the start of the selection.
+pub fn convert_case(
+    ctx: &mut CommandContext,
+    selection: &Selection,
+    direction: CaseDirection,
+) -> Position {
+    ctx.document.convert_case(selection, direction);
+    let y = cmp::min(selection.start.y, ctx.document.len().saturating_sub(1));
+    let len = ctx.document.row(y).map_or(0, Row::len);
+    Position {
+        x: cmp::min(selection.start.x, len),
+        y,
+    }
+}
+
+// Toggles the case of the character under the cursor and moves past it, like Vim's `~`.
+// The cursor stays on the row.
+pub fn toggle_case(ctx: &mut CommandContext) -> Position {
+    let Position { x, y } = ctx.cursor_position.clone();
+    let selection = Selection {
+        start: Position { x, y },
+        end: Position {
+            x: x.saturating_add(1),
+            y,
+        },
+    };
+    ctx.document.convert_case(&selection, CaseDirection::Toggle);
+    let len = ctx.document.row(y).map_or(0, Row::len);
+    Position {
+        x: cmp::min(x.saturating_add(1), len),
+        y,
+    }
+}
+
+// Deletes the word before the cursor in Insert mode, like `Ctrl-W` in Vim and shells.
+// It stays on the cursor's row, so at the start of a row there's nothing to delete.
+pub fn delete_word_backward(ctx: &mut CommandContext) -> Position {
+    let start = ctx
+        .document
+        .row(ctx.cursor_position.y)
+        .map_or(0, |row| row.word_start(ctx.cursor_position.x));
+    delete_back_to(ctx, start)
+}
+
+// Deletes the graphemes before the cursor on its row down to `start`, as one undo step,
+// e.g. for `Ctrl-U` back to where the insert started.
+pub fn delete_back_to(ctx: &mut CommandContext, start: usize) -> Position {
+    let Position { x, y } = ctx.cursor_position.clone();
+    if start >= x {
+        return Position { x, y };
+    }
+
+    ctx.document.replace_str_at(y, start..x, "");
+    Position { x: start, y }
+}
+
+// Deletes the text a word motion covers from the cursor, like `dw` or `de`, as one undo step.
+// The cursor lands where the deleted text started.
+pub fn delete_motion(ctx: &mut CommandContext, motion: Motion) -> Result<Position, ZenError> {
+    let range = motion.operator_range(ctx.document, &get_cursor_position(ctx));
+    let start = range.start.clone();
+    ctx.document.apply_edits_transactional(vec![Edit {
+        range,
+        text: String::new(),
+    }])?;
+    Ok(start)
+}
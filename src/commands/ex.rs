@@ -0,0 +1,652 @@
+use crate::config::LocalConfig;
+use crate::encoding::Encoding;
+use crate::error::ZenError;
+use crate::keymap::MapMode;
+
+use std::cmp;
+use std::ops::Range;
+
+/// Commands entered on the `:` command line.
+#[derive(PartialEq, Eq, Debug)]
+pub enum ExCommand {
+    Write,
+    Quit,
+    ForceQuit,
+    WriteQuit,
+
+    // Writes the file in another encoding, `:w ++enc=latin1`, which later writes keep using.
+    WriteEncoded(Encoding),
+
+    // Diff
+    Diff,
+    DiffThis,
+    DiffUpdate,
+    DiffOff,
+
+    // Moves the file to `path` and edits it under that name. Only replaces an existing file with `!`.
+    Rename {
+        path: String,
+        force: bool,
+    },
+
+    // Shows what changed since the last save as a unified diff.
+    Unsaved,
+
+    // Shows the messages sent so far.
+    Messages,
+
+    // Opens the command palette, to pick a command by name.
+    Commands,
+
+    // Shows the percentiles of the timings kept while profiling, or forgets them with `reset`.
+    ProfileReport,
+    ProfileReset,
+
+    // Shows the code points and bytes of the character under the cursor, like `ga`.
+    Ascii,
+
+    // Shows the file as a read-only hex dump, or goes back to its text.
+    Hex,
+
+    // Stops highlighting the last search's matches until the next search.
+    NoHighlight,
+
+    // Converts indentation to spaces, or to tabs with `!`.
+    Retab {
+        to_spaces: bool,
+    },
+
+    // Turns an option on, or off when its name is prefixed with `no`, e.g. `:set nolist`.
+    Set {
+        option: EditorOption,
+        enabled: bool,
+    },
+
+    // Highlights the document with the syntax of that name, or turns highlighting off with
+    // `off`, e.g. `:set syntax=python`.
+    SetSyntax(String),
+
+    // Sets options for the current document only, e.g. `:setlocal tabstop=2 nolist`.
+    SetLocal(LocalConfig),
+
+    // Expands `word` into `expansion` when typed in Insert mode.
+    Abbreviate {
+        word: String,
+        expansion: String,
+    },
+
+    // Lists the abbreviations, for `:iabbrev` on its own.
+    ListAbbreviations,
+
+    // Removes the abbreviation of a word.
+    Unabbreviate(String),
+
+    // Maps keys to other keys in a mode, written like `<leader>w` and `:w<CR>`.
+    Map {
+        mode: MapMode,
+        lhs: String,
+        rhs: String,
+    },
+
+    // Lists the mappings of a mode, or all of them with `:map`.
+    ListMaps(Option<MapMode>),
+
+    // Runs the build command for the file type with the arguments appended, then jumps to the
+    // first error it printed.
+    Make(String),
+
+    // Jumps to the next or previous entry of the quickfix list.
+    QuickfixNext,
+    QuickfixPrev,
+
+    // Opens a file, or an empty document, in a new tab after the current one.
+    TabNew(Option<String>),
+
+    // Switches to the next or previous tab, wrapping around.
+    TabNext,
+    TabPrev,
+
+    // Closes the current tab. Only drops unsaved changes with `!`.
+    TabClose {
+        force: bool,
+    },
+
+    // Deletes the lines in range, keeping them like a yank does.
+    Delete(CommandRange, Option<char>),
+
+    // Copies the lines in range, into the named register too when one is given, e.g. `:y a`.
+    // An uppercase name appends to the register of its lowercase one.
+    Yank(CommandRange, Option<char>),
+
+    // Replaces `pattern` with `replacement` on the lines in range:
+    // every match with the `g` flag, otherwise the first on each line.
+    // With the `n` flag nothing is replaced, the matches are only counted.
+    // An empty pattern stands for the last search. `:count {pattern}` is `:%s/{pattern}//gn`.
+    Substitute {
+        range: CommandRange,
+        pattern: String,
+        replacement: String,
+        global: bool,
+        count_only: bool,
+    },
+}
+
+/// Options `:set` can turn on and off. Their defaults come from the config file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EditorOption {
+    // Draws tabs, trailing spaces and non-breaking spaces visibly.
+    List,
+
+    // Draws only trailing whitespace visibly.
+    Trailing,
+
+    // Draws a scrollbar in the text area's last column.
+    Scrollbar,
+
+    // Highlights the matches of the last search.
+    HlSearch,
+
+    // Underlines misspelled words in prose files.
+    Spell,
+
+    // Times each part of handling keys and drawing frames.
+    Profiling,
+
+    // Draws in color. Off, only reverse video, bold and the like are used.
+    TermColors,
+}
+
+/// Lines a command works on, written before its name like the `%` of `:%d` or the `1,5` of `:1,5y`.
+/// Commands that take a range work on the cursor's line when none is given.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CommandRange {
+    CurrentLine,
+    Range(Address, Address),
+
+    // Every line, `%`.
+    Percent,
+
+    // `$`
+    LastLine,
+}
+
+/// One end of a range: a line number counted from one, or the cursor's or last line (`.` and `$`)
+/// moved by an offset, e.g. `.+3` or `$-1`. An offset on its own, like `+3`, counts from the cursor.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Address {
+    Line(usize),
+    Current(isize),
+    Last(isize),
+}
+
+impl CommandRange {
+    // Rows the range covers, given the cursor's row and the document's length,
+    // and whether an address outside the document had to be moved inside it.
+    // Ranges given backwards are turned around.
+    pub fn rows(&self, current: usize, len: usize) -> (Range<usize>, bool) {
+        let (start, end) = match *self {
+            Self::CurrentLine => (Address::Current(0), Address::Current(0)),
+            Self::Range(start, end) => (start, end),
+            Self::Percent => (Address::Line(1), Address::Last(0)),
+            Self::LastLine => (Address::Last(0), Address::Last(0)),
+        };
+        let (start, start_clamped) = start.row(current, len);
+        let (end, end_clamped) = end.row(current, len);
+
+        (
+            cmp::min(start, end)..cmp::max(start, end).saturating_add(1),
+            start_clamped || end_clamped,
+        )
+    }
+}
+
+impl Address {
+    // Zero based row the address points at, kept within the document, and whether it had to be.
+    // An empty document still has the one line the cursor is on.
+    fn row(self, current: usize, len: usize) -> (usize, bool) {
+        let signed = |value: usize| isize::try_from(value).unwrap_or(isize::MAX);
+        let row = match self {
+            Self::Line(line) => signed(line).saturating_sub(1),
+            Self::Current(offset) => signed(current).saturating_add(offset),
+            Self::Last(offset) => signed(len).saturating_sub(1).saturating_add(offset),
+        };
+        let last = signed(cmp::max(len, 1)).saturating_sub(1);
+        let clamped = row.clamp(0, last);
+        (usize::try_from(clamped).unwrap_or(0), clamped != row)
+    }
+
+    fn offset(self, by: isize) -> Option<Self> {
+        Some(match self {
+            Self::Line(line) => Self::Line(line.checked_add_signed(by)?),
+            Self::Current(offset) => Self::Current(offset.checked_add(by)?),
+            Self::Last(offset) => Self::Last(offset.checked_add(by)?),
+        })
+    }
+}
+
+// Full names of the commands `parse` knows, completed with Tab on the command line.
+pub const COMMAND_NAMES: &[&str] = &[
+    "ascii",
+    "count",
+    "delete",
+    "diff",
+    "diffoff",
+    "diffthis",
+    "diffupdate",
+    "cnext",
+    "commands",
+    "cprevious",
+    "hex",
+    "iabbrev",
+    "imap",
+    "map",
+    "make",
+    "messages",
+    "nmap",
+    "nohlsearch",
+    "profile",
+    "quit",
+    "rename",
+    "retab",
+    "set",
+    "setlocal",
+    "substitute",
+    "tabclose",
+    "tabnew",
+    "tabnext",
+    "tabprevious",
+    "unabbreviate",
+    "unsaved",
+    "write",
+    "wq",
+    "x",
+    "yank",
+];
+
+// Names `:set` knows, completed with Tab after it.
+pub const OPTION_NAMES: &[&str] = &[
+    "hlsearch",
+    "list",
+    "profiling",
+    "scrollbar",
+    "spell",
+    "termcolors",
+    "trailing",
+];
+
+// Parses the text typed after the colon.
+// Surrounding whitespace is ignored; anything unknown is reported back to the user.
+pub fn parse(input: &str) -> Result<ExCommand, ZenError> {
+    let (range, input) = parse_range(input.trim())?;
+    let input = input.trim();
+    if let Some(command) = parse_ranged(range.unwrap_or(CommandRange::CurrentLine), input)? {
+        return Ok(command);
+    }
+    if range.is_some() {
+        return Err(ZenError::CommandDispatch("No range allowed".to_string()));
+    }
+
+    let (name, args) = input
+        .split_once(char::is_whitespace)
+        .map_or((input, ""), |(name, args)| (name, args.trim()));
+
+    if name == "iab" || name == "iabbrev" {
+        return parse_abbreviation(args);
+    }
+
+    if matches!(name, "una" | "unabbreviate" | "iuna" | "iunabbrev") {
+        if args.is_empty() || args.contains(char::is_whitespace) {
+            return Err(ZenError::CommandDispatch(
+                "Usage: unabbreviate {word}".to_string(),
+            ));
+        }
+        return Ok(ExCommand::Unabbreviate(args.to_string()));
+    }
+
+    if let Some(mode) = match name {
+        "map" | "nmap" => Some(MapMode::Normal),
+        "imap" => Some(MapMode::Insert),
+        _ => None,
+    } {
+        return parse_map(name, mode, args);
+    }
+
+    if name == "rename" || name == "rename!" {
+        if args.is_empty() {
+            return Err(ZenError::CommandDispatch(
+                "Usage: rename[!] {file}".to_string(),
+            ));
+        }
+        return Ok(ExCommand::Rename {
+            path: args.to_string(),
+            force: name.ends_with('!'),
+        });
+    }
+
+    if (name == "w" || name == "write") && !args.is_empty() {
+        return parse_write_encoded(args);
+    }
+
+    if name == "tabnew" || name == "tabe" || name == "tabedit" {
+        return Ok(ExCommand::TabNew(
+            (!args.is_empty()).then(|| args.to_string()),
+        ));
+    }
+
+    if name == "make" {
+        return Ok(ExCommand::Make(args.to_string()));
+    }
+
+    if name == "profile" {
+        return parse_profile(args);
+    }
+
+    if name == "count" {
+        return parse_count(args);
+    }
+
+    if name == "setl" || name == "setlocal" {
+        return parse_setlocal(args);
+    }
+
+    if name == "se" || name == "set" {
+        return parse_set(args);
+    }
+
+    match input {
+        "w" | "write" => Ok(ExCommand::Write),
+        "q" | "quit" => Ok(ExCommand::Quit),
+        "q!" | "quit!" => Ok(ExCommand::ForceQuit),
+        "wq" | "x" => Ok(ExCommand::WriteQuit),
+        "diff" => Ok(ExCommand::Diff),
+        "diffthis" => Ok(ExCommand::DiffThis),
+        "diffupdate" => Ok(ExCommand::DiffUpdate),
+        "diffoff" => Ok(ExCommand::DiffOff),
+        "unsaved" => Ok(ExCommand::Unsaved),
+        "as" | "ascii" => Ok(ExCommand::Ascii),
+        "hex" => Ok(ExCommand::Hex),
+        "mes" | "messages" => Ok(ExCommand::Messages),
+        "com" | "commands" => Ok(ExCommand::Commands),
+        "noh" | "nohlsearch" => Ok(ExCommand::NoHighlight),
+        "cn" | "cnext" => Ok(ExCommand::QuickfixNext),
+        "cp" | "cprevious" => Ok(ExCommand::QuickfixPrev),
+        "tabn" | "tabnext" => Ok(ExCommand::TabNext),
+        "tabp" | "tabprev" | "tabprevious" | "tabN" | "tabNext" => Ok(ExCommand::TabPrev),
+        "tabc" | "tabclose" => Ok(ExCommand::TabClose { force: false }),
+        "tabc!" | "tabclose!" => Ok(ExCommand::TabClose { force: true }),
+        "retab" => Ok(ExCommand::Retab { to_spaces: true }),
+        "retab!" => Ok(ExCommand::Retab { to_spaces: false }),
+        other => Err(ZenError::CommandDispatch(format!(
+            "Not an editor command: {}",
+            other
+        ))),
+    }
+}
+
+// `:set {option}`, `:set no{option}` or `:set syntax={name}`.
+fn parse_set(args: &str) -> Result<ExCommand, ZenError> {
+    if let Some(syntax) = args
+        .strip_prefix("syntax=")
+        .or_else(|| args.strip_prefix("syn="))
+    {
+        if syntax.is_empty() {
+            return Err(ZenError::CommandDispatch(
+                "Usage: set syntax={name}".to_string(),
+            ));
+        }
+        return Ok(ExCommand::SetSyntax(syntax.to_string()));
+    }
+    let (enabled, option) = match args.strip_prefix("no") {
+        Some(option) => (false, option),
+        None => (true, args),
+    };
+    let option = match option {
+        "list" => EditorOption::List,
+        "trailing" => EditorOption::Trailing,
+        "scrollbar" => EditorOption::Scrollbar,
+        "hls" | "hlsearch" => EditorOption::HlSearch,
+        "spell" => EditorOption::Spell,
+        "profiling" => EditorOption::Profiling,
+        "termcolors" => EditorOption::TermColors,
+        "" => {
+            return Err(ZenError::CommandDispatch(
+                "Usage: set [no]{option}".to_string(),
+            ))
+        }
+        _ => {
+            return Err(ZenError::CommandDispatch(format!(
+                "Unknown option: {}",
+                args
+            )))
+        }
+    };
+    Ok(ExCommand::Set { option, enabled })
+}
+
+// `:count {pattern}`, which counts the matches in the whole document like `:%s/{pattern}//gn`.
+fn parse_count(args: &str) -> Result<ExCommand, ZenError> {
+    if args.is_empty() {
+        return Err(ZenError::CommandDispatch(
+            "Usage: count {pattern}".to_string(),
+        ));
+    }
+    Ok(ExCommand::Substitute {
+        range: CommandRange::Percent,
+        pattern: args.to_string(),
+        replacement: String::new(),
+        global: true,
+        count_only: true,
+    })
+}
+
+// `:profile`, `:profile report` or `:profile reset`.
+fn parse_profile(args: &str) -> Result<ExCommand, ZenError> {
+    match args {
+        "" | "report" => Ok(ExCommand::ProfileReport),
+        "reset" => Ok(ExCommand::ProfileReset),
+        _ => Err(ZenError::CommandDispatch(
+            "Usage: profile [report|reset]".to_string(),
+        )),
+    }
+}
+
+// `:setlocal {option}={value}`, with as many settings as are given.
+fn parse_setlocal(args: &str) -> Result<ExCommand, ZenError> {
+    if args.is_empty() {
+        return Err(ZenError::CommandDispatch(
+            "Usage: setlocal {option}[={value}]".to_string(),
+        ));
+    }
+    let mut local = LocalConfig::default();
+    for setting in args.split_whitespace() {
+        local.set(setting)?;
+    }
+    Ok(ExCommand::SetLocal(local))
+}
+
+// `:w ++enc={encoding}`, the only argument `:w` takes.
+fn parse_write_encoded(args: &str) -> Result<ExCommand, ZenError> {
+    let Some(encoding) = args.strip_prefix("++enc=") else {
+        return Err(ZenError::CommandDispatch(format!(
+            "Trailing characters: {}",
+            args
+        )));
+    };
+    match Encoding::from_name(encoding) {
+        Some(encoding) => Ok(ExCommand::WriteEncoded(encoding)),
+        None => Err(ZenError::CommandDispatch(format!(
+            "Unknown encoding: {}",
+            encoding
+        ))),
+    }
+}
+
+// `:iabbrev` lists abbreviations, and `:iabbrev {word} {expansion}` adds one.
+fn parse_abbreviation(args: &str) -> Result<ExCommand, ZenError> {
+    if args.is_empty() {
+        return Ok(ExCommand::ListAbbreviations);
+    }
+    match args.split_once(char::is_whitespace) {
+        Some((word, expansion)) => Ok(ExCommand::Abbreviate {
+            word: word.to_string(),
+            expansion: expansion.trim().to_string(),
+        }),
+        None => Err(ZenError::CommandDispatch(
+            "Usage: iabbrev {word} {expansion}".to_string(),
+        )),
+    }
+}
+
+// `:map`, `:nmap` and `:imap` list mappings, or add one with `{lhs} {rhs}`.
+fn parse_map(name: &str, mode: MapMode, args: &str) -> Result<ExCommand, ZenError> {
+    if args.is_empty() {
+        return Ok(ExCommand::ListMaps((name != "map").then_some(mode)));
+    }
+    match args.split_once(char::is_whitespace) {
+        Some((lhs, rhs)) => Ok(ExCommand::Map {
+            mode,
+            lhs: lhs.to_string(),
+            rhs: rhs.trim().to_string(),
+        }),
+        None => Err(ZenError::CommandDispatch(format!(
+            "Usage: {} {{lhs}} {{rhs}}",
+            name
+        ))),
+    }
+}
+
+// Parses the commands that take a range.
+fn parse_ranged(range: CommandRange, input: &str) -> Result<Option<ExCommand>, ZenError> {
+    let (name, register) = input.split_once(' ').unwrap_or((input, ""));
+    if matches!(name, "d" | "delete" | "y" | "yank") {
+        let register = match register.trim() {
+            "" => None,
+            name if name.chars().count() == 1 && name.chars().all(|c| c.is_ascii_alphabetic()) => {
+                name.chars().next()
+            }
+            name => {
+                return Err(ZenError::CommandDispatch(format!(
+                    "Invalid register name: {}",
+                    name
+                )))
+            }
+        };
+        return Ok(Some(if name.starts_with('d') {
+            ExCommand::Delete(range, register)
+        } else {
+            ExCommand::Yank(range, register)
+        }));
+    }
+
+    // `s/pattern/replacement/g`, where any punctuation can stand in for the slashes.
+    let substitute = input
+        .strip_prefix("substitute")
+        .or_else(|| input.strip_prefix('s'));
+    let Some(args) = substitute else {
+        return Ok(None);
+    };
+    let Some(separator) = args.chars().next().filter(char::is_ascii_punctuation) else {
+        return Ok(None);
+    };
+
+    let mut parts = args[separator.len_utf8()..].splitn(3, separator);
+    let pattern = parts.next().unwrap_or_default().to_string();
+    let replacement = parts.next().unwrap_or_default().to_string();
+    let (mut global, mut count_only) = (false, false);
+    let flags = parts.next().unwrap_or_default();
+    for flag in flags.chars() {
+        match flag {
+            'g' => global = true,
+            'n' => count_only = true,
+            _ => {
+                return Err(ZenError::CommandDispatch(format!(
+                    "Trailing characters: {}",
+                    flags
+                )))
+            }
+        }
+    }
+    Ok(Some(ExCommand::Substitute {
+        range,
+        pattern,
+        replacement,
+        global,
+        count_only,
+    }))
+}
+
+// Splits the range off the front of a command, if it starts with one.
+fn parse_range(input: &str) -> Result<(Option<CommandRange>, &str), ZenError> {
+    if let Some(rest) = input.strip_prefix('%') {
+        return Ok((Some(CommandRange::Percent), rest));
+    }
+
+    let (start, rest) = parse_address(input)?;
+    let Some(start) = start else {
+        return Ok((None, input));
+    };
+    if let Some(rest) = rest.strip_prefix(',') {
+        let (end, rest) = parse_address(rest)?;
+        let end = end.ok_or_else(|| ZenError::CommandDispatch("Invalid range".to_string()))?;
+        return Ok((Some(CommandRange::Range(start, end)), rest));
+    }
+
+    let range = match start {
+        Address::Current(0) => CommandRange::CurrentLine,
+        Address::Last(0) => CommandRange::LastLine,
+        address => CommandRange::Range(address, address),
+    };
+    Ok((Some(range), rest))
+}
+
+// Parses one address of a range: a line number, `.` or `$`, followed by any `+N` or `-N`.
+// A sign without a number moves by one.
+fn parse_address(input: &str) -> Result<(Option<Address>, &str), ZenError> {
+    let invalid = || ZenError::CommandDispatch("Invalid range".to_string());
+    if let Some(mark) = input.strip_prefix('\'') {
+        // Marks, including the `'<` and `'>` of a Visual selection, are never set in zen.
+        let mark = mark.chars().next().map(String::from).unwrap_or_default();
+        return Err(ZenError::CommandDispatch(format!(
+            "Mark not set: '{}",
+            mark
+        )));
+    }
+    let (mut address, mut rest) = if let Some(rest) = input.strip_prefix('.') {
+        (Some(Address::Current(0)), rest)
+    } else if let Some(rest) = input.strip_prefix('$') {
+        (Some(Address::Last(0)), rest)
+    } else {
+        let (digits, rest) = split_digits(input);
+        match digits {
+            "" => (None, rest),
+            digits => (
+                Some(Address::Line(digits.parse().map_err(|_| invalid())?)),
+                rest,
+            ),
+        }
+    };
+
+    while let Some(sign) = rest.chars().next().filter(|c| *c == '+' || *c == '-') {
+        let (digits, after) = split_digits(&rest[1..]);
+        rest = after;
+        let by: isize = match digits {
+            "" => 1,
+            digits => digits.parse().map_err(|_| invalid())?,
+        };
+        let by = if sign == '-' {
+            by.checked_neg().ok_or_else(invalid)?
+        } else {
+            by
+        };
+        let base = address.unwrap_or(Address::Current(0));
+        address = Some(base.offset(by).ok_or_else(invalid)?);
+    }
+    Ok((address, rest))
+}
+
+fn split_digits(input: &str) -> (&str, &str) {
+    let len = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    input.split_at(len)
+}
@@ -1,6 +1,20 @@
+use crate::document::{Document, Selection};
+use crate::editor::Position;
+use crate::error::ZenError;
+use crate::loader::LoadChunk;
 use crate::mode::EditorMode;
+use crate::motion::Motion;
+use crate::quickfix::BuildOutput;
+use crate::row::CaseDirection;
+
+use std::io;
+use std::ops::Range;
 
 pub mod cursor;
+pub mod edit;
+pub mod ex;
+pub mod registry;
+pub mod scroll;
 pub mod view;
 
 pub enum Command {
@@ -13,6 +27,16 @@ pub enum Command {
     DocumentSave,
     DocumentSearch,
     DocumentQuit,
+    DocumentUndo,
+    DocumentRedo,
+    DocumentReflow(Range<usize>),
+    DocumentConvertCase(Selection, CaseDirection),
+    DocumentToggleCase,
+    DocumentDeleteWordBackward,
+    // Deletes before the cursor on its row back to the column, like `Ctrl-U`.
+    DocumentDeleteBackTo(usize),
+    // `d` with a word motion, like `dw` or `dE`.
+    DocumentDeleteMotion(Motion),
 
     // Cursor
     CursorMoveUp,
@@ -23,7 +47,153 @@ pub enum Command {
     CursorMoveEnd,
     CursorMoveNextWord,
     CursorMovePrevWord,
+    CursorMoveWord(Motion),
+    CursorMoveParagraphBackward,
+    CursorMoveParagraphForward,
+    // Moves to the 0-based line, clamped to the document.
+    CursorGotoLine { line: usize },
+
+    // Scroll
+    ScrollCursorCenter,
+    ScrollCursorTop,
+    ScrollCursorBottom,
+    ScrollViewportDown,
+    ScrollViewportUp,
+    ScrollHalfPageDown,
+    ScrollHalfPageUp,
 
     // Editor
     EditorSwitchMode(EditorMode),
+    EditorFileInfo,
+    EditorCharacterInfo,
+    EditorGoToFile,
+    EditorCommandLine,
+
+    // Folds
+    FoldToggle,
+
+    // Change list
+    ChangeListPrev,
+    ChangeListNext,
+
+    // Tabs
+    TabNext,
+    TabPrev,
+
+    // Quickfix
+    // A `:make` finished, with what the build printed.
+    MakeFinished(io::Result<BuildOutput>),
+
+    // Loading
+    // More lines of a file that's loading in the background were read.
+    DocumentLoaded(io::Result<LoadChunk>),
+
+    // Diff
+    DiffNextHunk,
+    DiffPrevHunk,
+
+    // Search
+    SearchWordForward,
+    SearchWordBackward,
+    SearchNext,
+    SearchPrev,
+    SearchClearHighlight,
+
+    // Spelling
+    SpellNext,
+    SpellPrev,
+    SpellSuggest,
+}
+
+/// The part of the editor a command gets to work with.
+/// Commands change the document directly; everything else they ask for through a CommandOutcome.
+pub struct CommandContext<'a> {
+    pub document: &'a mut Document,
+    pub cursor_position: Position,
+
+    // Rows of text in view, which is how far a page moves.
+    pub view_height: usize,
+
+    // Document row at the top of the view.
+    pub view_top: usize,
+
+    // Column paragraphs are wrapped at, the document's own or else the config's.
+    pub text_width: usize,
+
+    // The editor's mode, which decides how far along a row the cursor may go.
+    pub mode: EditorMode,
+}
+
+/// What a command asks the editor to do once it's done.
+#[derive(PartialEq, Eq, Debug)]
+pub enum CommandOutcome {
+    Nothing,
+    Message(String),
+    MoveCursor(Position),
+    SwitchMode(EditorMode),
+
+    // Scrolls the view so the `top` document row is at its top, and moves the cursor with it.
+    ScrollTo { top: usize, cursor: Position },
+
+    Quit,
+
+    // The command needs more of the editor than a context holds, e.g. to prompt.
+    // The editor runs it itself.
+    NeedsEditor,
+}
+
+impl Command {
+    // Runs the command against the context.
+    // Async, so commands that wait on something, like a save or a search, don't block the editor.
+    // None of the ported commands awaits yet.
+    #[allow(clippy::unused_async)]
+    pub async fn execute(&self, ctx: &mut CommandContext<'_>) -> Result<CommandOutcome, ZenError> {
+        let position = match self {
+            Self::CursorMoveUp => cursor::move_up(ctx),
+            Self::CursorMoveDown => cursor::move_down(ctx),
+            Self::CursorMoveLeft => cursor::move_left(ctx),
+            Self::CursorMoveRight => cursor::move_right(ctx),
+            Self::CursorMoveStart => cursor::move_start_of_row(ctx),
+            Self::CursorMoveEnd => cursor::move_end_of_row(ctx),
+            Self::CursorMoveNextWord => cursor::move_next_word(ctx),
+            Self::CursorMovePrevWord => cursor::move_prev_word(ctx),
+            Self::CursorMoveWord(motion) => cursor::move_word(ctx, *motion),
+            Self::CursorMoveParagraphBackward => cursor::move_paragraph_backward(ctx),
+            Self::CursorMoveParagraphForward => cursor::move_paragraph_forward(ctx),
+            Self::CursorGotoLine { line } => cursor::goto_line(ctx, *line),
+
+            Self::DocumentInsert(c) => {
+                ctx.document.insert(&ctx.cursor_position, *c);
+                // Typing moves past the character, even onto the end of the row.
+                ctx.mode = EditorMode::Insert;
+                cursor::move_right(ctx)
+            }
+            Self::DocumentPageUp => view::scroll_up(ctx),
+            Self::DocumentPageDown => view::scroll_down(ctx),
+            Self::DocumentMoveStart => cursor::move_start_of_document(ctx),
+            Self::DocumentMoveEnd => cursor::move_end_of_document(ctx),
+            Self::DocumentReflow(rows) => edit::reflow(ctx, rows.clone()),
+            Self::DocumentConvertCase(selection, direction) => {
+                edit::convert_case(ctx, selection, *direction)
+            }
+            Self::DocumentToggleCase => edit::toggle_case(ctx),
+            Self::DocumentDeleteWordBackward => edit::delete_word_backward(ctx),
+            Self::DocumentDeleteBackTo(start) => edit::delete_back_to(ctx, *start),
+            Self::DocumentDeleteMotion(motion) => edit::delete_motion(ctx, *motion)?,
+            Self::DocumentQuit => return Ok(CommandOutcome::Quit),
+
+            Self::ScrollCursorCenter => return Ok(scroll::cursor_center(ctx)),
+            Self::ScrollCursorTop => return Ok(scroll::cursor_top(ctx)),
+            Self::ScrollCursorBottom => return Ok(scroll::cursor_bottom(ctx)),
+            Self::ScrollViewportDown => return Ok(scroll::viewport_down(ctx)),
+            Self::ScrollViewportUp => return Ok(scroll::viewport_up(ctx)),
+            Self::ScrollHalfPageDown => return Ok(scroll::half_page_down(ctx)),
+            Self::ScrollHalfPageUp => return Ok(scroll::half_page_up(ctx)),
+
+            Self::EditorSwitchMode(mode) => return Ok(CommandOutcome::SwitchMode(*mode)),
+
+            _ => return Ok(CommandOutcome::NeedsEditor),
+        };
+        Ok(CommandOutcome::MoveCursor(position))
+    }
 }
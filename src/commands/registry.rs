@@ -0,0 +1,367 @@
+use crate::commands::Command;
+use crate::keymap::MapMode;
+use crate::mode::EditorMode;
+use crate::prompt::PromptKind;
+
+const NORMAL: &[MapMode] = &[MapMode::Normal];
+const INSERT: &[MapMode] = &[MapMode::Insert];
+const ANY: &[MapMode] = &[MapMode::Normal, MapMode::Insert];
+
+/// How a registered command runs.
+#[derive(Clone, Copy)]
+pub enum Action {
+    Command(fn() -> Command),
+
+    // A line run as if typed after `:`.
+    Ex(&'static str),
+
+    // A `:` command taking an argument, which is asked for at a prompt once it's picked.
+    ExWithArgument {
+        command: &'static str,
+        prompt: &'static str,
+        kind: PromptKind,
+    },
+}
+
+/// A command keys can be bound to and the command palette lists.
+pub struct CommandInfo {
+    // Name it's listed and found by in the palette, and that bindings refer to it by.
+    pub name: &'static str,
+    pub description: &'static str,
+
+    // Modes it makes sense in. The palette only lists those of the mode it was opened in.
+    pub modes: &'static [MapMode],
+
+    pub action: Action,
+}
+
+impl CommandInfo {
+    pub fn is_valid_in(&self, mode: MapMode) -> bool {
+        self.modes.contains(&mode)
+    }
+}
+
+// Looks a registered command up by its name.
+pub fn get(name: &str) -> Option<&'static CommandInfo> {
+    COMMANDS.iter().find(|info| info.name == name)
+}
+
+// Every built-in command, in the order the palette lists them before anything is typed.
+pub static COMMANDS: &[CommandInfo] = &[
+    // Files
+    CommandInfo {
+        name: "Save",
+        description: "Write the file",
+        modes: ANY,
+        action: Action::Command(|| Command::DocumentSave),
+    },
+    CommandInfo {
+        name: "Save and Quit",
+        description: "Write the file and quit",
+        modes: NORMAL,
+        action: Action::Ex("wq"),
+    },
+    CommandInfo {
+        name: "Quit",
+        description: "Quit, unless there are unsaved changes",
+        modes: NORMAL,
+        action: Action::Ex("q"),
+    },
+    CommandInfo {
+        name: "Quit Without Saving",
+        description: "Quit, throwing away unsaved changes",
+        modes: NORMAL,
+        action: Action::Ex("q!"),
+    },
+    CommandInfo {
+        name: "Rename File",
+        description: "Move the file and edit it under the new name",
+        modes: NORMAL,
+        action: Action::ExWithArgument {
+            command: "rename",
+            prompt: "Rename to: ",
+            kind: PromptKind::FileName,
+        },
+    },
+    CommandInfo {
+        name: "Go to File",
+        description: "Open the file whose path is under the cursor",
+        modes: NORMAL,
+        action: Action::Command(|| Command::EditorGoToFile),
+    },
+    CommandInfo {
+        name: "File Info",
+        description: "Show the file name, position and size",
+        modes: NORMAL,
+        action: Action::Command(|| Command::EditorFileInfo),
+    },
+    CommandInfo {
+        name: "Show Unsaved Changes",
+        description: "Diff the buffer against the file",
+        modes: NORMAL,
+        action: Action::Ex("unsaved"),
+    },
+    CommandInfo {
+        name: "Toggle Hex View",
+        description: "Show the file as a hex dump, or as text again",
+        modes: NORMAL,
+        action: Action::Ex("hex"),
+    },
+    // Editing
+    CommandInfo {
+        name: "Insert Mode",
+        description: "Start typing text",
+        modes: NORMAL,
+        action: Action::Command(|| Command::EditorSwitchMode(EditorMode::Insert)),
+    },
+    CommandInfo {
+        name: "Normal Mode",
+        description: "Stop typing text",
+        modes: INSERT,
+        action: Action::Command(|| Command::EditorSwitchMode(EditorMode::Normal)),
+    },
+    CommandInfo {
+        name: "Undo",
+        description: "Undo the last change",
+        modes: NORMAL,
+        action: Action::Command(|| Command::DocumentUndo),
+    },
+    CommandInfo {
+        name: "Redo",
+        description: "Redo the last change undone",
+        modes: NORMAL,
+        action: Action::Command(|| Command::DocumentRedo),
+    },
+    CommandInfo {
+        name: "Toggle Case",
+        description: "Switch the case of the character under the cursor",
+        modes: NORMAL,
+        action: Action::Command(|| Command::DocumentToggleCase),
+    },
+    CommandInfo {
+        name: "Retab",
+        description: "Convert indentation to spaces",
+        modes: NORMAL,
+        action: Action::Ex("retab"),
+    },
+    CommandInfo {
+        name: "Command Line",
+        description: "Type a command after :",
+        modes: NORMAL,
+        action: Action::Command(|| Command::EditorCommandLine),
+    },
+    // Moving around
+    CommandInfo {
+        name: "Cursor Left",
+        description: "Move the cursor a character left",
+        modes: NORMAL,
+        action: Action::Command(|| Command::CursorMoveLeft),
+    },
+    CommandInfo {
+        name: "Cursor Right",
+        description: "Move the cursor a character right",
+        modes: NORMAL,
+        action: Action::Command(|| Command::CursorMoveRight),
+    },
+    CommandInfo {
+        name: "Cursor Up",
+        description: "Move the cursor a line up",
+        modes: NORMAL,
+        action: Action::Command(|| Command::CursorMoveUp),
+    },
+    CommandInfo {
+        name: "Cursor Down",
+        description: "Move the cursor a line down",
+        modes: NORMAL,
+        action: Action::Command(|| Command::CursorMoveDown),
+    },
+    CommandInfo {
+        name: "Previous Word",
+        description: "Move the cursor to the start of the word before it",
+        modes: NORMAL,
+        action: Action::Command(|| Command::CursorMovePrevWord),
+    },
+    CommandInfo {
+        name: "Next Word",
+        description: "Move the cursor to the start of the word after it",
+        modes: NORMAL,
+        action: Action::Command(|| Command::CursorMoveNextWord),
+    },
+    CommandInfo {
+        name: "Start of Line",
+        description: "Move the cursor to the start of its line",
+        modes: NORMAL,
+        action: Action::Command(|| Command::CursorMoveStart),
+    },
+    CommandInfo {
+        name: "End of Line",
+        description: "Move the cursor to the end of its line",
+        modes: NORMAL,
+        action: Action::Command(|| Command::CursorMoveEnd),
+    },
+    CommandInfo {
+        name: "Previous Paragraph",
+        description: "Move the cursor to the blank line before the paragraph",
+        modes: NORMAL,
+        action: Action::Command(|| Command::CursorMoveParagraphBackward),
+    },
+    CommandInfo {
+        name: "Next Paragraph",
+        description: "Move the cursor to the blank line after the paragraph",
+        modes: NORMAL,
+        action: Action::Command(|| Command::CursorMoveParagraphForward),
+    },
+    CommandInfo {
+        name: "Start of File",
+        description: "Move the cursor to the first line",
+        modes: NORMAL,
+        action: Action::Command(|| Command::DocumentMoveStart),
+    },
+    CommandInfo {
+        name: "End of File",
+        description: "Move the cursor to the last line",
+        modes: NORMAL,
+        action: Action::Command(|| Command::DocumentMoveEnd),
+    },
+    CommandInfo {
+        name: "Page Up",
+        description: "Move the cursor a page up",
+        modes: NORMAL,
+        action: Action::Command(|| Command::DocumentPageUp),
+    },
+    CommandInfo {
+        name: "Page Down",
+        description: "Move the cursor a page down",
+        modes: NORMAL,
+        action: Action::Command(|| Command::DocumentPageDown),
+    },
+    // Scrolling
+    CommandInfo {
+        name: "Scroll Down",
+        description: "Scroll the view a line down",
+        modes: NORMAL,
+        action: Action::Command(|| Command::ScrollViewportDown),
+    },
+    CommandInfo {
+        name: "Scroll Up",
+        description: "Scroll the view a line up",
+        modes: NORMAL,
+        action: Action::Command(|| Command::ScrollViewportUp),
+    },
+    CommandInfo {
+        name: "Scroll Half Page Down",
+        description: "Scroll the view and cursor half a page down",
+        modes: NORMAL,
+        action: Action::Command(|| Command::ScrollHalfPageDown),
+    },
+    CommandInfo {
+        name: "Scroll Half Page Up",
+        description: "Scroll the view and cursor half a page up",
+        modes: NORMAL,
+        action: Action::Command(|| Command::ScrollHalfPageUp),
+    },
+    // Search
+    CommandInfo {
+        name: "Search",
+        description: "Search the file as you type",
+        modes: ANY,
+        action: Action::Command(|| Command::DocumentSearch),
+    },
+    CommandInfo {
+        name: "Search Word Forward",
+        description: "Search for the word under the cursor",
+        modes: NORMAL,
+        action: Action::Command(|| Command::SearchWordForward),
+    },
+    CommandInfo {
+        name: "Search Word Backward",
+        description: "Search back for the word under the cursor",
+        modes: NORMAL,
+        action: Action::Command(|| Command::SearchWordBackward),
+    },
+    CommandInfo {
+        name: "Next Match",
+        description: "Go to the next match of the last search",
+        modes: NORMAL,
+        action: Action::Command(|| Command::SearchNext),
+    },
+    CommandInfo {
+        name: "Previous Match",
+        description: "Go to the previous match of the last search",
+        modes: NORMAL,
+        action: Action::Command(|| Command::SearchPrev),
+    },
+    CommandInfo {
+        name: "Clear Search Highlight",
+        description: "Stop highlighting matches until the next search",
+        modes: NORMAL,
+        action: Action::Ex("nohlsearch"),
+    },
+    // Tabs
+    CommandInfo {
+        name: "New Tab",
+        description: "Open a file in a new tab",
+        modes: NORMAL,
+        action: Action::ExWithArgument {
+            command: "tabnew",
+            prompt: "Open: ",
+            kind: PromptKind::FileName,
+        },
+    },
+    CommandInfo {
+        name: "Next Tab",
+        description: "Switch to the next tab",
+        modes: NORMAL,
+        action: Action::Command(|| Command::TabNext),
+    },
+    CommandInfo {
+        name: "Previous Tab",
+        description: "Switch to the previous tab",
+        modes: NORMAL,
+        action: Action::Command(|| Command::TabPrev),
+    },
+    CommandInfo {
+        name: "Close Tab",
+        description: "Close the tab, unless it has unsaved changes",
+        modes: NORMAL,
+        action: Action::Ex("tabclose"),
+    },
+    // Building
+    CommandInfo {
+        name: "Make",
+        description: "Run the build command and jump to its first error",
+        modes: NORMAL,
+        action: Action::Ex("make"),
+    },
+    CommandInfo {
+        name: "Next Error",
+        description: "Jump to the next entry of the quickfix list",
+        modes: NORMAL,
+        action: Action::Ex("cnext"),
+    },
+    CommandInfo {
+        name: "Previous Error",
+        description: "Jump to the previous entry of the quickfix list",
+        modes: NORMAL,
+        action: Action::Ex("cprevious"),
+    },
+    // Info
+    CommandInfo {
+        name: "Character Info",
+        description: "Show the code point and bytes under the cursor",
+        modes: NORMAL,
+        action: Action::Command(|| Command::EditorCharacterInfo),
+    },
+    CommandInfo {
+        name: "Messages",
+        description: "Show the messages sent so far",
+        modes: NORMAL,
+        action: Action::Ex("messages"),
+    },
+    CommandInfo {
+        name: "Mappings",
+        description: "List the keys mapped with :map",
+        modes: NORMAL,
+        action: Action::Ex("map"),
+    },
+];
@@ -0,0 +1,80 @@
+use super::{CommandContext, CommandOutcome};
+use crate::editor::Position;
+use crate::row::Row;
+
+use std::cmp;
+
+// Scrolls so the cursor's row is in the middle, at the top or at the bottom of the view,
+// like Vim's `zz`, `zt` and `zb`. Only the view moves; the cursor stays where it is.
+
+pub fn cursor_center(ctx: &CommandContext) -> CommandOutcome {
+    let y = ctx.cursor_position.y;
+    let top = y.saturating_sub(ctx.view_height.saturating_sub(1) / 2);
+    scroll_to(ctx, top, y)
+}
+
+pub fn cursor_top(ctx: &CommandContext) -> CommandOutcome {
+    let y = ctx.cursor_position.y;
+    scroll_to(ctx, y, y)
+}
+
+pub fn cursor_bottom(ctx: &CommandContext) -> CommandOutcome {
+    let y = ctx.cursor_position.y;
+    let top = y.saturating_add(1).saturating_sub(ctx.view_height);
+    scroll_to(ctx, top, y)
+}
+
+// Scrolls the view a line down or up, like Vim's Ctrl-E and Ctrl-Y.
+// The cursor only moves if it would leave the view.
+
+pub fn viewport_down(ctx: &CommandContext) -> CommandOutcome {
+    scroll_to(ctx, ctx.view_top.saturating_add(1), ctx.cursor_position.y)
+}
+
+pub fn viewport_up(ctx: &CommandContext) -> CommandOutcome {
+    scroll_to(ctx, ctx.view_top.saturating_sub(1), ctx.cursor_position.y)
+}
+
+// Scrolls the view and moves the cursor half a page down or up, like Vim's Ctrl-D and Ctrl-U.
+// The view stops once the last row is at its bottom; the cursor keeps going to that row.
+
+pub fn half_page_down(ctx: &CommandContext) -> CommandOutcome {
+    let half = half_page(ctx);
+    let last_top = ctx.document.len().saturating_sub(ctx.view_height);
+    let top = cmp::min(
+        ctx.view_top.saturating_add(half),
+        cmp::max(last_top, ctx.view_top),
+    );
+    let y = cmp::min(
+        ctx.cursor_position.y.saturating_add(half),
+        ctx.document.len().saturating_sub(1),
+    );
+    scroll_to(ctx, top, y)
+}
+
+pub fn half_page_up(ctx: &CommandContext) -> CommandOutcome {
+    let half = half_page(ctx);
+    let top = ctx.view_top.saturating_sub(half);
+    scroll_to(ctx, top, ctx.cursor_position.y.saturating_sub(half))
+}
+
+fn half_page(ctx: &CommandContext) -> usize {
+    cmp::max(ctx.view_height / 2, 1)
+}
+
+// Puts `top` at the top of the view and the cursor on row `y`.
+// The top row never goes past the last row of the document, and the cursor is kept in view.
+fn scroll_to(ctx: &CommandContext, top: usize, y: usize) -> CommandOutcome {
+    let top = cmp::min(top, ctx.document.len().saturating_sub(1));
+    let bottom = top.saturating_add(ctx.view_height).saturating_sub(1);
+    let y = y.clamp(top, cmp::max(bottom, top));
+
+    let Position { mut x, y: old_y } = ctx.cursor_position;
+    if y != old_y {
+        x = cmp::min(x, ctx.document.row(y).map_or(0, Row::len));
+    }
+    CommandOutcome::ScrollTo {
+        top,
+        cursor: Position { x, y },
+    }
+}
@@ -1,8 +1,9 @@
-use crate::editor::{Editor, Position};
+use super::CommandContext;
+use crate::editor::Position;
 
-pub fn scroll_up(editor: &mut Editor) {
-    let Position { mut y, mut x } = editor.cursor_position;
-    let terminal_height = editor.terminal.size().height as usize;
+pub fn scroll_up(ctx: &CommandContext) -> Position {
+    let Position { mut y, mut x } = ctx.cursor_position;
+    let terminal_height = ctx.view_height;
 
     y = if y > terminal_height {
         y - terminal_height
@@ -10,13 +11,13 @@ pub fn scroll_up(editor: &mut Editor) {
         0
     };
 
-    editor.cursor_position = Position { x, y }
+    Position { x, y }
 }
 
-pub fn scroll_down(editor: &mut Editor) {
-    let Position { mut y, mut x } = editor.cursor_position;
-    let terminal_height = editor.terminal.size().height as usize;
-    let height = editor.document.len();
+pub fn scroll_down(ctx: &CommandContext) -> Position {
+    let Position { mut y, mut x } = ctx.cursor_position;
+    let terminal_height = ctx.view_height;
+    let height = ctx.document.len();
 
     y = if y.saturating_add(terminal_height) < height {
         y.saturating_add(terminal_height)
@@ -24,5 +25,5 @@ pub fn scroll_down(editor: &mut Editor) {
         height
     };
 
-    editor.cursor_position = Position { x, y }
+    Position { x, y }
 }
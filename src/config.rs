@@ -0,0 +1,284 @@
+use crate::document::LONG_LINE_LENGTH;
+use crate::error::ZenError;
+use crate::hooks::Hook;
+use crate::terminal::ColorSupport;
+
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const DEFAULT_WELCOME_MESSAGE: &str = "Zen {version}\nHELP: i = insert | Ctrl-F = find | :q = quit";
+
+// Lines at the top and bottom of a file looked at for modelines, like Vim's default.
+pub const MODELINES: usize = 5;
+
+/// User configuration, loaded from `~/.config/zen/config.toml`.
+/// Every field is optional in the file and falls back to its default.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // Overrides the detected color support: "truecolor", "256", "16" or "monochrome".
+    pub colors: Option<ColorSupport>,
+
+    // Draws in color, like `:set termcolors`. Turned off, zen draws as if colors = "monochrome"
+    // until it's turned on again.
+    pub term_colors: bool,
+
+    // Columns a tab stands for, e.g. when converting indentation with :retab.
+    // Documents can have their own with `:setlocal tabstop=2` or a modeline, like text_width.
+    pub tab_width: usize,
+
+    // Column lines are wrapped at when reflowing paragraphs with `gq`.
+    pub text_width: usize,
+
+    // Seconds a message stays in the message bar. Errors stay until the next keypress.
+    pub message_timeout: u64,
+
+    // Draws tabs, trailing spaces and non-breaking spaces as dim symbols, like `:set list`.
+    pub list: bool,
+
+    // Draws only trailing whitespace as dim symbols, like `:set trailing`.
+    pub trailing: bool,
+
+    // Shows where the view is in the document in the text area's last column, like `:set scrollbar`.
+    pub scrollbar: bool,
+
+    // Highlights the matches of the last search in view, like `:set hlsearch`.
+    pub highlight_search: bool,
+
+    // Draws the cursor as a block in Normal mode and a bar in Insert mode. Turn it off for
+    // terminals that don't understand the escape for it (DECSCUSR) and print it instead.
+    pub cursor_shape: bool,
+
+    // Underlines words that aren't in the dictionary in text and Markdown files, like `:set spell`.
+    // Words in `words.txt` next to the config file are added to the ones zen comes with.
+    pub spell: bool,
+
+    // Searches go on from the other end of the document once they reach one, like `:set wrapscan`.
+    pub wrap_search: bool,
+
+    // Times each part of handling keys and drawing frames, like `:set profiling`. `:profile`
+    // shows the percentiles, and they're logged on quitting, e.g. with `RUST_LOG=info`.
+    pub profiling: bool,
+
+    // Keeps unsaved changes in recovery files, written a moment after each change and when zen
+    // is killed with SIGTERM or its terminal goes away. Opening a file that has one offers to
+    // recover it. They're kept in `$XDG_STATE_HOME/zen/recovery`, or `~/.local/state/zen/recovery`.
+    pub recovery: bool,
+
+    // Rows longer than this many characters are drawn plain and only as far as they're in view,
+    // e.g. minified code. 0 highlights rows however long they are.
+    pub long_line_length: usize,
+
+    // Words expanded as they're typed in Insert mode, e.g. `teh = "the"`.
+    pub abbreviations: HashMap<String, String>,
+
+    // Drawn at the start of each line past the end of the file, in a muted color. Set it to ""
+    // to leave those lines blank.
+    pub filler: String,
+
+    // Shown on an empty document, one line per `\n`, with `{version}` replaced by zen's version.
+    // Set it to "" to only show the filler.
+    #[serde(deserialize_with = "non_empty")]
+    pub welcome_message: Option<String>,
+
+    // Actions run on editor events, in the order they're listed. Each `[[hooks]]` table names
+    // an `event` and what to `run`: a built-in action or a `:` command, e.g. ":retab".
+    pub hooks: Vec<Hook>,
+
+    // Key `<leader>` stands for in mappings.
+    pub leader: char,
+
+    // Keys mapped to other keys in Normal and Insert mode, written like `:map` takes them,
+    // e.g. `"<leader>w" = ":w<CR>"`. Mappings take precedence over the built-in keys.
+    pub nmap: HashMap<String, String>,
+    pub imap: HashMap<String, String>,
+
+    // Build commands `:make` runs, by file type, e.g. `rs = "cargo build --message-format=short"`.
+    // File types without one run `make`.
+    pub makeprg: HashMap<String, String>,
+
+    // Digraphs added to the built-in ones `Ctrl-K` inserts, e.g. `"qq" = "ℚ"`.
+    pub digraphs: HashMap<String, char>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            colors: None,
+            term_colors: true,
+            tab_width: 4,
+            text_width: 80,
+            message_timeout: 5,
+            list: false,
+            trailing: false,
+            scrollbar: false,
+            highlight_search: true,
+            cursor_shape: true,
+            spell: false,
+            wrap_search: true,
+            profiling: false,
+            recovery: true,
+            long_line_length: LONG_LINE_LENGTH,
+            abbreviations: HashMap::new(),
+            filler: String::from("~"),
+            welcome_message: Some(String::from(DEFAULT_WELCOME_MESSAGE)),
+            hooks: Vec::new(),
+            leader: '\\',
+            nmap: HashMap::new(),
+            imap: HashMap::new(),
+            makeprg: HashMap::new(),
+            digraphs: HashMap::new(),
+        }
+    }
+}
+
+/// Settings of a single document, overriding the config's where they're set, from
+/// `:setlocal` or the file's modeline. None follows the config.
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct LocalConfig {
+    pub tab_width: Option<usize>,
+    pub text_width: Option<usize>,
+    pub list: Option<bool>,
+    pub trailing: Option<bool>,
+}
+
+impl LocalConfig {
+    // Reads one setting the way `:setlocal` and modelines write it, e.g. `ts=2` or `nolist`.
+    pub fn set(&mut self, setting: &str) -> Result<(), ZenError> {
+        let (name, value) = match setting.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (setting, None),
+        };
+        let number = || {
+            value
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| ZenError::CommandDispatch(format!("Invalid number: {}", setting)))
+        };
+        match (name, value) {
+            ("ts" | "tabstop", _) => self.tab_width = Some(number()?),
+            ("tw" | "textwidth", _) => self.text_width = Some(number()?),
+            ("list", None) => self.list = Some(true),
+            ("nolist", None) => self.list = Some(false),
+            ("trailing", None) => self.trailing = Some(true),
+            ("notrailing", None) => self.trailing = Some(false),
+            _ => {
+                return Err(ZenError::CommandDispatch(format!(
+                    "Unknown option: {}",
+                    setting
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    // The settings of a modeline, `vim: ts=4 tw=72` or `/* vim: set ts=4: */`, if the line
+    // has one. Options zen doesn't know are skipped, since modelines are mostly written for Vim.
+    pub fn from_modeline(line: &str) -> Option<Self> {
+        let rest = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+            line.match_indices(marker)
+                .find(|(at, _)| {
+                    line[..*at]
+                        .chars()
+                        .next_back()
+                        .is_none_or(char::is_whitespace)
+                })
+                .map(|(at, _)| &line[at.saturating_add(marker.len())..])
+        })?;
+
+        let rest = rest.trim_start();
+        let settings: Vec<&str> = match rest
+            .strip_prefix("set ")
+            .or_else(|| rest.strip_prefix("se "))
+        {
+            // The second form ends at a colon, with anything after it left alone.
+            Some(options) => options
+                .split(':')
+                .next()
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect(),
+            None => rest
+                .split(|c: char| c == ':' || c.is_whitespace())
+                .filter(|setting| !setting.is_empty())
+                .collect(),
+        };
+
+        let mut local = Self::default();
+        for setting in settings {
+            let _ = local.set(setting);
+        }
+        Some(local)
+    }
+
+    // Takes the settings the other one has, keeping the rest.
+    pub fn merge(&mut self, other: Self) {
+        self.tab_width = other.tab_width.or(self.tab_width);
+        self.text_width = other.text_width.or(self.text_width);
+        self.list = other.list.or(self.list);
+        self.trailing = other.trailing.or(self.trailing);
+    }
+}
+
+impl Config {
+    // Loads the config file, falling back to defaults when there is none.
+    // An invalid file is an error, so the editor can tell the user why it was ignored.
+    pub fn load() -> Result<Self, ZenError> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => {
+                Self::parse(&contents).map_err(|source| ZenError::ConfigParse { path, source })
+            }
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+
+    // Location of the config file, respecting $XDG_CONFIG_HOME.
+    pub fn path() -> Option<PathBuf> {
+        Some(config_dir()?.join("config.toml"))
+    }
+
+    // Directory of `.zen` scripts run at startup, next to the config file.
+    pub fn after_dir() -> Option<PathBuf> {
+        Some(config_dir()?.join("after"))
+    }
+
+    // Words spell checking allows on top of the ones zen comes with, one per line.
+    pub fn word_list() -> Option<PathBuf> {
+        Some(config_dir()?.join("words.txt"))
+    }
+
+    // Directory recovery files are kept in, respecting $XDG_STATE_HOME.
+    pub fn recovery_dir() -> Option<PathBuf> {
+        let state_dir = env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))?;
+        Some(state_dir.join("zen").join("recovery"))
+    }
+}
+
+fn config_dir() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_dir.join("zen"))
+}
+
+// An empty string turns an optional setting off.
+fn non_empty<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    Ok(Some(value).filter(|value| !value.is_empty()))
+}
@@ -0,0 +1,155 @@
+use similar::{ChangeTag, TextDiff};
+
+// Unchanged lines shown around each hunk of a unified diff.
+const UNIFIED_CONTEXT: usize = 3;
+
+/// How a line of the diff view relates the file on disk to the buffer.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum DiffTag {
+    // Present in both, unchanged.
+    Equal,
+
+    // Only present in the buffer.
+    Added,
+
+    // Only present on disk.
+    Removed,
+}
+
+/// One row of the side by side diff view.
+/// Lines missing from one side are left as filler on that side.
+#[derive(Clone, Debug)]
+pub struct DiffLine {
+    pub tag: DiffTag,
+
+    // Index of the line in the buffer, if it has one there.
+    pub buffer_line: Option<usize>,
+
+    // Index of the line on disk, if it has one there.
+    pub disk_line: Option<usize>,
+}
+
+/// Line based diff between a document's on-disk contents and its buffer.
+pub struct Diff {
+    // The contents the buffer is compared against.
+    disk: Vec<String>,
+
+    // Aligned rows, in display order.
+    lines: Vec<DiffLine>,
+
+    // Buffer line each hunk starts on, in ascending order.
+    hunks: Vec<usize>,
+}
+
+impl Diff {
+    // Compares the buffer's contents against what's on disk.
+    pub fn new(disk: &str, buffer: &str) -> Self {
+        let mut diff = Self {
+            disk: disk.lines().map(String::from).collect(),
+            lines: Vec::new(),
+            hunks: Vec::new(),
+        };
+        diff.update(buffer);
+        diff
+    }
+
+    // Recomputes the diff after the buffer changed, keeping the disk side as it was.
+    // Expects the buffer with a newline after every line, as Document::contents returns it.
+    pub fn update(&mut self, buffer: &str) {
+        let disk: String = self
+            .disk
+            .iter()
+            .flat_map(|line| [line.as_str(), "\n"])
+            .collect();
+        let text_diff = TextDiff::from_lines(&disk, buffer);
+
+        self.lines.clear();
+        self.hunks.clear();
+
+        // Buffer line the next change lands on; removed lines have no buffer index of their own.
+        let mut next_buffer_line = 0;
+        let mut in_hunk = false;
+
+        for change in text_diff.iter_all_changes() {
+            let tag = match change.tag() {
+                ChangeTag::Equal => DiffTag::Equal,
+                ChangeTag::Insert => DiffTag::Added,
+                ChangeTag::Delete => DiffTag::Removed,
+            };
+
+            if tag == DiffTag::Equal {
+                in_hunk = false;
+            } else if !in_hunk {
+                in_hunk = true;
+                self.hunks.push(next_buffer_line);
+            }
+
+            if let Some(index) = change.new_index() {
+                next_buffer_line = index.saturating_add(1);
+            }
+
+            self.lines.push(DiffLine {
+                tag,
+                buffer_line: change.new_index(),
+                disk_line: change.old_index(),
+            });
+        }
+    }
+
+    pub fn lines(&self) -> &[DiffLine] {
+        &self.lines
+    }
+
+    pub fn disk_line(&self, index: usize) -> Option<&str> {
+        self.disk.get(index).map(String::as_str)
+    }
+
+    pub fn hunk_count(&self) -> usize {
+        self.hunks.len()
+    }
+
+    // Display row of the given buffer line, or the end of the view if it's past the last line.
+    pub fn row_of(&self, buffer_line: usize) -> usize {
+        self.lines
+            .iter()
+            .position(|line| line.buffer_line == Some(buffer_line))
+            .unwrap_or(self.lines.len())
+    }
+
+    // Start of the first hunk below the given buffer line.
+    pub fn next_hunk(&self, buffer_line: usize) -> Option<usize> {
+        self.hunks.iter().copied().find(|&hunk| hunk > buffer_line)
+    }
+
+    // Start of the last hunk above the given buffer line.
+    pub fn prev_hunk(&self, buffer_line: usize) -> Option<usize> {
+        self.hunks
+            .iter()
+            .rev()
+            .copied()
+            .find(|&hunk| hunk < buffer_line)
+    }
+}
+
+// Unified diff of the buffer against the file on disk, one line per entry.
+// Hunk headers and context lines are tagged Equal, and lines keep their `+`, `-` or ` ` prefix.
+pub fn unified(disk: &str, buffer: &str) -> Vec<(DiffTag, String)> {
+    let text_diff = TextDiff::from_lines(disk, buffer);
+    let mut unified_diff = text_diff.unified_diff();
+    unified_diff.context_radius(UNIFIED_CONTEXT);
+
+    let mut lines = Vec::new();
+    for hunk in unified_diff.iter_hunks() {
+        lines.push((DiffTag::Equal, hunk.header().to_string()));
+        for change in hunk.iter_changes() {
+            let (tag, sign) = match change.tag() {
+                ChangeTag::Equal => (DiffTag::Equal, ' '),
+                ChangeTag::Insert => (DiffTag::Added, '+'),
+                ChangeTag::Delete => (DiffTag::Removed, '-'),
+            };
+            let line = change.value().trim_end_matches('\n');
+            lines.push((tag, format!("{}{}", sign, line)));
+        }
+    }
+    lines
+}
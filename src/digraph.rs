@@ -0,0 +1,219 @@
+use crate::error::ZenError;
+
+use std::collections::HashMap;
+
+/// Digraphs inserted with `Ctrl-K` in Insert mode: two characters standing for one that's hard
+/// to type, e.g. `a:` for ä or `->` for →. The built-in ones are a subset of RFC 1345's, and
+/// the config can add more or override them.
+#[derive(Default)]
+pub struct Digraphs {
+    custom: HashMap<(char, char), char>,
+}
+
+// Accents are the second character: `!` grave, `'` acute, `>` circumflex, `?` tilde,
+// `:` diaeresis, `,` cedilla, `/` stroke, `<` caron, `-` macron and `a` ring.
+// Greek letters are the Latin ones followed by `*`.
+const DIGRAPHS: &[(char, char, char)] = &[
+    // Latin letters with accents
+    ('A', '!', 'À'),
+    ('A', '\'', 'Á'),
+    ('A', '>', 'Â'),
+    ('A', '?', 'Ã'),
+    ('A', ':', 'Ä'),
+    ('A', 'A', 'Å'),
+    ('A', 'E', 'Æ'),
+    ('C', ',', 'Ç'),
+    ('E', '!', 'È'),
+    ('E', '\'', 'É'),
+    ('E', '>', 'Ê'),
+    ('E', ':', 'Ë'),
+    ('I', '!', 'Ì'),
+    ('I', '\'', 'Í'),
+    ('I', '>', 'Î'),
+    ('I', ':', 'Ï'),
+    ('N', '?', 'Ñ'),
+    ('O', '!', 'Ò'),
+    ('O', '\'', 'Ó'),
+    ('O', '>', 'Ô'),
+    ('O', '?', 'Õ'),
+    ('O', ':', 'Ö'),
+    ('O', '/', 'Ø'),
+    ('O', 'E', 'Œ'),
+    ('S', '<', 'Š'),
+    ('U', '!', 'Ù'),
+    ('U', '\'', 'Ú'),
+    ('U', '>', 'Û'),
+    ('U', ':', 'Ü'),
+    ('Y', '\'', 'Ý'),
+    ('Z', '<', 'Ž'),
+    ('a', '!', 'à'),
+    ('a', '\'', 'á'),
+    ('a', '>', 'â'),
+    ('a', '?', 'ã'),
+    ('a', ':', 'ä'),
+    ('a', 'a', 'å'),
+    ('a', 'e', 'æ'),
+    ('c', ',', 'ç'),
+    ('c', '<', 'č'),
+    ('e', '!', 'è'),
+    ('e', '\'', 'é'),
+    ('e', '>', 'ê'),
+    ('e', ':', 'ë'),
+    ('e', '-', 'ē'),
+    ('i', '!', 'ì'),
+    ('i', '\'', 'í'),
+    ('i', '>', 'î'),
+    ('i', ':', 'ï'),
+    ('n', '?', 'ñ'),
+    ('o', '!', 'ò'),
+    ('o', '\'', 'ó'),
+    ('o', '>', 'ô'),
+    ('o', '?', 'õ'),
+    ('o', ':', 'ö'),
+    ('o', '/', 'ø'),
+    ('o', 'e', 'œ'),
+    ('s', '<', 'š'),
+    ('s', 's', 'ß'),
+    ('u', '!', 'ù'),
+    ('u', '\'', 'ú'),
+    ('u', '>', 'û'),
+    ('u', ':', 'ü'),
+    ('y', '\'', 'ý'),
+    ('y', ':', 'ÿ'),
+    ('z', '<', 'ž'),
+    // Greek letters
+    ('A', '*', 'Α'),
+    ('B', '*', 'Β'),
+    ('G', '*', 'Γ'),
+    ('D', '*', 'Δ'),
+    ('Q', '*', 'Θ'),
+    ('L', '*', 'Λ'),
+    ('P', '*', 'Π'),
+    ('S', '*', 'Σ'),
+    ('F', '*', 'Φ'),
+    ('W', '*', 'Ω'),
+    ('a', '*', 'α'),
+    ('b', '*', 'β'),
+    ('g', '*', 'γ'),
+    ('d', '*', 'δ'),
+    ('e', '*', 'ε'),
+    ('z', '*', 'ζ'),
+    ('y', '*', 'η'),
+    ('h', '*', 'θ'),
+    ('i', '*', 'ι'),
+    ('k', '*', 'κ'),
+    ('l', '*', 'λ'),
+    ('m', '*', 'μ'),
+    ('n', '*', 'ν'),
+    ('c', '*', 'ξ'),
+    ('p', '*', 'π'),
+    ('r', '*', 'ρ'),
+    ('s', '*', 'σ'),
+    ('t', '*', 'τ'),
+    ('u', '*', 'υ'),
+    ('f', '*', 'φ'),
+    ('x', '*', 'χ'),
+    ('q', '*', 'ψ'),
+    ('w', '*', 'ω'),
+    // Arrows
+    ('<', '-', '←'),
+    ('-', '>', '→'),
+    ('-', '!', '↑'),
+    ('-', 'v', '↓'),
+    ('<', '>', '↔'),
+    ('<', '=', '⇐'),
+    ('=', '>', '⇒'),
+    ('=', '=', '⇔'),
+    // Mathematics
+    ('+', '-', '±'),
+    ('*', 'X', '×'),
+    ('-', ':', '÷'),
+    ('!', '=', '≠'),
+    ('=', '<', '≤'),
+    ('>', '=', '≥'),
+    ('?', '2', '≈'),
+    ('=', '3', '≡'),
+    ('0', '0', '∞'),
+    ('R', 'T', '√'),
+    ('F', 'A', '∀'),
+    ('d', 'P', '∂'),
+    ('T', 'E', '∃'),
+    ('/', '0', '∅'),
+    ('(', '-', '∈'),
+    ('(', 'U', '∩'),
+    (')', 'U', '∪'),
+    ('I', 'n', '∫'),
+    ('D', 'G', '°'),
+    ('1', '2', '½'),
+    ('1', '4', '¼'),
+    ('3', '4', '¾'),
+    ('2', 'S', '²'),
+    ('3', 'S', '³'),
+    ('M', 'y', 'µ'),
+    // Currency
+    ('C', 't', '¢'),
+    ('P', 'd', '£'),
+    ('Y', 'e', '¥'),
+    ('E', 'u', '€'),
+    // Punctuation and symbols
+    ('S', 'E', '§'),
+    ('P', 'I', '¶'),
+    ('C', 'o', '©'),
+    ('R', 'g', '®'),
+    ('T', 'M', '™'),
+    ('<', '<', '«'),
+    ('>', '>', '»'),
+    ('!', 'I', '¡'),
+    ('?', 'I', '¿'),
+    ('\'', '6', '‘'),
+    ('\'', '9', '’'),
+    ('"', '6', '“'),
+    ('"', '9', '”'),
+    ('-', 'N', '–'),
+    ('-', 'M', '—'),
+    ('.', '.', '‥'),
+    (',', '.', '…'),
+    ('.', 'M', '·'),
+    ('O', 'K', '✓'),
+    ('X', 'X', '✗'),
+    ('N', 'S', '\u{a0}'),
+];
+
+impl Digraphs {
+    // The built-in digraphs along with the ones from the config, which are written as two
+    // characters mapped to the one they stand for, e.g. `"qq" = "ℚ"`.
+    pub fn new(custom: &HashMap<String, char>) -> Result<Self, ZenError> {
+        let mut digraphs = Self::default();
+        for (keys, c) in custom {
+            let mut chars = keys.chars();
+            let (Some(first), Some(second), None) = (chars.next(), chars.next(), chars.next())
+            else {
+                return Err(ZenError::CommandDispatch(format!(
+                    "Digraphs are two characters: {}",
+                    keys
+                )));
+            };
+            digraphs.custom.insert((first, second), *c);
+        }
+        Ok(digraphs)
+    }
+
+    // The character the two stand for, the config's first. Like in Vim, the characters
+    // may be typed the other way around when that isn't a digraph of its own.
+    pub fn get(&self, first: char, second: char) -> Option<char> {
+        self.custom
+            .get(&(first, second))
+            .copied()
+            .or_else(|| lookup(first, second))
+            .or_else(|| self.custom.get(&(second, first)).copied())
+            .or_else(|| lookup(second, first))
+    }
+}
+
+// Looks a digraph up among the built-in ones, in the order it's written.
+pub fn lookup(first: char, second: char) -> Option<char> {
+    DIGRAPHS
+        .iter()
+        .find(|(a, b, _)| *a == first && *b == second)
+        .map(|(_, _, c)| *c)
+}
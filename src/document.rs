@@ -1,18 +1,102 @@
+use crate::config::{Config, LocalConfig, MODELINES};
 use crate::editor::{Position, SearchDirection};
-use crate::row::Row;
+use crate::encoding::Encoding;
+use crate::error::ZenError;
+use crate::hex::{self, HexView};
+use crate::history::{Change, History};
+use crate::loader::{LoadChunk, Loader, INCREMENTAL_LOAD_SIZE};
+use crate::row::{CaseDirection, Row};
+use crate::search::Pattern;
+use crate::terminal::ColorSupport;
 
+use std::cmp;
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fs;
-use std::io::Write;
+use std::io::{self, Read, Seek};
+use std::mem;
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use syntect::easy::HighlightLines;
-use syntect::highlighting::ThemeSet;
-use syntect::parsing::SyntaxSet;
+use syntect::highlighting::{Color, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use tokio::io::AsyncWriteExt;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Theme used to highlight documents and paint the text area.
+const THEME: &str = "base16-ocean.dark";
+
+// Rows longer than this many characters aren't highlighted by default, e.g. minified code.
+pub const LONG_LINE_LENGTH: usize = 3_000;
+
+/// Text between two positions, from `start` up to but not including `end`.
+/// Rows in between are covered whole, e.g. after a motion over several lines.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Selection {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Selection {
+    // Every row from `rows.start` up to `rows.end`, as a whole.
+    pub fn rows(rows: Range<usize>) -> Self {
+        Self {
+            start: Position {
+                x: 0,
+                y: rows.start,
+            },
+            end: Position { x: 0, y: rows.end },
+        }
+    }
+}
+
+/// Text replacing what's in `range`, which may span rows; either may be empty.
+/// Edits applied together all refer to positions in the document as it was before them.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Edit {
+    pub range: Selection,
+    pub text: String,
+}
+
+/// Rows an edit, undo or redo replaced: `removed` rows from `at` on became `inserted` rows.
+/// The editor follows these to keep what it tracks by row, like folds, in place.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RowSplice {
+    pub at: usize,
+    pub removed: usize,
+    pub inserted: usize,
+}
+
+/// Where a document came from: a new buffer, a file read from disk, or a file that couldn't be
+/// read, in which case the document is empty and saving it asks for a name rather than writing
+/// over the file.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub enum DocumentOrigin {
+    #[default]
+    New,
+    OpenedFromDisk {
+        path: PathBuf,
+    },
+    OpenFailed {
+        path: PathBuf,
+        error: String,
+    },
+}
+
+/// Which syntax highlights a document.
+#[derive(Clone, Debug)]
+enum SyntaxChoice {
+    // The one for the file's extension, if there is one.
+    ByExtension,
+
+    Named(Box<SyntaxReference>),
+
+    // None, with `:set syntax=off`.
+    Off,
+}
 
 /// Representation of a file, existing or new.
-#[derive(Default)]
 pub struct Document {
     // {name.extension} - Optional in the case of a new file that hasn't been saved.
     pub file_name: Option<String>,
@@ -20,12 +104,58 @@ pub struct Document {
     // {extension} - ex=rs,ts,go,md,toml
     file_type: String,
 
+    // Syntax chosen with `:set syntax=`, in place of the one the extension picks.
+    syntax: SyntaxChoice,
+
+    // Settings for this document alone, from `:setlocal` or the file's modeline.
+    pub local_config: LocalConfig,
+
+    // Whether the document is new, was read from a file, or stands in for one that couldn't be.
+    origin: DocumentOrigin,
+
     // Represents the file's contents, can be seen as a vec of lines.
     rows: Vec<Row>,
 
     // Has the document been modified since opening?
     dirty: bool,
 
+    // What the file is written in. Rows are UTF-8 in memory either way.
+    encoding: Encoding,
+
+    // Blocks edits and saving when set, e.g. by `--readonly`.
+    read_only: bool,
+
+    // How much of the file is read while the rest is still loading, in percent.
+    loading: Option<u8>,
+
+    // The file's bytes while it's shown as a hex dump, which is what the rows hold then.
+    hex: Option<HexView>,
+
+    // The text's rows, put aside while `:hex` shows them as a dump.
+    // None for binary files, which have no text to go back to.
+    text: Option<Vec<Row>>,
+
+    // Every edit made to the rows, for undo and redo.
+    history: History,
+
+    // How many edits were made so far, so the editor can tell when a key changed the text.
+    edits: usize,
+
+    // Rows replaced since the editor last took them.
+    splices: Vec<RowSplice>,
+
+    // Rows edited since the last highlight.
+    // Only these, and the rows after them, are highlighted again.
+    dirty_rows: HashSet<usize>,
+
+    // Rows and colors of the last highlight.
+    // Scrolling or changing colors highlights the whole view again.
+    highlighted: Option<(Range<usize>, ColorSupport)>,
+
+    // Rows longer than this are left plain, since highlighting them takes too long.
+    // 0 highlights every row.
+    long_line_length: usize,
+
     // A guideline on how to highlight the document's filetype.
     syntax_set: SyntaxSet,
 
@@ -33,38 +163,286 @@ pub struct Document {
     theme_set: ThemeSet,
 }
 
+impl Default for Document {
+    // An empty, unnamed document. Syntaxes and themes are still loaded so
+    // the text area is painted consistently and highlighting works once it's named.
+    fn default() -> Self {
+        Self {
+            file_name: None,
+            file_type: String::new(),
+            syntax: SyntaxChoice::ByExtension,
+            local_config: LocalConfig::default(),
+            origin: DocumentOrigin::New,
+            rows: Vec::new(),
+            dirty: false,
+            encoding: Encoding::default(),
+            read_only: false,
+            loading: None,
+            hex: None,
+            text: None,
+            history: History::default(),
+            edits: 0,
+            splices: Vec::new(),
+            dirty_rows: HashSet::new(),
+            highlighted: None,
+            long_line_length: LONG_LINE_LENGTH,
+            syntax_set: SyntaxSet::load_defaults_newlines(),
+            theme_set: ThemeSet::load_defaults(),
+        }
+    }
+}
+
 impl Document {
+    // An unnamed document holding the text, e.g. what `zen --pipe` reads from stdin.
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            rows: text.lines().map(Row::from).collect(),
+            ..Self::default()
+        }
+    }
+
     // Creates a new document (opens a file) based on the filename/path given.
-    pub fn open(filename: &str) -> Result<Self, std::io::Error> {
+    // Binary files are shown as a read-only hex dump.
+    pub async fn open(filename: &str) -> Result<Self, ZenError> {
         // Grab the contents of the file
-        let contents = fs::read_to_string(filename)?;
-
-        let file_type = Path::new(filename)
-            .extension()
-            .and_then(OsStr::to_str)
-            .unwrap_or(&"Unknown");
+        let bytes = tokio::fs::read(filename)
+            .await
+            .map_err(|error| ZenError::io(filename, error))?;
+        Ok(Self::from_bytes(filename, bytes))
+    }
 
-        let mut rows = Vec::new();
+    // Creates the document for a file read whole.
+    fn from_bytes(filename: &str, bytes: Vec<u8>) -> Self {
+        if hex::is_binary(&bytes) {
+            let view = HexView::new(bytes);
+            return Self {
+                file_name: Some(filename.to_string()),
+                file_type: file_type_of(filename),
+                origin: DocumentOrigin::OpenedFromDisk {
+                    path: PathBuf::from(filename),
+                },
+                rows: view.rows(),
+                hex: Some(view),
+                ..Self::default()
+            };
+        }
+        // Text that isn't UTF-8 is read in a single-byte encoding, and saved back in it.
+        let (contents, encoding) = match String::from_utf8(bytes) {
+            Ok(contents) => (contents, Encoding::Utf8),
+            Err(error) => Encoding::decode_single_byte(error.as_bytes()),
+        };
 
-        let ss = SyntaxSet::load_defaults_newlines();
-        let ts = ThemeSet::load_defaults();
+        let mut document = Self {
+            rows: contents.lines().map(Row::from).collect(),
+            file_name: Some(filename.to_string()),
+            file_type: file_type_of(filename),
+            origin: DocumentOrigin::OpenedFromDisk {
+                path: PathBuf::from(filename),
+            },
+            encoding,
+            ..Self::default()
+        };
+        document.read_modelines();
+        document
+    }
 
-        for value in contents.lines() {
-            rows.push(Row::from(value));
+    // Opens big files with only their first chunk read, and returns the loader that reads
+    // the rest. Files up to INCREMENTAL_LOAD_SIZE are read whole, with no loader.
+    pub fn open_incremental(filename: &str) -> Result<(Self, Option<Loader>), ZenError> {
+        let file = fs::File::open(filename).map_err(|error| ZenError::io(filename, error))?;
+        let size = file
+            .metadata()
+            .map_err(|error| ZenError::io(filename, error))?
+            .len();
+        // Only called while the editor starts up, before there's a runtime to await on.
+        if size <= INCREMENTAL_LOAD_SIZE || is_binary_file(&file) {
+            let bytes = fs::read(filename).map_err(|error| ZenError::io(filename, error))?;
+            return Ok((Self::from_bytes(filename, bytes), None));
         }
 
-        Ok(Self {
-            rows,
+        let mut loader = Loader::new(file, size);
+        let chunk = loader
+            .next_chunk()
+            .map_err(|error| ZenError::io(filename, error))?;
+        let mut document = Self {
             file_name: Some(filename.to_string()),
-            dirty: false,
-            file_type: file_type.to_string(),
-            syntax_set: ss,
-            theme_set: ts,
-        })
+            file_type: file_type_of(filename),
+            origin: DocumentOrigin::OpenedFromDisk {
+                path: PathBuf::from(filename),
+            },
+            loading: Some(0),
+            ..Self::default()
+        };
+        let done = chunk.done;
+        document.append_loaded(chunk);
+        Ok((document, (!done).then_some(loader)))
+    }
+
+    // The empty document that stands in for a file that couldn't be opened. It has no name,
+    // so saving it asks for one instead of writing over the file.
+    pub fn open_failed(filename: &str, error: &ZenError) -> Self {
+        Self {
+            origin: DocumentOrigin::OpenFailed {
+                path: PathBuf::from(filename),
+                error: error.to_string(),
+            },
+            ..Self::default()
+        }
+    }
+
+    // Adds the rows a loader read to the end of the document. They're part of the file
+    // as it was opened, so they're neither an edit nor undoable.
+    pub fn append_loaded(&mut self, chunk: LoadChunk) {
+        if self.loading.is_none() {
+            return;
+        }
+        self.dirty_rows.insert(self.rows.len());
+        self.rows
+            .extend(chunk.lines.iter().map(|line| Row::from(line.as_str())));
+        self.loading = (!chunk.done).then_some(chunk.percent);
+        self.encoding = chunk.encoding;
+        if chunk.done {
+            self.read_modelines();
+        }
+    }
+
+    // Stops waiting on the rest of the file, e.g. when reading it failed.
+    pub fn stop_loading(&mut self) {
+        self.loading = None;
+    }
+
+    // How much of the file is read while it's still loading, in percent.
+    pub fn load_progress(&self) -> Option<u8> {
+        self.loading
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    pub fn origin(&self) -> &DocumentOrigin {
+        &self.origin
+    }
+
+    // Whether the document wasn't read from a file, nor stands in for one that couldn't be read.
+    pub fn is_new_file(&self) -> bool {
+        matches!(self.origin, DocumentOrigin::New)
+    }
+
+    pub fn file_type(&self) -> &str {
+        &self.file_type
+    }
+
+    // Highlights the document with the syntax of that name, like `:set syntax=python`, whatever
+    // its extension. Names are matched ignoring case, and `off` turns highlighting off.
+    pub fn set_syntax(&mut self, syntax_name: &str) -> Result<(), ZenError> {
+        self.syntax = if syntax_name == "off" {
+            SyntaxChoice::Off
+        } else {
+            let syntax = self
+                .syntax_set
+                .find_syntax_by_name(syntax_name)
+                .or_else(|| {
+                    self.syntax_set
+                        .syntaxes()
+                        .iter()
+                        .find(|syntax| syntax.name.eq_ignore_ascii_case(syntax_name))
+                })
+                .ok_or_else(|| {
+                    ZenError::CommandDispatch(format!("Unknown syntax: {}", syntax_name))
+                })?;
+            SyntaxChoice::Named(Box::new(syntax.clone()))
+        };
+        for row in &mut self.rows {
+            row.clear_highlighting();
+        }
+        self.highlighted = None;
+        Ok(())
+    }
+
+    // The syntax highlighting the document: the one set, or else the extension's.
+    fn syntax(&self) -> Option<&SyntaxReference> {
+        match &self.syntax {
+            SyntaxChoice::ByExtension => self.syntax_set.find_syntax_by_extension(&self.file_type),
+            SyntaxChoice::Named(syntax) => Some(syntax),
+            SyntaxChoice::Off => None,
+        }
+    }
+
+    // Name of the syntax highlighting the document, or its file type when none does.
+    pub fn syntax_name(&self) -> &str {
+        self.syntax()
+            .map_or(self.file_type.as_str(), |syntax| syntax.name.as_str())
+    }
+
+    // Columns a tab stands for in this document, its own setting or else the config's.
+    pub fn effective_tab_width(&self, config: &Config) -> usize {
+        self.local_config.tab_width.unwrap_or(config.tab_width)
+    }
+
+    // Column paragraphs are wrapped at in this document, its own setting or else the config's.
+    pub fn effective_text_width(&self, config: &Config) -> usize {
+        self.local_config.text_width.unwrap_or(config.text_width)
+    }
+
+    // Takes the settings of modelines in the first and last few rows, the last ones winning.
+    fn read_modelines(&mut self) {
+        let last = self.rows.len().saturating_sub(MODELINES);
+        let rows = (0..cmp::min(MODELINES, self.rows.len()))
+            .chain(cmp::max(last, MODELINES)..self.rows.len());
+        for y in rows {
+            if let Some(local) = self
+                .rows
+                .get(y)
+                .and_then(|row| LocalConfig::from_modeline(&row.string))
+            {
+                self.local_config.merge(local);
+            }
+        }
     }
 
-    pub fn file_type(&self) -> String {
-        self.file_type.clone()
+    // The bytes behind the rows while the document is shown as a hex dump.
+    pub fn hex(&self) -> Option<&HexView> {
+        self.hex.as_ref()
+    }
+
+    // Shows the document as a hex dump of what saving it would write, or goes back
+    // to its text, edits and all. Binary files have no text to go back to.
+    pub fn show_hex(&mut self, on: bool) -> Result<(), ZenError> {
+        if on == self.hex.is_some() {
+            return Ok(());
+        }
+        let removed = self.rows.len();
+        if on {
+            let view = HexView::new(self.contents().into_bytes());
+            let rows = mem::replace(&mut self.rows, view.rows());
+            self.text = Some(rows);
+            self.hex = Some(view);
+        } else {
+            let rows = self.text.take().ok_or_else(|| {
+                ZenError::CommandDispatch("Binary files can only be shown as hex".to_string())
+            })?;
+            self.rows = rows;
+            self.hex = None;
+        }
+        self.splices.push(RowSplice {
+            at: 0,
+            removed,
+            inserted: self.rows.len(),
+        });
+        self.highlighted = None;
+        Ok(())
+    }
+
+    // Names the document's file, e.g. when saving an unnamed buffer or after a rename.
+    // Highlighting follows the new name's extension.
+    pub fn set_file_name(&mut self, file_name: String) {
+        if let DocumentOrigin::OpenedFromDisk { path } = &mut self.origin {
+            *path = PathBuf::from(&file_name);
+        }
+        self.file_type = file_type_of(&file_name);
+        self.file_name = Some(file_name);
+        self.highlighted = None;
     }
 
     pub fn row(&self, index: usize) -> Option<&Row> {
@@ -79,16 +457,28 @@ impl Document {
         self.rows.len()
     }
 
+    // Screen lines the rows take up when wrapped at `terminal_width` columns, which is more
+    // than there are rows once some are wider than that.
+    pub fn visual_line_count(&self, terminal_width: usize, tab_width: usize) -> usize {
+        self.rows.iter().fold(0, |count: usize, row| {
+            count.saturating_add(row.visual_line_count(terminal_width, tab_width))
+        })
+    }
+
     pub fn insert(&mut self, at: &Position, c: char) {
-        if at.y > self.len() {
+        if at.y > self.len() || self.is_read_only() {
             return;
         }
 
-        self.dirty = true;
-
         if c == '\n' {
             self.insert_newline(at);
-        } else if at.y == self.rows.len() {
+            return;
+        }
+
+        self.dirty = true;
+        let before = self.row_strings(at.y..at.y.saturating_add(1));
+
+        if at.y == self.rows.len() {
             // Handle insertion at the end of the document
             let mut row = Row::default();
             row.insert(0, c);
@@ -98,10 +488,90 @@ impl Document {
             let row = &mut self.rows[at.y];
             row.insert(at.x, c);
         }
+        let change = self.change(at.y, before, 1);
+        let to = (at.y, at.x.saturating_add(1));
+        self.history.record_typed(change, (at.y, at.x), to);
+    }
+
+    // Inserts text that may span rows as a single edit, as if it were typed, so it's undone
+    // along with what was typed right before it. Returns where the text ends.
+    pub fn insert_text(&mut self, at: &Position, text: &str) -> Position {
+        if at.y > self.len() || self.is_read_only() || text.is_empty() {
+            return at.clone();
+        }
+
+        self.dirty = true;
+        let before = self.row_strings(at.y..at.y.saturating_add(1));
+        if at.y == self.rows.len() {
+            self.rows.push(Row::default());
+        }
+        let mut lines = text
+            .split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line));
+        let first = lines.next().unwrap_or_default();
+        let mut new_rows: Vec<Row> = lines.map(Row::from).collect();
+        let added = new_rows.len();
+
+        let row = &mut self.rows[at.y];
+        let x = cmp::min(at.x, row.len());
+        let rest = row.split(x);
+        row.insert_str(x, first);
+        let end = if let Some(last) = new_rows.last_mut() {
+            let end = Position {
+                x: last.len(),
+                y: at.y.saturating_add(added),
+            };
+            last.append(&rest);
+            end
+        } else {
+            let end = Position {
+                x: row.len(),
+                y: at.y,
+            };
+            row.append(&rest);
+            end
+        };
+        let next = at.y.saturating_add(1);
+        self.rows.splice(next..next, new_rows);
+
+        let change = self.change(at.y, before, added.saturating_add(1));
+        self.history.record_typed(change, (at.y, x), (end.y, end.x));
+        end
+    }
+
+    // Inserts text without newlines into a row as a single edit.
+    pub fn insert_str_at(&mut self, at: &Position, text: &str) {
+        if at.y > self.len() || self.is_read_only() || text.is_empty() {
+            return;
+        }
+
+        self.dirty = true;
+        let before = self.row_strings(at.y..at.y.saturating_add(1));
+        if at.y == self.rows.len() {
+            self.rows.push(Row::from(text));
+        } else {
+            self.rows[at.y].insert_str(at.x, text);
+        }
+        self.record(at.y, before, 1);
+    }
+
+    // Replaces the graphemes in range on a row with text without newlines, as a single edit.
+    pub fn replace_str_at(&mut self, y: usize, range: Range<usize>, text: &str) {
+        if y >= self.len() || self.is_read_only() {
+            return;
+        }
+
+        let before = self.row_strings(y..y.saturating_add(1));
+        self.rows[y].replace_str(range.start, range.end, text);
+        if self.rows[y].string == before[0] {
+            return;
+        }
+        self.dirty = true;
+        self.record(y, before, 1);
     }
 
     pub fn insert_newline(&mut self, at: &Position) {
-        if at.y > self.len() {
+        if at.y > self.len() || self.is_read_only() {
             return;
         }
 
@@ -109,55 +579,665 @@ impl Document {
 
         if at.y == self.len() {
             self.rows.push(Row::default());
+            self.record(at.y, Vec::new(), 1);
             return;
         }
 
+        let before = self.row_strings(at.y..at.y.saturating_add(1));
         let current_row = &mut self.rows[at.y];
         let new_row = current_row.split(at.x);
         self.rows.insert(at.y + 1, new_row);
+        self.record(at.y, before, 2);
     }
 
     pub fn delete(&mut self, at: &Position) {
         let len = self.len();
-        if at.y >= self.len() {
+        if at.y >= self.len() || self.is_read_only() {
             return;
         }
 
         self.dirty = true;
         if at.x == self.rows.get_mut(at.y).unwrap().len() && at.y + 1 < len {
+            let before = self.row_strings(at.y..at.y.saturating_add(2));
             let next_row = self.rows.remove(at.y + 1);
             let row = self.rows.get_mut(at.y).unwrap();
             row.append(&next_row);
+            self.record(at.y, before, 1);
         } else {
+            let before = self.row_strings(at.y..at.y.saturating_add(1));
             let row = self.rows.get_mut(at.y).unwrap();
             row.delete(at.x);
+            self.record(at.y, before, 1);
+        }
+    }
+
+    // Changes the case of the selected text. Returns whether anything changed; undone in one step.
+    pub fn convert_case(&mut self, selection: &Selection, direction: CaseDirection) -> bool {
+        let last = cmp::min(selection.end.y, self.rows.len().saturating_sub(1));
+        let rows = selection.start.y..last.saturating_add(1);
+        if self.is_read_only() || selection.start.y >= self.rows.len() {
+            return false;
+        }
+
+        let before = self.row_strings(rows.clone());
+        let mut changed = false;
+        for y in rows.clone() {
+            let row = &mut self.rows[y];
+            let start = if y == selection.start.y {
+                selection.start.x
+            } else {
+                0
+            };
+            let end = if y == selection.end.y {
+                selection.end.x
+            } else {
+                row.len()
+            };
+            changed |= row.convert_case(start..end, direction);
+        }
+        if changed {
+            self.record(rows.start, before, rows.len());
+            self.dirty = true;
+        }
+        changed
+    }
+
+    // Converts the indentation of every row to spaces, or to tabs where it fits.
+    // Returns how many rows changed; they're undone together.
+    pub fn retab(&mut self, to_spaces: bool, tab_width: usize) -> usize {
+        if self.is_read_only() {
+            return 0;
+        }
+
+        let mut changed = 0;
+        self.history.begin_batch();
+        for index in 0..self.rows.len() {
+            let before = self.row_strings(index..index.saturating_add(1));
+            if self.rows[index].retab(to_spaces, tab_width) {
+                self.record(index, before, 1);
+                changed += 1;
+            }
         }
+        self.history.end_batch();
+
+        if changed > 0 {
+            self.dirty = true;
+        }
+        changed
     }
 
-    pub fn save(&mut self) -> Result<(), std::io::Error> {
-        if let Some(file_name) = &self.file_name {
-            let mut file = fs::File::create(file_name)?;
-            self.file_type = ".rs".to_string();
+    // Joins the lines of each paragraph in range and wraps them again at word boundaries,
+    // so no line is wider than `width` unless a single word is. Each paragraph keeps the
+    // indentation of its first line, and blank lines between paragraphs stay as they are.
+    // Returns how many rows the range spans afterwards.
+    pub fn reflow_paragraph(&mut self, range: Range<usize>, width: usize) -> usize {
+        let end = cmp::min(range.end, self.rows.len());
+        let start = cmp::min(range.start, end);
+        let before = self.row_strings(start..end);
+        if self.is_read_only() {
+            return before.len();
+        }
 
-            for row in &mut self.rows {
-                file.write_all(row.as_bytes())?;
-                file.write_all(b"\n")?;
+        let mut after = Vec::new();
+        let mut paragraph: Vec<&str> = Vec::new();
+        for line in &before {
+            if line.trim().is_empty() {
+                after.extend(wrap(&paragraph, width));
+                paragraph.clear();
+                after.push(line.clone());
+            } else {
+                paragraph.push(line);
             }
-            self.dirty = false;
+        }
+        after.extend(wrap(&paragraph, width));
+
+        if after != before {
+            self.replace_rows(start, before.len(), &after);
+            self.record(start, before, after.len());
+            self.dirty = true;
+        }
+        after.len()
+    }
+
+    // Removes the rows in range and returns their text. Undone in one step.
+    pub fn delete_rows(&mut self, range: Range<usize>) -> Vec<String> {
+        let end = cmp::min(range.end, self.rows.len());
+        let start = cmp::min(range.start, end);
+        if self.is_read_only() || start == end {
+            return Vec::new();
+        }
+
+        let before = self.row_strings(start..end);
+        self.replace_rows(start, before.len(), &[]);
+        self.record(start, before.clone(), 0);
+        self.dirty = true;
+        before
+    }
+
+    // Inserts a row holding the content before row `y`, or after the last row if `y` is the length.
+    pub fn insert_row_at(&mut self, y: usize, content: String) {
+        if y > self.len() || self.is_read_only() {
+            return;
+        }
+
+        self.rows.insert(y, Row::from(content.as_str()));
+        self.record(y, Vec::new(), 1);
+        self.dirty = true;
+    }
+
+    // Removes the row and returns it, as a single edit.
+    pub fn remove_row(&mut self, y: usize) -> Option<Row> {
+        if y >= self.len() || self.is_read_only() {
+            return None;
         }
 
+        let before = self.row_strings(y..y.saturating_add(1));
+        let row = self.rows.remove(y);
+        self.record(y, before, 0);
+        self.dirty = true;
+        Some(row)
+    }
+
+    // Replaces matches of the pattern on the rows in range with the replacement:
+    // all of them with `global`, otherwise the first on each row. Undone in one step.
+    // Returns how many matches were replaced, and on how many rows.
+    // A dry run finds the same matches without replacing them,
+    // so it works on read-only documents too.
+    pub fn substitute(
+        &mut self,
+        range: Range<usize>,
+        query: &str,
+        replacement: &str,
+        global: bool,
+        dry_run: bool,
+    ) -> (usize, usize) {
+        if self.is_read_only() && !dry_run {
+            return (0, 0);
+        }
+
+        let pattern = Pattern::parse(query);
+        let (mut count, mut rows) = (0, 0);
+        self.history.begin_batch();
+        for y in range.start..cmp::min(range.end, self.rows.len()) {
+            let mut matches = pattern.match_ranges(&self.rows[y].string);
+            if !global {
+                matches.truncate(1);
+            }
+            if matches.is_empty() {
+                continue;
+            }
+            count += matches.len();
+            rows += 1;
+            if dry_run {
+                continue;
+            }
+
+            let mut line = self.rows[y].string.clone();
+            let before = vec![line.clone()];
+            for range in matches.iter().rev() {
+                line.replace_range(range.clone(), replacement);
+            }
+            self.replace_rows(y, 1, &[line]);
+            self.record(y, before, 1);
+        }
+        self.history.end_batch();
+
+        if rows > 0 && !dry_run {
+            self.dirty = true;
+        }
+        (count, rows)
+    }
+
+    // Applies all the edits as one undo step, or none of them if any is out of the document or
+    // overlaps another. They're applied from the last in the document to the first, so none
+    // moves the text the ones before it refer to.
+    pub fn apply_edits_transactional(&mut self, mut edits: Vec<Edit>) -> Result<(), ZenError> {
+        if self.is_read_only() {
+            return Err(ZenError::CommandDispatch("File is read-only.".to_string()));
+        }
+
+        let key = |position: &Position| (position.y, position.x);
+        edits.sort_by_key(|edit| key(&edit.range.start));
+        for edit in &edits {
+            let Selection { start, end } = &edit.range;
+            if key(end) < key(start) || start.y > self.rows.len() {
+                return Err(ZenError::CommandDispatch(format!(
+                    "Edit out of range: {}:{} to {}:{}",
+                    start.y, start.x, end.y, end.x
+                )));
+            }
+        }
+        if edits
+            .windows(2)
+            .any(|pair| key(&pair[1].range.start) < key(&pair[0].range.end))
+        {
+            return Err(ZenError::CommandDispatch("Edits overlap".to_string()));
+        }
+
+        self.history.begin_batch();
+        for edit in edits.iter().rev() {
+            self.replace_range(&edit.range, &edit.text);
+        }
+        self.history.end_batch();
         Ok(())
     }
 
+    // Replaces the text in range, newlines included, as a single edit. A column past the end of
+    // its row stands for the end of the row, and a row past the last for the end of the document.
+    fn replace_range(&mut self, range: &Selection, text: &str) {
+        let y = range.start.y;
+        let last = cmp::min(range.end.y, self.rows.len().saturating_sub(1));
+        let before = self.row_strings(y..cmp::max(y, last).saturating_add(1));
+
+        let head: String = before.first().map_or_else(String::new, |row| {
+            row.graphemes(true).take(range.start.x).collect()
+        });
+        let tail: String = match before.last() {
+            Some(row) if range.end.y <= last => row.graphemes(true).skip(range.end.x).collect(),
+            _ => String::new(),
+        };
+        let replaced = [head, text.to_string(), tail].concat();
+        if before.is_empty() && replaced.is_empty() || before.len() == 1 && before[0] == replaced {
+            return;
+        }
+
+        let lines: Vec<String> = replaced.split('\n').map(str::to_string).collect();
+        self.replace_rows(y, before.len(), &lines);
+        self.dirty = true;
+        self.record(y, before, lines.len());
+    }
+
+    // Removes whitespace from the end of every row, as one undo step. Returns how many rows changed.
+    pub fn trim_trailing_whitespace(&mut self) -> usize {
+        let mut trimmed = 0;
+        self.begin_batch();
+        for y in 0..self.rows.len() {
+            let row = &self.rows[y];
+            let kept = row.string.trim_end().graphemes(true).count();
+            if kept < row.len() {
+                let len = row.len();
+                self.replace_str_at(y, kept..len, "");
+                trimmed += 1;
+            }
+        }
+        self.end_batch();
+        trimmed
+    }
+
+    // Rows of the paragraph around the given row, up to the blank lines on either side.
+    pub fn paragraph_range(&self, y: usize) -> Range<usize> {
+        let is_blank = |y: usize| self.rows[y].string.trim().is_empty();
+        if y >= self.rows.len() || is_blank(y) {
+            return y..y;
+        }
+
+        let mut start = y;
+        while start > 0 && !is_blank(start.saturating_sub(1)) {
+            start = start.saturating_sub(1);
+        }
+        let mut end = y.saturating_add(1);
+        while end < self.rows.len() && !is_blank(end) {
+            end = end.saturating_add(1);
+        }
+        start..end
+    }
+
+    // Where `{` moves to: the empty row above the paragraph, skipping the empty rows the
+    // position is on, or the first row if there isn't one.
+    pub fn paragraph_start(&self, at: &Position) -> Position {
+        let is_empty = |y: usize| self.rows.get(y).is_none_or(Row::is_empty);
+        let mut y = cmp::min(at.y, self.rows.len().saturating_sub(1));
+        while y > 0 && is_empty(y) {
+            y = y.saturating_sub(1);
+        }
+        while y > 0 && !is_empty(y) {
+            y = y.saturating_sub(1);
+        }
+        Position { x: 0, y }
+    }
+
+    // Where `}` moves to: the empty row below the paragraph, skipping the empty rows the
+    // position is on, or the end of the last row if there isn't one.
+    pub fn paragraph_end(&self, at: &Position) -> Position {
+        let last = self.rows.len().saturating_sub(1);
+        let is_empty = |y: usize| self.rows.get(y).is_none_or(Row::is_empty);
+        let mut y = cmp::min(at.y, last);
+        while y < last && is_empty(y) {
+            y = y.saturating_add(1);
+        }
+        while y < last && !is_empty(y) {
+            y = y.saturating_add(1);
+        }
+        let x = if is_empty(y) { 0 } else { self.rows[y].len() };
+        Position { x, y }
+    }
+
+    // Rows of the `ip` text object on a row: its paragraph, or its run of empty rows.
+    // `ap` (`around`) adds the empty rows after the paragraph, or the ones before it when it
+    // ends the document. On an empty row it adds the paragraph after the run instead.
+    pub fn paragraph_object(&self, y: usize, around: bool) -> Range<usize> {
+        let len = self.rows.len();
+        if y >= len {
+            return y..y;
+        }
+
+        let is_empty = |y: usize| self.rows[y].is_empty();
+        let empty = is_empty(y);
+        let mut start = y;
+        while start > 0 && is_empty(start.saturating_sub(1)) == empty {
+            start = start.saturating_sub(1);
+        }
+        let mut end = y.saturating_add(1);
+        while end < len && is_empty(end) == empty {
+            end = end.saturating_add(1);
+        }
+        if !around {
+            return start..end;
+        }
+
+        let mut after = end;
+        while after < len && is_empty(after) != empty {
+            after = after.saturating_add(1);
+        }
+        if after > end || empty {
+            return start..after;
+        }
+        while start > 0 && is_empty(start.saturating_sub(1)) {
+            start = start.saturating_sub(1);
+        }
+        start..end
+    }
+
+    // Sets how long a row may be and still get highlighted, 0 for no limit.
+    // Changing it highlights the view again.
+    pub fn set_long_line_length(&mut self, len: usize) {
+        if self.long_line_length != len {
+            self.long_line_length = len;
+            self.highlighted = None;
+        }
+    }
+
+    // Whether the row is too long to be highlighted.
+    pub fn is_long_line(&self, y: usize) -> bool {
+        self.long_line_length > 0
+            && self
+                .rows
+                .get(y)
+                .is_some_and(|row| row.len() > self.long_line_length)
+    }
+
+    // Rows replaced since the last call, oldest first.
+    pub fn take_splices(&mut self) -> Vec<RowSplice> {
+        std::mem::take(&mut self.splices)
+    }
+
+    // Edits made since the document was opened. Undo and redo don't count.
+    pub fn edit_count(&self) -> usize {
+        self.edits
+    }
+
+    // Groups the edits made until end_batch into a single undo step.
+    pub fn begin_batch(&mut self) {
+        self.history.begin_batch();
+    }
+
+    pub fn end_batch(&mut self) {
+        self.history.end_batch();
+    }
+
+    // Ends the run of typed characters undone together, e.g. when the cursor moves away.
+    pub fn break_typing(&mut self) {
+        self.history.break_typing();
+    }
+
+    // Reverts the last batch of edits.
+    // Returns where the cursor should go, or None if there's nothing to undo.
+    pub fn undo(&mut self) -> Option<Position> {
+        if self.is_read_only() {
+            return None;
+        }
+
+        let batch = self.history.pop_undo()?;
+        for change in batch.iter().rev() {
+            self.replace_rows(change.row, change.after.len(), &change.before);
+            self.splices.push(RowSplice {
+                at: change.row,
+                removed: change.after.len(),
+                inserted: change.before.len(),
+            });
+        }
+        let position = batch.first().map(|change| Position {
+            x: 0,
+            y: change.row,
+        });
+        self.history.push_redo(batch);
+        self.dirty = true;
+        position
+    }
+
+    // Re-applies the last undone batch of edits.
+    pub fn redo(&mut self) -> Option<Position> {
+        if self.is_read_only() {
+            return None;
+        }
+
+        let batch = self.history.pop_redo()?;
+        for change in &batch {
+            self.replace_rows(change.row, change.before.len(), &change.after);
+            self.splices.push(RowSplice {
+                at: change.row,
+                removed: change.before.len(),
+                inserted: change.after.len(),
+            });
+        }
+        let position = batch.first().map(|change| Position {
+            x: 0,
+            y: change.row,
+        });
+        self.history.push_undo(batch);
+        self.dirty = true;
+        position
+    }
+
+    // The text from `start` up to but not including `end`, with a newline between rows.
+    // Empty unless `end` comes after `start`. A column past the end of its row stands for the
+    // end of the row, and a row past the last for the end of the document, its newline included.
+    pub fn get_text_range(&self, start: &Position, end: &Position) -> String {
+        if (end.y, end.x) <= (start.y, start.x) {
+            return String::new();
+        }
+
+        let mut text = String::new();
+        for y in start.y..=cmp::min(end.y, self.rows.len().saturating_sub(1)) {
+            let Some(row) = self.rows.get(y) else {
+                break;
+            };
+            if y > start.y {
+                text.push('\n');
+            }
+            let from = if y == start.y { start.x } else { 0 };
+            let to = if y == end.y { end.x } else { row.len() };
+            text.extend(row.graphemes().skip(from).take(to.saturating_sub(from)));
+        }
+        if end.y >= self.rows.len() && start.y < self.rows.len() {
+            text.push('\n');
+        }
+        text
+    }
+
+    // The character at the position, the first code point of its grapheme when the grapheme
+    // has more than one. None past the end of the row.
+    pub fn char_at(&self, at: &Position) -> Option<char> {
+        self.rows.get(at.y)?.grapheme_at(at.x)?.chars().next()
+    }
+
+    // The character right before the position on its row, None at the start of the row.
+    pub fn char_before(&self, at: &Position) -> Option<char> {
+        self.char_at(&Position {
+            x: at.x.checked_sub(1)?,
+            y: at.y,
+        })
+    }
+
+    // The character right after the position on its row.
+    pub fn char_after(&self, at: &Position) -> Option<char> {
+        self.char_at(&Position {
+            x: at.x.checked_add(1)?,
+            y: at.y,
+        })
+    }
+
+    // Copies the text of the rows in range, skipping any past the end.
+    pub fn row_strings(&self, range: Range<usize>) -> Vec<String> {
+        self.rows
+            .get(range.start..cmp::min(range.end, self.rows.len()))
+            .unwrap_or_default()
+            .iter()
+            .map(|row| row.string.clone())
+            .collect()
+    }
+
+    // Adds an edit to the history: the rows from `row` that held `before` now span `after_len` rows.
+    fn record(&mut self, row: usize, before: Vec<String>, after_len: usize) {
+        let change = self.change(row, before, after_len);
+        self.history.record(change);
+    }
+
+    // Counts an edit: the rows from `row` that held `before` now span `after_len` rows.
+    fn change(&mut self, row: usize, before: Vec<String>, after_len: usize) -> Change {
+        self.edits = self.edits.saturating_add(1);
+        self.dirty_rows.insert(row);
+        self.splices.push(RowSplice {
+            at: row,
+            removed: before.len(),
+            inserted: after_len,
+        });
+        let after = self.row_strings(row..row.saturating_add(after_len));
+        Change { row, before, after }
+    }
+
+    fn replace_rows(&mut self, at: usize, count: usize, lines: &[String]) {
+        let end = cmp::min(at.saturating_add(count), self.rows.len());
+        let at = cmp::min(at, end);
+        self.dirty_rows.insert(at);
+        self.rows
+            .splice(at..end, lines.iter().map(|line| Row::from(line.as_str())));
+    }
+
+    // The document's text as it would be saved, with a newline after every row.
+    pub fn contents(&self) -> String {
+        let mut contents = String::new();
+        for row in &self.rows {
+            contents.push_str(&row.string);
+            contents.push('\n');
+        }
+        contents
+    }
+
+    // Writes the document in its encoding.
+    // Returns how many characters the encoding has no byte for; they are written as '?'.
+    pub async fn save(&mut self) -> Result<usize, ZenError> {
+        self.save_as_encoding(self.encoding).await
+    }
+
+    // Writes the document in the given encoding, which later saves keep using.
+    pub async fn save_as_encoding(&mut self, encoding: Encoding) -> Result<usize, ZenError> {
+        let Some(file_name) = &self.file_name else {
+            return Ok(0);
+        };
+        let (bytes, unencodable) = encoding.encode(&self.contents());
+        write_file(file_name, &bytes)
+            .await
+            .map_err(|error| ZenError::io(file_name, error))?;
+        self.origin = DocumentOrigin::OpenedFromDisk {
+            path: PathBuf::from(file_name),
+        };
+        self.encoding = encoding;
+        self.dirty = false;
+        Ok(unencodable)
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
     pub fn is_dirty(&self) -> bool {
         self.dirty
     }
 
-    pub fn find(&self, query: &str, at: &Position, direction: SearchDirection) -> Option<Position> {
+    // Counts whitespace separated words across all rows.
+    pub fn word_count(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|row| row.string.split_whitespace().count())
+            .sum()
+    }
+
+    // Size of the document as it would be written to disk, newlines included.
+    pub fn byte_count(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|row| row.as_bytes().len().saturating_add(1))
+            .sum()
+    }
+
+    // Hex dumps are always read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only || self.hex.is_some()
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    // Finds the query from `at` on, or before it when searching backward. With bounds, e.g. a
+    // selection's, only matches starting inside them are found, and searches from outside
+    // them start at their near end.
+    pub fn find(
+        &self,
+        query: &str,
+        at: &Position,
+        direction: SearchDirection,
+        bounds: Option<&Selection>,
+    ) -> Option<Position> {
+        let Some(bounds) = bounds else {
+            return self.find_from(query, at, direction);
+        };
+        let start = (bounds.start.y, bounds.start.x);
+        let end = (bounds.end.y, bounds.end.x);
+        let from = match direction {
+            SearchDirection::Forward if (at.y, at.x) < start => bounds.start.clone(),
+            SearchDirection::Backward if (at.y, at.x) > end => bounds.end.clone(),
+            _ => at.clone(),
+        };
+        // Bounds covering the last row whole end past it.
+        let from = if from.y >= self.rows.len() {
+            self.end_position()
+        } else {
+            from
+        };
+        self.find_from(query, &from, direction)
+            .filter(|found| (start..end).contains(&(found.y, found.x)))
+    }
+
+    fn find_from(
+        &self,
+        query: &str,
+        at: &Position,
+        direction: SearchDirection,
+    ) -> Option<Position> {
         if at.y >= self.rows.len() {
             return None;
         }
 
+        // Hex dumps are searched for the bytes a query like `de ad be ef` spells out,
+        // even across lines. Other queries search the dump's text.
+        if let Some((view, needle)) = self.hex.as_ref().zip(hex::parse_bytes(query)) {
+            return view
+                .find(&needle, view.offset_from(at), direction)
+                .map(|offset| view.position_of(offset));
+        }
+
         let mut position = Position { x: at.x, y: at.y };
 
         let start = if direction == SearchDirection::Forward {
@@ -174,7 +1254,7 @@ impl Document {
 
         for _ in start..end {
             if let Some(row) = self.rows.get(position.y) {
-                if let Some(x) = row.find(&query, position.x, direction) {
+                if let Some(x) = row.find(query, position.x, direction) {
                     position.x = x;
                     return Some(position);
                 }
@@ -193,17 +1273,202 @@ impl Document {
         None
     }
 
-    pub fn highlight(&mut self, visible_range: Range<usize>) {
-        if let Some(syntax) = self.syntax_set.find_syntax_by_extension(&self.file_type) {
-            let mut h = HighlightLines::new(&syntax, &self.theme_set.themes["base16-ocean.dark"]);
+    // Like find, but goes on from the other end of the document when there's no match before
+    // this one, the way Vim's searches wrap around. Also returns whether it wrapped.
+    // With bounds, it wraps around to their other end instead.
+    pub fn find_with_wrap(
+        &self,
+        query: &str,
+        at: &Position,
+        direction: SearchDirection,
+        bounds: Option<&Selection>,
+    ) -> (Option<Position>, bool) {
+        if let Some(position) = self.find(query, at, direction, bounds) {
+            return (Some(position), false);
+        }
+
+        let from = match (direction, bounds) {
+            (SearchDirection::Forward, Some(bounds)) => bounds.start.clone(),
+            (SearchDirection::Forward, None) => Position::default(),
+            (SearchDirection::Backward, Some(bounds)) => bounds.end.clone(),
+            (SearchDirection::Backward, None) => self.end_position(),
+        };
+        match self.find(query, &from, direction, bounds) {
+            Some(position) => (Some(position), true),
+            None => (None, false),
+        }
+    }
+
+    // Every match of the pattern in the document, top to bottom and left to right.
+    pub fn find_all(&self, query: &str, case_sensitive: bool) -> Vec<Position> {
+        self.find_all_in_range(query, 0..self.rows.len(), case_sensitive)
+    }
+
+    // Every match of the pattern on the given rows, e.g. the ones in view.
+    pub fn find_all_in_range(
+        &self,
+        query: &str,
+        range: Range<usize>,
+        case_sensitive: bool,
+    ) -> Vec<Position> {
+        let pattern = if case_sensitive {
+            Pattern::parse(query)
+        } else {
+            Pattern::parse(query).ignoring_case()
+        };
+
+        let mut matches = Vec::new();
+        for y in range {
+            if let Some(row) = self.rows.get(y) {
+                let row_matches = row.find_matches(&pattern).into_iter();
+                matches.extend(row_matches.map(|x| Position { x, y }));
+            }
+        }
+        matches
+    }
+
+    // Just past the last character of the last row.
+    fn end_position(&self) -> Position {
+        let y = self.rows.len().saturating_sub(1);
+        let x = self.rows.get(y).map_or(0, Row::len);
+        Position { x, y }
+    }
+
+    // Background color of the active theme, shared by every line of the text area.
+    pub fn background(&self) -> Option<Color> {
+        self.theme_set
+            .themes
+            .get(THEME)
+            .and_then(|theme| theme.settings.background)
+    }
+
+    // Muted foreground of the active theme, the one it gives line numbers in a gutter.
+    pub fn muted_foreground(&self) -> Option<Color> {
+        self.theme_set
+            .themes
+            .get(THEME)
+            .and_then(|theme| theme.settings.gutter_foreground)
+    }
+
+    // Highlights the visible rows that changed since the last call, along with the rows after them
+    // since an edit can change how the rest of the view is highlighted, e.g. by opening a comment.
+    // Nothing is done when the view and its text are unchanged.
+    pub fn highlight(
+        &mut self,
+        visible_range: Range<usize>,
+        color_support: ColorSupport,
+    ) -> Result<(), ZenError> {
+        // Hex dumps aren't the file's syntax.
+        if self.hex.is_some() {
+            return Ok(());
+        }
+        // Without colors there's nothing to highlight with, so rows draw their plain text.
+        if color_support == ColorSupport::Monochrome {
+            if self.highlighted.take().is_some() {
+                for row in &mut self.rows {
+                    row.clear_highlighting();
+                }
+            }
+            return Ok(());
+        }
+        let view = (visible_range.clone(), color_support);
+        let first_dirty = if self.highlighted.as_ref() == Some(&view) {
+            self.dirty_rows.iter().min().copied()
+        } else {
+            Some(visible_range.start)
+        };
+        self.dirty_rows.clear();
+        self.highlighted = Some(view);
+        let Some(first_dirty) = first_dirty.filter(|row| *row < visible_range.end) else {
+            return Ok(());
+        };
+
+        if let Some(syntax) = self.syntax() {
+            let theme = self
+                .theme_set
+                .themes
+                .get(THEME)
+                .ok_or_else(|| ZenError::SyntaxLoad(format!("missing theme {}", THEME)))?;
+            let mut h = HighlightLines::new(syntax, theme);
 
             for row_num in visible_range {
-                if let Some(row) = self.rows.get_mut(row_num) {
-                    row.highlight(&self.syntax_set, &mut h);
+                let Some(row) = self.rows.get_mut(row_num) else {
+                    break;
+                };
+                // Long rows stay plain, and the rows after them carry on as if they weren't there.
+                if self.long_line_length > 0 && row.len() > self.long_line_length {
+                    continue;
+                }
+                if row_num >= first_dirty {
+                    row.highlight(&self.syntax_set, &mut h, color_support)?;
+                } else {
+                    // Rows above the edit keep their highlighting but still carry the parser's state down.
+                    h.highlight_line(&row.string, &self.syntax_set)
+                        .map_err(|error| ZenError::SyntaxLoad(error.to_string()))?;
                 }
             }
         } else {
             // Handle this at some point
         }
+        Ok(())
+    }
+}
+
+// File type of a path, going by its extension.
+fn file_type_of(file_name: &str) -> String {
+    Path::new(file_name)
+        .extension()
+        .and_then(OsStr::to_str)
+        .unwrap_or("Unknown")
+        .to_string()
+}
+
+// Writes the bytes over the file, flushing them before the file is closed since tokio's writes
+// finish in the background.
+async fn write_file(file_name: &str, bytes: &[u8]) -> io::Result<()> {
+    let mut file = tokio::fs::File::create(file_name).await?;
+    file.write_all(bytes).await?;
+    file.flush().await
+}
+
+// Whether the file starts like a binary one, then goes back to its start for the loader.
+// Files that can't go back are counted as binary too, so they're read whole instead.
+fn is_binary_file(mut file: &fs::File) -> bool {
+    let mut head = Vec::new();
+    let binary = file
+        .take(hex::SNIFF_LEN as u64)
+        .read_to_end(&mut head)
+        .is_ok()
+        && hex::is_binary(&head);
+    binary || file.rewind().is_err()
+}
+
+// Fills lines up to the width with the paragraph's words, indented like its first line.
+fn wrap(paragraph: &[&str], width: usize) -> Vec<String> {
+    let Some(first) = paragraph.first() else {
+        return Vec::new();
+    };
+    let indent_len = first.len().saturating_sub(first.trim_start().len());
+    let indent = &first[..indent_len];
+    let indent_width = indent.graphemes(true).count();
+
+    let mut lines = Vec::new();
+    let mut line = String::from(indent);
+    let mut line_width = indent_width;
+    for word in paragraph.iter().flat_map(|line| line.split_whitespace()) {
+        let word_width = word.graphemes(true).count();
+        if line_width > indent_width {
+            if line_width.saturating_add(1).saturating_add(word_width) > width {
+                lines.push(std::mem::replace(&mut line, String::from(indent)));
+                line_width = indent_width;
+            } else {
+                line.push(' ');
+                line_width = line_width.saturating_add(1);
+            }
+        }
+        line.push_str(word);
+        line_width = line_width.saturating_add(word_width);
     }
+    lines.push(line);
+    lines
 }
@@ -1,34 +1,156 @@
+use crate::abbreviation;
+use crate::cli::LaunchOptions;
+use crate::clipboard;
+use crate::clock::{Clock, SystemClock};
 use crate::commands;
-use crate::commands::Command;
-use crate::document::Document;
-use crate::mode::EditorMode;
-use crate::row::Row;
-use crate::terminal::Terminal;
+use crate::commands::ex::{self, CommandRange, EditorOption, ExCommand};
+use crate::commands::registry::{self, Action, CommandInfo};
+use crate::commands::{Command, CommandContext, CommandOutcome};
+use crate::config::Config;
+use crate::diff::{self, Diff, DiffTag};
+use crate::digraph::Digraphs;
+use crate::document::{Document, DocumentOrigin, Edit, Selection};
+use crate::encoding::Encoding;
+use crate::error::ZenError;
+use crate::event::{self, CommandQueue, CommandReceiver, KeyInput, ProcessSignal, Signals};
+use crate::fold::{self, Folds};
+use crate::hooks::{Event, EventBus, HookAction};
+use crate::keymap::{Keymap, Lookup, MapMode};
+use crate::loader::{LoadChunk, Loader};
+use crate::message::{Level, Messages};
+use crate::mode::{EditorMode, PendingInput};
+use crate::motion::Motion;
+use crate::picker::{Picker, PickerAction, PickerItem};
+use crate::profile::{Phase, Profiler};
+use crate::prompt::{LineEditor, PromptAction, PromptHistory, PromptKind};
+use crate::quickfix::{self, BuildOutput, QuickfixList};
+use crate::recovery::{self, RecoveryChoice, RecoveryState};
+use crate::row::{self, CaseDirection, Row, Whitespace};
+use crate::search::{self, Pattern};
+use crate::spell;
+use crate::terminal::{
+    ansi, ColorSupport, CursorStyle, DefaultTerminal, KeyEvent, MouseButton, MouseEvent,
+    TerminalBackend,
+};
+use crate::util;
+use crate::workers::Workers;
 
 use std::cmp;
+use std::collections::{HashMap, VecDeque};
 use std::env;
+use std::fs;
+use std::io;
+use std::iter;
+use std::mem;
 use std::ops::Range;
-use std::time::Duration;
-use std::time::Instant;
-use termion::color;
-use termion::event::Key;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use syntect::highlighting::Color;
+use tokio::sync::Notify;
+use tokio::task::{self, JoinHandle};
+use tokio::time::{self, MissedTickBehavior};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 const QUIT_TIMES: u8 = 3;
+const TICK_INTERVAL: Duration = Duration::from_millis(500);
+// How long quitting waits for background workers to stop once they're cancelled.
+const WORKER_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+// Lines the cursor moves per turn of the mouse wheel.
+const WHEEL_LINES: usize = 3;
+// Columns at the right of the message bar kept for the keys of an unfinished sequence.
+const PENDING_INPUT_WIDTH: usize = 11;
+// How many edited positions the change list keeps.
+const CHANGE_LIST_LEN: usize = 100;
+
+// How long the cursor has to stay put after moving before CursorMoved hooks run.
+const CURSOR_MOVED_DELAY: Duration = Duration::from_millis(100);
+// How long keys that start a mapping wait for the rest of it before they're taken as typed.
+const MAPPING_TIMEOUT: Duration = Duration::from_secs(1);
+// Mappings expanded for one typed key before giving up on it as recursive.
+const MAX_MAPPING_DEPTH: usize = 1000;
+// Rows `:s///n` counts between checks of how long it has taken.
+const COUNT_CHUNK_ROWS: usize = 10_000;
+// How long `:s///n` counts before it gives up and reports the matches so far.
+const COUNT_TIME_LIMIT: Duration = Duration::from_secs(1);
+// Build command `:make` runs for file types the config has none for.
+const DEFAULT_MAKEPRG: &str = "make";
+// Drawn dim in the scrollbar's column, for the part of the document in view and the rest.
+const SCROLLBAR_THUMB: char = '█';
+const SCROLLBAR_TRACK: char = '│';
+const STATUS_BG_COLOR: Color = util::rgb(239, 239, 239);
+const STATUS_FG_COLOR: Color = util::rgb(63, 63, 63);
+const DIFF_ADDED_COLOR: Color = util::rgb(152, 195, 121);
+const DIFF_REMOVED_COLOR: Color = util::rgb(224, 108, 117);
+const ERROR_COLOR: Color = util::rgb(224, 108, 117);
+const PICKER_SELECTED_COLOR: Color = util::rgb(97, 175, 239);
 
 /// 2D Position
-#[derive(Default, Clone)]
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
 pub struct Position {
     pub x: usize,
     pub y: usize,
 }
 
-/// Used by the status bar to display text
-struct StatusMessage {
-    // Text displayed in the status bar
+/// Composed status bar text, along with the inputs it was built from.
+/// Only rebuilt when one of those inputs changes.
+#[derive(Default)]
+struct StatusBar {
     text: String,
+    mode: String,
+    notes: Vec<String>,
+    file_name: Option<String>,
+    syntax_name: String,
+    lines: usize,
+    line: usize,
+    dirty: bool,
+    read_only: bool,
+    width: usize,
+}
+
+/// Positions where the document was edited, oldest first, for `g;` and `g,` to go through.
+#[derive(Default)]
+struct ChangeList {
+    positions: VecDeque<Position>,
+
+    // Entry the last jump went to, or the length when not going through the list.
+    index: usize,
+}
+
+/// A tab's document and the view of it, kept aside while another tab is shown.
+#[derive(Default)]
+struct TabPage {
+    document: Document,
+    cursor_position: Position,
+    offset: Position,
+    folds: Folds,
+    changes: ChangeList,
+    diff: Option<Diff>,
+}
+
+/// A change `.` repeats, kept as what it did rather than where, so it's made again wherever
+/// the cursor is.
+#[derive(Clone)]
+enum Repeat {
+    // `cgn` and the text typed after it: the next match of the last search becomes the text.
+    ChangeMatch(String),
+}
+
+/// Lines drawn over the text area, each in its own color if it has one.
+struct Overlay {
+    lines: Vec<(String, Option<Color>)>,
+
+    // Index of the line at the top of the text area, once clamped to the last top line.
+    top: usize,
+}
 
-    // How long before the message should be displayed.
-    time: Instant,
+/// What picking an entry of the command palette runs.
+enum PaletteEntry {
+    Registered(&'static CommandInfo),
+
+    // A mapping's keys, which are fed back as if typed.
+    Mapped(Vec<KeyEvent>),
 }
 
 /// Used by the search functionality to dictate which direction we're looking for text
@@ -41,7 +163,10 @@ pub enum SearchDirection {
 /// Entry point for our application/editor.
 pub struct Editor {
     // Manages terminal impl
-    pub terminal: Terminal,
+    pub(crate) terminal: Box<dyn TerminalBackend>,
+
+    // User configuration loaded at startup
+    pub(crate) config: Config,
 
     // Manages active document
     pub document: Document,
@@ -50,7 +175,7 @@ pub struct Editor {
     pub cursor_position: Position,
 
     // Horizontal & vertical offset (x, y)
-    pub offset: Position,
+    pub(crate) offset: Position,
 
     // Current Editor mode the user is in
     mode: EditorMode,
@@ -58,444 +183,4194 @@ pub struct Editor {
     // Highlighted word, for search, etc.
     highlighted_word: Option<String>,
 
-    // Active status message for the status bar.
-    status_message: StatusMessage,
+    // Pattern of the last search, repeated by `n` and `N` in the direction it went.
+    last_search_query: Option<String>,
+    last_search_direction: SearchDirection,
+
+    // Matches of the last search within view, drawn in reverse video until `:noh`.
+    search_matches: Vec<Position>,
+    highlight_search: bool,
+
+    // Misspelled words found on rows in view, once `:set spell` first needed a dictionary.
+    spell: Option<spell::Checker>,
+
+    // Messages for the message bar, along with the ones already shown for `:messages`.
+    messages: Messages,
+
+    // What's being typed at a prompt, shown in the message bar instead of messages,
+    // and the column of the cursor within it.
+    prompt_line: Option<(String, usize)>,
+
+    // Lines entered at each kind of prompt, recalled with Up and Down.
+    prompt_history: PromptHistory,
+
+    // Lines drawn over the text area until closed, e.g. by `:messages`.
+    overlay: Option<Overlay>,
 
     // How many times the user should hit the quit hotkey before exiting a dirty document.
     quit_times: u8,
 
+    // Keypresses, read on a blocking task.
+    input: KeyInput,
+
+    // Commands queued by other tasks, and the sender handed out for them.
+    commands: CommandReceiver,
+    command_queue: CommandQueue,
+
+    // Notified to stop the event loop.
+    shutdown: Arc<Notify>,
+
+    // Time source for status message expiry; swapped for a MockClock in tests.
+    clock: Box<dyn Clock>,
+
+    // Side by side comparison against the file on disk, while diff mode is on.
+    diff: Option<Diff>,
+
+    // Lines copied by `:y`, or removed by `:d`.
+    yanked: Vec<String>,
+
+    // Lines copied by the last `:y`, which a `:d` afterwards leaves alone. Register `0`.
+    last_yank: Vec<String>,
+
+    // Lines yanked or deleted into a named register, e.g. with `:y a`, by the register's letter.
+    registers: HashMap<char, Vec<String>>,
+
+    // Rows folded away with `za`, each drawn as a single line.
+    folds: Folds,
+
+    // Hooks from the config, and events waiting to run theirs.
+    events: EventBus,
+
+    // Set while hooks run, so the commands they run don't set off more hooks.
+    running_hooks: bool,
+
+    // When the cursor last moved, until CursorMoved hooks ran for it.
+    cursor_moved_at: Option<Instant>,
+
+    // Where edits were made, for `g;` and `g,`.
+    changes: ChangeList,
+
+    // Errors and warnings from the last `:make`, whether one is running,
+    // and the counts it found, shown in the status bar.
+    quickfix: QuickfixList,
+    building: bool,
+    build_summary: Option<String>,
+
+    // Reads the rest of a big file the editor was started on, until run hands it to a
+    // background task. The task sends what it reads through the command queue, so rows are
+    // only ever appended here, in order, between other commands.
+    loader: Option<Loader>,
+
+    // Words expanded as they're typed in Insert mode, from the config or `:iabbrev`.
+    abbreviations: HashMap<String, String>,
+
+    // Text typed in the current Insert mode session, and in the last one, which Ctrl-A inserts again.
+    current_insert: String,
+    last_insert: String,
+
+    // Where the current Insert mode session started, or where the cursor was last moved to in
+    // it. `Ctrl-U` deletes no further back than this on its row.
+    insert_start: Position,
+
+    // The last change, which `.` makes again. Insert mode sessions started by `cgn` are
+    // recorded when they end.
+    last_change: Option<Repeat>,
+    changing_match: bool,
+
+    // Keys typed so far of a Normal mode sequence, e.g. the `]` of `]c` or the `gq` of `gqq`,
+    // or of an Insert mode `Ctrl-K` digraph or `Ctrl-V` character.
+    pending_input: PendingInput,
+
+    // Digraphs `Ctrl-K` inserts, the built-in ones and those from the config.
+    digraphs: Digraphs,
+
+    // Key mappings, the keys typed so far that start one and when the last of them was typed,
+    // and how many mappings the last typed key expanded to.
+    keymap: Keymap,
+    mapped_keys: Vec<KeyEvent>,
+    mapped_keys_at: Option<Instant>,
+    mapping_depth: usize,
+
+    // Tabs other than the one shown, in order, and where the shown one sits among all of them.
+    tabs: Vec<TabPage>,
+    active_tab: usize,
+
     // Breaks the run loop when set to true.
     should_quit: bool,
+
+    // Status bar text cached between frames.
+    status_bar: StatusBar,
+
+    // Content of each screen line as of the last frame.
+    // Lines that haven't changed since then are skipped when redrawing.
+    last_frame: Vec<Option<String>>,
+
+    // Width and height the last frame was drawn at. Once they change, every line is drawn again.
+    frame_size: (u16, u16),
+
+    // Cursor shape last written, only written again when the mode changes it.
+    cursor_style: Option<CursorStyle>,
+
+    // Signals, only listened for on a real terminal.
+    signals: Option<Signals>,
+
+    // Directory recovery files are kept in. None keeps none, e.g. in tests.
+    recovery_dir: Option<PathBuf>,
+
+    // When the active document's recovery file is due, and the write in progress if any.
+    recovery: RecoveryState,
+    recovery_write: Option<JoinHandle<io::Result<()>>>,
+
+    // Set once quitting because of a signal, which leaves the recovery files in place.
+    terminated: bool,
+
+    // Set while quitting waits for writes still going. Frames drawn meanwhile say it's saving.
+    shutting_down: bool,
+
+    // Background tasks, like reading the rest of the file and running builds.
+    workers: Workers,
+
+    // Timings of the event loop's phases, only kept with `:set profiling`.
+    profiler: Profiler,
+
+    // What the terminal was found to support, which `:set termcolors` goes back to.
+    detected_colors: ColorSupport,
 }
 
 impl Editor {
     // Main application loop. Used in main.rs to instantiate the editor.
     // Should quit check is called after the frame has finished initializing.
-    pub fn run(&mut self) {
-        loop {
-            if let Err(error) = self.refresh_screen() {
-                die(error);
+    // A fatal error clears the screen before it's returned, so the caller can print it.
+    pub async fn run(&mut self) -> Result<(), ZenError> {
+        self.start_loading();
+        // A signal to terminate may have come in already, in which case nobody's there to ask.
+        if self.document.file_name.is_some() && !self.terminated {
+            self.offer_recovery().await?;
+            self.fire(Event::BufOpen).await;
+        }
+        let result = self.event_loop().await;
+        if self.config.profiling {
+            for line in self.profiler.report() {
+                log::info!("{}", line);
             }
+        }
+        // Changes were saved or thrown away, unless quitting on a signal or an error.
+        if result.is_ok() && !self.terminated {
+            self.remove_recovery_files().await;
+        }
+        if result.is_err() {
+            self.terminal.clear_screen();
+            self.terminal.cursor_position(&Position::default());
+            self.restore_cursor_style();
+            self.terminal.cursor_show();
+            let _ = self.terminal.flush();
+        }
+        result
+    }
+
+    // Waits on keys, queued commands, signals, a periodic tick and shutdown, whichever comes first.
+    // Reading keys happens on a blocking task, so commands get handled while no key is pressed.
+    // The tick only exists to redraw, which is what expires status messages.
+    async fn event_loop(&mut self) -> Result<(), ZenError> {
+        let mut tick = time::interval(TICK_INTERVAL);
+        tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut busy = false;
 
+        loop {
             if self.should_quit {
-                break;
+                self.shut_down().await?;
+                return self.refresh_screen();
             }
+            self.refresh_screen()?;
+
+            // Biased, so commands queued before a key are handled before it.
+            tokio::select! {
+                biased;
 
-            if let Err(error) = self.process_keypress() {
-                die(error);
+                () = self.shutdown.notified() => self.should_quit = true,
+                Some(command) = self.commands.recv() => {
+                    let (position, edits) = (self.cursor_position.clone(), self.document.edit_count());
+                    self.execute(command).await?;
+                    self.note_edits(position, edits);
+                    self.fire_queued().await;
+                    self.sync_view();
+                    busy = self.report_backlog(busy);
+                }
+                key = self.input.next() => self.handle_key(key?).await?,
+                signal = event::next_signal(&mut self.signals) => self.handle_signal(signal).await?,
+                _ = tick.tick() => {
+                    self.update_recovery().await;
+                    self.fire_cursor_moved().await;
+                    self.flush_mapped_keys().await?;
+                }
             }
         }
     }
 
-    // Editor defaults.
-    // Handles arguments and initial editor states.
-    pub fn default() -> Self {
-        let args: Vec<String> = env::args().collect();
-        let mut initial_status =
-            String::from("HELP: Ctrl-F = find | Ctrl-S = save | Ctrl-Q = quit");
-        let document = if args.len() > 1 {
-            let file_name = &args[1];
-            let doc = Document::open(&file_name);
+    // Creates the editor from the parsed command line arguments, drawing to the real terminal.
+    // An invalid config is reported in the message bar, and the defaults are used instead.
+    pub fn new(options: LaunchOptions) -> Result<Self, ZenError> {
+        let (config, config_error) = match Config::load() {
+            Ok(config) => (config, None),
+            Err(error) => (Config::default(), Some(error)),
+        };
+        let terminal = DefaultTerminal::new()?;
 
-            if doc.is_ok() {
-                doc.unwrap()
-            } else {
-                initial_status = format!("ERR: Could not open file: {}", file_name);
-                Document::default()
+        let mut editor = Self::with_terminal(terminal, options);
+        // Without them, Ctrl-Z and Ctrl-C still work as keys, but signals from elsewhere stop
+        // or kill the editor with the terminal as it is.
+        editor.signals = Signals::new().ok();
+        editor.recovery_dir = Config::recovery_dir();
+        editor.set_config(config);
+        if let Some(error) = config_error {
+            editor.report(&error);
+        }
+        Ok(editor)
+    }
+
+    // Creates the editor on top of any terminal backend, e.g. a MockTerminal in tests.
+    // Handles the initial document, cursor line and editor states.
+    pub fn with_terminal<T>(terminal: T, options: LaunchOptions) -> Self
+    where
+        T: TerminalBackend + 'static,
+    {
+        let mut messages = Messages::new(Duration::from_secs(Config::default().message_timeout));
+        let mut initial_status = (
+            Level::Info,
+            String::from("HELP: Ctrl-F = find | Ctrl-S = save | Ctrl-Q = quit"),
+        );
+        let (mut document, loader) = if let Some(file_name) = &options.file_name {
+            match Document::open_incremental(file_name) {
+                Ok(opened) => opened,
+                Err(error) => {
+                    initial_status = (Level::Error, format!("Could not open file {}", error));
+                    (Document::open_failed(file_name, &error), None)
+                }
             }
         } else {
-            Document::default()
+            (Document::default(), None)
         };
+        document.set_read_only(options.readonly);
 
-        Self {
+        messages.push(initial_status.0, initial_status.1, SystemClock.now());
+
+        let (command_queue, commands, shutdown) = CommandQueue::new();
+        let input = KeyInput::new(terminal.input_events_stream());
+        let detected_colors = terminal.color_support();
+        let mut editor = Self {
             should_quit: false,
-            terminal: Terminal::default().expect("Failed to initialize terminal"),
+            input,
+            commands,
+            command_queue,
+            shutdown,
+            clock: Box::new(SystemClock),
+            terminal: Box::new(terminal),
+            config: Config::default(),
             document,
             cursor_position: Position::default(),
             offset: Position::default(),
-            status_message: StatusMessage::from(initial_status),
+            messages,
+            prompt_line: None,
+            prompt_history: PromptHistory::default(),
+            overlay: None,
             quit_times: QUIT_TIMES,
             highlighted_word: None,
+            last_search_query: None,
+            last_search_direction: SearchDirection::Forward,
+            search_matches: Vec::new(),
+            highlight_search: false,
+            spell: None,
+            diff: None,
+            pending_input: PendingInput::default(),
+            digraphs: Digraphs::default(),
+            keymap: Keymap::default(),
+            mapped_keys: Vec::new(),
+            mapped_keys_at: None,
+            mapping_depth: 0,
+            yanked: Vec::new(),
+            last_yank: Vec::new(),
+            registers: HashMap::new(),
+            folds: Folds::default(),
+            events: EventBus::default(),
+            running_hooks: false,
+            cursor_moved_at: None,
+            changes: ChangeList::default(),
+            quickfix: QuickfixList::default(),
+            building: false,
+            build_summary: None,
+            loader,
+            abbreviations: HashMap::new(),
+            current_insert: String::new(),
+            insert_start: Position::default(),
+            last_insert: String::new(),
+            last_change: None,
+            changing_match: false,
             mode: EditorMode::Normal,
+            tabs: Vec::new(),
+            active_tab: 0,
+            status_bar: StatusBar::default(),
+            last_frame: Vec::new(),
+            frame_size: (0, 0),
+            cursor_style: None,
+            signals: None,
+            recovery_dir: None,
+            recovery: RecoveryState::default(),
+            recovery_write: None,
+            terminated: false,
+            shutting_down: false,
+            workers: Workers::default(),
+            profiler: Profiler::default(),
+            detected_colors,
+        };
+
+        if let Some(line) = options.line {
+            editor.goto_line(line.saturating_sub(1));
+        }
+        // Hex dumps start on their first byte rather than its offset.
+        editor.clamp_cursor();
+
+        editor
+    }
+
+    // Applies a config to the running editor, e.g. one loaded from the config file.
+    pub fn set_config(&mut self, config: Config) {
+        self.abbreviations = config.abbreviations.clone();
+        match Digraphs::new(&config.digraphs) {
+            Ok(digraphs) => self.digraphs = digraphs,
+            Err(error) => self.report(&error),
+        }
+        self.events.set_hooks(config.hooks.clone());
+        self.keymap = Keymap::default();
+        let mappings = [
+            (MapMode::Normal, &config.nmap),
+            (MapMode::Insert, &config.imap),
+        ];
+        for (mode, mappings) in mappings {
+            for (lhs, rhs) in mappings {
+                if let Err(error) = self.keymap.map(mode, lhs, rhs, config.leader) {
+                    self.report(&error);
+                }
+            }
         }
+        self.messages
+            .set_timeout(Duration::from_secs(config.message_timeout));
+        self.config = config;
+        self.apply_colors();
+    }
+
+    // Draws in the colors the config asks for: none with `:set notermcolors`, else the ones
+    // it sets or the ones detected.
+    fn apply_colors(&mut self) {
+        let color_support = if self.config.term_colors {
+            self.config.colors.unwrap_or(self.detected_colors)
+        } else {
+            ColorSupport::Monochrome
+        };
+        if color_support != self.terminal.color_support() {
+            self.terminal.set_color_support(color_support);
+            // Every line is drawn again in the new colors.
+            self.last_frame.clear();
+        }
+    }
+
+    // Keeps recovery files in the directory rather than the user's state directory.
+    pub fn set_recovery_dir(&mut self, dir: PathBuf) {
+        self.recovery_dir = Some(dir);
+    }
+
+    // Replaces the editor's time source.
+    pub fn set_clock<C>(&mut self, clock: C)
+    where
+        C: Clock + 'static,
+    {
+        self.clock = Box::new(clock);
     }
 
     // Processes keypresses in the active terminal.
     // Used by the main editor loop and checked after a frame has finished rendering.
-    // TODO: These keymaps will be loaded through a configuration file.
-    fn process_keypress(&mut self) -> Result<(), std::io::Error> {
-        let pressed_key = Terminal::read_key()?;
+    pub async fn process_keypress(&mut self) -> Result<(), ZenError> {
+        let pressed_key = self.input.next().await?;
+        self.handle_key(pressed_key).await
+    }
 
-        match self.mode {
-            EditorMode::Normal => match pressed_key {
-                // Switch to Insert Mode
-                Key::Char('i') => self.execute(Command::EditorSwitchMode(EditorMode::Insert)),
-
-                Key::Char('h') => self.execute(Command::CursorMoveLeft),
-                Key::Char('j') => self.execute(Command::CursorMoveUp),
-                Key::Char('k') => self.execute(Command::CursorMoveDown),
-                Key::Char('l') => self.execute(Command::CursorMoveRight),
-
-                Key::Left => self.execute(Command::CursorMovePrevWord),
-                Key::Right => self.execute(Command::CursorMoveNextWord),
-
-                Key::Ctrl('J') => self.execute(Command::DocumentMoveStart),
-                Key::Ctrl('K') => self.execute(Command::DocumentMoveEnd),
-                Key::Char('J') => self.execute(Command::DocumentPageUp),
-                Key::Char('K') => self.execute(Command::DocumentPageDown),
-                Key::Char('H') => self.execute(Command::CursorMoveStart),
-                Key::Char('L') => self.execute(Command::CursorMoveEnd),
-
-                Key::Ctrl('q') => {
-                    if self.quit_times > 0 && self.document.is_dirty() {
-                        self.status_message = StatusMessage::from(format!(
-                        "WARNING! File has unsaved changes. Press Ctrl-Q {} more times to quit.",
-                        self.quit_times
-                    ));
-                        self.quit_times -= 1;
-                        return Ok(());
-                    }
-                    self.should_quit = true
-                }
-                _ => (),
-            },
-            EditorMode::Insert => match pressed_key {
-                // Switch to Normal mode
-                Key::Esc => self.execute(Command::EditorSwitchMode(EditorMode::Normal)),
-
-                Key::Ctrl('s') => self.execute(Command::DocumentSave),
-                Key::Ctrl('f') => self.execute(Command::DocumentSearch),
-                Key::Char(c) => self.execute(Command::DocumentInsert(c)),
-                Key::Delete => self.document.delete(&self.cursor_position),
-                Key::Backspace => {
-                    if self.cursor_position.x > 0 || self.cursor_position.y > 0 {
-                        self.execute(Command::CursorMoveLeft);
-                        self.document.delete(&self.cursor_position);
-                    }
-                }
-                Key::Up => self.execute(Command::CursorMoveUp),
-                Key::Down => self.execute(Command::CursorMoveDown),
-                Key::Left => self.execute(Command::CursorMoveLeft),
-                Key::Right => self.execute(Command::CursorMoveRight),
-                Key::PageUp => self.execute(Command::DocumentPageUp),
-                Key::PageDown => self.execute(Command::DocumentPageDown),
-                Key::Home => self.execute(Command::CursorMoveStart),
-                Key::End => self.execute(Command::CursorMoveEnd),
-                _ => (),
-            },
-            EditorMode::Command => match pressed_key {
-                _ => (),
-            },
+    // Hands the owner a sender for running commands on this editor from other tasks.
+    pub fn command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+
+    // Handles a typed key through the mappings, along with the keys it expands to.
+    async fn handle_key(&mut self, pressed_key: KeyEvent) -> Result<(), ZenError> {
+        let started = self.profile_start();
+        self.flush_mapped_keys().await?;
+        self.mapping_depth = 0;
+        let key = self.map_key(pressed_key);
+        let result = self.run_keys(key).await;
+        self.profile_end(Phase::Input, started);
+        result
+    }
+
+    // Runs the key, if any, then the keys queued as typed, each through the mappings.
+    async fn run_keys(&mut self, mut key: Option<KeyEvent>) -> Result<(), ZenError> {
+        loop {
+            if let Some(key) = key {
+                self.run_key(key).await?;
+            }
+            let Some(queued) = self.input.next_queued() else {
+                return Ok(());
+            };
+            key = self.map_key(queued);
         }
+    }
 
-        self.scroll();
-        if self.quit_times < QUIT_TIMES {
-            self.quit_times = QUIT_TIMES;
-            self.status_message = StatusMessage::from(String::new());
+    // Mode whose mappings apply to the next key, if any do.
+    // Keys continuing a sequence, like the `q` of `gq` or the characters of a digraph, are never mapped.
+    fn mapping_mode(&self) -> Option<MapMode> {
+        match self.mode {
+            _ if self.overlay.is_some() || !self.pending_input.is_empty() => None,
+            EditorMode::Normal => Some(MapMode::Normal),
+            EditorMode::Insert => Some(MapMode::Insert),
+            _ => None,
         }
-        Ok(())
     }
 
-    // Executes a command given
-    // Matches commands::Command to a public function found in the commands folder.
-    // TODO: The goal of having the commands folder is for the potential use of a plugin
-    // system that could utilize these functions to interact with with the editor.
-    fn execute(&mut self, command: Command) {
-        match command {
-            Command::CursorMoveUp => commands::cursor::move_up(self),
-            Command::CursorMoveDown => commands::cursor::move_down(self),
-            Command::CursorMoveLeft => commands::cursor::move_left(self),
-            Command::CursorMoveRight => commands::cursor::move_right(self),
-            Command::CursorMoveStart => commands::cursor::move_start_of_row(self),
-            Command::CursorMoveEnd => commands::cursor::move_end_of_row(self),
-            Command::CursorMoveNextWord => commands::cursor::move_next_word(self),
-            Command::CursorMovePrevWord => commands::cursor::move_prev_word(self),
-
-            Command::DocumentInsert(c) => {
-                self.document.insert(&self.cursor_position, c);
-                self.execute(Command::CursorMoveRight);
-            }
-            Command::DocumentSave => self.save(),
-            Command::DocumentSearch => self.search(),
-            Command::DocumentPageUp => commands::view::scroll_up(self),
-            Command::DocumentPageDown => commands::view::scroll_down(self),
-            Command::DocumentMoveStart => commands::cursor::move_start_of_document(self),
-            Command::DocumentMoveEnd => commands::cursor::move_end_of_document(self),
-
-            Command::EditorSwitchMode(mode) => self.mode = mode,
-            _ => (),
+    // Returns the key if it's to run as typed. Keys that may start a mapping are held until
+    // the rest of it is typed, and a mapping's keys are queued in place of the keys typed.
+    fn map_key(&mut self, key: KeyEvent) -> Option<KeyEvent> {
+        let Some(mode) = self
+            .mapping_mode()
+            .filter(|_| !matches!(key, KeyEvent::Mouse(_)))
+        else {
+            if self.mapped_keys.is_empty() {
+                return Some(key);
+            }
+            self.input.push_front(&[key]);
+            return self.resolve_mapped_keys();
+        };
+
+        self.mapped_keys.push(key);
+        self.mapped_keys_at = Some(self.clock.now());
+        match self.keymap.lookup(mode, &self.mapped_keys) {
+            Lookup::Prefix => None,
+            Lookup::Found(_) | Lookup::None => self.resolve_mapped_keys(),
         }
     }
 
-    // Handles terminal scrolling by adjusting the offset.
-    fn scroll(&mut self) {
-        let Position { x, y } = self.cursor_position;
-        let width = self.terminal.size().width as usize;
-        let height = self.terminal.size().height as usize;
+    // Settles the held keys once no more will be added to them: the longest run from the first
+    // that is mapped is expanded, and the keys after it are queued again. Without one, the first
+    // key is returned to run as typed.
+    fn resolve_mapped_keys(&mut self) -> Option<KeyEvent> {
+        let keys = std::mem::take(&mut self.mapped_keys);
+        self.mapped_keys_at = None;
+        let mapped = self.mapping_mode().and_then(|mode| {
+            (1..=keys.len())
+                .rev()
+                .find_map(|len| self.keymap.get(mode, &keys[..len]))
+        });
+        let Some(mapping) = mapped else {
+            let (first, rest) = keys.split_first()?;
+            self.input.push_front(rest);
+            return Some(*first);
+        };
 
-        let mut offset = &mut self.offset;
-        if y < offset.y {
-            offset.y = y;
-        } else if y >= offset.y.saturating_add(height) {
-            offset.y = y.saturating_sub(height).saturating_add(1);
+        self.mapping_depth = self.mapping_depth.saturating_add(1);
+        if self.mapping_depth > MAX_MAPPING_DEPTH {
+            self.input.clear_queued();
+            self.notify(Level::Error, "Recursive mapping");
+            return None;
         }
-        if x < offset.x {
-            offset.x = x;
-        } else if x >= offset.x.saturating_add(width) {
-            offset.x = x.saturating_sub(width).saturating_add(1);
+        let expansion = mapping.expansion.clone();
+        self.input.push_front(&keys[mapping.keys.len()..]);
+        self.input.push_front(&expansion);
+        None
+    }
+
+    // Takes held keys as they are once the rest of a mapping hasn't been typed in time.
+    async fn flush_mapped_keys(&mut self) -> Result<(), ZenError> {
+        let Some(typed_at) = self.mapped_keys_at else {
+            return Ok(());
+        };
+        if self.clock.now().saturating_duration_since(typed_at) < MAPPING_TIMEOUT {
+            return Ok(());
         }
+        self.mapping_depth = 0;
+        let key = self.resolve_mapped_keys();
+        self.run_keys(key).await
     }
 
-    // Handles frame/screen refreshes.
-    // Includes highlighting & redrawing the rows & TUI
-    fn refresh_screen(&mut self) -> Result<(), std::io::Error> {
-        Terminal::cursor_hide();
-        Terminal::cursor_position(&Position::default());
-        if self.should_quit {
-            println!("Goodbye.\r");
-            Terminal::clear_screen();
-        } else {
-            let viewport = self.calculate_viewport();
+    // Handles a key, adding where it edited the document to the change list.
+    // Runs the hooks of the events the key set off afterwards.
+    async fn run_key(&mut self, pressed_key: KeyEvent) -> Result<(), ZenError> {
+        self.fire_cursor_moved().await;
+        let position = self.cursor_position.clone();
+        let edits = self.document.edit_count();
+        self.dispatch_key(pressed_key).await?;
+        self.note_edits(position.clone(), edits);
+        if self.cursor_position != position {
+            self.cursor_moved_at = Some(self.clock.now());
+        }
+        self.fire_queued().await;
+        Ok(())
+    }
 
-            // It's important that we highlight before drawing
-            // We will only be highlighting the rows visible in the viewport to improve performance
-            self.document.highlight(viewport);
-            self.draw_rows();
-            self.draw_status_bar();
-            self.draw_message_bar();
-            Terminal::cursor_position(&Position {
-                x: self.cursor_position.x.saturating_sub(self.offset.x),
-                y: self.cursor_position.y.saturating_sub(self.offset.y),
-            });
+    // Adds the position to the change list if the document was edited since it had `edits` edits.
+    fn note_edits(&mut self, position: Position, edits: usize) {
+        if self.document.edit_count() != edits {
+            self.changes.push(position);
         }
-        Terminal::cursor_show();
-        Terminal::flush()
     }
 
-    // Returns a range of the row indexes within the terminal's view.
-    fn calculate_viewport(&self) -> Range<usize> {
-        let height = self.terminal.size().height as usize;
-        let start_row = self.offset.y;
-        let end_row = cmp::min(self.offset.y + height, self.document.len());
+    async fn dispatch_key(&mut self, pressed_key: KeyEvent) -> Result<(), ZenError> {
+        self.messages.key_pressed(self.clock.now());
+        if self.overlay.is_some() {
+            self.scroll_overlay(pressed_key);
+            return Ok(());
+        }
 
-        start_row..end_row
-    }
+        if let KeyEvent::Mouse(mouse) = pressed_key {
+            self.pending_input.clear();
+            self.handle_mouse(mouse).await?;
+            self.sync_view();
+            return Ok(());
+        }
 
-    // Handles re-rendering all rows within the terminal's view.
-    // Will account for cases such as an empty document, or an empty row.
-    // (?) Might move that...
-    //
-    // In the case of a populated row, we will call self.draw_row(row),
-    // which will then call row.render(), and self.draw_row will print
-    // the string that returns from row.render().
-    //
-    // This is probably overcomplicated and will be rewritten.
-    fn draw_rows(&self) {
-        let height = self.terminal.size().height;
-        for terminal_row in 0..height {
-            Terminal::clear_current_line();
+        if !self.pending_input.is_empty() {
+            return self.dispatch_pending(pressed_key).await;
+        }
 
-            if let Some(row) = self
-                .document
-                .row(self.offset.y.saturating_add(terminal_row as usize))
-            {
-                self.draw_row(row);
-            } else if self.document.is_empty() && terminal_row == height / 3 {
-                self.draw_welcome_message()
-            } else {
-                println!("~\r");
+        // Keys bound to a registered command run it. The rest are handled here.
+        let bound = self
+            .mapping_mode()
+            .and_then(|mode| self.keymap.binding(mode, pressed_key));
+        match (self.mode, bound) {
+            (_, Some(info)) => self.run_registered(info).await?,
+            // The warning about unsaved changes stays up while it counts down.
+            (EditorMode::Normal, None) if pressed_key == KeyEvent::Ctrl('q') => {
+                if self.warn_before_quitting() {
+                    return Ok(());
+                }
+                self.should_quit = true;
             }
+            (EditorMode::Normal, None) => self.dispatch_normal(pressed_key).await?,
+            (EditorMode::Insert, None) => self.dispatch_insert(pressed_key).await?,
+            (EditorMode::Command, None) => (),
         }
-    }
 
-    // Draws a welcome message in the case of an empty document.
-    // The case check can currently be found here, in self.draw_rows()
-    // (As of pre-0.1)
-    fn draw_welcome_message(&self) {
-        let mut welcome_message = format!("Zen {}\r", env!("CARGO_PKG_VERSION"));
-        let width = self.terminal.size().width as usize;
-        let len = welcome_message.len();
-        let padding = width.saturating_sub(len) / 2;
-        let spaces = " ".repeat(padding.saturating_sub(1));
+        self.sync_view();
+        if self.quit_times < QUIT_TIMES {
+            self.quit_times = QUIT_TIMES;
+            self.messages.dismiss(self.clock.now());
+        }
+        Ok(())
+    }
 
-        welcome_message = format!("~{}{}", spaces, welcome_message);
-        welcome_message.truncate(width);
-        println!("{}\r", welcome_message);
+    // Completes a key sequence like `gq` or `]c`, or keeps waiting for the rest of it.
+    async fn dispatch_pending(&mut self, pressed_key: KeyEvent) -> Result<(), ZenError> {
+        if let KeyEvent::Char(c) = pressed_key {
+            self.pending_input.push(c);
+        }
+        // Insert mode's `Ctrl-K` digraphs, `Ctrl-R` registers and `Ctrl-V` characters.
+        if self.pending_input.keys().starts_with('^') {
+            let complete = self.insert_special(pressed_key).await?;
+            if complete {
+                self.pending_input.clear();
+                self.sync_view();
+            }
+            return Ok(());
+        }
+        match self.pending_input.keys() {
+            keys if matches!(pressed_key, KeyEvent::Char(_)) && awaits_more_keys(keys) => {
+                return Ok(())
+            }
+            "cgn" => {
+                self.change_next_match().await?;
+            }
+            keys if keys.starts_with("gq") => {
+                if let Some(rows) = self.reflow_rows(&keys[2..]) {
+                    self.execute(Command::DocumentReflow(rows)).await?;
+                }
+            }
+            // `gU` and `gu` take the same line motions and text objects, `w` for the rest of
+            // the word and `$` for the rest of the line. Doubling the last key converts the whole line.
+            keys if keys.starts_with("gU") || keys.starts_with("gu") => {
+                if let Some(command) = self.convert_case_command(keys) {
+                    self.execute(command).await?;
+                }
+            }
+            keys => {
+                if let Some(command) = sequence_command(keys) {
+                    self.execute(command).await?;
+                } else if let Some(motion) = operator_motion(keys) {
+                    // `d` and `c` with a word motion, e.g. `dw` or `cE`.
+                    self.apply_operator(keys.starts_with('c'), motion).await?;
+                }
+            }
+        }
+        self.pending_input.clear();
+        self.sync_view();
+        Ok(())
     }
 
-    // Handles printing a row to the terminal with the String provided
-    // by row.render()
-    fn draw_row(&self, row: &Row) {
-        let row = row.render();
-        println!("{}\r", row)
+    // Changes, for `c`, or deletes, for `d`, what a word motion moves over.
+    async fn apply_operator(&mut self, change: bool, motion: Motion) -> Result<(), ZenError> {
+        if change {
+            self.change_motion(motion).await
+        } else {
+            self.execute(Command::DocumentDeleteMotion(motion)).await?;
+            self.clamp_cursor();
+            Ok(())
+        }
     }
 
-    // Draws a status bar to the terminal.
-    // This is primarily used for information on the document, such
-    // as the file opened, dirty status, document's language, etc.
-    // TODO: Stylize this with the active theme.
-    fn draw_status_bar(&self) {
-        let mut status;
-        let width = self.terminal.size().width as usize;
-        let modified_indicator = if self.document.is_dirty() {
-            " (modified)"
+    // The command `gU` or `gu` runs with the motion or text object after it.
+    fn convert_case_command(&self, keys: &str) -> Option<Command> {
+        let direction = if keys.starts_with("gU") {
+            CaseDirection::Upper
         } else {
-            ""
+            CaseDirection::Lower
         };
+        let selection = self.case_selection(keys.get(2..)?)?;
+        Some(Command::DocumentConvertCase(selection, direction))
+    }
 
-        let mut file_name = "[No Name]".to_string();
-        if let Some(name) = &self.document.file_name {
-            file_name = name.clone();
-            file_name.truncate(20);
+    // Rows `gq` reflows with the motion or text object after it.
+    fn reflow_rows(&self, motion: &str) -> Option<Range<usize>> {
+        let y = self.cursor_position.y;
+        match motion {
+            "q" => Some(self.document.paragraph_range(y)),
+            "j" => Some(y.saturating_sub(1)..y.saturating_add(1)),
+            "k" => Some(y..y.saturating_add(2)),
+            "ip" | "ap" => Some(self.document.paragraph_object(y, motion == "ap")),
+            _ => None,
         }
-        status = format!(
-            "{} - {} lines{}",
-            file_name,
-            self.document.len(),
-            modified_indicator
-        );
-
-        let line_indicator = format!(
-            "{} | {}/{}",
-            self.document.file_type(),
-            self.cursor_position.y.saturating_add(1),
-            self.document.len()
-        );
-
-        let len = status.len() + line_indicator.len();
-        status.push_str(&" ".repeat(width.saturating_sub(len)));
-        status = format!("{}{}", status, line_indicator);
-        status.truncate(width);
-
-        Terminal::set_bg_color(color::Rgb(239, 239, 239));
-        Terminal::set_fg_color(color::Rgb(63, 63, 63));
-        println!("{}\r", status);
-        Terminal::reset_fg_color();
-        Terminal::reset_bg_color();
     }
 
-    // Message bar used to display text and command assistance.
-    fn draw_message_bar(&self) {
-        Terminal::clear_current_line();
-        let message = &self.status_message;
+    // Keys of Normal mode that aren't bound to a registered command.
+    async fn dispatch_normal(&mut self, pressed_key: KeyEvent) -> Result<(), ZenError> {
+        match pressed_key {
+            // While the quit warning is up.
+            KeyEvent::Char('d') if self.quit_times < QUIT_TIMES => {
+                self.show_unsaved_changes();
+            }
 
-        if Instant::now() - message.time < Duration::new(5, 0) {
-            let mut text = message.text.clone();
-            text.truncate(self.terminal.size().width as usize);
-            print!("{}", text);
+            KeyEvent::Char(c @ ('w' | 'b' | 'e' | 'W' | 'B' | 'E')) => {
+                if let Some(motion) = Motion::from_key(c) {
+                    self.execute(Command::CursorMoveWord(motion)).await?;
+                }
+            }
+            KeyEvent::Ctrl('z') => self.suspend()?,
+            KeyEvent::Ctrl('p') => self.command_palette().await?,
+            // Ctrl-C quits most terminal programs, so it says how to quit this one instead.
+            KeyEvent::Ctrl('c') => self.notify(Level::Info, "Type :q and press Enter to quit"),
+            // Clears the search highlight and redraws the whole screen.
+            KeyEvent::Ctrl('l') => {
+                self.execute(Command::SearchClearHighlight).await?;
+                self.last_frame.clear();
+            }
+            KeyEvent::Char('.') => self.repeat_change().await?,
+            KeyEvent::Char(c @ (']' | '[' | 'g' | 'z' | 'c' | 'd')) => self.pending_input.push(c),
+            _ => (),
         }
+        Ok(())
     }
 
-    // Used by search and command operations by providing an input state.
-    // This uses the message bar.
-    fn prompt<C>(&mut self, prompt: &str, mut callback: C) -> Result<Option<String>, std::io::Error>
-    where
-        C: FnMut(&mut Self, Key, &String),
-    {
-        let mut result = String::new();
-
-        loop {
-            self.status_message = StatusMessage::from(format!("{}{}", prompt, result));
-            self.refresh_screen()?;
-
-            let key = Terminal::read_key()?;
-            match key {
-                Key::Backspace => result.truncate(result.len().saturating_sub(1)),
-                Key::Char('\n') => break,
-                Key::Char(c) => {
-                    if !c.is_control() {
-                        result.push(c);
-                    }
+    // Keys of Insert mode that aren't bound to a registered command.
+    async fn dispatch_insert(&mut self, pressed_key: KeyEvent) -> Result<(), ZenError> {
+        match pressed_key {
+            KeyEvent::Char(_)
+            | KeyEvent::Ctrl('a' | 'k' | 'r' | 'v' | 'w' | 'u')
+            | KeyEvent::Delete
+            | KeyEvent::Backspace
+                if self.document.is_read_only() =>
+            {
+                self.notify(Level::Warning, "File is read-only.");
+            }
+            KeyEvent::Char(c) => {
+                if !search::is_word_char(c) {
+                    self.expand_abbreviation();
                 }
-                Key::Esc => {
-                    result.truncate(0);
-                    break;
+                self.current_insert.push(c);
+                self.execute(Command::DocumentInsert(c)).await?
+            }
+            KeyEvent::Ctrl('a') => self.insert_last_insert().await?,
+            KeyEvent::Ctrl('p') => self.command_palette().await?,
+            KeyEvent::Ctrl('k') => self.pending_input.push_str("^K"),
+            KeyEvent::Ctrl('r') => self.pending_input.push_str("^R"),
+            KeyEvent::Ctrl('v') => self.pending_input.push_str("^V"),
+            KeyEvent::Ctrl('w') => {
+                self.delete_before_cursor(Command::DocumentDeleteWordBackward)
+                    .await?
+            }
+            KeyEvent::Ctrl('u') => {
+                let start = self.insert_start_column();
+                self.delete_before_cursor(Command::DocumentDeleteBackTo(start))
+                    .await?
+            }
+            KeyEvent::Delete => self.document.delete(&self.cursor_position),
+            KeyEvent::Backspace => {
+                self.current_insert.pop();
+                if self.cursor_position.x > 0 || self.cursor_position.y > 0 {
+                    self.execute(Command::CursorMoveLeft).await?;
+                    self.document.delete(&self.cursor_position);
                 }
-                _ => (),
             }
-            callback(self, key, &result);
+            KeyEvent::Up => self.move_in_insert(Command::CursorMoveUp).await?,
+            KeyEvent::Down => self.move_in_insert(Command::CursorMoveDown).await?,
+            KeyEvent::Left => self.move_in_insert(Command::CursorMoveLeft).await?,
+            KeyEvent::Right => self.move_in_insert(Command::CursorMoveRight).await?,
+            KeyEvent::PageUp => self.move_in_insert(Command::DocumentPageUp).await?,
+            KeyEvent::PageDown => self.move_in_insert(Command::DocumentPageDown).await?,
+            KeyEvent::Home => self.move_in_insert(Command::CursorMoveStart).await?,
+            KeyEvent::End => self.move_in_insert(Command::CursorMoveEnd).await?,
+            _ => (),
         }
+        Ok(())
+    }
 
-        self.status_message = StatusMessage::from(String::new());
-        if result.is_empty() {
-            return Ok(None);
+    // Warns about unsaved changes on Ctrl-Q, until it's pressed QUIT_TIMES more times.
+    // Returns whether it warned rather than letting the editor quit.
+    fn warn_before_quitting(&mut self) -> bool {
+        let dirty = self.document.is_dirty() || self.tabs.iter().any(|tab| tab.document.is_dirty());
+        if self.quit_times == 0 || !dirty {
+            return false;
         }
-
-        Ok(Some(result))
+        // Each warning replaces the last, rather than lining up behind it.
+        if self.quit_times < QUIT_TIMES {
+            self.messages.dismiss(self.clock.now());
+        }
+        self.notify(
+            Level::Warning,
+            format!(
+                "WARNING! File has unsaved changes. Press Ctrl-Q {} more times to quit, (d)iff to see them.",
+                self.quit_times
+            ),
+        );
+        self.quit_times -= 1;
+        true
     }
 
-    // Saves the active document.
-    fn save(&mut self) {
-        if self.document.file_name.is_none() {
-            let new_name = self.prompt("Save as: ", |_, _, _| {}).unwrap_or(None);
+    // Inserts the character a `Ctrl-K` digraph or a `Ctrl-V` sequence stands for once all its
+    // keys are typed, and returns whether they are. `Ctrl-V u` takes four hex digits of a code
+    // point, and `Ctrl-V` before any other character inserts that character as is.
+    // `Ctrl-R` inserts the register named by the key after it instead.
+    // A key that isn't a character cancels the sequence.
+    async fn insert_special(&mut self, pressed_key: KeyEvent) -> Result<bool, ZenError> {
+        if !matches!(pressed_key, KeyEvent::Char(_)) {
+            return Ok(true);
+        }
+        let keys = self.pending_input.keys();
+        if let Some(name) = keys.strip_prefix("^R").and_then(|name| name.chars().next()) {
+            self.insert_register(name).await?;
+            return Ok(true);
+        }
+        let inserted = if let Some(digraph) = keys.strip_prefix("^K") {
+            let mut chars = digraph.chars();
+            let (Some(first), Some(second)) = (chars.next(), chars.next()) else {
+                return Ok(false);
+            };
+            self.digraphs
+                .get(first, second)
+                .ok_or_else(|| format!("Unknown digraph: {}{}", first, second))
+        } else if let Some(hex) = keys.strip_prefix("^Vu") {
+            if hex.len() < 4 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Ok(false);
+            }
+            u32::from_str_radix(hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| format!("Invalid code point: {}", hex))
+        } else {
+            // `^V` and the character typed after it.
+            match keys.chars().nth(2) {
+                Some(c) => Ok(c),
+                None => return Ok(true),
+            }
+        };
 
-            if new_name.is_none() {
-                self.status_message = StatusMessage::from("Save aborted.".to_string());
-                return;
+        match inserted {
+            Ok(c) => {
+                self.current_insert.push(c);
+                self.execute(Command::DocumentInsert(c)).await?;
             }
+            Err(message) => self.notify(Level::Error, message),
+        }
+        Ok(true)
+    }
 
-            self.document.file_name = new_name;
+    // Runs a command deleting text before the cursor on its row in Insert mode. What it deletes
+    // is dropped from the text typed in this session, like Backspace does.
+    async fn delete_before_cursor(&mut self, command: Command) -> Result<(), ZenError> {
+        let before = self.cursor_position.x;
+        self.execute(command).await?;
+        let deleted = before.saturating_sub(self.cursor_position.x);
+        for _ in 0..deleted {
+            self.current_insert.pop();
         }
+        Ok(())
+    }
 
-        if self.document.save().is_ok() {
-            self.status_message = StatusMessage::from("File saved successfully.".to_string());
+    // Where `Ctrl-U` stops on the cursor's row: where the insert started, when that's on this
+    // row, so text from before it is kept. On rows typed since, it's the start of the row.
+    fn insert_start_column(&self) -> usize {
+        if self.insert_start.y == self.cursor_position.y {
+            cmp::min(self.insert_start.x, self.cursor_position.x)
         } else {
-            self.status_message = StatusMessage::from("Error writing file".to_string());
+            0
         }
     }
 
-    // Active document search functionality.
-    fn search(&mut self) {
-        let old_position = self.cursor_position.clone();
-
+    // Moves the cursor in Insert mode, which starts the insert over from there, like in Vim.
+    async fn move_in_insert(&mut self, command: Command) -> Result<(), ZenError> {
+        self.document.break_typing();
+        self.execute(command).await?;
+        self.insert_start = self.cursor_position.clone();
+        Ok(())
+    }
+
+    // Inserts the text typed in the last Insert mode session again, as one undo step.
+    // It counts as typed in this session too.
+    async fn insert_last_insert(&mut self) -> Result<(), ZenError> {
+        if self.last_insert.is_empty() {
+            self.notify(Level::Warning, "No inserted text yet");
+            return Ok(());
+        }
+        let text = self.last_insert.clone();
+        self.document.begin_batch();
+        for c in text.chars() {
+            self.execute(Command::DocumentInsert(c)).await?;
+        }
+        self.document.end_batch();
+        self.current_insert.push_str(&text);
+        Ok(())
+    }
+
+    // Inserts a register for `Ctrl-R` as if it were typed, undone along with what was typed
+    // before it. Lines are each followed by a newline; `%` is the file's name and `+` the system
+    // clipboard.
+    async fn insert_register(&mut self, name: char) -> Result<(), ZenError> {
+        // `%` and `+` hold text rather than lines, so nothing ends them with a newline.
+        let text = match name {
+            '%' => self.document.file_name.clone(),
+            '+' => clipboard::paste().await.filter(|text| !text.is_empty()),
+            '"' | '0' | 'a'..='z' | 'A'..='Z' => self.register(name).map(|lines| {
+                lines
+                    .iter()
+                    .flat_map(|line| [line, "\n"])
+                    .collect::<String>()
+            }),
+            _ => {
+                self.notify(Level::Error, format!("Invalid register name: {}", name));
+                return Ok(());
+            }
+        };
+        let Some(text) = text else {
+            self.notify(Level::Error, format!("Nothing in register {}", name));
+            return Ok(());
+        };
+        let end = self.document.insert_text(&self.cursor_position, &text);
+        self.apply(CommandOutcome::MoveCursor(end));
+        self.current_insert.push_str(&text);
+        Ok(())
+    }
+
+    // A click puts the cursor where it landed, and the wheel moves it a few lines up or down.
+    // The middle button would paste, but there's nothing to paste from yet.
+    async fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<(), ZenError> {
+        self.document.break_typing();
+        match mouse.button {
+            MouseButton::Left => {
+                // Clicks on the bars and the scrollbar are ignored, and so are clicks in diff mode,
+                // where screen rows don't line up with document rows.
+                let Some(terminal_row) = (mouse.y as usize).checked_sub(self.tab_bar_height())
+                else {
+                    return Ok(());
+                };
+                if self.diff.is_some()
+                    || mouse.y >= self.terminal.size().height
+                    || mouse.x as usize >= self.text_area_width()
+                {
+                    return Ok(());
+                }
+                let len = self.document.len();
+                let y = self
+                    .folds
+                    .lines(self.offset.y, len)
+                    .nth(terminal_row)
+                    .unwrap_or_else(|| self.folds.line_start(len.saturating_sub(1)));
+                let column = self.offset.x.saturating_add(mouse.x as usize);
+                let x = if self.document.is_long_line(y) {
+                    // Long rows are drawn plain, a column a grapheme.
+                    self.document
+                        .row(y)
+                        .map_or(0, |row| cmp::min(column, row.len()))
+                } else {
+                    let tab_width = self.whitespace().tab_width;
+                    self.document
+                        .row(y)
+                        .map_or(0, |row| row.grapheme_at_visual_column(column, tab_width))
+                };
+                self.apply(CommandOutcome::MoveCursor(Position { x, y }));
+            }
+            MouseButton::WheelUp => {
+                for _ in 0..WHEEL_LINES {
+                    self.execute(Command::CursorMoveUp).await?;
+                }
+            }
+            MouseButton::WheelDown => {
+                for _ in 0..WHEEL_LINES {
+                    self.execute(Command::CursorMoveDown).await?;
+                }
+            }
+            MouseButton::Middle => (),
+        }
+        Ok(())
+    }
+
+    // Executes a command given
+    // Commands run against a CommandContext, and the editor applies the outcome they return.
+    // The few that need the whole editor, e.g. to prompt, are run by the editor itself.
+    // TODO: The goal of having the commands folder is for the potential use of a plugin
+    // system that could utilize these functions to interact with with the editor.
+    // Errors the user can act on are shown in the message bar by the commands themselves,
+    // so only fatal ones are returned.
+    async fn execute(&mut self, command: Command) -> Result<(), ZenError> {
+        let started = self.profile_start();
+        let result = self.execute_command(command).await;
+        self.profile_end(Phase::Command, started);
+        result
+    }
+
+    async fn execute_command(&mut self, command: Command) -> Result<(), ZenError> {
+        if matches!(command, Command::DocumentMoveEnd) {
+            self.wait_for_loading().await?;
+        }
+        let outcome = match command.execute(&mut self.context()).await {
+            Ok(outcome) => outcome,
+            // Reported, and so kept for `:messages`, rather than ending the editor.
+            Err(error) => {
+                self.report(&error);
+                return Ok(());
+            }
+        };
+        if outcome == CommandOutcome::NeedsEditor {
+            return self.execute_on_editor(command).await;
+        }
+        self.apply(outcome);
+        Ok(())
+    }
+
+    // What commands get to see of the editor.
+    fn context(&mut self) -> CommandContext<'_> {
+        let view_height = self.text_area_height();
+        let text_width = self.document.effective_text_width(&self.config);
+        CommandContext {
+            document: &mut self.document,
+            cursor_position: self.cursor_position.clone(),
+            view_height,
+            view_top: self.offset.y,
+            text_width,
+            mode: self.mode,
+        }
+    }
+
+    // Applies what a command asked for to the editor.
+    pub fn apply(&mut self, outcome: CommandOutcome) {
+        match outcome {
+            CommandOutcome::Nothing | CommandOutcome::NeedsEditor => (),
+            CommandOutcome::Message(text) => {
+                self.notify(Level::Info, text);
+            }
+            CommandOutcome::MoveCursor(position) => {
+                let from = self.cursor_position.y;
+                self.cursor_position = position;
+                self.skip_folds(from);
+            }
+            CommandOutcome::SwitchMode(mode) => {
+                // What's typed after switching is undone apart from what was typed before.
+                self.document.break_typing();
+                if self.mode == EditorMode::Insert && mode != EditorMode::Insert {
+                    self.last_insert = std::mem::take(&mut self.current_insert);
+                    if self.changing_match {
+                        self.changing_match = false;
+                        self.last_change = Some(Repeat::ChangeMatch(self.last_insert.clone()));
+                    }
+                    // Like Vim, leaving Insert mode steps back onto the last character typed.
+                    self.cursor_position.x = self.cursor_position.x.saturating_sub(1);
+                }
+                if self.mode != mode {
+                    self.events.emit(Event::ModeChanged);
+                }
+                let entering_insert = mode == EditorMode::Insert && self.mode != EditorMode::Insert;
+                self.mode = mode;
+                self.update_cursor_style();
+                self.clamp_cursor();
+                if entering_insert {
+                    self.insert_start = self.cursor_position.clone();
+                }
+            }
+            CommandOutcome::ScrollTo { top, cursor } => {
+                let from = self.cursor_position.y;
+                self.offset.y = top;
+                self.cursor_position = cursor;
+                self.skip_folds(from);
+            }
+            CommandOutcome::Quit => self.should_quit = true,
+        }
+    }
+
+    async fn execute_on_editor(&mut self, command: Command) -> Result<(), ZenError> {
+        match command {
+            Command::DocumentSave => return self.save(None).await,
+            Command::DocumentSearch => return self.search().await,
+            Command::DocumentUndo => {
+                let position = self.document.undo();
+                self.after_undo(position, "Already at oldest change");
+            }
+            Command::DocumentRedo => {
+                let position = self.document.redo();
+                self.after_undo(position, "Already at newest change");
+            }
+
+            Command::EditorFileInfo => self.file_info(),
+            Command::EditorCharacterInfo => self.character_info(),
+            Command::EditorGoToFile => self.open_file_at_cursor().await,
+            Command::EditorCommandLine => return self.command_line().await,
+
+            Command::FoldToggle => self.toggle_fold(),
+
+            Command::ChangeListPrev => self.jump_to_change(SearchDirection::Backward),
+            Command::ChangeListNext => self.jump_to_change(SearchDirection::Forward),
+
+            Command::TabNext => self.cycle_tab(SearchDirection::Forward),
+            Command::TabPrev => self.cycle_tab(SearchDirection::Backward),
+
+            Command::MakeFinished(output) => self.finish_make(output).await,
+            Command::DocumentLoaded(chunk) => self.append_loaded(chunk),
+
+            Command::DiffNextHunk => self.jump_to_hunk(SearchDirection::Forward),
+            Command::DiffPrevHunk => self.jump_to_hunk(SearchDirection::Backward),
+
+            Command::SearchWordForward => self.search_word(SearchDirection::Forward),
+            Command::SearchWordBackward => self.search_word(SearchDirection::Backward),
+            Command::SearchNext => self.search_next(false),
+            Command::SearchPrev => self.search_next(true),
+            Command::SearchClearHighlight => self.clear_search_highlight(),
+
+            Command::SpellNext => self.jump_to_misspelling(SearchDirection::Forward),
+            Command::SpellPrev => self.jump_to_misspelling(SearchDirection::Backward),
+            Command::SpellSuggest => return self.suggest_spelling().await,
+            _ => (),
+        }
+        Ok(())
+    }
+
+    // Moves the cursor to the 0-based line, as far along it as it was, and scrolls it into view.
+    // Lines past the end land on the last one.
+    pub fn goto_line(&mut self, line: usize) {
+        let y = cmp::min(line, self.document.len().saturating_sub(1));
+        let x = self.cursor_position.x;
+        self.cursor_position =
+            commands::cursor::clamp(&self.document, &Position { x, y }, self.mode);
+        self.scroll();
+    }
+
+    // The editor's current mode.
+    pub fn mode(&self) -> EditorMode {
+        self.mode
+    }
+
+    // Lines copied by the last `:y` or `:d`.
+    pub fn yanked(&self) -> &[String] {
+        &self.yanked
+    }
+
+    // Keys typed so far of an unfinished Normal mode sequence.
+    pub fn pending_input(&self) -> &PendingInput {
+        &self.pending_input
+    }
+
+    // Brings what's derived from the document and cursor up to date after a change.
+    fn sync_view(&mut self) {
+        self.clamp_cursor();
+        self.sync_folds();
+        if let Some(diff) = &mut self.diff {
+            diff.update(&self.document.contents());
+        }
+        self.scroll();
+        self.update_search_matches();
+    }
+
+    // Draws the cursor in the mode's shape: a bar between characters while typing, a block on
+    // one otherwise, like most Vims. Nothing is written while the config turns shapes off.
+    fn update_cursor_style(&mut self) {
+        let style = match self.mode {
+            EditorMode::Insert => CursorStyle::Bar,
+            EditorMode::Normal | EditorMode::Command => CursorStyle::Block,
+        };
+        if self.config.cursor_shape && self.cursor_style != Some(style) {
+            self.terminal.set_cursor_style(style);
+            self.cursor_style = Some(style);
+        }
+    }
+
+    // Gives the terminal back the user's own cursor shape, if the editor changed it.
+    fn restore_cursor_style(&mut self) {
+        if self.cursor_style.take().is_some() {
+            self.terminal.set_cursor_style(CursorStyle::Default);
+        }
+    }
+
+    // Moves the cursor back onto the document, where the mode allows it to be.
+    fn clamp_cursor(&mut self) {
+        self.cursor_position =
+            commands::cursor::clamp(&self.document, &self.cursor_position, self.mode);
+    }
+
+    // Says so in the message bar once pushed commands start waiting for room in the queue,
+    // and takes that down once they all got in. Returns whether any are still waiting.
+    fn report_backlog(&mut self, busy: bool) -> bool {
+        let backlog = self.commands.backlog();
+        if backlog > 0 && !busy {
+            self.notify(
+                Level::Warning,
+                format!("Editor busy, {} commands waiting", backlog),
+            );
+        } else if backlog == 0 && busy {
+            self.messages.dismiss(self.clock.now());
+        }
+        backlog > 0
+    }
+
+    // Sends a message to the message bar.
+    pub fn notify<S>(&mut self, level: Level, text: S)
+    where
+        S: Into<String>,
+    {
+        self.messages.push(level, text.into(), self.clock.now());
+    }
+
+    // Shows a non-fatal error in the message bar.
+    fn report(&mut self, error: &ZenError) {
+        self.notify(Level::Error, error.to_string());
+    }
+
+    // When a timed phase starts, while profiling. Otherwise None, so timing costs a branch.
+    fn profile_start(&self) -> Option<Instant> {
+        self.config.profiling.then(Instant::now)
+    }
+
+    // Keeps how long the phase took, if it was timed.
+    fn profile_end(&mut self, phase: Phase, started: Option<Instant>) {
+        if let Some(started) = started {
+            self.profiler.record(phase, started.elapsed());
+        }
+    }
+
+    // Whitespace the rows draw visibly, as set with `:set list` and `:set trailing`,
+    // or `:setlocal` for the document.
+    fn whitespace(&self) -> Whitespace {
+        let local = &self.document.local_config;
+        Whitespace {
+            list: local.list.unwrap_or(self.config.list),
+            trailing: local.trailing.unwrap_or(self.config.trailing),
+            tab_width: self.document.effective_tab_width(&self.config),
+        }
+    }
+
+    // Screen column of the cursor within its row, counting tabs and wide characters as the
+    // columns they're drawn in. Long rows are drawn plain, a column a grapheme.
+    fn cursor_column(&self) -> usize {
+        let Position { x, y } = self.cursor_position;
+        if self.document.is_long_line(y) {
+            return x;
+        }
+        self.document
+            .row(y)
+            .map_or(x, |row| row.visual_column(x, self.whitespace().tab_width))
+    }
+
+    // Columns rows are drawn in, leaving the last one to the scrollbar when it's on.
+    fn text_area_width(&self) -> usize {
+        let width = self.terminal.size().width as usize;
+        if self.config.scrollbar {
+            width.saturating_sub(1)
+        } else {
+            width
+        }
+    }
+
+    // Screen lines rows are drawn in, below the tab bar when there's more than one tab.
+    fn text_area_height(&self) -> usize {
+        (self.terminal.size().height as usize).saturating_sub(self.tab_bar_height())
+    }
+
+    // The tab bar takes the top line once a second tab is opened.
+    fn tab_bar_height(&self) -> usize {
+        usize::from(!self.tabs.is_empty())
+    }
+
+    // Rows of the text area the scrollbar's thumb covers, given the document's length.
+    // The view counts as part of the document when it reaches past the end, so a short or
+    // empty document gets a thumb as tall as the text area.
+    fn scrollbar_thumb(&self) -> Range<usize> {
+        let height = self.text_area_height();
+        let total = cmp::max(self.document.len(), self.offset.y.saturating_add(height));
+        if total == 0 {
+            return 0..0;
+        }
+        let size = cmp::max(height.saturating_mul(height) / total, 1);
+        // The thumb reaches the bottom exactly when the view does.
+        let travel = height.saturating_sub(size);
+        let top = match total.saturating_sub(height) {
+            0 => 0,
+            scrollable => cmp::min(self.offset.y.saturating_mul(travel) / scrollable, travel),
+        };
+        top..top.saturating_add(size)
+    }
+
+    // Handles terminal scrolling by adjusting the offset.
+    // Closed folds count as a single line.
+    fn scroll(&mut self) {
+        let y = self.folds.line_start(self.cursor_position.y);
+        let x = self.cursor_column();
+        let width = self.text_area_width();
+        let height = self.text_area_height();
+
+        let folds = &self.folds;
+        let mut offset = &mut self.offset;
+        offset.y = folds.line_start(offset.y);
+        if y < offset.y {
+            offset.y = y;
+        } else if folds.lines_between(offset.y, y) >= height {
+            offset.y = folds.line_above(y, height.saturating_sub(1));
+        }
+        if x < offset.x {
+            offset.x = x;
+        } else if x >= offset.x.saturating_add(width) {
+            offset.x = x.saturating_sub(width).saturating_add(1);
+        }
+    }
+
+    // Handles frame/screen refreshes.
+    // Includes highlighting & redrawing the rows & TUI
+    // Only screen lines whose content changed since the last frame are rewritten.
+    pub fn refresh_screen(&mut self) -> Result<(), ZenError> {
+        let mut render_started = None;
+        self.terminal.cursor_hide();
+        if self.should_quit && !self.shutting_down {
+            // Nothing is cleared: the shell's screen comes back when the terminal leaves the
+            // alternate screen.
+            self.restore_cursor_style();
+            self.last_frame.clear();
+        } else {
+            let size = self.terminal.size();
+            let frame_size = (size.width, size.height);
+            if self.frame_size != frame_size {
+                self.frame_size = frame_size;
+                self.last_frame.clear();
+            }
+            let viewport = self.calculate_viewport();
+            self.document
+                .set_long_line_length(self.config.long_line_length);
+
+            // It's important that we highlight before drawing
+            // We will only be highlighting the rows visible in the viewport to improve performance
+            let started = self.profile_start();
+            if let Err(error) = self
+                .document
+                .highlight(viewport, self.terminal.color_support())
+            {
+                self.report(&error);
+            }
+            self.profile_end(Phase::Highlight, started);
+            render_started = self.profile_start();
+            self.update_misspellings();
+            self.draw_tab_bar();
+            self.draw_rows();
+            self.draw_status_bar();
+            self.draw_message_bar();
+            let position = if let Some((_, x)) = &self.prompt_line {
+                // While prompting, the cursor is in the message bar.
+                let width = self.terminal.size().width as usize;
+                Position {
+                    x: cmp::min(*x, width.saturating_sub(1)),
+                    y: (self.terminal.size().height as usize).saturating_add(1),
+                }
+            } else {
+                let y = match &self.diff {
+                    Some(diff) => diff
+                        .row_of(self.cursor_position.y)
+                        .saturating_sub(self.diff_top(diff)),
+                    None => self
+                        .folds
+                        .lines_between(self.offset.y, self.cursor_position.y),
+                };
+                Position {
+                    x: self.cursor_column().saturating_sub(self.offset.x),
+                    y: y.saturating_add(self.tab_bar_height()),
+                }
+            };
+            self.terminal.cursor_position(&position);
+            self.update_cursor_style();
+        }
+        // Overlays take the whole text area, so there's no cursor to show unless prompting.
+        if self.overlay.is_none() || self.prompt_line.is_some() || self.should_quit {
+            self.terminal.cursor_show();
+        }
+        let flushed = self.terminal.flush();
+        self.profile_end(Phase::Render, render_started);
+        flushed
+    }
+
+    // Returns a range of the row indexes within the terminal's view.
+    // Rows in closed folds count too, the highlighting carries on through them.
+    fn calculate_viewport(&self) -> Range<usize> {
+        let height = self.text_area_height();
+        let len = self.document.len();
+        let start_row = self.offset.y;
+        let end_row = self
+            .folds
+            .lines(start_row, len)
+            .take(height)
+            .last()
+            .map_or(start_row, |row| cmp::min(self.folds.next_line(row), len));
+
+        start_row..end_row
+    }
+
+    // Handles re-rendering all rows within the terminal's view.
+    // Will account for cases such as an empty document, or an empty row.
+    // (?) Might move that...
+    //
+    // Each screen line is composed first and only printed when it differs
+    // from the previous frame, so pure cursor movement redraws no rows.
+    fn draw_rows(&mut self) {
+        if self.overlay.is_some() {
+            self.draw_overlay();
+            return;
+        }
+        if self.diff.is_some() {
+            self.draw_diff_rows();
+            return;
+        }
+
+        let height = self.text_area_height();
+        let first_row = self.tab_bar_height();
+        let filler = self.filler();
+        let welcome_lines = if self.document.is_empty() {
+            self.welcome_lines(&filler)
+        } else {
+            Vec::new()
+        };
+        // The message's first line sits a third of the way down, like it always has.
+        let welcome_top = (height / 3).saturating_sub(welcome_lines.len().saturating_sub(1) / 2);
+
+        // Every line is painted with the theme's background up to the right edge,
+        // so highlighted, plain and filler lines all look the same.
+        let background = self
+            .document
+            .background()
+            .map(|color| util::background_to_termion(color, self.terminal.color_support()))
+            .unwrap_or_default();
+        let line_end = format!("{}{}{}", background, ansi::CLEAR_UNTIL_NEWLINE, ansi::RESET);
+        let mut line = String::new();
+        let mut marked;
+        let whitespace = self.whitespace();
+        let width = self.text_area_width();
+        let thumb = self.scrollbar_thumb();
+        let lines: Vec<usize> = self
+            .folds
+            .lines(self.offset.y, self.document.len())
+            .take(height)
+            .collect();
+
+        for terminal_row in 0..height {
+            // Rows past the end of the document keep counting on, for the welcome message.
+            let y = lines.get(terminal_row).copied().unwrap_or_else(|| {
+                let last = lines
+                    .last()
+                    .map_or(self.offset.y, |row| self.folds.next_line(*row));
+                last.saturating_add(terminal_row.saturating_sub(lines.len()))
+            });
+            let content = if let (Some(row), Some(fold)) = (self.document.row(y), self.folds.at(y))
+            {
+                marked = fold_line(row, fold.len(), width);
+                &marked
+            } else if let (Some(row), true) = (self.document.row(y), self.document.is_long_line(y))
+            {
+                // Only the part in view is drawn, plain, however far the row goes on.
+                row.slice(self.offset.x, width)
+            } else if let Some(row) = self.document.row(y) {
+                let marks = self.search_marks(y);
+                let misspelled = self.spell_marks(y);
+                if marks.is_empty()
+                    && misspelled.is_empty()
+                    && !whitespace.is_visible()
+                    && !row.has_tabs()
+                {
+                    row.render()
+                } else {
+                    marked = row.render_styled(&marks, misspelled, whitespace);
+                    &marked
+                }
+            } else if let Some(welcome_line) = terminal_row
+                .checked_sub(welcome_top)
+                .and_then(|index| welcome_lines.get(index))
+            {
+                welcome_line
+            } else {
+                &filler
+            };
+
+            line.clear();
+            line.push_str(&background);
+            line.push_str(truncate_rendered(content, width));
+            line.push_str(&line_end);
+
+            // The scrollbar is drawn after the line is cleared, in the last column.
+            let line_len = line.len();
+            if self.config.scrollbar {
+                let bar = if thumb.contains(&terminal_row) {
+                    SCROLLBAR_THUMB
+                } else {
+                    SCROLLBAR_TRACK
+                };
+                line.push_str(ansi::DIM);
+                line.push(bar);
+                line.push_str(ansi::RESET);
+            }
+
+            if begin_line(
+                &mut *self.terminal,
+                &mut self.last_frame,
+                first_row.saturating_add(terminal_row),
+                &line,
+            ) {
+                self.terminal.write(&line[..line_len]);
+                if line_len < line.len() {
+                    self.terminal.cursor_position(&Position {
+                        x: width,
+                        y: first_row.saturating_add(terminal_row),
+                    });
+                    self.terminal.write(&line[line_len..]);
+                }
+            }
+        }
+    }
+
+    // Draws the overlay's lines over the text area from its top line on.
+    // Overlays shorter than the text area are bottom aligned, like Vim's messages.
+    fn draw_overlay(&mut self) {
+        let Some(overlay) = &self.overlay else {
+            return;
+        };
+        let height = self.text_area_height();
+        let first_row = self.tab_bar_height();
+        let width = self.terminal.size().width as usize;
+        let top = cmp::min(overlay.top, overlay.last_top(height));
+        let blank = height.saturating_sub(overlay.lines.len());
+
+        for terminal_row in 0..height {
+            let line = terminal_row
+                .checked_sub(blank)
+                .and_then(|index| overlay.lines.get(top.saturating_add(index)));
+            let text = match line {
+                Some((text, Some(color))) => format!(
+                    "{}{}{}",
+                    util::fg_escape(*color, self.terminal.color_support()),
+                    truncate(text, width),
+                    ansi::FG_RESET
+                ),
+                Some((text, None)) => truncate(text, width).to_string(),
+                None => String::new(),
+            };
+
+            if begin_line(
+                &mut *self.terminal,
+                &mut self.last_frame,
+                first_row.saturating_add(terminal_row),
+                &text,
+            ) {
+                self.terminal.write(&text);
+            }
+        }
+    }
+
+    // Scrolls the overlay with j/k, Up/Down, Space and PageUp/PageDown while it's taller
+    // than the text area. Any other key closes it, and does nothing else.
+    fn scroll_overlay(&mut self, key: KeyEvent) {
+        let height = self.text_area_height();
+        let Some(overlay) = &mut self.overlay else {
+            return;
+        };
+        let last_top = overlay.last_top(height);
+        let top = cmp::min(overlay.top, last_top);
+
+        overlay.top = match key {
+            _ if last_top == 0 => {
+                self.overlay = None;
+                return;
+            }
+            KeyEvent::Char('j') | KeyEvent::Down => cmp::min(top.saturating_add(1), last_top),
+            KeyEvent::Char('k') | KeyEvent::Up => top.saturating_sub(1),
+            KeyEvent::Char(' ') | KeyEvent::PageDown => {
+                cmp::min(top.saturating_add(height), last_top)
+            }
+            KeyEvent::PageUp => top.saturating_sub(height),
+            _ => {
+                self.overlay = None;
+                return;
+            }
+        };
+    }
+
+    // Draws the buffer and its file on disk side by side while diff mode is on.
+    // Lines only in the buffer are green on the left, lines only on disk red on the right.
+    fn draw_diff_rows(&mut self) {
+        let diff = match &self.diff {
+            Some(diff) => diff,
+            None => return,
+        };
+        let height = self.text_area_height();
+        let first_row = self.tab_bar_height();
+        let width = self.terminal.size().width as usize;
+        let left_width = width.saturating_sub(1) / 2;
+        let right_width = width.saturating_sub(left_width).saturating_sub(1);
+        let color_support = self.terminal.color_support();
+        let top = self.diff_top(diff);
+
+        let background = self
+            .document
+            .background()
+            .map(|color| util::background_to_termion(color, color_support))
+            .unwrap_or_default();
+        let line_end = format!("{}{}{}", background, ansi::CLEAR_UNTIL_NEWLINE, ansi::RESET);
+        let filler = self.filler();
+        let mut line = String::new();
+
+        for terminal_row in 0..height {
+            line.clear();
+            line.push_str(&background);
+
+            if let Some(diff_line) = diff.lines().get(top.saturating_add(terminal_row)) {
+                let left = diff_line
+                    .buffer_line
+                    .and_then(|index| self.document.row(index))
+                    .map_or("", |row| row.string.as_str());
+                let right = diff_line
+                    .disk_line
+                    .and_then(|index| diff.disk_line(index))
+                    .unwrap_or("");
+                let (left_color, right_color) = match diff_line.tag {
+                    DiffTag::Equal => (None, None),
+                    DiffTag::Added => (Some(DIFF_ADDED_COLOR), None),
+                    DiffTag::Removed => (None, Some(DIFF_REMOVED_COLOR)),
+                };
+
+                let left = truncate(left, left_width);
+                if let Some(color) = left_color {
+                    line.push_str(&util::fg_escape(color, color_support));
+                }
+                line.push_str(left);
+                line.push_str(ansi::FG_RESET);
+                line.push_str(&" ".repeat(left_width.saturating_sub(left.chars().count())));
+                line.push('│');
+                if let Some(color) = right_color {
+                    line.push_str(&util::fg_escape(color, color_support));
+                }
+                line.push_str(truncate(right, right_width));
+                line.push_str(ansi::FG_RESET);
+            } else {
+                line.push_str(truncate_rendered(&filler, width));
+            }
+            line.push_str(&line_end);
+
+            if begin_line(
+                &mut *self.terminal,
+                &mut self.last_frame,
+                first_row.saturating_add(terminal_row),
+                &line,
+            ) {
+                self.terminal.write(&line);
+            }
+        }
+    }
+
+    // First row of the diff view on screen. Follows the document's offset,
+    // but moves further down if lines removed above would push the cursor out of view.
+    fn diff_top(&self, diff: &Diff) -> usize {
+        let height = self.text_area_height();
+        let top = if self.offset.y == 0 {
+            0
+        } else {
+            diff.row_of(self.offset.y.saturating_sub(1))
+                .saturating_add(1)
+        };
+        let cursor_row = diff.row_of(self.cursor_position.y);
+
+        if cursor_row >= top.saturating_add(height) {
+            cursor_row.saturating_sub(height).saturating_add(1)
+        } else {
+            top
+        }
+    }
+
+    // Builds the welcome screen shown in the case of an empty document, one centered line
+    // per line of the configured message after the filler. The case check can be found in
+    // self.draw_rows().
+    fn welcome_lines(&self, filler: &str) -> Vec<String> {
+        let Some(message) = self.config.welcome_message.as_deref() else {
+            return Vec::new();
+        };
+        let width = self.terminal.size().width as usize;
+        let filler_width = self.config.filler.width();
+        let message = message.replace("{version}", env!("CARGO_PKG_VERSION"));
+
+        message
+            .lines()
+            .map(|line| {
+                let padding = width.saturating_sub(line.width()) / 2;
+                let spaces = " ".repeat(padding.saturating_sub(filler_width));
+                format!("{}{}{}", filler, spaces, line)
+            })
+            .collect()
+    }
+
+    // What's drawn on lines past the end of the document, in the theme's muted color, or dim
+    // when it has none. Empty when the config leaves those lines blank.
+    fn filler(&self) -> String {
+        let filler = &self.config.filler;
+        if filler.is_empty() {
+            return String::new();
+        }
+        match self.document.muted_foreground() {
+            Some(color) => format!(
+                "{}{}{}",
+                util::fg_escape(color, self.terminal.color_support()),
+                filler,
+                ansi::FG_RESET
+            ),
+            None => format!("{}{}{}", ansi::DIM, filler, ansi::DIM_RESET),
+        }
+    }
+
+    // Draws a label for each tab on the top line while more than one is open, the current one
+    // in reverse video. Labels share the width equally, and names are cut to fit.
+    fn draw_tab_bar(&mut self) {
+        if self.tabs.is_empty() {
+            return;
+        }
+        let width = self.terminal.size().width as usize;
+        let count = self.tabs.len().saturating_add(1);
+        let label_width = cmp::max(width / count, 1);
+
+        let mut others = self.tabs.iter().map(|tab| &tab.document);
+        let mut line = String::new();
+        for index in 0..count {
+            let document = if index == self.active_tab {
+                &self.document
+            } else {
+                match others.next() {
+                    Some(document) => document,
+                    None => break,
+                }
+            };
+            let name = document
+                .file_name
+                .as_deref()
+                .map_or("[No Name]", |file_name| {
+                    Path::new(file_name)
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .unwrap_or(file_name)
+                });
+            let dirty = if document.is_dirty() { "+" } else { "" };
+            let label = format!(" {} {}{} ", index.saturating_add(1), name, dirty);
+            let label = truncate(&label, label_width);
+            if index == self.active_tab {
+                line.push_str(ansi::REVERSE);
+                line.push_str(label);
+                line.push_str(ansi::REVERSE_RESET);
+            } else {
+                line.push_str(label);
+            }
+        }
+
+        if begin_line(&mut *self.terminal, &mut self.last_frame, 0, &line) {
+            self.terminal.write(&line);
+        }
+    }
+
+    // Draws a status bar to the terminal.
+    // This is primarily used for information on the document, such
+    // as the file opened, dirty status, document's language, etc.
+    // TODO: Stylize this with the active theme.
+    fn draw_status_bar(&mut self) {
+        let width = self.terminal.size().width as usize;
+        let line = self.cursor_position.y.saturating_add(1);
+        // `Ctrl-R` waits on a register name.
+        let mode = if self.pending_input.keys() == "^R" {
+            format!("{} (^R)", self.mode)
+        } else {
+            self.mode.to_string()
+        };
+
+        let mut notes = Vec::new();
+        // The offset of the byte the cursor is on in a hex dump, written like the dump's.
+        let byte = self
+            .document
+            .hex()
+            .filter(|view| !view.is_empty())
+            .map(|view| format!("byte 0x{:x}", view.offset_at(&self.cursor_position)));
+        if let Some(byte) = &byte {
+            notes.push(byte.as_str());
+        }
+        match self.document.origin() {
+            DocumentOrigin::New => notes.push("New File"),
+            DocumentOrigin::OpenFailed { .. } => notes.push("Open failed"),
+            DocumentOrigin::OpenedFromDisk { .. } => (),
+        }
+        if self.shutting_down {
+            notes.push("saving…");
+        }
+        if self.building {
+            notes.push("building…");
+        } else if let Some(summary) = &self.build_summary {
+            notes.push(summary);
+        }
+        let loading = self
+            .document
+            .load_progress()
+            .map(|percent| format!("loading… {}%", percent));
+        if let Some(loading) = &loading {
+            notes.push(loading);
+        }
+        if self.document.is_long_line(self.cursor_position.y) {
+            notes.push("long line, highlighting disabled");
+        }
+        if !self
+            .status_bar
+            .is_current(&self.document, &mode, line, width, &notes)
+        {
+            self.status_bar = StatusBar::compose(&self.document, &mode, line, width, &notes);
+        }
+
+        let terminal_row = self.terminal.size().height as usize;
+        if begin_line(
+            &mut *self.terminal,
+            &mut self.last_frame,
+            terminal_row,
+            &self.status_bar.text,
+        ) {
+            self.terminal.set_bg_color(STATUS_BG_COLOR);
+            self.terminal.set_fg_color(STATUS_FG_COLOR);
+            self.terminal.write(&self.status_bar.text);
+            self.terminal.reset_fg_color();
+            self.terminal.reset_bg_color();
+        }
+    }
+
+    // Message bar used to display text and command assistance.
+    // Keys of an unfinished Normal mode sequence show at its right end, like Vim's showcmd.
+    fn draw_message_bar(&mut self) {
+        let pending = self.pending_input.to_string();
+        let full_width = self.terminal.size().width as usize;
+        let width = if pending.is_empty() || self.prompt_line.is_some() {
+            full_width
+        } else {
+            // A column stays blank between the two.
+            full_width.saturating_sub(PENDING_INPUT_WIDTH.saturating_add(1))
+        };
+        let now = self.clock.now();
+        // The line, and how many columns of it are visible.
+        let (mut line, len) = match &self.prompt_line {
+            Some((prompt_line, _)) => {
+                let text = truncate(prompt_line, width);
+                (text.to_string(), text.chars().count())
+            }
+            None => match self.messages.current(now) {
+                // The count of messages waiting stays in view, however long this one is.
+                Some((message, waiting)) => {
+                    let more = if waiting > 0 {
+                        format!(" ({} more)", waiting)
+                    } else {
+                        String::new()
+                    };
+                    let text = message.to_string();
+                    let text = truncate(&text, width.saturating_sub(more.len()));
+                    let len = text.chars().count().saturating_add(more.len());
+                    let line = if message.level == Level::Error {
+                        format!(
+                            "{}{}{}{}",
+                            util::fg_escape(ERROR_COLOR, self.terminal.color_support()),
+                            text,
+                            more,
+                            ansi::FG_RESET
+                        )
+                    } else {
+                        format!("{}{}", text, more)
+                    };
+                    (line, len)
+                }
+                None => (String::new(), 0),
+            },
+        };
+        if width < full_width {
+            line.push_str(&" ".repeat(width.saturating_add(1).saturating_sub(len)));
+            line.push_str(truncate(&pending, PENDING_INPUT_WIDTH));
+        }
+
+        let terminal_row = (self.terminal.size().height as usize).saturating_add(1);
+        if begin_line(
+            &mut *self.terminal,
+            &mut self.last_frame,
+            terminal_row,
+            &line,
+        ) {
+            self.terminal.write(&line);
+        }
+    }
+
+    // Used by search and command operations by providing an input state.
+    // This uses the message bar, where the line can be edited, recalled from the
+    // history of its kind with Up and Down, and completed with Tab.
+    async fn prompt<C>(
+        &mut self,
+        prompt: &str,
+        kind: PromptKind,
+        mut callback: C,
+    ) -> Result<Option<String>, ZenError>
+    where
+        C: FnMut(&mut Self, KeyEvent, &String),
+    {
+        let mut line = LineEditor::default();
+
+        let submitted = loop {
+            self.prompt_line = Some((
+                format!("{}{}", prompt, line.text()),
+                prompt.chars().count().saturating_add(line.cursor()),
+            ));
+            self.refresh_screen()?;
+
+            let key = self.input.next().await?;
+            let action = line.handle_key(key, kind, self.prompt_history.get(kind));
+            // File names Tab could complete to are listed above the prompt, like a shell does.
+            if kind == PromptKind::FileName {
+                let width = self.terminal.size().width as usize;
+                self.overlay = line.matches().map(|matches| Overlay {
+                    top: 0,
+                    lines: list_file_names(matches, width)
+                        .into_iter()
+                        .map(|line| (line, None))
+                        .collect(),
+                });
+            }
+            match action {
+                PromptAction::Submit => break true,
+                PromptAction::Cancel => break false,
+                PromptAction::Continue => callback(self, key, &line.text()),
+            }
+        };
+
+        if kind == PromptKind::FileName {
+            self.overlay = None;
+        }
+        self.prompt_line = None;
+        let result = line.text();
+        if !submitted || result.is_empty() {
+            return Ok(None);
+        }
+
+        self.prompt_history.push(kind, result.clone());
+        Ok(Some(result))
+    }
+
+    // Saves the active document, in the given encoding or else the one it was last written in.
+    async fn save(&mut self, encoding: Option<Encoding>) -> Result<(), ZenError> {
+        self.wait_for_loading().await?;
+        if self.document.is_read_only() {
+            self.notify(Level::Warning, "File is read-only.");
+            return Ok(());
+        }
+
+        // Only new documents have no name yet, and ones standing in for a file that couldn't be
+        // read, which are never written over that file without asking.
+        if self.document.file_name.is_none() {
+            let new_name = self
+                .prompt("Save as: ", PromptKind::FileName, |_, _, _| {})
+                .await?;
+
+            if new_name.is_none() {
+                self.notify(Level::Info, "Save aborted.");
+                return Ok(());
+            }
+
+            if let Some(new_name) = new_name {
+                self.document.set_file_name(new_name);
+            }
+        }
+
+        let encoding = encoding.unwrap_or_else(|| self.document.encoding());
+        if !self.fire(Event::BufWritePre).await {
+            self.notify(Level::Warning, "Write cancelled by a BufWritePre hook");
+            return Ok(());
+        }
+        match self.document.save_as_encoding(encoding).await {
+            Ok(0) => self.notify(Level::Info, "File saved successfully."),
+            Ok(unencodable) => self.notify(
+                Level::Warning,
+                format!(
+                    "{} characters could not be encoded, replaced with '?'",
+                    unencodable
+                ),
+            ),
+            Err(error) => {
+                self.report(&error);
+                return Ok(());
+            }
+        }
+        self.forget_recovery().await;
+        self.fire(Event::BufWritePost).await;
+        Ok(())
+    }
+
+    // Moves the document's file to a new path, which the document is saved to from then on.
+    // Unsaved changes stay in the buffer.
+    async fn rename(&mut self, path: String, force: bool) {
+        let Some(file_name) = self.document.file_name.clone() else {
+            self.notify(Level::Error, "No file name, save it with :w first");
+            return;
+        };
+        if !force && tokio::fs::metadata(&path).await.is_ok() {
+            self.notify(Level::Error, "File exists (add ! to override)");
+            return;
+        }
+
+        match move_file(&file_name, &path).await {
+            Ok(()) => {
+                self.notify(Level::Info, format!("Renamed to \"{}\"", path));
+                self.document.set_file_name(path);
+            }
+            Err(error) => self.report(&ZenError::io(file_name, error)),
+        }
+    }
+
+    // Shows what the character under the cursor is made of, for `ga` and `:ascii`.
+    fn character_info(&mut self) {
+        let Position { x, y } = self.cursor_position;
+        let text = match self.document.row(y).and_then(|row| row.grapheme_at(x)) {
+            Some(grapheme) => row::describe_grapheme(grapheme),
+            None => "NUL".to_string(),
+        };
+        self.notify(Level::Info, text);
+    }
+
+    // Shows the document as a hex dump for `:hex`, or its text again.
+    // The cursor starts over at the top either way.
+    fn toggle_hex(&mut self) {
+        let on = self.document.hex().is_none();
+        match self.document.show_hex(on) {
+            Ok(()) => {
+                self.cursor_position = Position::default();
+                self.clamp_cursor();
+            }
+            Err(error) => self.report(&error),
+        }
+    }
+
+    // Shows where the cursor is in the document, along with the document's size.
+    // e.g. "main.rs" [modified] line 42 of 1337, col 10 -- 3% -- (42, 10) -- 120 words, 2048 bytes
+    fn file_info(&mut self) {
+        let file_name = self.document.file_name.as_deref().unwrap_or("[No Name]");
+        let modified = if self.document.is_dirty() {
+            " [modified]"
+        } else if self.document.is_read_only() {
+            " [readonly]"
+        } else {
+            ""
+        };
+
+        let lines = self.document.len();
+        let line = self.cursor_position.y.saturating_add(1);
+        let percent = cmp::min(line, lines)
+            .saturating_mul(100)
+            .checked_div(lines)
+            .unwrap_or(0);
+        let col = self.cursor_position.x.saturating_add(1);
+
+        let text = format!(
+            "\"{}\"{} line {} of {}, col {} -- {}% -- ({}, {}) -- {} words, {} bytes",
+            file_name,
+            modified,
+            line,
+            lines,
+            col,
+            percent,
+            line,
+            col,
+            self.document.word_count(),
+            self.document.byte_count(),
+        );
+        self.notify(Level::Info, text);
+    }
+
+    // Reads a command from the message bar after `:` and runs it.
+    async fn command_line(&mut self) -> Result<(), ZenError> {
+        self.mode = EditorMode::Command;
+        let input = self.prompt(":", PromptKind::Command, |_, _, _| {}).await;
+        self.mode = EditorMode::Normal;
+
+        if let Some(input) = input? {
+            match ex::parse(&input) {
+                Ok(command) => return self.run_ex_command(command).await,
+                Err(error) => self.report(&error),
+            }
+        }
+        Ok(())
+    }
+
+    // Runs a registered command, whether its key was pressed or it was picked from the palette.
+    // Commands taking an argument ask for it first, and do nothing if none is given.
+    async fn run_registered(&mut self, info: &CommandInfo) -> Result<(), ZenError> {
+        let line = match info.action {
+            Action::Command(command) => return self.execute(command()).await,
+            Action::Ex(line) => line.to_string(),
+            Action::ExWithArgument {
+                command,
+                prompt,
+                kind,
+            } => match self.prompt(prompt, kind, |_, _, _| {}).await? {
+                Some(argument) => format!("{} {}", command, argument),
+                None => return Ok(()),
+            },
+        };
+        match ex::parse(&line) {
+            Ok(command) => return self.run_ex_command(command).await,
+            Err(error) => self.report(&error),
+        }
+        Ok(())
+    }
+
+    // Lists the registered commands of the mode and the mappings made in it, to pick one by
+    // name. A mapping picked runs as if its keys were typed.
+    async fn command_palette(&mut self) -> Result<(), ZenError> {
+        let mode = match self.mode {
+            EditorMode::Insert => MapMode::Insert,
+            EditorMode::Normal | EditorMode::Command => MapMode::Normal,
+        };
+        let mut entries = Vec::new();
+        let mut items = Vec::new();
+        for info in registry::COMMANDS
+            .iter()
+            .filter(|info| info.is_valid_in(mode))
+        {
+            let keys = self.keymap.keys_for(mode, info.name);
+            let detail = if keys.is_empty() {
+                info.description.to_string()
+            } else {
+                format!("{} ({})", info.description, keys.join(", "))
+            };
+            entries.push(PaletteEntry::Registered(info));
+            items.push(PickerItem {
+                label: info.name.to_string(),
+                detail,
+            });
+        }
+        for mapping in self.keymap.mappings(Some(mode)) {
+            entries.push(PaletteEntry::Mapped(mapping.expansion.clone()));
+            items.push(PickerItem {
+                label: format!("Mapping {}", mapping.lhs()),
+                detail: mapping.rhs().to_string(),
+            });
+        }
+
+        match self
+            .pick("Command: ", items)
+            .await?
+            .map(|index| entries.swap_remove(index))
+        {
+            Some(PaletteEntry::Registered(info)) => self.run_registered(info).await?,
+            Some(PaletteEntry::Mapped(keys)) => self.input.push_front(&keys),
+            None => (),
+        }
+        Ok(())
+    }
+
+    // Lets one of the items be picked from a list over the text area, which narrows to those
+    // matching what's typed at the prompt. Returns the index of the item, or None if cancelled.
+    async fn pick(
+        &mut self,
+        prompt: &str,
+        items: Vec<PickerItem>,
+    ) -> Result<Option<usize>, ZenError> {
+        let mut picker = Picker::new(items);
+        let picked = loop {
+            let height = self.text_area_height();
+            let lines = picker
+                .lines(height)
+                .into_iter()
+                .map(|(line, selected)| (line, selected.then_some(PICKER_SELECTED_COLOR)))
+                .collect();
+            self.overlay = Some(Overlay { top: 0, lines });
+            self.prompt_line = Some((
+                format!("{}{}", prompt, picker.query()),
+                prompt
+                    .chars()
+                    .count()
+                    .saturating_add(picker.query().chars().count()),
+            ));
+            self.refresh_screen()?;
+
+            match picker.handle_key(self.input.next().await?) {
+                PickerAction::Continue => (),
+                PickerAction::Submit(index) => break index,
+                PickerAction::Cancel => break None,
+            }
+        };
+        self.overlay = None;
+        self.prompt_line = None;
+        Ok(picked)
+    }
+
+    // Runs the `.zen` scripts in the config's `after` directory, in name order.
+    pub async fn run_startup_scripts(&mut self) {
+        if let Some(dir) = Config::after_dir() {
+            self.run_scripts(&dir).await;
+        }
+    }
+
+    // Runs every `.zen` file in `dir` as a script. A missing directory has nothing to run.
+    pub async fn run_scripts(&mut self, dir: &Path) {
+        let mut entries = match tokio::fs::read_dir(dir).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return,
+            Err(error) => return self.report(&ZenError::io(dir, error)),
+        };
+
+        let mut scripts = Vec::new();
+        loop {
+            match entries.next_entry().await {
+                Ok(Some(entry)) => scripts.push(entry.path()),
+                Ok(None) => break,
+                Err(error) => return self.report(&ZenError::io(dir, error)),
+            }
+        }
+        scripts.retain(|path| path.extension().is_some_and(|ext| ext == "zen"));
+        scripts.sort();
+
+        for script in scripts {
+            self.run_script(&script).await;
+        }
+    }
+
+    // Runs each line of a script as if typed after `:`. Blank lines and lines starting with `"` are skipped.
+    // A line that fails is reported with where it came from, and the rest of the script still runs.
+    pub async fn run_script(&mut self, path: &Path) {
+        let contents = match tokio::fs::read_to_string(path).await {
+            Ok(contents) => contents,
+            Err(error) => return self.report(&ZenError::io(path, error)),
+        };
+
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        );
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('"') {
+                continue;
+            }
+            let result = match ex::parse(line) {
+                Ok(command) => self.run_ex_command(command).await,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                self.notify(
+                    Level::Error,
+                    format!("{}:{}: {}", name, index.saturating_add(1), error),
+                );
+            }
+        }
+    }
+
+    async fn run_ex_command(&mut self, command: ExCommand) -> Result<(), ZenError> {
+        match command {
+            ExCommand::Write => return self.save(None).await,
+            ExCommand::WriteEncoded(encoding) => return self.save(Some(encoding)).await,
+            ExCommand::Rename { path, force } => self.rename(path, force).await,
+            // With more than one tab open, quitting closes the current one.
+            ExCommand::Quit if !self.tabs.is_empty() => self.close_tab(false),
+            ExCommand::ForceQuit if !self.tabs.is_empty() => self.close_tab(true),
+            ExCommand::Quit if self.document.is_dirty() => {
+                self.notify(
+                    Level::Warning,
+                    "No write since last change (add ! to override)",
+                );
+            }
+            ExCommand::Quit | ExCommand::ForceQuit => self.should_quit = true,
+            ExCommand::WriteQuit => {
+                self.save(None).await?;
+                if !self.document.is_dirty() {
+                    if self.tabs.is_empty() {
+                        self.should_quit = true;
+                    } else {
+                        self.close_tab(false);
+                    }
+                }
+            }
+            ExCommand::TabNew(path) => self.new_tab(path).await,
+            ExCommand::TabNext => self.cycle_tab(SearchDirection::Forward),
+            ExCommand::TabPrev => self.cycle_tab(SearchDirection::Backward),
+            ExCommand::TabClose { force } => self.close_tab(force),
+            ExCommand::Diff | ExCommand::DiffUpdate => self.diff_with_disk(),
+            ExCommand::DiffThis => {
+                self.notify(
+                    Level::Info,
+                    "Only one buffer is open, use :diff to compare with the file on disk.",
+                );
+            }
+            ExCommand::DiffOff => self.diff = None,
+            ExCommand::Unsaved => self.show_unsaved_changes(),
+            ExCommand::Ascii => self.character_info(),
+            ExCommand::Hex => self.toggle_hex(),
+            ExCommand::NoHighlight => self.clear_search_highlight(),
+            ExCommand::Messages => self.show_messages(),
+            ExCommand::Commands => return Box::pin(self.command_palette()).await,
+            ExCommand::ProfileReport => self.show_profile(),
+            ExCommand::ProfileReset => self.profiler.clear(),
+            ExCommand::Abbreviate { word, expansion } => {
+                self.abbreviations.insert(word, expansion);
+            }
+            ExCommand::ListAbbreviations => self.show_abbreviations(),
+            ExCommand::Unabbreviate(word) => {
+                if self.abbreviations.remove(&word).is_none() {
+                    self.notify(Level::Error, format!("No such abbreviation: {}", word));
+                }
+            }
+            ExCommand::Map { mode, lhs, rhs } => {
+                if let Err(error) = self.keymap.map(mode, &lhs, &rhs, self.config.leader) {
+                    self.report(&error);
+                }
+            }
+            ExCommand::ListMaps(mode) => self.show_mappings(mode),
+            ExCommand::Make(args) => self.make(&args),
+            ExCommand::QuickfixNext => self.jump_quickfix(SearchDirection::Forward).await,
+            ExCommand::QuickfixPrev => self.jump_quickfix(SearchDirection::Backward).await,
+            ExCommand::Set { option, enabled } => self.set_option(option, enabled),
+            ExCommand::SetSyntax(name) => {
+                if let Err(error) = self.document.set_syntax(&name) {
+                    self.report(&error);
+                }
+            }
+            ExCommand::SetLocal(local) => self.document.local_config.merge(local),
+            ExCommand::Retab { to_spaces } => {
+                let tab_width = self.document.effective_tab_width(&self.config);
+                let changed = self.document.retab(to_spaces, tab_width);
+                self.notify(Level::Info, format!("{} lines changed", changed));
+            }
+            ExCommand::Delete(range, register) => self.delete_lines(range, register),
+            ExCommand::Yank(range, register) => self.yank_lines(range, register),
+            ExCommand::Substitute {
+                range,
+                pattern,
+                replacement,
+                global,
+                count_only,
+            } => {
+                self.wait_for_loading().await?;
+                self.substitute(range, pattern, &replacement, global, count_only);
+            }
+        }
+        Ok(())
+    }
+
+    // Sets an option for `:set`, or unsets it for `:set no...`.
+    fn set_option(&mut self, option: EditorOption, enabled: bool) {
+        match option {
+            EditorOption::List => self.config.list = enabled,
+            EditorOption::Trailing => self.config.trailing = enabled,
+            EditorOption::Scrollbar => {
+                self.config.scrollbar = enabled;
+                self.scroll();
+            }
+            EditorOption::HlSearch => {
+                self.config.highlight_search = enabled;
+                self.update_search_matches();
+            }
+            EditorOption::Profiling => self.config.profiling = enabled,
+            EditorOption::TermColors => {
+                self.config.term_colors = enabled;
+                self.apply_colors();
+            }
+            EditorOption::Spell => {
+                self.config.spell = enabled;
+                if enabled && !spell::is_prose(self.document.file_type()) {
+                    self.notify(Level::Info, "Spelling is only checked in text files");
+                }
+            }
+        }
+    }
+
+    // Copies the lines in range for `:y`.
+    fn yank_lines(&mut self, range: CommandRange, register: Option<char>) {
+        let rows = self.ex_rows(range);
+        let lines = self.document.row_strings(rows);
+        self.last_yank.clone_from(&lines);
+        self.set_register(register, lines);
+        self.notify(Level::Info, format!("{} lines yanked", self.yanked.len()));
+    }
+
+    // Keeps yanked or deleted lines, in the named register too when there's one.
+    // An uppercase name appends them to its lowercase register.
+    fn set_register(&mut self, register: Option<char>, lines: Vec<String>) {
+        if let Some(name) = register {
+            let kept = self.registers.entry(name.to_ascii_lowercase()).or_default();
+            if !name.is_ascii_uppercase() {
+                kept.clear();
+            }
+            kept.extend(lines.iter().cloned());
+        }
+        self.yanked = lines;
+    }
+
+    // The lines in a register: `"` for the last yank or delete, `0` for the last yank, or a
+    // letter for a named one.
+    fn register(&self, name: char) -> Option<&[String]> {
+        let lines = match name {
+            '"' => &self.yanked,
+            '0' => &self.last_yank,
+            name => self.registers.get(&name.to_ascii_lowercase())?,
+        };
+        (!lines.is_empty()).then_some(lines.as_slice())
+    }
+
+    // Rows a `:` command's range covers. Addresses outside the document are moved to its
+    // first or last line, with a warning.
+    fn ex_rows(&mut self, range: CommandRange) -> Range<usize> {
+        let (rows, clamped) = range.rows(self.cursor_position.y, self.document.len());
+        if clamped {
+            self.notify(
+                Level::Warning,
+                format!(
+                    "Range clamped to {},{}",
+                    rows.start.saturating_add(1),
+                    rows.end
+                ),
+            );
+        }
+        rows
+    }
+
+    // Deletes the lines in range for `:d`, keeping them like `:y` would.
+    fn delete_lines(&mut self, range: CommandRange, register: Option<char>) {
+        if self.document.is_read_only() {
+            self.notify(Level::Warning, "File is read-only.");
+            return;
+        }
+        let rows = self.ex_rows(range);
+        let lines = self.document.delete_rows(rows.clone());
+        self.set_register(register, lines);
+        let y = cmp::min(rows.start, self.document.len().saturating_sub(1));
+        self.cursor_position = Position { x: 0, y };
+        self.notify(Level::Info, format!("{} fewer lines", self.yanked.len()));
+    }
+
+    // Replaces the pattern on the lines in range for `:s`, searching for the last search's if it's empty.
+    fn substitute(
+        &mut self,
+        range: CommandRange,
+        pattern: String,
+        replacement: &str,
+        global: bool,
+        count_only: bool,
+    ) {
+        if self.document.is_read_only() && !count_only {
+            self.notify(Level::Warning, "File is read-only.");
+            return;
+        }
+        let pattern = if pattern.is_empty() {
+            match &self.last_search_query {
+                Some(query) => query.clone(),
+                None => {
+                    self.notify(Level::Error, "No previous search");
+                    return;
+                }
+            }
+        } else {
+            pattern
+        };
+        let rows = self.ex_rows(range);
+        if count_only {
+            self.count_matches(rows, &pattern, global);
+            return;
+        }
+        let last_row = rows.end.saturating_sub(1);
+        match self
+            .document
+            .substitute(rows, &pattern, replacement, global, false)
+        {
+            (0, _) => self.notify(Level::Error, format!("Pattern not found: {}", pattern)),
+            (count, lines) => {
+                self.cursor_position = Position { x: 0, y: last_row };
+                self.notify(
+                    Level::Info,
+                    format!("{} substitutions on {} lines", count, lines),
+                );
+            }
+        }
+    }
+
+    // Counts the pattern's matches for `:s///n` with a dry run of the substitute.
+    // It goes a chunk of rows at a time, and stops once it has taken COUNT_TIME_LIMIT,
+    // so a huge document can't hang the editor. Matches never span rows, so the chunks
+    // add up to the same count as one pass.
+    fn count_matches(&mut self, rows: Range<usize>, pattern: &str, global: bool) {
+        let started = Instant::now();
+        let end = cmp::min(rows.end, self.document.len());
+        let (mut count, mut lines) = (0_usize, 0_usize);
+        let mut start = rows.start;
+        while start < end {
+            let chunk_end = cmp::min(start.saturating_add(COUNT_CHUNK_ROWS), end);
+            let (chunk_count, chunk_lines) =
+                self.document
+                    .substitute(start..chunk_end, pattern, "", global, true);
+            count = count.saturating_add(chunk_count);
+            lines = lines.saturating_add(chunk_lines);
+            start = chunk_end;
+            if start < end && started.elapsed() > COUNT_TIME_LIMIT {
+                self.notify(
+                    Level::Warning,
+                    format!(
+                        "Stopped counting at line {}: {} matches on {} lines so far",
+                        start.saturating_add(1),
+                        count,
+                        lines
+                    ),
+                );
+                return;
+            }
+        }
+
+        if count == 0 {
+            self.notify(Level::Error, format!("Pattern not found: {}", pattern));
+        } else {
+            self.notify(Level::Info, format!("{} matches on {} lines", count, lines));
+        }
+    }
+
+    // Turns on diff mode, comparing the buffer against its file as it is on disk now.
+    fn diff_with_disk(&mut self) {
+        let file_name = match &self.document.file_name {
+            Some(file_name) => file_name,
+            None => {
+                self.notify(Level::Error, "No file name to diff against.");
+                return;
+            }
+        };
+
+        match fs::read_to_string(file_name) {
+            Ok(disk) => {
+                let diff = Diff::new(&disk, &self.document.contents());
+                self.notify(Level::Info, format!("{} hunks", diff.hunk_count()));
+                self.diff = Some(diff);
+                // The diff view shows every row, so folds would only hide where the cursor is.
+                self.folds = Folds::default();
+            }
+            Err(error) => {
+                self.notify(
+                    Level::Error,
+                    format!("Could not read {}: {}", file_name, error),
+                );
+            }
+        }
+    }
+
+    // Shows the lines over the text area, from the top, until the next keypress.
+    fn show_overlay(&mut self, mut lines: Vec<String>) {
+        lines.push("Press any key to continue".to_string());
+        self.overlay = Some(Overlay {
+            top: 0,
+            lines: lines.into_iter().map(|line| (line, None)).collect(),
+        });
+    }
+
+    // Shows the messages sent so far over the text area, newest first and with how long ago
+    // each was sent, until the next keypress.
+    fn show_messages(&mut self) {
+        let now = self.clock.now();
+        let lines: Vec<String> = self
+            .messages
+            .history()
+            .rev()
+            .map(|message| format!("{:>3} ago  {}", message.age(now), message))
+            .collect();
+        self.show_overlay(lines);
+    }
+
+    // Shows the percentiles of the timings kept while profiling over the text area.
+    fn show_profile(&mut self) {
+        let lines = self.profiler.report();
+        if lines.len() == 1 {
+            let hint = if self.config.profiling {
+                "Nothing timed yet"
+            } else {
+                "Nothing timed, turn profiling on with :set profiling"
+            };
+            self.notify(Level::Info, hint);
+            return;
+        }
+        self.show_overlay(lines);
+    }
+
+    // Lists the mappings of the mode, or of every mode, over the text area.
+    fn show_mappings(&mut self, mode: Option<MapMode>) {
+        let lines: Vec<String> = self
+            .keymap
+            .mappings(mode)
+            .map(ToString::to_string)
+            .collect();
+        if lines.is_empty() {
+            self.notify(Level::Info, "No mapping found");
+            return;
+        }
+        self.show_overlay(lines);
+    }
+
+    // Shows the buffer's changes since it was last saved as a unified diff over the text area.
+    // A file that doesn't exist yet has everything added.
+    fn show_unsaved_changes(&mut self) {
+        let file_name = self.document.file_name.as_deref().unwrap_or("[No Name]");
+        let (disk_name, disk) = match fs::read_to_string(file_name) {
+            Ok(disk) => (file_name, disk),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => ("/dev/null", String::new()),
+            Err(error) => {
+                self.notify(
+                    Level::Error,
+                    format!("Could not read {}: {}", file_name, error),
+                );
+                return;
+            }
+        };
+
+        let changes = diff::unified(&disk, &self.document.contents());
+        if changes.is_empty() {
+            self.notify(Level::Info, "No unsaved changes.");
+            return;
+        }
+        let mut lines = vec![
+            (format!("--- {}", disk_name), None),
+            (format!("+++ {}", file_name), None),
+        ];
+        lines.extend(changes.into_iter().map(|(tag, line)| {
+            let color = match tag {
+                DiffTag::Added => Some(DIFF_ADDED_COLOR),
+                DiffTag::Removed => Some(DIFF_REMOVED_COLOR),
+                DiffTag::Equal => None,
+            };
+            (line, color)
+        }));
+        self.overlay = Some(Overlay { lines, top: 0 });
+    }
+
+    // Replaces the word before the cursor with its expansion, if it's an abbreviation.
+    // Called when a non-word character is about to be typed; the replacement is undone in one step.
+    fn expand_abbreviation(&mut self) {
+        let Position { x, y } = self.cursor_position;
+        let Some(row) = self.document.row(y) else {
+            return;
+        };
+        let Some((len, expansion)) = abbreviation::expand(row.slice(0, x), &self.abbreviations)
+        else {
+            return;
+        };
+
+        let start = x.saturating_sub(len);
+        let expansion = expansion.to_string();
+        self.document.replace_str_at(y, start..x, &expansion);
+
+        self.cursor_position = Position {
+            x: start.saturating_add(expansion.graphemes(true).count()),
+            y,
+        };
+        self.notify(Level::Info, "Abbreviation expanded");
+    }
+
+    // Suspends to the shell until brought back with `fg`. The window may have been resized and
+    // the screen drawn over meanwhile, so the view is fit to the size and drawn whole again.
+    fn suspend(&mut self) -> Result<(), ZenError> {
+        // The shell gets the user's cursor back, and the mode's is drawn again after.
+        self.restore_cursor_style();
+        match self.terminal.suspend() {
+            Err(error) if error.is_fatal() => return Err(error),
+            Err(error) => self.report(&error),
+            Ok(()) => (),
+        }
+        self.last_frame.clear();
+        self.scroll();
+        Ok(())
+    }
+
+    // Handles a signal the event loop got. An interrupt from elsewhere is taken as Ctrl-C, which
+    // can be mapped, and a request to terminate quits once unsaved changes are kept safe.
+    pub async fn handle_signal(&mut self, signal: ProcessSignal) -> Result<(), ZenError> {
+        match signal {
+            ProcessSignal::Stop => self.suspend(),
+            ProcessSignal::Continue => self.resume(),
+            ProcessSignal::Interrupt => self.handle_key(KeyEvent::Ctrl('c')).await,
+            ProcessSignal::Terminate => {
+                self.write_recovery_files().await;
+                self.terminated = true;
+                self.should_quit = true;
+                Ok(())
+            }
+        }
+    }
+
+    // Winds down before the terminal is handed back: a write still going is waited for, with
+    // the status bar saying so, then the background workers are cancelled and given a moment
+    // to stop. The command queue is closed before that, so none of them blocks on a full queue.
+    async fn shut_down(&mut self) -> Result<(), ZenError> {
+        if self
+            .recovery_write
+            .as_ref()
+            .is_some_and(|write| !write.is_finished())
+        {
+            self.shutting_down = true;
+            self.refresh_screen()?;
+            self.finish_recovery_write().await;
+            self.shutting_down = false;
+        }
+        self.commands.close();
+        if !self.workers.shut_down(WORKER_SHUTDOWN_TIMEOUT).await {
+            log::warn!("Background workers were still running on quitting");
+        }
+        Ok(())
+    }
+
+    // Writes each document with unsaved changes to its recovery file right away, on the way
+    // out. Documents without a name go to `untitled-<n>.recovery` among the recovery files.
+    // Nothing can be reported then, so files that can't be written are only logged.
+    async fn write_recovery_files(&mut self) {
+        let Some(dir) = self.recovery_dir.clone().filter(|_| self.config.recovery) else {
+            return;
+        };
+        self.finish_recovery_write().await;
+        let documents = iter::once(&self.document).chain(self.tabs.iter().map(|tab| &tab.document));
+        let mut untitled = 0_usize;
+        for document in documents.filter(|document| document.is_dirty()) {
+            let path = match &document.file_name {
+                Some(file_name) => recovery::path_for(&dir, file_name),
+                None => {
+                    untitled = untitled.saturating_add(1);
+                    dir.join(format!("untitled-{}.recovery", untitled))
+                }
+            };
+            if let Err(error) = recovery::write(&path, &document.contents()) {
+                log::warn!("Could not write {}: {}", path.display(), error);
+            }
+        }
+    }
+
+    // Removes the recovery files of all documents, once their changes are saved or dropped.
+    async fn remove_recovery_files(&mut self) {
+        self.finish_recovery_write().await;
+        let documents = iter::once(&self.document).chain(self.tabs.iter().map(|tab| &tab.document));
+        let paths: Vec<PathBuf> = documents
+            .filter_map(|document| self.recovery_path(document))
+            .collect();
+        for path in paths {
+            if let Err(error) = recovery::remove(&path) {
+                log::warn!("Could not remove {}: {}", path.display(), error);
+            }
+        }
+    }
+
+    // Where the document's recovery file goes, if it has a name and recovery files are kept.
+    fn recovery_path(&self, document: &Document) -> Option<PathBuf> {
+        let dir = self
+            .recovery_dir
+            .as_ref()
+            .filter(|_| self.config.recovery)?;
+        Some(recovery::path_for(dir, document.file_name.as_ref()?))
+    }
+
+    // Writes the active document's recovery file on a background task once it's been left
+    // alone for a moment after changes, and removes it once it's back to what was saved.
+    // Called on the event loop's tick, so neither holds up drawing.
+    async fn update_recovery(&mut self) {
+        if self
+            .recovery_write
+            .as_ref()
+            .is_some_and(|write| !write.is_finished())
+        {
+            return;
+        }
+        self.finish_recovery_write().await;
+        let Some(path) = self.recovery_path(&self.document) else {
+            return;
+        };
+        let edits = self.document.edit_count();
+
+        if !self.document.is_dirty() {
+            if self.recovery.has_written(&path) {
+                if let Err(error) = recovery::remove(&path) {
+                    self.report(&ZenError::io(path.display().to_string(), error));
+                }
+                self.recovery.clear();
+            }
+            return;
+        }
+        if self.document.is_loading() || !self.recovery.is_due(&path, edits, self.clock.now()) {
+            return;
+        }
+        let contents = self.document.contents();
+        self.recovery.written(path.clone(), edits);
+        self.recovery_write = Some(task::spawn_blocking(move || {
+            recovery::write(&path, &contents)
+        }));
+    }
+
+    // Removes the active document's recovery file once it's saved. A write still going is
+    // waited for first, so it can't put the file back.
+    async fn forget_recovery(&mut self) {
+        self.finish_recovery_write().await;
+        let Some(path) = self.recovery_path(&self.document) else {
+            return;
+        };
+        if let Err(error) = recovery::remove(&path) {
+            self.report(&ZenError::io(path.display().to_string(), error));
+        }
+        self.recovery.clear();
+    }
+
+    // Waits for the recovery file being written, if any, and reports if it couldn't be.
+    async fn finish_recovery_write(&mut self) {
+        let Some(write) = self.recovery_write.take() else {
+            return;
+        };
+        match write.await {
+            Ok(Ok(())) => (),
+            Ok(Err(error)) => self.notify(
+                Level::Warning,
+                format!("Could not write the recovery file: {}", error),
+            ),
+            Err(error) => self.notify(
+                Level::Warning,
+                format!("Could not write the recovery file: {}", error),
+            ),
+        }
+    }
+
+    // Asks what to do when the document's file has a recovery file, left by an editor that
+    // didn't get to quit: recover the changes it holds, delete it, or open the file anyway.
+    async fn offer_recovery(&mut self) -> Result<(), ZenError> {
+        let Some(path) = self
+            .recovery_path(&self.document)
+            .filter(|path| path.exists())
+        else {
+            return Ok(());
+        };
+        let Some(file_name) = self.document.file_name.clone() else {
+            return Ok(());
+        };
+        self.wait_for_loading().await?;
+
+        let age = if recovery::is_newer(&path, Path::new(&file_name)) {
+            "It's newer than the file, with changes that were never saved."
+        } else {
+            "It's older than the file, which was saved since."
+        };
+        let lines = [
+            format!("Found a recovery file for \"{}\":", file_name),
+            format!("  {}", path.display()),
+            age.to_string(),
+            "(R)ecover, (D)elete it, or (O)pen the file anyway".to_string(),
+        ];
+        self.overlay = Some(Overlay {
+            top: 0,
+            lines: lines.into_iter().map(|line| (line, None)).collect(),
+        });
+        let choice = loop {
+            self.refresh_screen()?;
+            if let Some(choice) = RecoveryChoice::from_key(self.input.next().await?) {
+                break choice;
+            }
+        };
+        self.overlay = None;
+
+        match choice {
+            RecoveryChoice::Recover => match fs::read_to_string(&path) {
+                Ok(contents) => self.recover(&contents),
+                Err(error) => self.report(&ZenError::io(path.display().to_string(), error)),
+            },
+            RecoveryChoice::Delete => match recovery::remove(&path) {
+                Ok(()) => self.notify(Level::Info, "Recovery file deleted"),
+                Err(error) => self.report(&ZenError::io(path.display().to_string(), error)),
+            },
+            RecoveryChoice::Open => (),
+        }
+        Ok(())
+    }
+
+    // Puts the recovered contents in place of the document's as one change, which leaves it
+    // unsaved, and which undo takes back to the file.
+    fn recover(&mut self, contents: &str) {
+        let last = self.document.len().saturating_sub(1);
+        let end = Position {
+            x: self.document.row(last).map_or(0, Row::len),
+            y: last,
+        };
+        let edit = Edit {
+            range: Selection {
+                start: Position::default(),
+                end,
+            },
+            text: contents.strip_suffix('\n').unwrap_or(contents).to_string(),
+        };
+        match self.document.apply_edits_transactional(vec![edit]) {
+            Ok(()) => {
+                self.clamp_cursor();
+                self.notify(Level::Info, "Recovered unsaved changes, :w to keep them");
+            }
+            Err(error) => self.report(&error),
+        }
+    }
+
+    // Sets the terminal up again after the editor was stopped by something other than Ctrl-Z
+    // and continued, e.g. SIGCONT after `kill -STOP`. Like after Ctrl-Z, it's drawn whole again.
+    pub fn resume(&mut self) -> Result<(), ZenError> {
+        match self.terminal.resume() {
+            Err(error) if error.is_fatal() => return Err(error),
+            Err(error) => self.report(&error),
+            Ok(()) => (),
+        }
+        self.cursor_style = None;
+        self.last_frame.clear();
+        self.scroll();
+        Ok(())
+    }
+
+    // Lists the abbreviations over the text area.
+    fn show_abbreviations(&mut self) {
+        let lines = abbreviation::list(&self.abbreviations);
+        if lines.is_empty() {
+            self.notify(Level::Info, "No abbreviation found");
+            return;
+        }
+        self.show_overlay(lines);
+    }
+
+    // Moves the cursor to where an undo or redo happened,
+    // or tells the user there was nothing left to do.
+    fn after_undo(&mut self, position: Option<Position>, nothing_left: &str) {
+        match position {
+            Some(position) => {
+                let y = cmp::min(position.y, self.document.len().saturating_sub(1));
+                self.sync_folds();
+                self.folds.open(y);
+                self.cursor_position = Position { x: position.x, y };
+            }
+            None => {
+                self.notify(Level::Info, nothing_left);
+            }
+        }
+    }
+
+    // Text a `gU` or `gu` motion covers from the cursor, or None for an unknown motion.
+    fn case_selection(&self, motion: &str) -> Option<Selection> {
+        let Position { x, y } = self.cursor_position.clone();
+        let row = self.document.row(y);
+        let end_x = match motion {
+            "U" | "u" => return Some(Selection::rows(y..y.saturating_add(1))),
+            // `j` moves up in zen, and `k` down.
+            "j" => return Some(Selection::rows(y.saturating_sub(1)..y.saturating_add(1))),
+            "k" => return Some(Selection::rows(y..y.saturating_add(2))),
+            "w" => row.map_or(x, |row| row.next_word_start(x)),
+            "$" => row.map_or(x, Row::len),
+            "ip" | "ap" => {
+                let rows = self.document.paragraph_object(y, motion == "ap");
+                return Some(Selection::rows(rows));
+            }
+            _ => return None,
+        };
+        Some(Selection {
+            start: Position { x, y },
+            end: Position { x: end_x, y },
+        })
+    }
+
+    // Follows the rows edits replaced with the folds.
+    fn sync_folds(&mut self) {
+        for splice in self.document.take_splices() {
+            self.folds.splice(splice);
+        }
+    }
+
+    // Opens the closed fold under the cursor, or closes the rows indented further below it.
+    fn toggle_fold(&mut self) {
+        if self.diff.is_some() {
+            self.notify(Level::Warning, "Folds are not available in diff mode");
+            return;
+        }
+        self.sync_folds();
+        let y = self.cursor_position.y;
+        if self.folds.open(y) {
+            return;
+        }
+        let tab_width = self.document.effective_tab_width(&self.config);
+        match fold::indent_fold(&self.document, y, tab_width) {
+            Some(rows) => self.folds.close(rows),
+            None => self.notify(Level::Error, "No fold found"),
+        }
+    }
+
+    // Keeps the cursor off rows hidden in closed folds after it moved from row `from`.
+    // It lands on a fold's first row, which the fold is drawn on, or moving down from there
+    // goes on to the row after the fold.
+    fn skip_folds(&mut self, from: usize) {
+        self.sync_folds();
+        let y = self.cursor_position.y;
+        let Some(fold) = self.folds.at(y) else {
+            return;
+        };
+        self.cursor_position.y =
+            if y > from && fold.contains(&from) && fold.end < self.document.len() {
+                fold.end
+            } else {
+                fold.start
+            };
+    }
+
+    // Runs the event's hooks in order. A hook fails when its command returns or reports an error,
+    // which is reported without stopping the hooks after it. Returns false when a required hook
+    // failed on a `Pre` event, to cancel what was about to happen.
+    async fn fire(&mut self, event: Event) -> bool {
+        if self.running_hooks {
+            return true;
+        }
+        let hooks = self.events.hooks_for(event);
+        if hooks.is_empty() {
+            return true;
+        }
+
+        self.running_hooks = true;
+        let mut proceed = true;
+        for hook in hooks {
+            let errors = self.messages.error_count();
+            let result = match &hook.run {
+                HookAction::TrimTrailingWhitespace => {
+                    self.document.trim_trailing_whitespace();
+                    Ok(())
+                }
+                // Boxed, since the command may be a write that fires hooks in turn.
+                HookAction::Command(command) => match ex::parse(command) {
+                    Ok(command) => Box::pin(self.run_ex_command(command)).await,
+                    Err(error) => Err(error),
+                },
+            };
+            if let Err(error) = &result {
+                self.notify(Level::Error, format!("{} hook failed: {}", event, error));
+            }
+            let failed = result.is_err() || self.messages.error_count() != errors;
+            if failed && hook.required && event.is_pre() {
+                proceed = false;
+                break;
+            }
+        }
+        self.running_hooks = false;
+        self.sync_view();
+        proceed
+    }
+
+    // Runs the hooks of the events emitted since the last time.
+    async fn fire_queued(&mut self) {
+        while let Some(event) = self.events.next_queued() {
+            self.fire(event).await;
+        }
+    }
+
+    // Runs the CursorMoved hooks once the cursor stayed put long enough after moving.
+    async fn fire_cursor_moved(&mut self) {
+        let Some(moved_at) = self.cursor_moved_at else {
+            return;
+        };
+        if self.clock.now().saturating_duration_since(moved_at) >= CURSOR_MOVED_DELAY {
+            self.cursor_moved_at = None;
+            self.fire(Event::CursorMoved).await;
+        }
+    }
+
+    // Moves the cursor to an older or newer entry of the change list.
+    // Lines may have been removed since, so the position is kept within the document.
+    fn jump_to_change(&mut self, direction: SearchDirection) {
+        let position = match direction {
+            SearchDirection::Backward => self.changes.older(),
+            SearchDirection::Forward => self.changes.newer(),
+        };
+        let Some(position) = position else {
+            let text = if self.changes.positions.is_empty() {
+                "Change list is empty"
+            } else if direction == SearchDirection::Backward {
+                "At start of change list"
+            } else {
+                "At end of change list"
+            };
+            self.notify(Level::Info, text);
+            return;
+        };
+
+        let y = cmp::min(position.y, self.document.len().saturating_sub(1));
+        let len = self.document.row(y).map_or(0, Row::len);
+        self.folds.open(y);
+        self.apply(CommandOutcome::MoveCursor(Position {
+            x: cmp::min(position.x, len),
+            y,
+        }));
+    }
+
+    // Moves the cursor to the start of the next or previous diff hunk.
+    fn jump_to_hunk(&mut self, direction: SearchDirection) {
+        let diff = match &self.diff {
+            Some(diff) => diff,
+            None => {
+                self.notify(Level::Warning, "Not in diff mode.");
+                return;
+            }
+        };
+
+        let y = self.cursor_position.y;
+        let hunk = match direction {
+            SearchDirection::Forward => diff.next_hunk(y),
+            SearchDirection::Backward => diff.prev_hunk(y),
+        };
+
+        match hunk {
+            Some(line) => {
+                let y = cmp::min(line, self.document.len().saturating_sub(1));
+                self.cursor_position = Position { x: 0, y };
+            }
+            None => {
+                self.notify(Level::Info, "No more hunks.");
+            }
+        }
+    }
+
+    // Hands the loader of the file the editor was started on to a blocking task, which reads
+    // the rest of it. Chunks wait for room in the command queue, so a huge file isn't read
+    // much ahead of what the editor has taken in.
+    fn start_loading(&mut self) {
+        let Some(mut loader) = self.loader.take() else {
+            return;
+        };
+        let queue = self.command_queue.clone();
+        self.workers.spawn_blocking(move |cancellation| loop {
+            if cancellation.is_cancelled() {
+                break;
+            }
+            let chunk = loader.next_chunk();
+            let done = chunk.as_ref().map_or(true, |chunk| chunk.done);
+            // Only fails once the editor is gone, which is when to stop reading.
+            if queue.blocking_send(Command::DocumentLoaded(chunk)).is_err() || done {
+                break;
+            }
+        });
+    }
+
+    // Appends what the loader read to the document that's loading, the one shown or another tab's.
+    fn append_loaded(&mut self, chunk: io::Result<LoadChunk>) {
+        let document = if self.document.is_loading() {
+            &mut self.document
+        } else {
+            match self
+                .tabs
+                .iter_mut()
+                .map(|page| &mut page.document)
+                .find(|document| document.is_loading())
+            {
+                Some(document) => document,
+                None => return,
+            }
+        };
+        match chunk {
+            Ok(chunk) => document.append_loaded(chunk),
+            Err(error) => {
+                document.stop_loading();
+                self.notify(
+                    Level::Error,
+                    format!("Could not read the rest of the file: {}", error),
+                );
+            }
+        }
+    }
+
+    // Waits for the document to finish loading, for commands that need all of it, like `G`,
+    // `:s` and saving. Other commands queued meanwhile run after them, in order.
+    async fn wait_for_loading(&mut self) -> Result<(), ZenError> {
+        if !self.document.is_loading() {
+            return Ok(());
+        }
+        // Before the event loop runs, the rest of the file is read right here.
+        if let Some(mut loader) = self.loader.take() {
+            while self.document.is_loading() {
+                self.append_loaded(loader.next_chunk());
+            }
+            return Ok(());
+        }
+
+        let mut deferred = Vec::new();
+        while self.document.is_loading() {
+            self.refresh_screen()?;
+            match self.commands.recv().await {
+                Some(Command::DocumentLoaded(chunk)) => self.append_loaded(chunk),
+                Some(command) => deferred.push(command),
+                None => break,
+            }
+        }
+        for command in deferred {
+            // Only fails once the editor is stopping, when they wouldn't run anyway.
+            let _ = self.command_queue.push(command);
+        }
+        Ok(())
+    }
+
+    // Runs the build command on a worker, so the editor stays usable meanwhile.
+    // What it printed comes back through the command queue. Quitting kills it.
+    fn make(&mut self, args: &str) {
+        if self.building {
+            self.notify(Level::Warning, "A build is already running");
+            return;
+        }
+        let program = self
+            .config
+            .makeprg
+            .get(self.document.file_type())
+            .map_or(DEFAULT_MAKEPRG, String::as_str);
+        let command = if args.is_empty() {
+            program.to_string()
+        } else {
+            format!("{} {}", program, args)
+        };
+
+        self.building = true;
+        self.build_summary = None;
+        let queue = self.command_queue.clone();
+        self.workers.spawn(|mut cancellation| async move {
+            tokio::select! {
+                output = quickfix::run_build(&command) => {
+                    // Only fails once the editor is gone, and the output isn't needed anymore.
+                    let _ = queue.push(Command::MakeFinished(output));
+                }
+                // Dropping the build kills it.
+                () = cancellation.cancelled() => (),
+            }
+        });
+    }
+
+    // Replaces the quickfix list with what the build printed, then jumps to its first error.
+    async fn finish_make(&mut self, output: io::Result<BuildOutput>) {
+        self.building = false;
+        let output = match output {
+            Ok(output) => output,
+            Err(error) => {
+                self.notify(Level::Error, format!("Could not run the build: {}", error));
+                return;
+            }
+        };
+
+        self.quickfix = QuickfixList::new(quickfix::parse(&output.text));
+        let (errors, warnings) = self.quickfix.counts();
+        self.build_summary = Some(format!(
+            "{} {}, {} {}",
+            errors,
+            if errors == 1 { "error" } else { "errors" },
+            warnings,
+            if warnings == 1 { "warning" } else { "warnings" }
+        ));
+        if !self.quickfix.is_empty() {
+            self.go_to_quickfix_entry().await;
+        } else if output.success {
+            self.notify(Level::Info, "Build succeeded");
+        } else {
+            self.notify(Level::Error, "Build failed, but printed no errors");
+        }
+    }
+
+    // Moves to the next or previous entry of the quickfix list.
+    async fn jump_quickfix(&mut self, direction: SearchDirection) {
+        if self.quickfix.is_empty() {
+            self.notify(Level::Error, "No errors");
+            return;
+        }
+        let entry = match direction {
+            SearchDirection::Forward => self.quickfix.next_entry(),
+            SearchDirection::Backward => self.quickfix.prev_entry(),
+        };
+        if entry.is_none() {
+            self.notify(Level::Error, "No more items");
+            return;
+        }
+        self.go_to_quickfix_entry().await;
+    }
+
+    // Puts the cursor where the current quickfix entry points, opening its file if it's another
+    // one. The document has to be saved for that.
+    async fn go_to_quickfix_entry(&mut self) {
+        let Some(entry) = self.quickfix.current().cloned() else {
+            return;
+        };
+        if !self.is_document_file(&entry.file) {
+            if self.document.is_dirty() {
+                self.notify(
+                    Level::Error,
+                    format!("No write since last change, can't open {}", entry.file),
+                );
+                return;
+            }
+            if !self.open_file(&entry.file).await {
+                return;
+            }
+        }
+
+        let y = cmp::min(
+            entry.line.saturating_sub(1),
+            self.document.len().saturating_sub(1),
+        );
+        let x = self
+            .document
+            .row(y)
+            .map_or(0, |row| cmp::min(entry.column.saturating_sub(1), row.len()));
+        self.cursor_position = Position { x, y };
+        self.folds.open(y);
+        if let Some(text) = self.quickfix.describe_current() {
+            self.notify(Level::Info, text);
+        }
+    }
+
+    // Whether the path names the document's file, however it's written.
+    fn is_document_file(&self, path: &str) -> bool {
+        let Some(file_name) = self.document.file_name.as_deref() else {
+            return false;
+        };
+        match (fs::canonicalize(file_name), fs::canonicalize(path)) {
+            (Ok(file_name), Ok(path)) => file_name == path,
+            _ => file_name == path,
+        }
+    }
+
+    // Opens the file whose path is under the cursor, for `gf`. It's edited in place of the
+    // document, or in a new tab when the document has unsaved changes.
+    async fn open_file_at_cursor(&mut self) {
+        let Position { x, y } = self.cursor_position;
+        let Some(path) = self
+            .document
+            .row(y)
+            .and_then(|row| row.path_at(x))
+            .map(str::to_string)
+        else {
+            self.notify(Level::Error, "No file name under the cursor");
+            return;
+        };
+        let Some(found) = self.find_file(&path) else {
+            self.notify(Level::Error, format!("No such file: {}", path));
+            return;
+        };
+        let found = found.display().to_string();
+        if self.document.is_dirty() {
+            self.new_tab(Some(found)).await;
+        } else {
+            self.open_file(&found).await;
+        }
+    }
+
+    // Where the path written in the document leads: `~/` is the home directory, and a relative
+    // path is looked for from the document's directory and each of its parents in turn, so
+    // `../lib.rs` or `src/main.rs` are found from deeper down, then from the working directory.
+    fn find_file(&self, path: &str) -> Option<PathBuf> {
+        let path = match path.strip_prefix("~/") {
+            Some(rest) => PathBuf::from(env::var_os("HOME")?).join(rest),
+            None => PathBuf::from(path),
+        };
+        if path.is_absolute() {
+            return path.is_file().then_some(path);
+        }
+        let directory = self
+            .document
+            .file_name
+            .as_deref()
+            .and_then(|file_name| fs::canonicalize(file_name).ok());
+        let found = directory
+            .iter()
+            .flat_map(|directory| directory.ancestors().skip(1))
+            .find_map(|directory| {
+                Some(directory.join(&path)).filter(|candidate| candidate.is_file())
+            });
+        match found {
+            // Without the `..` it was found through.
+            Some(found) => Some(fs::canonicalize(&found).unwrap_or(found)),
+            None => path.is_file().then_some(path),
+        }
+    }
+
+    // Edits another file in place of the document, starting over with what belonged to it.
+    // Returns whether the file could be opened.
+    async fn open_file(&mut self, path: &str) -> bool {
+        let mut document = match Document::open(path).await {
+            Ok(document) => document,
+            Err(error) => {
+                self.report(&error);
+                return false;
+            }
+        };
+        document.set_read_only(self.document.is_read_only());
+        self.document = document;
+        self.cursor_position = Position::default();
+        self.offset = Position::default();
+        self.folds = Folds::default();
+        self.changes = ChangeList::default();
+        self.diff = None;
+        self.search_matches.clear();
+        self.last_frame.clear();
+        if let Err(error) = self.offer_recovery().await {
+            self.report(&error);
+        }
+        self.fire(Event::BufOpen).await;
+        true
+    }
+
+    // Opens the file in a new tab after the current one, or an empty document without one.
+    // A file that doesn't exist yet is created by the first write.
+    async fn new_tab(&mut self, path: Option<String>) {
+        let mut document = Document::default();
+        if let Some(path) = path {
+            if Path::new(&path).exists() {
+                match Document::open(&path).await {
+                    Ok(opened) => document = opened,
+                    Err(error) => {
+                        self.report(&error);
+                        return;
+                    }
+                }
+            } else {
+                document.set_file_name(path);
+            }
+        }
+        document.set_read_only(self.document.is_read_only());
+
+        let page = self.swap_page(TabPage {
+            document,
+            ..TabPage::default()
+        });
+        self.tabs.insert(self.active_tab, page);
+        self.active_tab = self.active_tab.saturating_add(1);
+        if self.document.file_name.is_some() {
+            if let Err(error) = self.offer_recovery().await {
+                self.report(&error);
+            }
+            self.fire(Event::BufOpen).await;
+        }
+    }
+
+    // Goes to the next or previous tab, from the last one back to the first and the other way.
+    fn cycle_tab(&mut self, direction: SearchDirection) {
+        let count = self.tabs.len().saturating_add(1);
+        let index = match direction {
+            SearchDirection::Forward => self.active_tab.saturating_add(1) % count,
+            SearchDirection::Backward => self
+                .active_tab
+                .checked_sub(1)
+                .unwrap_or(count.saturating_sub(1)),
+        };
+        self.switch_tab(index);
+    }
+
+    // Shows the tab at the index, counting every tab.
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.active_tab || index > self.tabs.len() {
+            return;
+        }
+        // Tabs after the shown one sit a place earlier in `tabs`, since it isn't there.
+        let stored = if index < self.active_tab {
+            index
+        } else {
+            index.saturating_sub(1)
+        };
+        let page = self.tabs.remove(stored);
+        let previous = self.swap_page(page);
+        let previous_index = if index < self.active_tab {
+            self.active_tab.saturating_sub(1)
+        } else {
+            self.active_tab
+        };
+        self.tabs.insert(previous_index, previous);
+        self.active_tab = index;
+    }
+
+    // Closes the current tab and shows the one after it, or the one before when it was the last.
+    // Unsaved changes keep it open unless forced.
+    fn close_tab(&mut self, force: bool) {
+        if self.tabs.is_empty() {
+            self.notify(Level::Error, "Cannot close last tab");
+            return;
+        }
+        if !force && self.document.is_dirty() {
+            self.notify(
+                Level::Warning,
+                "No write since last change (add ! to override)",
+            );
+            return;
+        }
+
+        let index = cmp::min(self.active_tab, self.tabs.len().saturating_sub(1));
+        let page = self.tabs.remove(index);
+        self.swap_page(page);
+        self.active_tab = index;
+    }
+
+    // Shows the page's document and view in place of the current ones, and returns those.
+    fn swap_page(&mut self, page: TabPage) -> TabPage {
+        let previous = TabPage {
+            document: mem::replace(&mut self.document, page.document),
+            cursor_position: mem::replace(&mut self.cursor_position, page.cursor_position),
+            offset: mem::replace(&mut self.offset, page.offset),
+            folds: mem::replace(&mut self.folds, page.folds),
+            changes: mem::replace(&mut self.changes, page.changes),
+            diff: mem::replace(&mut self.diff, page.diff),
+        };
+        self.search_matches.clear();
+        self.last_frame.clear();
+        previous
+    }
+
+    // Active document search functionality.
+    async fn search(&mut self) -> Result<(), ZenError> {
+        let old_position = self.cursor_position.clone();
+
         let mut direction = SearchDirection::Forward;
         let query = self
             .prompt(
-                "Search (ESC to cancel, Arrows to navigate): ",
+                "Search (ESC to cancel, Ctrl-G/Ctrl-T to navigate): ",
+                PromptKind::Search,
                 |editor, key, query| {
                     let mut moved = false;
                     match key {
-                        Key::Right | Key::Down => {
+                        KeyEvent::Ctrl('g') => {
                             direction = SearchDirection::Forward;
-                            editor.execute(Command::CursorMoveRight);
+                            editor.cursor_position =
+                                commands::cursor::move_right(&editor.context());
                             moved = true;
                         }
-                        Key::Left | Key::Up => direction = SearchDirection::Backward,
+                        KeyEvent::Ctrl('t') => direction = SearchDirection::Backward,
                         _ => direction = SearchDirection::Forward,
                     }
-                    if let Some(position) =
+                    let found = if editor.config.wrap_search {
+                        let (found, wrapped) = editor.document.find_with_wrap(
+                            query,
+                            &editor.cursor_position,
+                            direction,
+                            None,
+                        );
+                        if wrapped {
+                            editor.notify(Level::Info, wrap_message(direction));
+                        }
+                        found
+                    } else {
                         editor
                             .document
-                            .find(&query, &editor.cursor_position, direction)
-                    {
+                            .find(query, &editor.cursor_position, direction, None)
+                    };
+                    if let Some(position) = found {
+                        editor.folds.open(position.y);
                         editor.cursor_position = position;
                         editor.scroll();
                     } else if moved {
-                        editor.execute(Command::CursorMoveLeft);
+                        editor.cursor_position = commands::cursor::move_left(&editor.context());
                     }
                     editor.highlighted_word = Some(query.to_string());
                 },
             )
-            .unwrap_or(None);
+            .await;
+        self.highlighted_word = None;
+
+        match query? {
+            Some(query) => {
+                self.last_search_query = Some(query);
+                self.last_search_direction = SearchDirection::Forward;
+                self.highlight_search = true;
+            }
+            None => {
+                self.cursor_position = old_position;
+                self.scroll();
+            }
+        }
+        Ok(())
+    }
+
+    // Searches for the whole word under the cursor, like `*` and `#` in Vim.
+    fn search_word(&mut self, direction: SearchDirection) {
+        let Position { x, y } = self.cursor_position;
+        let word = self
+            .document
+            .row(y)
+            .and_then(|row| row.word_at(x))
+            .map(str::to_string);
+
+        match word {
+            Some(word) => self.search_set_pattern(Pattern::whole_word(&word), direction),
+            None => {
+                self.notify(Level::Error, "No string under cursor");
+            }
+        }
+    }
+
+    // Deletes the next match of the last search, or the one the cursor is on, and starts Insert
+    // mode in its place, like Vim's `cgn`. Leaving Insert mode records the change for `.`.
+    // Returns whether there was a match to change.
+    async fn change_next_match(&mut self) -> Result<bool, ZenError> {
+        let Some(query) = self.last_search_query.clone() else {
+            self.notify(Level::Error, "No previous search");
+            return Ok(false);
+        };
+        if self.document.is_read_only() {
+            self.notify(Level::Warning, "File is read-only.");
+            return Ok(false);
+        }
+        let direction = SearchDirection::Forward;
+        let (found, wrapped) = if self.config.wrap_search {
+            self.document
+                .find_with_wrap(&query, &self.cursor_position, direction, None)
+        } else {
+            let found = self
+                .document
+                .find(&query, &self.cursor_position, direction, None);
+            (found, false)
+        };
+        let Some(position) = found else {
+            self.notify(Level::Error, format!("Pattern not found: {}", query));
+            return Ok(false);
+        };
+        if wrapped {
+            self.notify(Level::Info, wrap_message(direction));
+        }
+
+        // The match's own length, which ignoring case can make differ from the pattern's.
+        let pattern = Pattern::parse(&query);
+        let end = self
+            .document
+            .row(position.y)
+            .and_then(|row| {
+                row.find_match_ranges(&pattern)
+                    .into_iter()
+                    .find(|range| range.start == position.x)
+            })
+            .map_or(position.x, |range| range.end);
+        self.document
+            .replace_str_at(position.y, position.x..end, "");
+        self.folds.open(position.y);
+        self.cursor_position = position;
+        self.execute(Command::EditorSwitchMode(EditorMode::Insert))
+            .await?;
+        self.changing_match = true;
+        Ok(true)
+    }
+
+    // Deletes what a word motion covers and goes on to Insert mode, like `cw` or `cB` in Vim.
+    async fn change_motion(&mut self, motion: Motion) -> Result<(), ZenError> {
+        if self.document.is_read_only() {
+            self.notify(Level::Warning, "File is read-only.");
+            return Ok(());
+        }
+        self.clamp_cursor();
+        let motion = motion.for_change(&self.document, &self.cursor_position);
+        self.execute(Command::DocumentDeleteMotion(motion)).await?;
+        self.execute(Command::EditorSwitchMode(EditorMode::Insert))
+            .await
+    }
+
+    // Makes the last change again for `.`, as a single undo step.
+    async fn repeat_change(&mut self) -> Result<(), ZenError> {
+        let Some(change) = self.last_change.clone() else {
+            return Ok(());
+        };
+        match change {
+            Repeat::ChangeMatch(text) => {
+                self.document.begin_batch();
+                if self.change_next_match().await? {
+                    for c in text.chars() {
+                        self.execute(Command::DocumentInsert(c)).await?;
+                    }
+                    self.current_insert.push_str(&text);
+                    self.execute(Command::EditorSwitchMode(EditorMode::Normal))
+                        .await?;
+                }
+                self.document.end_batch();
+            }
+        }
+        Ok(())
+    }
+
+    // Makes the pattern the one `n` and `N` repeat, highlights it in view and jumps to its next match.
+    pub fn search_set_pattern(&mut self, pattern: String, direction: SearchDirection) {
+        self.last_search_query = Some(pattern);
+        self.last_search_direction = direction;
+        self.highlight_search = true;
+        self.update_search_matches();
+        self.search_next(false);
+    }
+
+    // Jumps to the next match of the last search, or the previous one when reversed.
+    // Wraps around the document's ends like Vim does, saying so in the message bar.
+    fn search_next(&mut self, reverse: bool) {
+        let query = match &self.last_search_query {
+            Some(query) => query,
+            None => {
+                self.notify(Level::Error, "No previous search");
+                return;
+            }
+        };
+        let direction = match (self.last_search_direction, reverse) {
+            (SearchDirection::Forward, false) | (SearchDirection::Backward, true) => {
+                SearchDirection::Forward
+            }
+            _ => SearchDirection::Backward,
+        };
+
+        // Forward searches skip the match under the cursor.
+        let Position { x, y } = self.cursor_position;
+        let from = match direction {
+            SearchDirection::Forward => Position {
+                x: x.saturating_add(1),
+                y,
+            },
+            SearchDirection::Backward => Position { x, y },
+        };
+        let found = if self.config.wrap_search {
+            let (found, wrapped) = self.document.find_with_wrap(query, &from, direction, None);
+            match found {
+                Some(_) if wrapped => self.notify(Level::Info, wrap_message(direction)),
+                Some(_) => (),
+                None => self.notify(Level::Error, format!("Pattern not found: {}", query)),
+            }
+            found
+        } else {
+            let found = self.document.find(query, &from, direction, None);
+            if found.is_none() {
+                let end = match direction {
+                    SearchDirection::Forward => "BOTTOM",
+                    SearchDirection::Backward => "TOP",
+                };
+                let message = format!("search hit {} without match for: {}", end, query);
+                self.notify(Level::Error, message);
+            }
+            found
+        };
 
-        if query.is_none() {
-            self.cursor_position = old_position;
-            self.scroll();
+        if let Some(position) = found {
+            self.folds.open(position.y);
+            self.cursor_position = position;
+            self.highlight_search = true;
+            self.sync_view();
         }
+    }
+
+    // Stops highlighting the last search's matches until the next search, like `:nohlsearch`.
+    fn clear_search_highlight(&mut self) {
+        self.highlight_search = false;
         self.highlighted_word = None;
+        self.search_matches.clear();
+    }
+
+    // Finds the matches of the last search in view again, after the document or view changed.
+    // Nothing is highlighted while the hlsearch option is off.
+    fn update_search_matches(&mut self) {
+        self.search_matches = match &self.last_search_query {
+            Some(query) if self.highlight_search && self.config.highlight_search => self
+                .document
+                .find_all_in_range(query, self.calculate_viewport(), true),
+            _ => Vec::new(),
+        };
+    }
+
+    // Columns of the search matches on the given document row.
+    fn search_marks(&self, y: usize) -> Vec<Range<usize>> {
+        let len = self
+            .last_search_query
+            .as_deref()
+            .map_or(0, |query| Pattern::parse(query).len());
+
+        self.search_matches
+            .iter()
+            .filter(|position| position.y == y)
+            .map(|position| position.x..position.x.saturating_add(len))
+            .collect()
+    }
+
+    // Whether misspelled words are looked for in this document: `:set spell` is on and it's prose.
+    fn checks_spelling(&self) -> bool {
+        self.config.spell && spell::is_prose(self.document.file_type())
+    }
+
+    // Loads the dictionary the first time spelling is checked: the words zen comes with, and
+    // the ones in the user's word list if there is one.
+    fn load_dictionary(&mut self) {
+        if self.spell.is_some() {
+            return;
+        }
+        let mut dictionary = spell::Dictionary::bundled();
+        if let Some(path) = Config::word_list() {
+            match fs::read_to_string(&path) {
+                Ok(words) => dictionary.add_words(&words),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+                Err(error) => self.report(&ZenError::io(path, error)),
+            }
+        }
+        self.spell = Some(spell::Checker::new(dictionary));
+    }
+
+    // Looks for misspelled words on the rows in view that changed since they were last checked.
+    fn update_misspellings(&mut self) {
+        if !self.checks_spelling() {
+            return;
+        }
+        self.load_dictionary();
+        let viewport = self.calculate_viewport();
+        let Some(checker) = &mut self.spell else {
+            return;
+        };
+        checker.retain(&viewport);
+        for y in viewport {
+            if let Some(row) = self.document.row(y) {
+                checker.check(y, &row.string);
+            }
+        }
+    }
+
+    // Columns of the misspelled words on the given document row, once it was checked.
+    fn spell_marks(&self, y: usize) -> &[Range<usize>] {
+        match &self.spell {
+            Some(checker) if self.checks_spelling() => checker.checked(y),
+            _ => &[],
+        }
+    }
+
+    // Tells the user why `]s`, `[s` and `z=` do nothing when spelling isn't checked.
+    fn spelling_available(&mut self) -> bool {
+        if !self.config.spell {
+            self.notify(
+                Level::Error,
+                "Spell checking is off, turn it on with :set spell",
+            );
+            false
+        } else if !spell::is_prose(self.document.file_type()) {
+            self.notify(Level::Error, "Spelling is only checked in text files");
+            false
+        } else {
+            true
+        }
+    }
+
+    // Moves the cursor to the next misspelled word in the direction, like `]s` and `[s`.
+    // Past the end of the document it goes on from the other one while wrap_search is on.
+    fn jump_to_misspelling(&mut self, direction: SearchDirection) {
+        if !self.spelling_available() {
+            return;
+        }
+        self.load_dictionary();
+        let Some(checker) = &mut self.spell else {
+            return;
+        };
+
+        // Rows in the order they're searched, and whether the search wrapped to get there.
+        // The cursor's row comes up again last, for the words on the other side of the cursor.
+        let Position { x, y } = self.cursor_position.clone();
+        let len = self.document.len();
+        let rows: Vec<(usize, bool)> = match direction {
+            SearchDirection::Forward => (y..len)
+                .map(|row| (row, false))
+                .chain((0..=y).map(|row| (row, true)))
+                .collect(),
+            SearchDirection::Backward => (0..=y)
+                .rev()
+                .map(|row| (row, false))
+                .chain((y..len).rev().map(|row| (row, true)))
+                .collect(),
+        };
+
+        let mut found = None;
+        for (row_y, wrapped) in rows {
+            if wrapped && !self.config.wrap_search {
+                break;
+            }
+            let Some(row) = self.document.row(row_y) else {
+                continue;
+            };
+            let words = checker.check(row_y, &row.string);
+            let past_cursor = |start: usize| match direction {
+                _ if row_y != y || wrapped => true,
+                SearchDirection::Forward => start > x,
+                SearchDirection::Backward => start < x,
+            };
+            let word = match direction {
+                SearchDirection::Forward => words.iter().find(|word| past_cursor(word.start)),
+                SearchDirection::Backward => {
+                    words.iter().rev().find(|word| past_cursor(word.start))
+                }
+            };
+            if let Some(word) = word {
+                found = Some((
+                    Position {
+                        x: word.start,
+                        y: row_y,
+                    },
+                    wrapped,
+                ));
+                break;
+            }
+        }
+
+        match found {
+            Some((position, wrapped)) => {
+                if wrapped {
+                    self.notify(Level::Info, wrap_message(direction));
+                }
+                self.folds.open(position.y);
+                self.cursor_position = position;
+            }
+            None => self.notify(Level::Info, "No misspelled words"),
+        }
+    }
+
+    // Offers the words from the dictionary the one under the cursor might have been meant to
+    // be, like `z=`, and replaces it with the one whose number is typed. Any other key keeps it.
+    async fn suggest_spelling(&mut self) -> Result<(), ZenError> {
+        if !self.spelling_available() {
+            return Ok(());
+        }
+        let Position { x, y } = self.cursor_position.clone();
+        let Some((range, word)) = self
+            .document
+            .row(y)
+            .and_then(|row| spell::word_at(&row.string, x))
+        else {
+            self.notify(Level::Error, "No word under the cursor");
+            return Ok(());
+        };
+        self.load_dictionary();
+        let suggestions = self
+            .spell
+            .as_ref()
+            .map(|checker| checker.dictionary().suggest(&word))
+            .unwrap_or_default();
+        if suggestions.is_empty() {
+            self.notify(Level::Info, format!("No suggestions for: {}", word));
+            return Ok(());
+        }
+
+        let mut lines = vec![format!("Change \"{}\" to:", word)];
+        lines.extend(
+            suggestions
+                .iter()
+                .zip('1'..='9')
+                .map(|(suggestion, digit)| format!("{:>2} \"{}\"", digit, suggestion)),
+        );
+        lines.push("Type a number to pick one, any other key to keep it".to_string());
+        self.overlay = Some(Overlay {
+            top: 0,
+            lines: lines.into_iter().map(|line| (line, None)).collect(),
+        });
+        self.refresh_screen()?;
+        let key = self.input.next().await?;
+        self.overlay = None;
+
+        let picked = suggestions
+            .iter()
+            .zip('1'..='9')
+            .find(|(_, digit)| key == KeyEvent::Char(*digit));
+        if let Some((suggestion, _)) = picked {
+            self.document.replace_str_at(y, range.clone(), suggestion);
+            self.cursor_position = Position { x: range.start, y };
+        }
+        Ok(())
+    }
+}
+
+// What the message bar says when a search went on from the other end of the document.
+fn wrap_message(direction: SearchDirection) -> &'static str {
+    match direction {
+        SearchDirection::Forward => "search hit BOTTOM, continuing at TOP",
+        SearchDirection::Backward => "search hit TOP, continuing at BOTTOM",
+    }
+}
+
+// The command a complete sequence of keys like `]c` or `g;` runs, for those that just run one.
+fn sequence_command(keys: &str) -> Option<Command> {
+    let command = match keys {
+        "]c" => Command::DiffNextHunk,
+        "[c" => Command::DiffPrevHunk,
+        "]s" => Command::SpellNext,
+        "[s" => Command::SpellPrev,
+        "z=" => Command::SpellSuggest,
+        "ga" => Command::EditorCharacterInfo,
+        "gf" => Command::EditorGoToFile,
+        "gt" => Command::TabNext,
+        "gT" => Command::TabPrev,
+        "g;" => Command::ChangeListPrev,
+        "g," => Command::ChangeListNext,
+        "za" => Command::FoldToggle,
+        "zz" => Command::ScrollCursorCenter,
+        "zt" => Command::ScrollCursorTop,
+        "zb" => Command::ScrollCursorBottom,
+        _ => return None,
+    };
+    Some(command)
+}
+
+// Whether the keys start a sequence that isn't complete yet. `gq` takes a motion: the paragraph
+// with `q`, or a line up or down with `j` and `k`, or the `ip` and `ap` text objects, which `i`
+// and `a` start. `gU` and `gu` take the same.
+fn awaits_more_keys(keys: &str) -> bool {
+    let object_start = keys.len() == 3
+        && (keys.starts_with("gq") || keys.starts_with("gU") || keys.starts_with("gu"))
+        && (keys.ends_with('i') || keys.ends_with('a'));
+    object_start || matches!(keys, "g" | "gq" | "gU" | "gu" | "c" | "cg" | "d")
+}
+
+// The word motion of an operator and motion typed in Normal mode, like the `w` of `dw`.
+fn operator_motion(keys: &str) -> Option<Motion> {
+    let mut chars = keys.chars();
+    match (chars.next(), chars.next()) {
+        (Some('d' | 'c'), Some(key)) => Motion::from_key(key),
+        _ => None,
+    }
+}
+
+// A closed fold's line: how many rows it holds and the text of its first, dotted out to the width.
+fn fold_line(row: &Row, lines: usize, width: usize) -> String {
+    let text = format!(
+        "+--{:>4} lines: {} ",
+        lines,
+        row.string.trim().replace('\t', " ")
+    );
+    let fill = width.saturating_sub(text.graphemes(true).count());
+    format!(
+        "{}{}{}{}",
+        ansi::DIM,
+        text,
+        "·".repeat(fill),
+        ansi::DIM_RESET
+    )
+}
+
+// Compares a screen line against what was drawn there last frame.
+// Returns true (and moves the cursor to the start of the cleared line) if it needs redrawing.
+fn begin_line(
+    terminal: &mut dyn TerminalBackend,
+    last_frame: &mut Vec<Option<String>>,
+    terminal_row: usize,
+    content: &str,
+) -> bool {
+    if last_frame.len() <= terminal_row {
+        last_frame.resize(terminal_row.saturating_add(1), None);
+    }
+
+    if last_frame[terminal_row].as_deref() == Some(content) {
+        return false;
+    }
+
+    last_frame[terminal_row] = Some(content.to_string());
+    terminal.cursor_position(&Position {
+        x: 0,
+        y: terminal_row,
+    });
+    terminal.clear_current_line();
+    true
+}
+
+// Returns the longest prefix of the text that fits within the given amount of characters.
+fn truncate(text: &str, width: usize) -> &str {
+    match text.char_indices().nth(width) {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
+
+// Renames a file. Renames can't cross file systems, so there it's copied and the original removed.
+async fn move_file(from: &str, to: &str) -> io::Result<()> {
+    match tokio::fs::rename(from, to).await {
+        Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {
+            tokio::fs::copy(from, to).await?;
+            tokio::fs::remove_file(from).await
+        }
+        result => result,
+    }
+}
+
+// File names out of completed paths, packed into as few lines of the width as they fit in.
+// Directories keep their trailing slash.
+fn list_file_names(paths: &[String], width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for path in paths {
+        let start = path
+            .trim_end_matches('/')
+            .rfind('/')
+            .map_or(0, |index| index.saturating_add(1));
+        let name = &path[start..];
+        match lines.last_mut() {
+            Some(line)
+                if line
+                    .chars()
+                    .count()
+                    .saturating_add(name.chars().count())
+                    .saturating_add(2)
+                    <= width =>
+            {
+                line.push_str("  ");
+                line.push_str(name);
+            }
+            _ => lines.push(name.to_string()),
+        }
+    }
+    lines
+}
+
+// Like truncate, for rendered text: escape sequences are kept and don't count, and wide
+// characters count as the two columns they take up.
+fn truncate_rendered(text: &str, width: usize) -> &str {
+    let mut columns: usize = 0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        if rest.starts_with('\x1b') {
+            let len = rest
+                .char_indices()
+                .skip(2)
+                .find(|&(_, c)| ('@'..='~').contains(&c))
+                .map_or(rest.len(), |(end, c)| end.saturating_add(c.len_utf8()));
+            rest = &rest[len..];
+            continue;
+        }
+        let text_len = rest.find('\x1b').unwrap_or(rest.len());
+        for (index, grapheme) in rest[..text_len].grapheme_indices(true) {
+            // A wide character that would only half fit is left out.
+            let next = columns.saturating_add(grapheme.width());
+            if next > width {
+                let end = text.len().saturating_sub(rest.len()).saturating_add(index);
+                return &text[..end];
+            }
+            columns = next;
+        }
+        rest = &rest[text_len..];
+    }
+    text
+}
+
+impl ChangeList {
+    // Adds where an edit was made. An edit within two lines of the last one moves that entry
+    // instead, so typing a paragraph leaves a single entry. Jumps start from the newest again.
+    fn push(&mut self, position: Position) {
+        match self.positions.back_mut() {
+            Some(last) if last.y.abs_diff(position.y) <= 2 => *last = position,
+            _ => {
+                if self.positions.len() == CHANGE_LIST_LEN {
+                    self.positions.pop_front();
+                }
+                self.positions.push_back(position);
+            }
+        }
+        self.index = self.positions.len();
+    }
+
+    fn older(&mut self) -> Option<Position> {
+        self.index = self.index.checked_sub(1)?;
+        self.positions.get(self.index).cloned()
+    }
+
+    fn newer(&mut self) -> Option<Position> {
+        let index = self.index.saturating_add(1);
+        let position = self.positions.get(index).cloned()?;
+        self.index = index;
+        Some(position)
     }
 }
 
-fn die(e: std::io::Error) {
-    print!("{}", termion::clear::All);
-    panic!("{e:?}");
+impl Overlay {
+    // Top line that puts the last line at the bottom of the text area.
+    fn last_top(&self, height: usize) -> usize {
+        self.lines.len().saturating_sub(height)
+    }
 }
 
-impl StatusMessage {
-    fn from(message: String) -> Self {
+impl StatusBar {
+    // Builds the status bar text for the given document state, after the editor's mode.
+    // Notes follow the file's in brackets, like the state of the last `:make`.
+    fn compose(document: &Document, mode: &str, line: usize, width: usize, notes: &[&str]) -> Self {
+        let modified_indicator = if document.is_dirty() {
+            " (modified)"
+        } else if document.is_read_only() {
+            " (readonly)"
+        } else {
+            ""
+        };
+
+        let notes_text: String = notes.iter().flat_map(|note| [" [", note, "]"]).collect();
+        let line_indicator = format!("{} | {}/{}", document.syntax_name(), line, document.len());
+
+        // Long file names give way to the rest when the bar is narrow, down to ten characters.
+        let rest = format!(
+            "-- {} --  - {} lines{} ",
+            mode,
+            document.len(),
+            modified_indicator
+        );
+        let rest_len =
+            rest.chars().count() + notes_text.chars().count() + line_indicator.chars().count();
+        let name_width = width.saturating_sub(rest_len).clamp(10, 20);
+        let file_name = document
+            .file_name
+            .as_deref()
+            .map_or("[No Name]", |name| truncate(name, name_width));
+        let mut text = format!(
+            "-- {} -- {} - {} lines{}{}",
+            mode,
+            file_name,
+            document.len(),
+            modified_indicator,
+            notes_text
+        );
+
+        let len = text.len() + line_indicator.len();
+        text.push_str(&" ".repeat(width.saturating_sub(len)));
+        text.push_str(&line_indicator);
+        let text = truncate(&text, width).to_string();
+
         Self {
-            time: Instant::now(),
-            text: message,
+            text,
+            notes: notes.iter().map(ToString::to_string).collect(),
+            mode: mode.to_string(),
+            file_name: document.file_name.clone(),
+            syntax_name: document.syntax_name().to_string(),
+            lines: document.len(),
+            line,
+            dirty: document.is_dirty(),
+            read_only: document.is_read_only(),
+            width,
         }
     }
+
+    // Checks whether the cached text still reflects the given document state.
+    fn is_current(
+        &self,
+        document: &Document,
+        mode: &str,
+        line: usize,
+        width: usize,
+        notes: &[&str],
+    ) -> bool {
+        self.width == width
+            && self.mode == mode
+            && self.notes == notes
+            && self.line == line
+            && self.lines == document.len()
+            && self.dirty == document.is_dirty()
+            && self.read_only == document.is_read_only()
+            && self.syntax_name == document.syntax_name()
+            && self.file_name.as_deref() == document.file_name.as_deref()
+    }
 }
@@ -0,0 +1,103 @@
+use encoding_rs::{mem, EncoderResult, WINDOWS_1252};
+
+/// Encodings a document is read and written in. Files that aren't UTF-8 are read in one of
+/// the others, and `:w ++enc={name}` writes in another. Rows are always UTF-8 in memory;
+/// they are only converted when read and written.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+
+    // ISO-8859-1, the first 256 code points as single bytes.
+    Latin1,
+
+    // Windows-1252, Latin-1 with printable characters instead of the C1 controls in 0x80-0x9F.
+    Windows1252,
+}
+
+// Written in place of characters the encoding has no byte for.
+const REPLACEMENT: u8 = b'?';
+
+impl Encoding {
+    // Looks an encoding up by one of its usual names, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(Self::Utf8),
+            "latin1" | "latin-1" | "iso-8859-1" => Some(Self::Latin1),
+            "cp1252" | "windows-1252" => Some(Self::Windows1252),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf-8",
+            Self::Latin1 => "latin1",
+            Self::Windows1252 => "cp1252",
+        }
+    }
+
+    // Reads text that isn't UTF-8 as Windows-1252, which has a character for every byte,
+    // so files in it or in Latin-1 are saved back unchanged.
+    // Returns the text and the encoding it was read in.
+    pub fn decode_single_byte(bytes: &[u8]) -> (String, Self) {
+        let (text, _) = WINDOWS_1252.decode_without_bom_handling(bytes);
+        (text.into_owned(), Self::Windows1252)
+    }
+
+    // Converts bytes in this encoding to text, or None if they aren't valid UTF-8.
+    // Every byte is a character in the single-byte encodings.
+    pub fn decode(self, bytes: &[u8]) -> Option<String> {
+        match self {
+            Self::Utf8 => String::from_utf8(bytes.to_vec()).ok(),
+            Self::Latin1 => Some(mem::decode_latin1(bytes).into_owned()),
+            Self::Windows1252 => Some(
+                WINDOWS_1252
+                    .decode_without_bom_handling(bytes)
+                    .0
+                    .into_owned(),
+            ),
+        }
+    }
+
+    // Converts the text to bytes in this encoding.
+    // Returns the bytes and how many characters had no byte and were written as '?'.
+    pub fn encode(self, text: &str) -> (Vec<u8>, usize) {
+        match self {
+            Self::Utf8 => (text.as_bytes().to_vec(), 0),
+            Self::Latin1 => {
+                let mut unencodable = 0;
+                let bytes = text
+                    .chars()
+                    .map(|c| {
+                        u8::try_from(c).unwrap_or_else(|_| {
+                            unencodable += 1;
+                            REPLACEMENT
+                        })
+                    })
+                    .collect();
+                (bytes, unencodable)
+            }
+            Self::Windows1252 => {
+                let mut encoder = WINDOWS_1252.new_encoder();
+                let mut bytes = Vec::with_capacity(text.len());
+                let mut unencodable = 0;
+                let mut rest = text;
+                loop {
+                    let (result, read) =
+                        encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut bytes, true);
+                    rest = &rest[read..];
+                    match result {
+                        EncoderResult::InputEmpty => break,
+                        EncoderResult::OutputFull => bytes.reserve(rest.len()),
+                        EncoderResult::Unmappable(_) => {
+                            bytes.push(REPLACEMENT);
+                            unencodable += 1;
+                        }
+                    }
+                }
+                (bytes, unencodable)
+            }
+        }
+    }
+}
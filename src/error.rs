@@ -0,0 +1,53 @@
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Everything that can go wrong in the editor.
+/// Most errors are shown in the message bar; only terminal errors end the session.
+#[derive(Debug, Error)]
+pub enum ZenError {
+    // Reading or writing a file failed.
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    // The terminal couldn't be set up, read from or written to.
+    #[error("terminal error: {0}")]
+    Terminal(#[source] io::Error),
+
+    // A command couldn't be parsed or run.
+    #[error("{0}")]
+    CommandDispatch(String),
+
+    // The config file exists but isn't valid.
+    #[error("invalid config {}: {source}", path.display())]
+    ConfigParse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+
+    // The syntax or theme needed for highlighting couldn't be used.
+    #[error("highlighting failed: {0}")]
+    SyntaxLoad(String),
+}
+
+impl ZenError {
+    pub fn io<P>(path: P, source: io::Error) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    // Whether the editor can't keep running after this error.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, Self::Terminal(_))
+    }
+}
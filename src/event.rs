@@ -0,0 +1,324 @@
+use crate::commands::Command;
+use crate::error::ZenError;
+use crate::terminal::{KeyEvent, KeySource};
+
+use std::collections::VecDeque;
+use std::future::{self, Future};
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{ready, Context, Poll};
+#[cfg(unix)]
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, Notify};
+use tokio::task::{self, JoinHandle};
+use tokio_stream::{Stream, StreamExt};
+
+// How many queued commands can wait before senders have to.
+pub const COMMAND_QUEUE_CAPACITY: usize = 100;
+
+type KeyRead = (Box<dyn KeySource>, Result<KeyEvent, ZenError>);
+
+/// The terminal's keys as an async stream. Each key is read on a blocking task, so the editor's
+/// task stays free while waiting. A read that was waited on but not finished is picked up again
+/// by the next poll, which makes `next` safe to use in `select!`: no key gets lost.
+/// The stream ends once the blocking task reading a key fails.
+pub struct KeyStream {
+    source: Option<Box<dyn KeySource>>,
+    pending: Option<JoinHandle<KeyRead>>,
+}
+
+impl KeyStream {
+    pub fn new(source: Box<dyn KeySource>) -> Self {
+        Self {
+            source: Some(source),
+            pending: None,
+        }
+    }
+}
+
+impl Stream for KeyStream {
+    type Item = Result<KeyEvent, ZenError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let pending = match &mut this.pending {
+            Some(pending) => pending,
+            None => {
+                let Some(mut source) = this.source.take() else {
+                    return Poll::Ready(None);
+                };
+                this.pending.insert(task::spawn_blocking(move || {
+                    let key = source.read_key();
+                    (source, key)
+                }))
+            }
+        };
+
+        // Kept in the stream while waiting, so a `next` dropped before the read finishes loses no key.
+        let read = ready!(Pin::new(pending).poll(cx));
+        this.pending = None;
+        Poll::Ready(Some(match read {
+            Ok((source, key)) => {
+                this.source = Some(source);
+                key
+            }
+            Err(error) => Err(ZenError::Terminal(io::Error::other(error))),
+        }))
+    }
+}
+
+/// Where the editor reads keys from: the terminal's key stream, behind keys queued to be read
+/// as if typed, like the keys a mapping expands to. Those come first, so a prompt a mapping
+/// opens reads the rest of the mapping. Safe to use in `select!`, like the stream.
+pub struct KeyInput {
+    stream: KeyStream,
+    queued: VecDeque<KeyEvent>,
+}
+
+impl KeyInput {
+    pub fn new(stream: KeyStream) -> Self {
+        Self {
+            stream,
+            queued: VecDeque::new(),
+        }
+    }
+
+    // Queues the keys ahead of any queued before, to be read in order.
+    pub fn push_front(&mut self, keys: &[KeyEvent]) {
+        for key in keys.iter().rev() {
+            self.queued.push_front(*key);
+        }
+    }
+
+    // The next queued key, without waiting on the terminal.
+    pub fn next_queued(&mut self) -> Option<KeyEvent> {
+        self.queued.pop_front()
+    }
+
+    pub fn clear_queued(&mut self) {
+        self.queued.clear();
+    }
+
+    pub async fn next(&mut self) -> Result<KeyEvent, ZenError> {
+        if let Some(key) = self.queued.pop_front() {
+            return Ok(key);
+        }
+        self.stream
+            .next()
+            .await
+            .unwrap_or_else(|| Err(ZenError::Terminal(io::Error::other("key reader is gone"))))
+    }
+}
+
+/// Signals the editor handles itself, rather than being stopped or killed with the terminal
+/// left as it was.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProcessSignal {
+    // SIGTSTP, e.g. from `kill -TSTP`: the editor hands the terminal back and stops.
+    Stop,
+
+    // SIGCONT: the editor was stopped by something else and is running again.
+    Continue,
+
+    // SIGINT from elsewhere, since raw mode reads Ctrl-C as a key. It's taken as that key.
+    Interrupt,
+
+    // SIGTERM or SIGHUP: the editor is being shut down, or its terminal went away.
+    Terminate,
+}
+
+/// Listens for signals. Needs a runtime with IO enabled.
+/// Only Unix has them.
+pub struct Signals {
+    #[cfg(unix)]
+    stop: Signal,
+    #[cfg(unix)]
+    resume: Signal,
+    #[cfg(unix)]
+    interrupt: Signal,
+    #[cfg(unix)]
+    terminate: Signal,
+    #[cfg(unix)]
+    hangup: Signal,
+}
+
+impl Signals {
+    #[cfg(unix)]
+    pub fn new() -> io::Result<Self> {
+        // tokio has no names for SIGTSTP and SIGCONT, and their numbers differ between platforms.
+        Ok(Self {
+            stop: signal(SignalKind::from_raw(libc::SIGTSTP))?,
+            resume: signal(SignalKind::from_raw(libc::SIGCONT))?,
+            interrupt: signal(SignalKind::interrupt())?,
+            terminate: signal(SignalKind::terminate())?,
+            hangup: signal(SignalKind::hangup())?,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn new() -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "signals aren't supported here",
+        ))
+    }
+
+    #[cfg(unix)]
+    pub async fn recv(&mut self) -> ProcessSignal {
+        tokio::select! {
+            Some(()) = self.stop.recv() => ProcessSignal::Stop,
+            Some(()) = self.resume.recv() => ProcessSignal::Continue,
+            Some(()) = self.interrupt.recv() => ProcessSignal::Interrupt,
+            Some(()) = self.terminate.recv() => ProcessSignal::Terminate,
+            Some(()) = self.hangup.recv() => ProcessSignal::Terminate,
+            else => future::pending().await,
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub async fn recv(&mut self) -> ProcessSignal {
+        future::pending().await
+    }
+}
+
+// The next signal, never coming when there's nothing listening for them, e.g. in tests.
+pub async fn next_signal(signals: &mut Option<Signals>) -> ProcessSignal {
+    match signals {
+        Some(signals) => signals.recv().await,
+        None => future::pending().await,
+    }
+}
+
+/// Sending side of the editor's command queue.
+/// Lets other tasks run commands on the editor and shut it down.
+#[derive(Clone)]
+pub struct CommandQueue {
+    sender: mpsc::Sender<Command>,
+    overflow: Overflow,
+    shutdown: Arc<Notify>,
+}
+
+/// Receiving side of the command queue, read by the editor's event loop.
+pub struct CommandReceiver {
+    receiver: mpsc::Receiver<Command>,
+    sender: mpsc::Sender<Command>,
+    overflow: Overflow,
+}
+
+// Commands pushed while the channel was full, oldest first.
+// They move into the channel in order as the editor makes room, so nothing pushed gets lost.
+type Overflow = Arc<Mutex<VecDeque<Command>>>;
+
+impl CommandQueue {
+    // Creates the queue, along with the receiver the editor reads from.
+    pub fn new() -> (Self, CommandReceiver, Arc<Notify>) {
+        let (sender, receiver) = mpsc::channel(COMMAND_QUEUE_CAPACITY);
+        let overflow = Overflow::default();
+        let shutdown = Arc::new(Notify::new());
+        let queue = Self {
+            sender: sender.clone(),
+            overflow: Arc::clone(&overflow),
+            shutdown: Arc::clone(&shutdown),
+        };
+        let receiver = CommandReceiver {
+            receiver,
+            sender,
+            overflow,
+        };
+        (queue, receiver, shutdown)
+    }
+
+    // Queues a command, waiting for room while the queue is full.
+    // While pushed commands are still waiting for room, this one lines up behind them.
+    pub async fn send(&self, command: Command) -> Result<(), ZenError> {
+        let command = {
+            let mut overflow = lock(&self.overflow);
+            if !overflow.is_empty() {
+                overflow.push_back(command);
+                return Ok(());
+            }
+            command
+        };
+
+        self.sender.send(command).await.map_err(|_| stopped())
+    }
+
+    // Like send, for blocking tasks: the thread waits for room in the queue.
+    // Must not be called from async code, which would block the runtime.
+    pub fn blocking_send(&self, command: Command) -> Result<(), ZenError> {
+        let command = {
+            let mut overflow = lock(&self.overflow);
+            if !overflow.is_empty() {
+                overflow.push_back(command);
+                return Ok(());
+            }
+            command
+        };
+
+        self.sender.blocking_send(command).map_err(|_| stopped())
+    }
+
+    // Queues a command without waiting, for callers that can't.
+    // A full queue keeps the command aside until the editor catches up instead of dropping it.
+    pub fn push(&self, command: Command) -> Result<(), ZenError> {
+        let mut overflow = lock(&self.overflow);
+        if !overflow.is_empty() {
+            overflow.push_back(command);
+            return Ok(());
+        }
+
+        match self.sender.try_send(command) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(command)) => {
+                overflow.push_back(command);
+                Ok(())
+            }
+            Err(TrySendError::Closed(_)) => Err(stopped()),
+        }
+    }
+
+    // Stops the editor's event loop, unsaved changes or not.
+    pub fn shutdown(&self) {
+        self.shutdown.notify_one();
+    }
+}
+
+impl CommandReceiver {
+    // Waits for the next command, then lets pushed commands that are waiting take the freed up room.
+    pub async fn recv(&mut self) -> Option<Command> {
+        let command = self.receiver.recv().await?;
+
+        let mut overflow = lock(&self.overflow);
+        while let Some(waiting) = overflow.pop_front() {
+            if let Err(TrySendError::Full(waiting) | TrySendError::Closed(waiting)) =
+                self.sender.try_send(waiting)
+            {
+                overflow.push_front(waiting);
+                break;
+            }
+        }
+        Some(command)
+    }
+
+    // Stops taking commands, on quitting. Those already queued can still be received, but
+    // sending fails from now on, including for senders waiting for room.
+    pub fn close(&mut self) {
+        self.receiver.close();
+        lock(&self.overflow).clear();
+    }
+
+    // How many pushed commands are waiting for room in the queue.
+    pub fn backlog(&self) -> usize {
+        lock(&self.overflow).len()
+    }
+}
+
+fn lock(overflow: &Overflow) -> MutexGuard<'_, VecDeque<Command>> {
+    overflow.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn stopped() -> ZenError {
+    ZenError::CommandDispatch("the editor has stopped".to_string())
+}
@@ -0,0 +1,155 @@
+use crate::document::{Document, RowSplice};
+
+use std::cmp;
+use std::ops::Range;
+
+/// Closed folds: runs of document rows drawn as a single screen line.
+/// They belong to the view rather than the document, so the editor moves them along as rows
+/// are added and removed, and opens any an edit lands in.
+///
+/// Screen lines are counted here too. Each starts at a document row, the first of its fold if
+/// it's in one, which is what the drawing, scrolling and cursor code go by.
+#[derive(Default)]
+pub struct Folds {
+    // Sorted and never overlapping.
+    closed: Vec<Range<usize>>,
+}
+
+impl Folds {
+    pub fn is_empty(&self) -> bool {
+        self.closed.is_empty()
+    }
+
+    // The closed fold the row is in, if any.
+    pub fn at(&self, row: usize) -> Option<Range<usize>> {
+        let index = self.closed.partition_point(|fold| fold.end <= row);
+        self.closed
+            .get(index)
+            .filter(|fold| fold.contains(&row))
+            .cloned()
+    }
+
+    // Closes the rows into a fold, taking in any closed fold within them.
+    pub fn close(&mut self, fold: Range<usize>) {
+        if fold.is_empty() {
+            return;
+        }
+        self.closed
+            .retain(|closed| closed.end <= fold.start || closed.start >= fold.end);
+        let index = self
+            .closed
+            .partition_point(|closed| closed.end <= fold.start);
+        self.closed.insert(index, fold);
+    }
+
+    // Opens the fold the row is in. Returns whether there was one.
+    pub fn open(&mut self, row: usize) -> bool {
+        let len = self.closed.len();
+        self.closed.retain(|fold| !fold.contains(&row));
+        self.closed.len() != len
+    }
+
+    // First row of the screen line the row is drawn on.
+    pub fn line_start(&self, row: usize) -> usize {
+        self.at(row).map_or(row, |fold| fold.start)
+    }
+
+    // First row of the screen line after the one the row is drawn on.
+    pub fn next_line(&self, row: usize) -> usize {
+        self.at(row).map_or(row.saturating_add(1), |fold| fold.end)
+    }
+
+    // First row of the screen line before the one the row is drawn on, if it isn't the first.
+    pub fn prev_line(&self, row: usize) -> Option<usize> {
+        let start = self.line_start(row);
+        start.checked_sub(1).map(|row| self.line_start(row))
+    }
+
+    // First rows of the screen lines from `top` on, up to the end of a document of `len` rows.
+    pub fn lines(&self, top: usize, len: usize) -> impl Iterator<Item = usize> + '_ {
+        let top = self.line_start(top);
+        std::iter::successors(Some(top), move |row| Some(self.next_line(*row)))
+            .take_while(move |row| *row < len)
+    }
+
+    // Screen lines between two rows, counting the one `from` is on but not the one `to` is on.
+    pub fn lines_between(&self, from: usize, to: usize) -> usize {
+        if self.closed.is_empty() {
+            return to.saturating_sub(from);
+        }
+        let to = self.line_start(to);
+        self.lines(from, to).count()
+    }
+
+    // First row of the screen line `count` lines above the one the row is drawn on,
+    // or of the first line if there aren't that many.
+    pub fn line_above(&self, row: usize, count: usize) -> usize {
+        if self.closed.is_empty() {
+            return row.saturating_sub(count);
+        }
+        let mut row = self.line_start(row);
+        for _ in 0..count {
+            match self.prev_line(row) {
+                Some(prev) => row = prev,
+                None => break,
+            }
+        }
+        row
+    }
+
+    // Moves the folds along after an edit replaced rows. Folds the edit touched are opened,
+    // since their rows may not belong together anymore.
+    pub fn splice(&mut self, splice: RowSplice) {
+        let RowSplice {
+            at,
+            removed,
+            inserted,
+        } = splice;
+        let edited_end = at.saturating_add(removed);
+        self.closed.retain_mut(|fold| {
+            if fold.end <= at {
+                return true;
+            }
+            if fold.start < edited_end || (removed == 0 && fold.start <= at) {
+                return false;
+            }
+            fold.start = fold.start.saturating_sub(removed).saturating_add(inserted);
+            fold.end = fold.end.saturating_sub(removed).saturating_add(inserted);
+            true
+        });
+    }
+}
+
+// The fold `za` closes on a row: the row itself and the rows after it indented further,
+// along with blank lines between them. None if the next row isn't indented further.
+pub fn indent_fold(document: &Document, row: usize, tab_width: usize) -> Option<Range<usize>> {
+    let indent_of = |y: usize| {
+        document.row(y).and_then(|row| {
+            let text = &row.string;
+            if text.trim().is_empty() {
+                return None;
+            }
+            let columns = text
+                .chars()
+                .take_while(|c| c.is_whitespace())
+                .map(|c| if c == '\t' { cmp::max(tab_width, 1) } else { 1 })
+                .sum::<usize>();
+            Some(columns)
+        })
+    };
+    let base = indent_of(row)?;
+
+    let mut end = row.saturating_add(1);
+    let mut scan = end;
+    while scan < document.len() {
+        match indent_of(scan) {
+            Some(indent) if indent > base => end = scan.saturating_add(1),
+            Some(_) => break,
+            // Blank lines only belong to the fold when more of it follows.
+            None => (),
+        }
+        scan = scan.saturating_add(1);
+    }
+
+    (end > row.saturating_add(1)).then_some(row..end)
+}
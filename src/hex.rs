@@ -0,0 +1,184 @@
+use crate::editor::{Position, SearchDirection};
+use crate::row::Row;
+
+use std::cmp;
+
+// Bytes shown on each line of the dump.
+pub const BYTES_PER_LINE: usize = 16;
+
+// Columns of the first byte's hex digits and of its character in the ASCII gutter.
+// A line reads `00000010  de ad be ef …  |....|`: the offset, a cell of two digits and
+// a space per byte, then the bytes again as characters between bars.
+const HEX_START: usize = 10;
+const ASCII_START: usize = HEX_START + BYTES_PER_LINE * 3 + 2;
+
+// How much of a file is looked at to tell whether it's binary, like Git does.
+pub const SNIFF_LEN: usize = 8000;
+
+/// A file's bytes shown as a hex dump, for binary files or with `:hex`.
+/// Each byte is a cell the cursor moves between, either among the hex digits or in the
+/// ASCII gutter, so cursor positions on the dump's rows map back to byte offsets.
+pub struct HexView {
+    bytes: Vec<u8>,
+}
+
+// Whether the bytes look like a binary file: a NUL byte early on.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(SNIFF_LEN).any(|&byte| byte == 0)
+}
+
+// Reads a search query as bytes written in hex, e.g. `de ad be ef` or `deadbeef`.
+// Anything else isn't a byte search, so None.
+pub fn parse_bytes(query: &str) -> Option<Vec<u8>> {
+    let digits: String = query.split_whitespace().collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(digits.get(index..index.saturating_add(2))?, 16).ok())
+        .collect()
+}
+
+impl HexView {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self { bytes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    // The dump's lines, one row for every 16 bytes.
+    pub fn rows(&self) -> Vec<Row> {
+        self.bytes
+            .chunks(BYTES_PER_LINE)
+            .enumerate()
+            .map(|(line, bytes)| {
+                let hex: Vec<String> = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+                let ascii: String = bytes
+                    .iter()
+                    .map(|&byte| {
+                        if byte.is_ascii_graphic() || byte == b' ' {
+                            char::from(byte)
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect();
+                let text = format!(
+                    "{:08x}  {:<width$}  |{}|",
+                    line.saturating_mul(BYTES_PER_LINE),
+                    hex.join(" "),
+                    ascii,
+                    width = BYTES_PER_LINE * 3 - 1
+                );
+                Row::from(text.as_str())
+            })
+            .collect()
+    }
+
+    // Offset of the byte whose cell the position is on. Columns between cells belong to the
+    // cell before them, and positions past the end to the last byte.
+    pub fn offset_at(&self, position: &Position) -> usize {
+        let cell = if position.x >= ASCII_START {
+            position.x.saturating_sub(ASCII_START)
+        } else {
+            position.x.saturating_sub(HEX_START) / 3
+        };
+        self.offset(position.y, cell)
+    }
+
+    // Offset of the first byte whose cell starts at the position or after it, which is where
+    // a search from there starts. Can be the file's length, past the last byte.
+    pub fn offset_from(&self, position: &Position) -> usize {
+        let cell = if position.x >= ASCII_START {
+            position.x.saturating_sub(ASCII_START)
+        } else {
+            position.x.saturating_sub(HEX_START).div_ceil(3)
+        };
+        let line_start = position.y.saturating_mul(BYTES_PER_LINE);
+        cmp::min(
+            line_start.saturating_add(cmp::min(cell, BYTES_PER_LINE)),
+            self.bytes.len(),
+        )
+    }
+
+    // Where the cursor goes for the byte at the offset: its hex digits.
+    pub fn position_of(&self, offset: usize) -> Position {
+        Position {
+            x: HEX_START.saturating_add((offset % BYTES_PER_LINE).saturating_mul(3)),
+            y: offset / BYTES_PER_LINE,
+        }
+    }
+
+    // Moves the position onto the start of the cell it's on, in the hex digits or the gutter.
+    pub fn snap(&self, position: &Position) -> Position {
+        let offset = self.offset_at(position);
+        let cell = offset % BYTES_PER_LINE;
+        let y = offset / BYTES_PER_LINE;
+        let x = if position.x >= ASCII_START {
+            ASCII_START.saturating_add(cell)
+        } else {
+            HEX_START.saturating_add(cell.saturating_mul(3))
+        };
+        Position { x, y }
+    }
+
+    // The cell before or after the position's on the same line, staying in the hex digits or
+    // the gutter. At either end of a line the position stays put, like `h` and `l` on a row.
+    pub fn step(&self, position: &Position, direction: SearchDirection) -> Position {
+        let snapped = self.snap(position);
+        let offset = self.offset_at(&snapped);
+        let cell = offset % BYTES_PER_LINE;
+        let next = match direction {
+            SearchDirection::Forward
+                if cell.saturating_add(1) < BYTES_PER_LINE
+                    && offset.saturating_add(1) < self.bytes.len() =>
+            {
+                offset.saturating_add(1)
+            }
+            SearchDirection::Backward if cell > 0 => offset.saturating_sub(1),
+            _ => return snapped,
+        };
+        let width = if snapped.x >= ASCII_START { 1 } else { 3 };
+        let x = match direction {
+            SearchDirection::Forward => snapped.x.saturating_add(width),
+            SearchDirection::Backward => snapped.x.saturating_sub(width),
+        };
+        Position {
+            x,
+            y: next / BYTES_PER_LINE,
+        }
+    }
+
+    // Finds the bytes from `from` on, or before it when searching backward.
+    pub fn find(&self, needle: &[u8], from: usize, direction: SearchDirection) -> Option<usize> {
+        if needle.is_empty() || needle.len() > self.bytes.len() {
+            return None;
+        }
+        let starts = self.bytes.windows(needle.len()).enumerate();
+        match direction {
+            SearchDirection::Forward => starts
+                .skip(from)
+                .find(|(_, window)| *window == needle)
+                .map(|(offset, _)| offset),
+            SearchDirection::Backward => starts
+                .take(from)
+                .rev()
+                .find(|(_, window)| *window == needle)
+                .map(|(offset, _)| offset),
+        }
+    }
+
+    fn offset(&self, line: usize, cell: usize) -> usize {
+        let offset = line
+            .saturating_mul(BYTES_PER_LINE)
+            .saturating_add(cmp::min(cell, BYTES_PER_LINE.saturating_sub(1)));
+        cmp::min(offset, self.bytes.len().saturating_sub(1))
+    }
+}
@@ -0,0 +1,98 @@
+/// A run of rows that got replaced, starting at `row`.
+/// Holding the text from before and after makes it both undoable and redoable.
+pub struct Change {
+    pub row: usize,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// Undo and redo stacks of a document.
+/// Each entry is a batch of changes that are undone and redone together.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Vec<Change>>,
+    redo: Vec<Vec<Change>>,
+
+    // Changes collected while a batch is open, and how many times it was opened.
+    batch: Vec<Change>,
+    depth: usize,
+
+    // The row and column where a typed character would join the last undo step, while
+    // characters are being typed one after another.
+    typing: Option<(usize, usize)>,
+}
+
+impl History {
+    // Records a change made to the document, clearing whatever could be redone.
+    pub fn record(&mut self, change: Change) {
+        self.redo.clear();
+        self.typing = None;
+        if self.depth > 0 {
+            self.batch.push(change);
+        } else {
+            self.undo.push(vec![change]);
+        }
+    }
+
+    // Records text typed from the row and column `from`, ending at `to`. Text typed one piece
+    // after another is undone together, until break_typing or any other change.
+    pub fn record_typed(&mut self, change: Change, from: (usize, usize), to: (usize, usize)) {
+        if self.depth == 0 && self.typing == Some(from) {
+            if let Some([last]) = self.undo.last_mut().map(Vec::as_mut_slice) {
+                // Where the change's rows are among the ones the last step left.
+                let offset = change.row.checked_sub(last.row).filter(|offset| {
+                    offset.saturating_add(change.before.len()) <= last.after.len()
+                });
+                if let Some(offset) = offset {
+                    let end = offset.saturating_add(change.before.len());
+                    last.after.splice(offset..end, change.after);
+                    self.redo.clear();
+                    self.typing = Some(to);
+                    return;
+                }
+            }
+        }
+        let grouping = self.depth == 0;
+        self.record(change);
+        if grouping {
+            self.typing = Some(to);
+        }
+    }
+
+    // Makes the next typed character start an undo step of its own.
+    pub fn break_typing(&mut self) {
+        self.typing = None;
+    }
+
+    // Groups every change recorded until the matching end_batch into one undo step.
+    // Batches can be nested; only the outermost one ends up on the stack.
+    pub fn begin_batch(&mut self) {
+        self.depth = self.depth.saturating_add(1);
+    }
+
+    pub fn end_batch(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 && !self.batch.is_empty() {
+            let batch = std::mem::take(&mut self.batch);
+            self.undo.push(batch);
+        }
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Vec<Change>> {
+        self.typing = None;
+        self.undo.pop()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Vec<Change>> {
+        self.typing = None;
+        self.redo.pop()
+    }
+
+    pub fn push_undo(&mut self, batch: Vec<Change>) {
+        self.undo.push(batch);
+    }
+
+    pub fn push_redo(&mut self, batch: Vec<Change>) {
+        self.redo.push(batch);
+    }
+}
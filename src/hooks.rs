@@ -0,0 +1,108 @@
+use crate::commands::ex;
+
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Things happening in the editor that hooks from the config can run on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum Event {
+    // A file was opened, at startup or to go to an error `:make` found in it.
+    BufOpen,
+
+    // The document is about to be written. A required hook that fails cancels the write.
+    BufWritePre,
+
+    // The document was written.
+    BufWritePost,
+
+    // The editor switched modes, e.g. from Normal to Insert.
+    ModeChanged,
+
+    // The cursor moved, and then stayed put for a moment.
+    CursorMoved,
+}
+
+impl Event {
+    // Whether the event comes before something a hook can still cancel.
+    pub fn is_pre(self) -> bool {
+        self == Self::BufWritePre
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// A hook from the config file, a `[[hooks]]` table like
+/// `event = "BufWritePre"` and `run = "trim_trailing_whitespace"`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Hook {
+    pub event: Event,
+    pub run: HookAction,
+
+    // On a `Pre` event, the hook failing cancels what was about to happen.
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// What a hook runs: one of the built-in actions, or a command typed after `:`.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub enum HookAction {
+    TrimTrailingWhitespace,
+    Command(String),
+}
+
+impl TryFrom<String> for HookAction {
+    type Error = String;
+
+    // Commands are checked when the config is loaded, so a typo shows up at startup.
+    fn try_from(run: String) -> Result<Self, Self::Error> {
+        if run == "trim_trailing_whitespace" {
+            return Ok(Self::TrimTrailingWhitespace);
+        }
+        match run.strip_prefix(':') {
+            Some(command) => ex::parse(command)
+                .map(|_| Self::Command(command.to_string()))
+                .map_err(|error| error.to_string()),
+            None => Err(format!("unknown hook action: {}", run)),
+        }
+    }
+}
+
+/// Hooks by event, in the order the config lists them, along with events waiting to run theirs.
+/// Code that can't run hooks right away emits events, and the editor fires them after the key.
+#[derive(Default)]
+pub struct EventBus {
+    hooks: Vec<Hook>,
+    queued: VecDeque<Event>,
+}
+
+impl EventBus {
+    pub fn set_hooks(&mut self, hooks: Vec<Hook>) {
+        self.hooks = hooks;
+    }
+
+    // The event's hooks, in order.
+    pub fn hooks_for(&self, event: Event) -> Vec<Hook> {
+        self.hooks
+            .iter()
+            .filter(|hook| hook.event == event)
+            .cloned()
+            .collect()
+    }
+
+    // Queues the event, unless no hook runs on it.
+    pub fn emit(&mut self, event: Event) {
+        if self.hooks.iter().any(|hook| hook.event == event) {
+            self.queued.push_back(event);
+        }
+    }
+
+    pub fn next_queued(&mut self) -> Option<Event> {
+        self.queued.pop_front()
+    }
+}
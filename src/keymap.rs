@@ -0,0 +1,277 @@
+use crate::commands::registry::{self, CommandInfo};
+use crate::error::ZenError;
+use crate::terminal::KeyEvent;
+
+use std::fmt;
+
+// Built-in keys of each mode and the names of the registered commands they run.
+// Keys that take more than a command, like `.` or the starts of sequences, are handled by the editor.
+const BINDINGS: &[(MapMode, KeyEvent, &str)] = &[
+    (MapMode::Normal, KeyEvent::Char('i'), "Insert Mode"),
+    (MapMode::Normal, KeyEvent::Char('~'), "Toggle Case"),
+    (MapMode::Normal, KeyEvent::Char('h'), "Cursor Left"),
+    (MapMode::Normal, KeyEvent::Char('j'), "Cursor Up"),
+    (MapMode::Normal, KeyEvent::Char('k'), "Cursor Down"),
+    (MapMode::Normal, KeyEvent::Char('l'), "Cursor Right"),
+    (MapMode::Normal, KeyEvent::Left, "Previous Word"),
+    (MapMode::Normal, KeyEvent::Right, "Next Word"),
+    (MapMode::Normal, KeyEvent::Char('{'), "Previous Paragraph"),
+    (MapMode::Normal, KeyEvent::Char('}'), "Next Paragraph"),
+    (MapMode::Normal, KeyEvent::Ctrl('J'), "Start of File"),
+    (MapMode::Normal, KeyEvent::Ctrl('K'), "End of File"),
+    (MapMode::Normal, KeyEvent::Char('J'), "Page Up"),
+    (MapMode::Normal, KeyEvent::Char('K'), "Page Down"),
+    (MapMode::Normal, KeyEvent::Char('H'), "Start of Line"),
+    (MapMode::Normal, KeyEvent::Char('L'), "End of Line"),
+    (MapMode::Normal, KeyEvent::Ctrl('e'), "Scroll Down"),
+    (MapMode::Normal, KeyEvent::Ctrl('y'), "Scroll Up"),
+    (
+        MapMode::Normal,
+        KeyEvent::Ctrl('d'),
+        "Scroll Half Page Down",
+    ),
+    (MapMode::Normal, KeyEvent::Ctrl('u'), "Scroll Half Page Up"),
+    (MapMode::Normal, KeyEvent::Ctrl('g'), "File Info"),
+    (MapMode::Normal, KeyEvent::Char(':'), "Command Line"),
+    (MapMode::Normal, KeyEvent::Char('u'), "Undo"),
+    (MapMode::Normal, KeyEvent::Ctrl('r'), "Redo"),
+    (MapMode::Normal, KeyEvent::Char('*'), "Search Word Forward"),
+    (MapMode::Normal, KeyEvent::Char('#'), "Search Word Backward"),
+    (MapMode::Normal, KeyEvent::Char('n'), "Next Match"),
+    (MapMode::Normal, KeyEvent::Char('N'), "Previous Match"),
+    (MapMode::Insert, KeyEvent::Esc, "Normal Mode"),
+    (MapMode::Insert, KeyEvent::Ctrl('c'), "Normal Mode"),
+    (MapMode::Insert, KeyEvent::Ctrl('s'), "Save"),
+    (MapMode::Insert, KeyEvent::Ctrl('f'), "Search"),
+];
+
+/// Modes mappings apply in. `:map` and `:nmap` map in Normal mode, `:imap` in Insert mode.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MapMode {
+    Normal,
+    Insert,
+}
+
+/// Keys that stand for other keys, e.g. `<leader>w` for `:w<CR>`.
+/// The keys a mapping stands for are fed back as if typed, so they may be mapped too.
+#[derive(Clone, Debug)]
+pub struct Mapping {
+    pub mode: MapMode,
+    pub keys: Vec<KeyEvent>,
+    pub expansion: Vec<KeyEvent>,
+
+    // As they were written, for listing.
+    lhs: String,
+    rhs: String,
+}
+
+/// What the keys typed so far are to the mappings of a mode.
+pub enum Lookup<'a> {
+    // Not the start of any mapping.
+    None,
+
+    // The start of a longer mapping, so more keys are needed to tell.
+    Prefix,
+
+    Found(&'a Mapping),
+}
+
+/// Every mapping, defined in the config or with `:map`.
+/// Mappings win over built-in keys: a key that starts one is held until it's clear which it is.
+#[derive(Default)]
+pub struct Keymap {
+    mappings: Vec<Mapping>,
+}
+
+impl Keymap {
+    // Maps the keys written as `lhs` to those written as `rhs`, replacing an earlier mapping
+    // of the same keys. `<leader>` stands for the leader key as it is now.
+    pub fn map(
+        &mut self,
+        mode: MapMode,
+        lhs: &str,
+        rhs: &str,
+        leader: char,
+    ) -> Result<(), ZenError> {
+        let keys = parse_keys(lhs, leader)?;
+        let expansion = parse_keys(rhs, leader)?;
+        if keys.is_empty() || expansion.is_empty() {
+            return Err(ZenError::CommandDispatch(
+                "Usage: map {lhs} {rhs}".to_string(),
+            ));
+        }
+
+        self.mappings
+            .retain(|mapping| mapping.mode != mode || mapping.keys != keys);
+        self.mappings.push(Mapping {
+            mode,
+            keys,
+            expansion,
+            lhs: lhs.to_string(),
+            rhs: rhs.to_string(),
+        });
+        Ok(())
+    }
+
+    pub fn lookup(&self, mode: MapMode, typed: &[KeyEvent]) -> Lookup<'_> {
+        let mut found = None;
+        for mapping in self.mappings.iter().filter(|mapping| mapping.mode == mode) {
+            if mapping.keys == typed {
+                found = Some(mapping);
+            } else if mapping.keys.starts_with(typed) {
+                return Lookup::Prefix;
+            }
+        }
+        found.map_or(Lookup::None, Lookup::Found)
+    }
+
+    // The mapping of exactly these keys, if there is one.
+    pub fn get(&self, mode: MapMode, keys: &[KeyEvent]) -> Option<&Mapping> {
+        self.mappings
+            .iter()
+            .find(|mapping| mapping.mode == mode && mapping.keys == keys)
+    }
+
+    // The registered command a key runs in the mode, when it isn't mapped.
+    pub fn binding(&self, mode: MapMode, key: KeyEvent) -> Option<&'static CommandInfo> {
+        BINDINGS
+            .iter()
+            .find(|(bound_mode, bound_key, _)| *bound_mode == mode && *bound_key == key)
+            .and_then(|(_, _, name)| registry::get(name))
+    }
+
+    // The keys that run the registered command in the mode, written like mappings are.
+    pub fn keys_for(&self, mode: MapMode, name: &str) -> Vec<String> {
+        BINDINGS
+            .iter()
+            .filter(|(bound_mode, _, bound_name)| *bound_mode == mode && *bound_name == name)
+            .map(|(_, key, _)| key_name(*key))
+            .collect()
+    }
+
+    // Mappings of the mode, or of every mode, in the order they were defined.
+    pub fn mappings(&self, mode: Option<MapMode>) -> impl Iterator<Item = &Mapping> {
+        self.mappings
+            .iter()
+            .filter(move |mapping| mode.is_none_or(|mode| mapping.mode == mode))
+    }
+}
+
+impl Mapping {
+    // The keys mapped, as they were written.
+    pub fn lhs(&self) -> &str {
+        &self.lhs
+    }
+
+    // The keys they stand for, as they were written.
+    pub fn rhs(&self) -> &str {
+        &self.rhs
+    }
+}
+
+impl fmt::Display for Mapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self.mode {
+            MapMode::Normal => 'n',
+            MapMode::Insert => 'i',
+        };
+        write!(f, "{}  {:<12} {}", mode, self.lhs, self.rhs)
+    }
+}
+
+// Reads keys written like Vim's mappings: characters stand for themselves, and names in angle
+// brackets for other keys, e.g. `<CR>`, `<Esc>`, `<C-s>` or `<leader>`. `<lt>` is a `<`, and a
+// `<` that doesn't start a name is taken as is.
+pub fn parse_keys(text: &str, leader: char) -> Result<Vec<KeyEvent>, ZenError> {
+    let mut keys = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let name = rest
+            .strip_prefix('<')
+            .and_then(|after| after.split_once('>'))
+            .map(|(name, _)| name)
+            .filter(|name| {
+                !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || c == '<')
+            });
+        let Some(name) = name else {
+            keys.push(KeyEvent::Char(c));
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+
+        let key = named_key(name, leader)
+            .ok_or_else(|| ZenError::CommandDispatch(format!("Unknown key: <{}>", name)))?;
+        keys.push(key);
+        rest = &rest[name.len().saturating_add(2)..];
+    }
+    Ok(keys)
+}
+
+fn named_key(name: &str, leader: char) -> Option<KeyEvent> {
+    let lower = name.to_ascii_lowercase();
+    let modified = |prefix: &str| {
+        let mut chars = lower.strip_prefix(prefix)?.chars();
+        let c = chars.next()?;
+        chars.next().is_none().then_some(c)
+    };
+    if let Some(c) = modified("c-") {
+        return Some(KeyEvent::Ctrl(c));
+    }
+    if let Some(c) = modified("a-").or_else(|| modified("m-")) {
+        return Some(KeyEvent::Alt(c));
+    }
+    if let Some(number) = lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+        return Some(KeyEvent::F(number));
+    }
+
+    Some(match lower.as_str() {
+        "leader" => KeyEvent::Char(leader),
+        "cr" | "enter" | "return" => KeyEvent::Char('\n'),
+        "tab" => KeyEvent::Char('\t'),
+        "space" => KeyEvent::Char(' '),
+        "lt" => KeyEvent::Char('<'),
+        "bar" => KeyEvent::Char('|'),
+        "esc" => KeyEvent::Esc,
+        "bs" => KeyEvent::Backspace,
+        "del" => KeyEvent::Delete,
+        "up" => KeyEvent::Up,
+        "down" => KeyEvent::Down,
+        "left" => KeyEvent::Left,
+        "right" => KeyEvent::Right,
+        "home" => KeyEvent::Home,
+        "end" => KeyEvent::End,
+        "pageup" => KeyEvent::PageUp,
+        "pagedown" => KeyEvent::PageDown,
+        _ => return None,
+    })
+}
+
+// Writes a key the way `parse_keys` reads it, e.g. `<C-s>` or `<Esc>`.
+pub fn key_name(key: KeyEvent) -> String {
+    let name = match key {
+        KeyEvent::Char('\n') => "CR",
+        KeyEvent::Char('\t') => "Tab",
+        KeyEvent::Char(' ') => "Space",
+        KeyEvent::Char('<') => "lt",
+        KeyEvent::Char('|') => "Bar",
+        KeyEvent::Char(c) => return c.to_string(),
+        KeyEvent::Ctrl(c) => return format!("<C-{}>", c),
+        KeyEvent::Alt(c) => return format!("<A-{}>", c),
+        KeyEvent::F(number) => return format!("<F{}>", number),
+        KeyEvent::Esc => "Esc",
+        KeyEvent::Backspace => "BS",
+        KeyEvent::Delete => "Del",
+        KeyEvent::Up => "Up",
+        KeyEvent::Down => "Down",
+        KeyEvent::Left => "Left",
+        KeyEvent::Right => "Right",
+        KeyEvent::Home => "Home",
+        KeyEvent::End => "End",
+        KeyEvent::PageUp => "PageUp",
+        KeyEvent::PageDown => "PageDown",
+        KeyEvent::BackTab => "S-Tab",
+        KeyEvent::Insert => "Insert",
+        KeyEvent::Mouse(_) | KeyEvent::Null => "Nop",
+    };
+    format!("<{}>", name)
+}
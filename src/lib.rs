@@ -0,0 +1,43 @@
+#![warn(clippy::all, clippy::pedantic, clippy::restriction)]
+#![allow(
+    clippy::missing_docs_in_private_items,
+    clippy::implicit_return,
+    clippy::shadow_reuse,
+    clippy::print_stdout,
+    clippy::wildcard_enum_match_arm,
+    clippy::else_if_without_else
+)]
+pub mod abbreviation;
+pub mod cli;
+pub mod clipboard;
+pub mod clock;
+pub mod commands;
+pub mod config;
+pub mod diff;
+pub mod digraph;
+pub mod document;
+pub mod editor;
+pub mod encoding;
+pub mod error;
+pub mod event;
+pub mod fold;
+pub mod hex;
+pub mod history;
+pub mod hooks;
+pub mod keymap;
+pub mod loader;
+pub mod message;
+pub mod mode;
+pub mod motion;
+pub mod picker;
+pub mod pipe;
+pub mod profile;
+pub mod prompt;
+pub mod quickfix;
+pub mod recovery;
+pub mod row;
+pub mod search;
+pub mod spell;
+pub mod terminal;
+pub mod util;
+pub mod workers;
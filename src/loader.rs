@@ -0,0 +1,105 @@
+use crate::encoding::Encoding;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::str;
+
+// Files bigger than this are opened with only their first chunk read, the rest following
+// in the background.
+pub const INCREMENTAL_LOAD_SIZE: u64 = 4 * 1024 * 1024;
+
+// Lines read at a time.
+const CHUNK_LINES: usize = 10_000;
+
+/// Reads a file a chunk of lines at a time, for files too big to wait on before showing them.
+/// The loader only reads; the rows are appended by whoever holds the document, so the file's
+/// lines reach it in order through a single owner, e.g. over the editor's command queue.
+pub struct Loader {
+    reader: BufReader<File>,
+
+    // Bytes read so far, and the file's size when it was opened.
+    read: u64,
+    size: u64,
+
+    // The encoding the file is read in, decided by its first chunk.
+    encoding: Option<Encoding>,
+}
+
+/// Lines read by a loader, without their line endings.
+pub struct LoadChunk {
+    pub lines: Vec<String>,
+
+    // How much of the file has been read, from 0 to 100.
+    pub percent: u8,
+
+    // Whether this is the file's last chunk.
+    pub done: bool,
+
+    // The encoding the lines were read in.
+    pub encoding: Encoding,
+}
+
+impl Loader {
+    pub fn new(file: File, size: u64) -> Self {
+        Self {
+            reader: BufReader::new(file),
+            read: 0,
+            size,
+            encoding: None,
+        }
+    }
+
+    // Reads the next lines of the file. Once it's all read, the chunk says it's done.
+    // A first chunk that isn't all UTF-8 makes the whole file read as Windows-1252, which
+    // has a character for every byte. A later line that isn't UTF-8 in a UTF-8 file is an error.
+    pub fn next_chunk(&mut self) -> io::Result<LoadChunk> {
+        let mut raw_lines = Vec::with_capacity(CHUNK_LINES);
+        let mut done = false;
+        while raw_lines.len() < CHUNK_LINES {
+            let mut line = Vec::new();
+            let len = self.reader.read_until(b'\n', &mut line)?;
+            if len == 0 {
+                done = true;
+                break;
+            }
+            self.read = self.read.saturating_add(len as u64);
+            if line.ends_with(b"\n") {
+                line.pop();
+                if line.ends_with(b"\r") {
+                    line.pop();
+                }
+            }
+            raw_lines.push(line);
+        }
+
+        let encoding = *self.encoding.get_or_insert_with(|| {
+            if raw_lines.iter().all(|line| str::from_utf8(line).is_ok()) {
+                Encoding::Utf8
+            } else {
+                Encoding::Windows1252
+            }
+        });
+        let lines = raw_lines
+            .iter()
+            .map(|line| {
+                encoding.decode(line).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "stream did not contain valid UTF-8",
+                    )
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let percent = if done || self.size == 0 {
+            100
+        } else {
+            (self.read.saturating_mul(100) / self.size).min(99) as u8
+        };
+        Ok(LoadChunk {
+            lines,
+            percent,
+            done,
+            encoding,
+        })
+    }
+}
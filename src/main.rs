@@ -1,24 +1,53 @@
-#![warn(clippy::all, clippy::pedantic, clippy::restriction)]
-#![allow(
-    clippy::missing_docs_in_private_items,
-    clippy::implicit_return,
-    clippy::shadow_reuse,
-    clippy::print_stdout,
-    clippy::wildcard_enum_match_arm,
-    clippy::else_if_without_else
-)]
-mod commands;
-mod document;
-mod editor;
-mod mode;
-mod row;
-mod terminal;
-mod util;
-
-use editor::Editor;
+use std::env;
+use std::process;
+use tokio::runtime;
+use zen::cli::{self, Action};
+use zen::editor::Editor;
+use zen::error::ZenError;
+use zen::pipe;
 
 fn main() {
     env_logger::init();
 
-    Editor::default().run();
+    match cli::parse(env::args().skip(1)) {
+        Ok(Action::Run(options)) => {
+            if let Err(error) = run(options) {
+                eprintln!("zen: {}", error);
+                process::exit(1);
+            }
+        }
+        Ok(Action::Pipe(commands)) => {
+            if let Err(error) = pipe::run_stdin(&commands) {
+                eprintln!("zen: {}", error);
+                process::exit(1);
+            }
+        }
+        Ok(Action::Help) => print!("{}", cli::USAGE),
+        Ok(Action::Version) => println!("zen {}", env!("CARGO_PKG_VERSION")),
+        Err(error) => {
+            eprintln!("zen: {}", error);
+            eprintln!("Try 'zen --help' for more information.");
+            process::exit(1);
+        }
+    }
+}
+
+// Runs the editor on a single threaded runtime, with IO enabled for signals.
+// The editor is dropped before returning, so the terminal has left raw mode by the time errors are printed.
+fn run(options: cli::LaunchOptions) -> Result<(), ZenError> {
+    let runtime = runtime::Builder::new_current_thread()
+        .enable_io()
+        .enable_time()
+        .build()
+        .map_err(ZenError::Terminal)?;
+
+    let result = runtime.block_on(async {
+        let mut editor = Editor::new(options)?;
+        editor.run_startup_scripts().await;
+        editor.run().await
+    });
+
+    // The key reader may still be blocked on stdin; don't wait for it.
+    runtime.shutdown_background();
+    result
 }
@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// How many messages `:messages` can look back on.
+const HISTORY_LEN: usize = 200;
+
+/// How much a message matters, which decides how long it stays and how it's drawn.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A message for the message bar.
+#[derive(Clone)]
+pub struct Message {
+    pub level: Level,
+    pub text: String,
+
+    // When it went up on the message bar, or was sent while still waiting to.
+    pub time: Instant,
+}
+
+/// Messages for the message bar, shown one at a time in the order they were sent.
+/// Info and warnings go away after the timeout, errors stay until the next keypress.
+/// Whatever is waiting behind the current message goes up as soon as it's gone,
+/// or right away on a keypress, so nothing sent in a burst gets lost.
+pub struct Messages {
+    current: Option<Message>,
+
+    // Whether the current message was up while a key was pressed.
+    // A new message takes the place of one that was seen instead of waiting behind it.
+    seen: bool,
+
+    queue: VecDeque<Message>,
+    history: VecDeque<Message>,
+    timeout: Duration,
+
+    // Errors sent so far, so a caller can tell whether something it ran reported one.
+    errors: usize,
+}
+
+impl Messages {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            current: None,
+            seen: false,
+            queue: VecDeque::new(),
+            history: VecDeque::new(),
+            timeout,
+            errors: 0,
+        }
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    // Shows the message, or lines it up behind the one showing.
+    // The same text sent again only keeps the one already there up longer.
+    pub fn push(&mut self, level: Level, text: String, now: Instant) {
+        let message = Message {
+            level,
+            text,
+            time: now,
+        };
+        if level == Level::Error {
+            self.errors = self.errors.saturating_add(1);
+        }
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(message.clone());
+
+        self.expire(now);
+        if self.seen && self.queue.is_empty() {
+            self.current = None;
+            self.seen = false;
+        }
+        let last = self.queue.back_mut().or(self.current.as_mut());
+        match last {
+            Some(last) if last.level == level && last.text == message.text => last.time = now,
+            Some(_) => self.queue.push_back(message),
+            None => self.current = Some(message),
+        }
+    }
+
+    // The message to show and how many are waiting behind it.
+    pub fn current(&mut self, now: Instant) -> Option<(&Message, usize)> {
+        self.expire(now);
+        let waiting = self.queue.len();
+        self.current.as_ref().map(|message| (message, waiting))
+    }
+
+    // A key was pressed: errors have been seen, and anything waiting can go up.
+    pub fn key_pressed(&mut self, now: Instant) {
+        self.expire(now);
+        let dismissed = self
+            .current
+            .as_ref()
+            .is_some_and(|message| message.level == Level::Error || !self.queue.is_empty());
+        if dismissed {
+            self.advance(now);
+        } else {
+            self.seen = self.current.is_some();
+        }
+    }
+
+    // Takes the current message down, e.g. once what it warned about no longer applies.
+    pub fn dismiss(&mut self, now: Instant) {
+        self.advance(now);
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.errors
+    }
+
+    // Messages sent so far, oldest first.
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &Message> {
+        self.history.iter()
+    }
+
+    // Takes down messages that were up for longer than the timeout, errors aside.
+    fn expire(&mut self, now: Instant) {
+        while let Some(message) = &self.current {
+            let expiry = message.time.checked_add(self.timeout).unwrap_or(now);
+            if message.level == Level::Error || now < expiry {
+                return;
+            }
+            // The next one went up when this one went away, not when we got to look.
+            self.advance(expiry);
+        }
+    }
+
+    // Replaces the current message with the next one waiting, which goes up at `now`.
+    fn advance(&mut self, now: Instant) {
+        self.seen = false;
+        self.current = self.queue.pop_front().map(|mut message| {
+            message.time = now;
+            message
+        });
+    }
+}
+
+impl Message {
+    // How long ago the message was sent, in its largest whole unit, e.g. "5s" or "3m".
+    pub fn age(&self, now: Instant) -> String {
+        let seconds = now.saturating_duration_since(self.time).as_secs();
+        match seconds {
+            0..=59 => format!("{}s", seconds),
+            60..=3599 => format!("{}m", seconds / 60),
+            3600..=86399 => format!("{}h", seconds / 3600),
+            _ => format!("{}d", seconds / 86400),
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    // Errors say so up front, the rest show as they are.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.level {
+            Level::Error => write!(f, "ERR: {}", self.text),
+            Level::Info | Level::Warning => write!(f, "{}", self.text),
+        }
+    }
+}
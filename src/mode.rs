@@ -1,5 +1,58 @@
+use std::fmt;
+
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
 pub enum EditorMode {
+    #[default]
     Normal,
     Insert,
     Command,
 }
+
+impl fmt::Display for EditorMode {
+    // The mode's name as the status bar shows it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Normal => "NORMAL",
+            Self::Insert => "INSERT",
+            Self::Command => "COMMAND",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Keys typed so far of a Normal mode sequence, e.g. the `]` of `]c` or the `gq` of `gqq`,
+/// or of an Insert mode one, where `Ctrl-K`, `Ctrl-R` and `Ctrl-V` are written `^K`, `^R` and `^V`.
+/// Cleared once the sequence completes, or when a key doesn't continue it, like Esc.
+/// Zen has no counts or `"a` register prefixes yet; they'd be kept here too once it does.
+#[derive(Default, PartialEq, Eq, Debug)]
+pub struct PendingInput {
+    keys: String,
+}
+
+impl PendingInput {
+    pub fn push(&mut self, c: char) {
+        self.keys.push(c);
+    }
+
+    pub fn push_str(&mut self, keys: &str) {
+        self.keys.push_str(keys);
+    }
+
+    pub fn keys(&self) -> &str {
+        &self.keys
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+}
+
+impl fmt::Display for PendingInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.keys)
+    }
+}
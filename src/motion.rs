@@ -0,0 +1,259 @@
+use crate::document::{Document, Selection};
+use crate::editor::Position;
+use crate::row::Row;
+use crate::search::is_word_char;
+
+/// Vim's word motions. A word is a run of letters, digits and underscores, or a run of other
+/// non-blank characters, while a WORD is any run of non-blank ones: `foo.bar(baz)` is six
+/// words but a single WORD. An empty row counts as a word of its own to `w` and `b`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Motion {
+    WordForward,
+    WordBackward,
+    WordEnd,
+    BigWordForward,
+    BigWordBackward,
+    BigWordEnd,
+}
+
+// Each motion's key, and whether it's inclusive: an operator takes the character an inclusive
+// motion lands on, and stops right before it for an exclusive one. `dw` leaves the start of the
+// next word and `db` the character under the cursor, but `de` deletes the last one of the word.
+const MOTIONS: [(char, Motion, bool); 6] = [
+    ('w', Motion::WordForward, false),
+    ('b', Motion::WordBackward, false),
+    ('e', Motion::WordEnd, true),
+    ('W', Motion::BigWordForward, false),
+    ('B', Motion::BigWordBackward, false),
+    ('E', Motion::BigWordEnd, true),
+];
+
+// What a motion makes of a character: words end where the class changes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Blank,
+    Word,
+    Punctuation,
+}
+
+impl Motion {
+    pub fn from_key(key: char) -> Option<Self> {
+        MOTIONS
+            .iter()
+            .find(|(motion_key, _, _)| *motion_key == key)
+            .map(|(_, motion, _)| *motion)
+    }
+
+    pub fn is_inclusive(self) -> bool {
+        MOTIONS
+            .iter()
+            .any(|(_, motion, inclusive)| *motion == self && *inclusive)
+    }
+
+    fn is_big(self) -> bool {
+        matches!(
+            self,
+            Self::BigWordForward | Self::BigWordBackward | Self::BigWordEnd
+        )
+    }
+
+    // Where the motion takes the cursor from `from`, across rows. Past the last word it stops at
+    // the end of the document, and before the first at its start.
+    pub fn target(self, document: &Document, from: &Position) -> Position {
+        let text = Text {
+            document,
+            big: self.is_big(),
+        };
+        match self {
+            Self::WordForward | Self::BigWordForward => text.next_start(from),
+            Self::WordBackward | Self::BigWordBackward => text.prev_start(from),
+            Self::WordEnd | Self::BigWordEnd => text.next_end(from),
+        }
+    }
+
+    // The motion `cw` and `cW` really make: on a word they change up to its end like `ce` and
+    // `cE`, leaving the blanks after it, as in Vim.
+    pub fn for_change(self, document: &Document, at: &Position) -> Self {
+        let text = Text {
+            document,
+            big: self.is_big(),
+        };
+        match self {
+            Self::WordForward if text.class(at) != Class::Blank => Self::WordEnd,
+            Self::BigWordForward if text.class(at) != Class::Blank => Self::BigWordEnd,
+            motion => motion,
+        }
+    }
+
+    // The text an operator like `d` covers with the motion from `from`.
+    pub fn operator_range(self, document: &Document, from: &Position) -> Selection {
+        let to = self.target(document, from);
+        if (to.y, to.x) < (from.y, from.x) {
+            return Selection {
+                start: to,
+                end: from.clone(),
+            };
+        }
+
+        let end = if self.is_inclusive() {
+            Position {
+                x: to.x.saturating_add(1),
+                y: to.y,
+            }
+        } else if to.y > from.y {
+            // When the last word moved over ends its row, the operator stops at the end of that
+            // row rather than taking the line break and the next row's indent too.
+            Position {
+                x: document.row(from.y).map_or(0, Row::len),
+                y: from.y,
+            }
+        } else {
+            to
+        };
+        Selection {
+            start: from.clone(),
+            end,
+        }
+    }
+}
+
+// The document as a motion walks it: a row's end counts as a blank between it and the next.
+struct Text<'a> {
+    document: &'a Document,
+    big: bool,
+}
+
+impl Text<'_> {
+    fn class(&self, at: &Position) -> Class {
+        let Some(grapheme) = self
+            .document
+            .row(at.y)
+            .and_then(|row| row.grapheme_at(at.x))
+        else {
+            return Class::Blank;
+        };
+        if grapheme.chars().all(char::is_whitespace) {
+            Class::Blank
+        } else if self.big || grapheme.chars().next().is_some_and(is_word_char) {
+            Class::Word
+        } else {
+            Class::Punctuation
+        }
+    }
+
+    fn row_len(&self, y: usize) -> usize {
+        self.document.row(y).map_or(0, Row::len)
+    }
+
+    fn is_empty_row(&self, y: usize) -> bool {
+        self.row_len(y) == 0
+    }
+
+    fn end(&self) -> Position {
+        let y = self.document.len().saturating_sub(1);
+        Position {
+            x: self.row_len(y),
+            y,
+        }
+    }
+
+    // The next character, or the end of the row before the next row. None at the document's end.
+    fn next(&self, at: &Position) -> Option<Position> {
+        if at.x < self.row_len(at.y) {
+            return Some(Position {
+                x: at.x.saturating_add(1),
+                y: at.y,
+            });
+        }
+        let y = at.y.saturating_add(1);
+        (y < self.document.len()).then_some(Position { x: 0, y })
+    }
+
+    // The character before, or the end of the row above. None at the document's start.
+    fn prev(&self, at: &Position) -> Option<Position> {
+        if at.x > 0 {
+            return Some(Position {
+                x: at.x.saturating_sub(1),
+                y: at.y,
+            });
+        }
+        let y = at.y.checked_sub(1)?;
+        Some(Position {
+            x: self.row_len(y),
+            y,
+        })
+    }
+
+    // `w`: past the rest of the word under the cursor and the blanks after it.
+    fn next_start(&self, from: &Position) -> Position {
+        let mut at = from.clone();
+        let class = self.class(&at);
+        while class != Class::Blank && self.class(&at) == class {
+            match self.next(&at) {
+                Some(next) => at = next,
+                None => return self.end(),
+            }
+        }
+        while self.class(&at) == Class::Blank {
+            if at.y != from.y && self.is_empty_row(at.y) {
+                break;
+            }
+            match self.next(&at) {
+                Some(next) => at = next,
+                None => return self.end(),
+            }
+        }
+        at
+    }
+
+    // `b`: back over the blanks before the cursor to the start of the word before them.
+    fn prev_start(&self, from: &Position) -> Position {
+        let Some(mut at) = self.prev(from) else {
+            return from.clone();
+        };
+        while self.class(&at) == Class::Blank {
+            if self.is_empty_row(at.y) {
+                return Position { x: 0, y: at.y };
+            }
+            match self.prev(&at) {
+                Some(prev) => at = prev,
+                None => return at,
+            }
+        }
+        let class = self.class(&at);
+        while at.x > 0
+            && self.class(&Position {
+                x: at.x.saturating_sub(1),
+                y: at.y,
+            }) == class
+        {
+            at.x = at.x.saturating_sub(1);
+        }
+        at
+    }
+
+    // `e`: to the last character of the word, or of the next one when already there.
+    // Unlike `w` and `b` it doesn't stop on empty rows.
+    fn next_end(&self, from: &Position) -> Position {
+        let Some(mut at) = self.next(from) else {
+            return self.end();
+        };
+        while self.class(&at) == Class::Blank {
+            match self.next(&at) {
+                Some(next) => at = next,
+                None => return self.end(),
+            }
+        }
+        let class = self.class(&at);
+        loop {
+            let next = Position {
+                x: at.x.saturating_add(1),
+                y: at.y,
+            };
+            if self.class(&next) != class {
+                return at;
+            }
+            at = next;
+        }
+    }
+}
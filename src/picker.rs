@@ -0,0 +1,162 @@
+use crate::terminal::KeyEvent;
+
+use std::cmp;
+
+/// An entry of a picker's list: what it's found by, and a note drawn after it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PickerItem {
+    pub label: String,
+    pub detail: String,
+}
+
+/// What the picker should do after a key was handled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PickerAction {
+    Continue,
+
+    // Enter was pressed on the item at this index of the items, if any matched.
+    Submit(Option<usize>),
+
+    Cancel,
+}
+
+/// A list drawn over the text area that narrows to the items fuzzily matching what's typed,
+/// best first, and picks one with Enter. The command palette is one.
+pub struct Picker {
+    items: Vec<PickerItem>,
+    query: String,
+
+    // Indexes of the items matching the query, best first.
+    matches: Vec<usize>,
+
+    // Index into the matches of the one Enter picks.
+    selected: usize,
+}
+
+impl Picker {
+    pub fn new(items: Vec<PickerItem>) -> Self {
+        let matches = (0..items.len()).collect();
+        Self {
+            items,
+            query: String::new(),
+            matches,
+            selected: 0,
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    // The items matching the query, best first.
+    pub fn matches(&self) -> impl Iterator<Item = &PickerItem> {
+        self.matches.iter().map(|&index| &self.items[index])
+    }
+
+    // Index into the items of the one Enter picks.
+    pub fn selected(&self) -> Option<usize> {
+        self.matches.get(self.selected).copied()
+    }
+
+    // Typing narrows the list, Backspace and Ctrl-U widen it again, and Up and Down, Ctrl-P and
+    // Ctrl-N or Tab and Shift-Tab move through it, wrapping around.
+    pub fn handle_key(&mut self, key: KeyEvent) -> PickerAction {
+        match key {
+            KeyEvent::Char('\n') => return PickerAction::Submit(self.selected()),
+            KeyEvent::Esc | KeyEvent::Ctrl('c') => return PickerAction::Cancel,
+            KeyEvent::Down | KeyEvent::Ctrl('n') | KeyEvent::Char('\t') => self.select(1),
+            KeyEvent::Up | KeyEvent::Ctrl('p') | KeyEvent::BackTab => {
+                self.select(self.matches.len().saturating_sub(1));
+            }
+            KeyEvent::Backspace => {
+                self.query.pop();
+                self.filter();
+            }
+            KeyEvent::Ctrl('u') => {
+                self.query.clear();
+                self.filter();
+            }
+            KeyEvent::Char(c) if !c.is_control() => {
+                self.query.push(c);
+                self.filter();
+            }
+            _ => (),
+        }
+        PickerAction::Continue
+    }
+
+    // The lines of the list that fit in `height` rows, each with whether it's the selected one.
+    // Labels are padded to line the details up, and the list scrolls to keep the selection in view.
+    pub fn lines(&self, height: usize) -> Vec<(String, bool)> {
+        let label_width = self
+            .matches()
+            .map(|item| item.label.chars().count())
+            .max()
+            .unwrap_or(0);
+        let first = self.selected.saturating_add(1).saturating_sub(height);
+        self.matches()
+            .enumerate()
+            .skip(first)
+            .take(height)
+            .map(|(index, item)| {
+                let marker = if index == self.selected { '>' } else { ' ' };
+                let line = format!(
+                    "{} {:<width$}  {}",
+                    marker,
+                    item.label,
+                    item.detail,
+                    width = label_width
+                );
+                (line.trim_end().to_string(), index == self.selected)
+            })
+            .collect()
+    }
+
+    // Moves the selection `by` places down the matches, wrapping around.
+    fn select(&mut self, by: usize) {
+        if !self.matches.is_empty() {
+            self.selected = self.selected.saturating_add(by) % self.matches.len();
+        }
+    }
+
+    // Matches the items against the query again, and selects the best.
+    fn filter(&mut self) {
+        let mut scored: Vec<(usize, usize)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| fuzzy_score(&self.query, &item.label).map(|s| (index, s)))
+            .collect();
+        // Stable, so equally good items keep their order.
+        scored.sort_by_key(|&(_, score)| cmp::Reverse(score));
+        self.matches = scored.into_iter().map(|(index, _)| index).collect();
+        self.selected = 0;
+    }
+}
+
+// How well `text` matches `query`, or None if it doesn't: the query's characters have to appear
+// in the text in order, ignoring case. Runs of them and ones starting words count for more, so
+// `sw` matches "Search Word Forward" better than "Show Unsaved Changes".
+pub fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0_usize;
+    let mut from = 0;
+    let mut last: Option<usize> = None;
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = (from..text.len()).find(|&index| chars_match(wanted, text[index]))?;
+        score = score.saturating_add(1);
+        if found > 0 && last == found.checked_sub(1) {
+            score = score.saturating_add(4);
+        }
+        if found == 0 || !text[found.saturating_sub(1)].is_alphanumeric() {
+            score = score.saturating_add(8);
+        }
+        last = Some(found);
+        from = found.saturating_add(1);
+    }
+    Some(score)
+}
+
+fn chars_match(wanted: char, c: char) -> bool {
+    wanted.to_lowercase().eq(c.to_lowercase())
+}
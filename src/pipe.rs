@@ -0,0 +1,94 @@
+use crate::commands::ex::{self, ExCommand};
+use crate::config::Config;
+use crate::document::Document;
+use crate::error::ZenError;
+use std::cmp;
+use std::io::{self, Read, Write};
+
+// Runs `:` commands over what's piped in and prints the result, for `zen --pipe`.
+// The terminal is never set up, so this works with stdin and stdout both redirected.
+pub fn run_stdin(commands: &[String]) -> Result<(), ZenError> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|error| ZenError::io("<stdin>", error))?;
+    let output = run(&input, commands)?;
+    io::stdout()
+        .write_all(output.as_bytes())
+        .map_err(|error| ZenError::io("<stdout>", error))
+}
+
+// The text `--pipe` prints for the input and commands. They run as if typed after `:` with
+// the cursor on the first line, so `s/a/b/` only changes that line and `%s/a/b/` every one.
+// The first command that fails stops the run.
+pub fn run(input: &str, commands: &[String]) -> Result<String, ZenError> {
+    let config = Config::default();
+    let mut document = Document::from_text(input);
+    // The row ranges are relative to, which moves like the editor's cursor would.
+    let mut current = 0;
+
+    for line in commands {
+        let result = ex::parse(line)
+            .and_then(|command| run_command(&mut document, &config, &mut current, command));
+        if let Err(error) = result {
+            return Err(ZenError::CommandDispatch(format!("{}: {}", line, error)));
+        }
+    }
+    Ok(document.contents())
+}
+
+// Runs one command over the document. Only commands that edit the text make sense here:
+// there's no screen to show anything on, and no keys to answer a prompt with.
+fn run_command(
+    document: &mut Document,
+    config: &Config,
+    current: &mut usize,
+    command: ExCommand,
+) -> Result<(), ZenError> {
+    match command {
+        ExCommand::Substitute {
+            range,
+            pattern,
+            replacement,
+            global,
+            count_only,
+        } => {
+            // There's no search before the commands for an empty pattern to repeat.
+            if pattern.is_empty() {
+                return Err(ZenError::CommandDispatch(String::from(
+                    "No previous search",
+                )));
+            }
+            let (rows, _) = range.rows(*current, document.len());
+            let last_row = rows.end.saturating_sub(1);
+            let (count, _) = document.substitute(rows, &pattern, &replacement, global, count_only);
+            if count == 0 {
+                return Err(ZenError::CommandDispatch(format!(
+                    "Pattern not found: {}",
+                    pattern
+                )));
+            }
+            if !count_only {
+                *current = last_row;
+            }
+        }
+        ExCommand::Delete(range, _) => {
+            let (rows, _) = range.rows(*current, document.len());
+            document.delete_rows(rows.clone());
+            *current = cmp::min(rows.start, document.len().saturating_sub(1));
+        }
+        // Yanked lines have nowhere to be put.
+        ExCommand::Yank(..) => {}
+        ExCommand::Retab { to_spaces } => {
+            let tab_width = document.effective_tab_width(config);
+            document.retab(to_spaces, tab_width);
+        }
+        ExCommand::SetLocal(local) => document.local_config.merge(local),
+        _ => {
+            return Err(ZenError::CommandDispatch(String::from(
+                "Not available with --pipe",
+            )))
+        }
+    }
+    Ok(())
+}
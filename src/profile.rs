@@ -0,0 +1,117 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// How many of the latest timings of each phase the percentiles are taken over.
+pub const SAMPLES: usize = 1000;
+
+/// Parts of an event loop iteration timed while profiling, with `:set profiling`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    // Handling a key, along with the commands it runs.
+    Input,
+
+    // Running a command, from a key or the command queue.
+    Command,
+
+    // Highlighting the rows in view before a frame is drawn.
+    Highlight,
+
+    // Composing the frame and writing it to the terminal.
+    Render,
+}
+
+impl Phase {
+    pub const ALL: [Self; 4] = [Self::Input, Self::Command, Self::Highlight, Self::Render];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Input => "input",
+            Self::Command => "command",
+            Self::Highlight => "highlight",
+            Self::Render => "render",
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Self::Input => 0,
+            Self::Command => 1,
+            Self::Highlight => 2,
+            Self::Render => 3,
+        }
+    }
+}
+
+/// The latest timings of each phase, for the percentiles `:profile` shows.
+#[derive(Default)]
+pub struct Profiler {
+    samples: [VecDeque<Duration>; 4],
+}
+
+impl Profiler {
+    // Keeps a timing, dropping the oldest of the phase once it has SAMPLES of them.
+    pub fn record(&mut self, phase: Phase, duration: Duration) {
+        let Some(samples) = self.samples.get_mut(phase.index()) else {
+            return;
+        };
+        if samples.len() == SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(duration);
+    }
+
+    pub fn count(&self, phase: Phase) -> usize {
+        self.samples.get(phase.index()).map_or(0, VecDeque::len)
+    }
+
+    // The timing `percent` percent of the phase's timings are at or under, e.g. 50 for the
+    // median. None before any were kept.
+    pub fn percentile(&self, phase: Phase, percent: usize) -> Option<Duration> {
+        let mut sorted: Vec<Duration> = self.samples.get(phase.index())?.iter().copied().collect();
+        sorted.sort_unstable();
+        // Nearest rank: the smallest that at least `percent` percent are under or equal to.
+        let rank = sorted
+            .len()
+            .saturating_mul(percent.min(100))
+            .saturating_add(99)
+            / 100;
+        sorted.get(rank.saturating_sub(1)).copied()
+    }
+
+    pub fn clear(&mut self) {
+        for samples in &mut self.samples {
+            samples.clear();
+        }
+    }
+
+    // A table of the phases with how many timings were kept and their percentiles, in
+    // microseconds. Phases without timings are left out.
+    pub fn report(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{:<10}{:>7}{:>9}{:>9}{:>9}{:>9}",
+            "phase", "count", "p50", "p90", "p99", "max"
+        )];
+        for phase in Phase::ALL {
+            let count = self.count(phase);
+            if count == 0 {
+                continue;
+            }
+            let micros = |percent| {
+                self.percentile(phase, percent)
+                    .map_or(String::new(), |duration| {
+                        format!("{}µs", duration.as_micros())
+                    })
+            };
+            lines.push(format!(
+                "{:<10}{:>7}{:>9}{:>9}{:>9}{:>9}",
+                phase.name(),
+                count,
+                micros(50),
+                micros(90),
+                micros(99),
+                micros(100)
+            ));
+        }
+        lines
+    }
+}
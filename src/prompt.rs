@@ -0,0 +1,273 @@
+use crate::commands::ex;
+use crate::terminal::KeyEvent;
+
+use std::collections::HashMap;
+use std::fs;
+
+// How many lines each prompt's history holds.
+const HISTORY_LEN: usize = 100;
+
+/// What a prompt asks for. Each kind has its own history and completions.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PromptKind {
+    Search,
+    Command,
+    FileName,
+}
+
+/// What the prompt should do after a key was handled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PromptAction {
+    Continue,
+    Submit,
+    Cancel,
+}
+
+/// Lines entered at each kind of prompt, oldest first. Kept for as long as the editor runs.
+#[derive(Default)]
+pub struct PromptHistory {
+    lines: HashMap<PromptKind, Vec<String>>,
+}
+
+impl PromptHistory {
+    // Adds a line, moving it to the end if it was entered before.
+    pub fn push(&mut self, kind: PromptKind, line: String) {
+        let lines = self.lines.entry(kind).or_default();
+        lines.retain(|entry| *entry != line);
+        if lines.len() == HISTORY_LEN {
+            lines.remove(0);
+        }
+        lines.push(line);
+    }
+
+    pub fn get(&self, kind: PromptKind) -> &[String] {
+        self.lines.get(&kind).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// The line being typed at a prompt, with a cursor that can move within it.
+#[derive(Default)]
+pub struct LineEditor {
+    chars: Vec<char>,
+    cursor: usize,
+
+    // Index of the history line on display, and what was typed before going through history.
+    recall: Option<(usize, String)>,
+
+    // Matches Tab goes through, which one is in the line, and where the completed word starts.
+    // None is in the line while it only holds what the matches have in common.
+    completion: Option<(Vec<String>, Option<usize>, usize)>,
+}
+
+impl LineEditor {
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    // Column of the cursor within the line.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    // The matches Tab goes through, while there's more than one.
+    pub fn matches(&self) -> Option<&[String]> {
+        match &self.completion {
+            Some((matches, _, _)) if matches.len() > 1 => Some(matches),
+            _ => None,
+        }
+    }
+
+    // Edits the line according to the key.
+    // Up and Down go through the given history, Tab through the completions for the prompt's kind.
+    pub fn handle_key(
+        &mut self,
+        key: KeyEvent,
+        kind: PromptKind,
+        history: &[String],
+    ) -> PromptAction {
+        if key != KeyEvent::Char('\t') {
+            self.completion = None;
+        }
+        if key != KeyEvent::Up && key != KeyEvent::Down {
+            self.recall = None;
+        }
+
+        match key {
+            KeyEvent::Char('\n') => return PromptAction::Submit,
+            KeyEvent::Esc | KeyEvent::Ctrl('c') => return PromptAction::Cancel,
+            KeyEvent::Char('\t') => self.complete(kind),
+            KeyEvent::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyEvent::Right if self.cursor < self.chars.len() => {
+                self.cursor = self.cursor.saturating_add(1);
+            }
+            KeyEvent::Home | KeyEvent::Ctrl('a') => self.cursor = 0,
+            KeyEvent::End | KeyEvent::Ctrl('e') => self.cursor = self.chars.len(),
+            KeyEvent::Backspace if self.cursor > 0 => {
+                self.cursor = self.cursor.saturating_sub(1);
+                self.chars.remove(self.cursor);
+            }
+            KeyEvent::Delete if self.cursor < self.chars.len() => {
+                self.chars.remove(self.cursor);
+            }
+            KeyEvent::Ctrl('w') => self.delete_word(),
+            KeyEvent::Up => self.recall_older(history),
+            KeyEvent::Down => self.recall_newer(history),
+            KeyEvent::Char(c) if !c.is_control() => {
+                self.chars.insert(self.cursor, c);
+                self.cursor = self.cursor.saturating_add(1);
+            }
+            _ => (),
+        }
+        PromptAction::Continue
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    // Deletes the word before the cursor, along with the whitespace after it.
+    fn delete_word(&mut self) {
+        let mut start = self.cursor;
+        while start > 0 && self.chars[start.saturating_sub(1)].is_whitespace() {
+            start = start.saturating_sub(1);
+        }
+        while start > 0 && !self.chars[start.saturating_sub(1)].is_whitespace() {
+            start = start.saturating_sub(1);
+        }
+        self.chars.drain(start..self.cursor);
+        self.cursor = start;
+    }
+
+    fn recall_older(&mut self, history: &[String]) {
+        let index = match &self.recall {
+            Some((index, _)) => index.saturating_sub(1),
+            None if history.is_empty() => return,
+            None => {
+                self.recall = Some((history.len(), self.text()));
+                history.len().saturating_sub(1)
+            }
+        };
+        if let Some((recalled, _)) = &mut self.recall {
+            *recalled = index;
+        }
+        self.set_text(&history[index]);
+    }
+
+    // Going past the newest line brings back what was typed before.
+    fn recall_newer(&mut self, history: &[String]) {
+        let Some((index, draft)) = self.recall.take() else {
+            return;
+        };
+        let index = index.saturating_add(1);
+        match history.get(index) {
+            Some(line) => {
+                self.set_text(line);
+                self.recall = Some((index, draft));
+            }
+            None => self.set_text(&draft),
+        }
+    }
+
+    // Replaces the word before the cursor with its first completion,
+    // or with the next one if Tab was the last key pressed too.
+    // File names at a file name prompt complete as far as all the matches go first, like a shell.
+    fn complete(&mut self, kind: PromptKind) {
+        let (matches, index, start) = match self.completion.take() {
+            Some((matches, index, start)) => {
+                let index = index.map_or(0, |index| index.saturating_add(1) % matches.len());
+                (matches, index, start)
+            }
+            None => {
+                let mut start = self.cursor;
+                while start > 0 && !self.chars[start.saturating_sub(1)].is_whitespace() {
+                    start = start.saturating_sub(1);
+                }
+                let before: String = self.chars[..start].iter().collect();
+                let word: String = self.chars[start..self.cursor].iter().collect();
+                let matches = completions(kind, &before, &word);
+                if matches.is_empty() {
+                    return;
+                }
+                if kind == PromptKind::FileName && matches.len() > 1 {
+                    let common = common_prefix(&matches);
+                    self.chars.splice(start..self.cursor, common.chars());
+                    self.cursor = start.saturating_add(common.chars().count());
+                    self.completion = Some((matches, None, start));
+                    return;
+                }
+                (matches, 0, start)
+            }
+        };
+
+        // The word typed so far, or the completion before this one, ends at the cursor.
+        let completed: Vec<char> = matches[index].chars().collect();
+        let end = self.cursor;
+        self.cursor = start.saturating_add(completed.len());
+        self.chars.splice(start..end, completed);
+        self.completion = Some((matches, Some(index), start));
+    }
+}
+
+// The longest start all the strings share.
+fn common_prefix(strings: &[String]) -> String {
+    let Some((first, rest)) = strings.split_first() else {
+        return String::new();
+    };
+    let mut len = first.len();
+    for string in rest {
+        len = first
+            .char_indices()
+            .zip(string.chars())
+            .take_while(|((index, a), b)| *index < len && a == b)
+            .last()
+            .map_or(0, |((index, a), _)| index.saturating_add(a.len_utf8()));
+    }
+    first[..len].to_string()
+}
+
+// Completions for the word being typed, given what comes before it on the line.
+// Command names complete at the start of a `:` line, option names after `:set`
+// and file paths after any other command.
+pub fn completions(kind: PromptKind, before: &str, word: &str) -> Vec<String> {
+    match kind {
+        PromptKind::Search => Vec::new(),
+        PromptKind::Command if before.trim().is_empty() => ex::COMMAND_NAMES
+            .iter()
+            .filter(|name| name.starts_with(word))
+            .map(ToString::to_string)
+            .collect(),
+        PromptKind::Command if before.trim() == "set" || before.trim() == "se" => ex::OPTION_NAMES
+            .iter()
+            .flat_map(|name| [name.to_string(), format!("no{}", name)])
+            .filter(|name| name.starts_with(word))
+            .collect(),
+        PromptKind::Command | PromptKind::FileName => path_completions(word),
+    }
+}
+
+// Files and directories starting with the path typed so far. Directories end in a slash.
+fn path_completions(path: &str) -> Vec<String> {
+    let (dir, prefix) = match path.rfind('/') {
+        Some(index) => path.split_at(index.saturating_add(1)),
+        None => ("", path),
+    };
+    let Ok(entries) = fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            // Hidden files only show up once a dot is typed.
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let slash = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir, name, slash))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
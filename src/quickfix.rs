@@ -0,0 +1,203 @@
+use std::fmt;
+use std::io;
+use tokio::process::Command;
+
+/// How bad a quickfix entry is, as the compiler put it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A place in a file and what the build said about it.
+/// Lines and columns count from one, like compilers print them.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Entry {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Entries found in the output of the last `:make`, and the one last jumped to.
+#[derive(Default)]
+pub struct QuickfixList {
+    entries: Vec<Entry>,
+    index: usize,
+}
+
+/// What a build printed, stdout and stderr together, and whether it exited successfully.
+pub struct BuildOutput {
+    pub text: String,
+    pub success: bool,
+}
+
+impl QuickfixList {
+    // A list starting at its first error, or its first entry when there are only warnings.
+    pub fn new(entries: Vec<Entry>) -> Self {
+        let index = entries
+            .iter()
+            .position(|entry| entry.severity == Severity::Error)
+            .unwrap_or(0);
+        Self { entries, index }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn current(&self) -> Option<&Entry> {
+        self.entries.get(self.index)
+    }
+
+    // Moves to the entry after the current one, if there is one.
+    pub fn next_entry(&mut self) -> Option<&Entry> {
+        let index = self.index.saturating_add(1);
+        let entry = self.entries.get(index)?;
+        self.index = index;
+        Some(entry)
+    }
+
+    // Moves to the entry before the current one, if there is one.
+    pub fn prev_entry(&mut self) -> Option<&Entry> {
+        let index = self.index.checked_sub(1)?;
+        self.index = index;
+        self.entries.get(index)
+    }
+
+    // How many errors and how many warnings there are.
+    pub fn counts(&self) -> (usize, usize) {
+        let errors = self
+            .entries
+            .iter()
+            .filter(|entry| entry.severity == Severity::Error)
+            .count();
+        (errors, self.entries.len().saturating_sub(errors))
+    }
+
+    // "(2 of 5) error: message", for the message bar.
+    pub fn describe_current(&self) -> Option<String> {
+        let entry = self.current()?;
+        Some(format!(
+            "({} of {}) {}",
+            self.index.saturating_add(1),
+            self.entries.len(),
+            entry
+        ))
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}", severity, self.message)
+    }
+}
+
+// Runs the build command through the shell and waits for it. Dropping the future before
+// it's done kills the shell.
+pub async fn run_build(command: &str) -> io::Result<BuildOutput> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok(BuildOutput {
+        text,
+        success: output.status.success(),
+    })
+}
+
+// Finds the errors and warnings in a build's output. Two styles are understood:
+// `file:line:col: error: message` lines, as gcc, clang and `rustc --message-format=short`
+// print them, where the column is optional; and rustc's usual
+// `error[E0425]: message` line followed by a ` --> file:line:col` line.
+// Notes and anything else without a location are skipped.
+pub fn parse(output: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    // A rustc style message waiting for the location on the next line.
+    let mut pending: Option<(Severity, String)> = None;
+
+    for line in output.lines() {
+        if let Some(location) = line.trim_start().strip_prefix("--> ") {
+            if let Some((severity, message)) = pending.take() {
+                if let Some((file, line, column, _)) = split_location(location) {
+                    entries.push(Entry {
+                        file,
+                        line,
+                        column,
+                        severity,
+                        message,
+                    });
+                }
+            }
+            continue;
+        }
+
+        pending = None;
+        if let Some((file, line, column, rest)) = split_location(line) {
+            if let Some((severity, message)) = split_severity(rest) {
+                entries.push(Entry {
+                    file,
+                    line,
+                    column,
+                    severity,
+                    message,
+                });
+            }
+        } else {
+            pending = split_severity(line);
+        }
+    }
+    entries
+}
+
+// Splits `file:line:col: rest` or `file:line: rest` at the first `:line` that's a number.
+// Returns the column as 1 when there's none.
+fn split_location(text: &str) -> Option<(String, usize, usize, &str)> {
+    for (index, _) in text.match_indices(':').filter(|(index, _)| *index > 0) {
+        let Some((line, rest)) = number(&text[index.saturating_add(1)..]) else {
+            continue;
+        };
+        if !(rest.is_empty() || rest.starts_with(':')) {
+            continue;
+        }
+        let rest = rest.strip_prefix(':').unwrap_or(rest);
+        let (column, rest) = match number(rest) {
+            Some((column, after)) if after.is_empty() || after.starts_with(':') => {
+                (column, after.strip_prefix(':').unwrap_or(after))
+            }
+            _ => (1, rest),
+        };
+        let file = text[..index].trim().to_string();
+        return Some((file, line, column, rest.trim()));
+    }
+    None
+}
+
+// Splits `error: message`, `error[E0425]: message` or `warning: message`.
+fn split_severity(text: &str) -> Option<(Severity, String)> {
+    let (kind, message) = text.split_once(": ")?;
+    let kind = kind.split('[').next().unwrap_or(kind);
+    let severity = match kind {
+        "error" | "fatal error" => Severity::Error,
+        "warning" => Severity::Warning,
+        _ => return None,
+    };
+    Some((severity, message.trim().to_string()))
+}
+
+// Splits the number the text starts with from the rest.
+fn number(text: &str) -> Option<(usize, &str)> {
+    let digits = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    Some((text[..digits].parse().ok()?, &text[digits..]))
+}
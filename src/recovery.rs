@@ -0,0 +1,128 @@
+use crate::terminal::KeyEvent;
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{self, Path, PathBuf};
+use std::time::{Duration, Instant};
+
+// How long a document is left alone after a change before its recovery file is written,
+// so typing doesn't write it on every key.
+pub const WRITE_DELAY: Duration = Duration::from_secs(2);
+
+/// What to do with a recovery file found for a file being opened.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecoveryChoice {
+    // Loads what the recovery file holds in place of the file, as unsaved changes.
+    Recover,
+
+    // Removes the recovery file and opens the file as it is.
+    Delete,
+
+    // Opens the file as it is. The recovery file stays until the document's own changes are
+    // written over it, or it's saved, or the editor quits.
+    Open,
+}
+
+impl RecoveryChoice {
+    // The choice a key at the recovery prompt makes, if any. Esc opens the file as it is.
+    pub fn from_key(key: KeyEvent) -> Option<Self> {
+        match key {
+            KeyEvent::Char('r' | 'R') => Some(Self::Recover),
+            KeyEvent::Char('d' | 'D') => Some(Self::Delete),
+            KeyEvent::Char('o' | 'O') | KeyEvent::Esc => Some(Self::Open),
+            _ => None,
+        }
+    }
+}
+
+/// Keeps track of when the active document's recovery file is due: once it's been left alone
+/// for WRITE_DELAY after changes that aren't in the file yet.
+#[derive(Default)]
+pub struct RecoveryState {
+    // Edit count last seen and when it was first seen.
+    changed: Option<(usize, Instant)>,
+
+    // Recovery file last written and the edit count it holds.
+    written: Option<(PathBuf, usize)>,
+}
+
+impl RecoveryState {
+    // Whether the recovery file at `path` should be written now, with the document at `edits`.
+    pub fn is_due(&mut self, path: &Path, edits: usize, now: Instant) -> bool {
+        if self
+            .written
+            .as_ref()
+            .is_some_and(|(written, count)| written == path && *count == edits)
+        {
+            return false;
+        }
+        match self.changed {
+            Some((count, at)) if count == edits => now.saturating_duration_since(at) >= WRITE_DELAY,
+            _ => {
+                self.changed = Some((edits, now));
+                false
+            }
+        }
+    }
+
+    // Notes that the recovery file at `path` holds the document at `edits`.
+    pub fn written(&mut self, path: PathBuf, edits: usize) {
+        self.written = Some((path, edits));
+    }
+
+    // Notes that the recovery file is gone, once the document was saved.
+    pub fn clear(&mut self) {
+        self.written = None;
+    }
+
+    // Whether a recovery file is known to be at `path`.
+    pub fn has_written(&self, path: &Path) -> bool {
+        self.written
+            .as_ref()
+            .is_some_and(|(written, _)| written == path)
+    }
+}
+
+// Where the recovery file for a file goes in `dir`: named after the file's absolute path,
+// with `%` for each `/`, like Vim's swap and undo directories.
+pub fn path_for(dir: &Path, file: &str) -> PathBuf {
+    let absolute = path::absolute(file).unwrap_or_else(|_| PathBuf::from(file));
+    let name: String = absolute
+        .to_string_lossy()
+        .chars()
+        .map(|c| if path::is_separator(c) { '%' } else { c })
+        .collect();
+    dir.join(format!("{}.recovery", name))
+}
+
+// Whether the recovery file was written after the file last was, so it holds changes that
+// never made it to disk. A recovery file is newer than a file that's gone.
+pub fn is_newer(recovery: &Path, file: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified());
+    match (modified(recovery), modified(file)) {
+        (Ok(recovery), Ok(file)) => recovery > file,
+        (Ok(_), Err(_)) => true,
+        _ => false,
+    }
+}
+
+// Writes the contents and syncs them to disk, so they survive a crash right after.
+// They go to a temporary file first, which takes the recovery file's place once complete.
+pub fn write(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temporary = path.with_extension("recovery.tmp");
+    let mut file = File::create(&temporary)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&temporary, path)
+}
+
+// Removes the recovery file, if there is one.
+pub fn remove(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
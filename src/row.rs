@@ -1,10 +1,59 @@
 use crate::editor::SearchDirection;
+use crate::error::ZenError;
+use crate::search::{is_word_char, Pattern};
+use crate::terminal::{ansi, ColorSupport};
+use crate::util::style_to_termion;
 
+use std::cmp;
+use std::ops::Range;
 use syntect::easy::HighlightLines;
 use syntect::highlighting::Style;
 use syntect::parsing::SyntaxSet;
-use syntect::util::as_24_bit_terminal_escaped;
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Which whitespace rows draw visibly. Only the rendering changes, never a row's string.
+#[derive(Clone, Copy, Default)]
+pub struct Whitespace {
+    // Tabs and non-breaking spaces anywhere in the row, and trailing spaces, like Vim's 'list'.
+    pub list: bool,
+
+    // Only the whitespace at the end of the row.
+    pub trailing: bool,
+
+    // Columns between tab stops, which tabs are drawn up to whether they're visible or not.
+    pub tab_width: usize,
+}
+
+impl Whitespace {
+    pub fn is_visible(&self) -> bool {
+        self.list || self.trailing
+    }
+
+    // Columns a grapheme takes up when drawn at `column`.
+    fn width(&self, grapheme: &str, column: usize) -> usize {
+        grapheme_width(grapheme, column, self.tab_width)
+    }
+}
+
+// Columns a grapheme takes up when drawn at `column`: a tab goes on to the next tab stop,
+// wide characters like CJK take two columns and combining marks none.
+fn grapheme_width(grapheme: &str, column: usize, tab_width: usize) -> usize {
+    if grapheme == "\t" {
+        let tab_width = cmp::max(tab_width, 1);
+        tab_width.saturating_sub(column % tab_width)
+    } else {
+        grapheme.width()
+    }
+}
+
+/// How `gU`, `gu` and `~` change the case of letters.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CaseDirection {
+    Upper,
+    Lower,
+    Toggle,
+}
 
 /// Implementation of a document's row/line.
 #[derive(Default)]
@@ -16,9 +65,10 @@ pub struct Row {
     // or on change, but will be initialized with the same value as self.string.
     highlighting: String,
 
-    // String length with graphemes in consideration
-    // Updated on change
-    len: usize,
+    // Byte index each grapheme starts at, so a grapheme index finds its place in the string
+    // without walking the graphemes before it. Their count is the row's length.
+    // Edits only segment the string again around where they happened.
+    boundaries: Vec<usize>,
 }
 
 impl From<&str> for Row {
@@ -27,7 +77,7 @@ impl From<&str> for Row {
         let mut row = Self {
             string: String::from(slice),
             highlighting: String::from(slice),
-            len: 0,
+            boundaries: Vec::new(),
         };
         row.update_len();
         row
@@ -36,155 +86,523 @@ impl From<&str> for Row {
 
 impl Row {
     // Returns a display-ready string for the terminal
-    pub fn render(&self) -> String {
-        self.highlighting.clone()
+    pub fn render(&self) -> &str {
+        &self.highlighting
     }
 
     // Gets the length of a string with graphemes in consideration
     pub fn len(&self) -> usize {
-        self.len
+        self.boundaries.len()
+    }
+
+    // The row's graphemes, which is what indexes into a row count.
+    pub fn graphemes(&self) -> impl Iterator<Item = &str> {
+        self.string.graphemes(true)
+    }
+
+    // The grapheme at the index, if the row is that long.
+    pub fn grapheme_at(&self, at: usize) -> Option<&str> {
+        let start = *self.boundaries.get(at)?;
+        Some(&self.string[start..self.byte_index(at.saturating_add(1))])
+    }
+
+    // The text of `len` graphemes from `start` on, as far as the row goes.
+    pub fn slice(&self, start: usize, len: usize) -> &str {
+        let end = self.byte_index(start.saturating_add(len));
+        &self.string[self.byte_index(start)..end]
     }
 
     // Checks if the row's string is empty
     pub fn is_empty(&self) -> bool {
-        self.len == 0
+        self.boundaries.is_empty()
     }
 
-    // Keeps the string's length updated on change
+    // Finds the graphemes again after the string was changed as a whole.
+    // The plain string is rendered until the row gets highlighted again.
     pub fn update_len(&mut self) {
-        self.len = self.string[..].graphemes(true).count();
+        self.boundaries = self
+            .string
+            .grapheme_indices(true)
+            .map(|(index, _)| index)
+            .collect();
+        self.highlighting.clone_from(&self.string);
     }
 
     // Handles row insertions
     pub fn insert(&mut self, at: usize, c: char) {
-        if at >= self.len() {
-            self.string.push(c);
-        } else {
-            let mut result: String = self.string[..].graphemes(true).take(at).collect();
-            let remainder: String = self.string[..].graphemes(true).skip(at).collect();
-
-            result.push(c);
-            result.push_str(&remainder);
-            self.string = result;
-        }
-        self.update_len();
+        self.splice(at, at, c.encode_utf8(&mut [0; 4]));
     }
 
     // Handles row insertions, alternative for a string.
-    // Probably not necessary.
     pub fn insert_str(&mut self, at: usize, str: &str) {
-        if at >= self.len() {
-            self.string.push_str(str);
-        } else {
-            let mut result: String = self.string[..].graphemes(true).take(at).collect();
-            let remainder: String = self.string[..].graphemes(true).skip(at).collect();
+        self.splice(at, at, str);
+    }
 
-            result.push_str(str);
-            result.push_str(&remainder);
-            self.string = result;
-        }
-        self.update_len();
+    // Replaces the graphemes from `start` up to `end` with the replacement in one pass.
+    // The range is clamped to the row, so an empty one inserts and an empty replacement deletes.
+    pub fn replace_str(&mut self, start: usize, end: usize, replacement: &str) {
+        self.splice(start, end, replacement);
     }
 
     // Handles deletions to the row's string.
     pub fn delete(&mut self, at: usize) {
-        if at >= self.len() {
-            return;
-        } else {
-            let mut result: String = self.string[..].graphemes(true).take(at).collect();
-            let remainder: String = self.string[..].graphemes(true).skip(at + 1).collect();
-
-            result.push_str(&remainder);
-            self.string = result;
+        if at < self.len() {
+            self.splice(at, at.saturating_add(1), "");
         }
-        self.update_len();
     }
 
     pub fn append(&mut self, new: &Self) {
-        self.string = format!("{}{}", self.string, new.string);
-        self.update_len();
+        let len = self.len();
+        self.splice(len, len, &new.string);
     }
 
     pub fn split(&mut self, at: usize) -> Self {
-        let mut row: String = String::new();
-        let mut length = 0;
-        let mut splitted_row: String = String::new();
-        let mut splitted_length = 0;
-
-        for (index, grapheme) in self.string[..].graphemes(true).enumerate() {
-            if index < at {
-                length += 1;
-                row.push_str(grapheme);
-            } else {
-                splitted_length += 1;
-                splitted_row.push_str(grapheme);
-            }
+        let at = cmp::min(at, self.len());
+        let byte = self.byte_index(at);
+        let string = self.string.split_off(byte);
+        // Both halves start on a boundary, which is where segmenting them starts anyway.
+        let boundaries = self
+            .boundaries
+            .split_off(at)
+            .into_iter()
+            .map(|index| index.saturating_sub(byte))
+            .collect();
+        self.highlighting.clone_from(&self.string);
+        Self {
+            highlighting: string.clone(),
+            string,
+            boundaries,
         }
+    }
 
-        self.string = row;
-        self.len = length;
-        let highlighting = splitted_row.clone();
-        Self {
-            string: splitted_row,
-            len: splitted_length,
-            highlighting: highlighting,
+    // Byte index of the grapheme at `at`, or the string's length past the end.
+    fn byte_index(&self, at: usize) -> usize {
+        self.boundaries
+            .get(at)
+            .copied()
+            .unwrap_or(self.string.len())
+    }
+
+    // Replaces the graphemes from `start` up to `end`, clamped to the row, with the text.
+    // Graphemes can join across the edit, e.g. when a combining accent is typed, so the string
+    // is segmented again from the grapheme before it until the boundaries line up with the old
+    // ones again. Those before and after are kept, the ones after moved along.
+    fn splice(&mut self, start: usize, end: usize, text: &str) {
+        let end = cmp::min(end, self.len());
+        let start = cmp::min(start, end);
+        let (start_byte, end_byte) = (self.byte_index(start), self.byte_index(end));
+        self.string.replace_range(start_byte..end_byte, text);
+
+        // Where the old text after the edit starts now.
+        let resume = start_byte.saturating_add(text.len());
+        let shift = |index: usize| index.saturating_sub(end_byte).saturating_add(resume);
+        let from = start.saturating_sub(1);
+        let from_byte = self.boundaries.get(from).copied().unwrap_or(0);
+
+        let mut after = self.boundaries.split_off(end).into_iter().peekable();
+        self.boundaries.truncate(from);
+        for (offset, _) in self.string[from_byte..].grapheme_indices(true) {
+            let index = from_byte.saturating_add(offset);
+            if index >= resume {
+                while after.next_if(|&old| shift(old) < index).is_some() {}
+                if after.peek().map(|&old| shift(old)) == Some(index) {
+                    self.boundaries.extend(after.map(shift));
+                    break;
+                }
+            }
+            self.boundaries.push(index);
         }
+        self.highlighting.clone_from(&self.string);
     }
 
     pub fn as_bytes(&self) -> &[u8] {
         self.string.as_bytes()
     }
 
+    // Finds the pattern starting at or after `at` going forward, or before `at` going backward.
     pub fn find(&self, query: &str, at: usize, direction: SearchDirection) -> Option<usize> {
-        if at > self.len || query.is_empty() {
+        if at > self.len() || query.is_empty() {
             return None;
         }
 
-        let start = if direction == SearchDirection::Forward {
-            at
+        let mut matches = self.find_all(query).into_iter();
+        if direction == SearchDirection::Forward {
+            matches.find(|&x| x >= at)
         } else {
-            0
+            matches.rfind(|&x| x < at)
+        }
+    }
+
+    // Grapheme indices of every match of the pattern in the row.
+    pub fn find_all(&self, query: &str) -> Vec<usize> {
+        self.find_matches(&Pattern::parse(query))
+    }
+
+    // Matches that don't start on a grapheme boundary, e.g. an accent on its own, are skipped.
+    pub fn find_matches(&self, pattern: &Pattern) -> Vec<usize> {
+        self.find_match_ranges(pattern)
+            .into_iter()
+            .map(|range| range.start)
+            .collect()
+    }
+
+    // Grapheme ranges of every match of the pattern in the row. A match can be a different
+    // length from the pattern, e.g. when case is ignored. One that ends inside a grapheme
+    // takes in the rest of it.
+    pub fn find_match_ranges(&self, pattern: &Pattern) -> Vec<Range<usize>> {
+        pattern
+            .match_ranges(&self.string)
+            .into_iter()
+            .filter_map(|range| {
+                let start = self.boundaries.binary_search(&range.start).ok()?;
+                let end = self
+                    .boundaries
+                    .binary_search(&range.end)
+                    .unwrap_or_else(|end| end);
+                Some(start..end)
+            })
+            .collect()
+    }
+
+    // The word under the cursor, or the first one after it on the row, like Vim's `*` picks.
+    pub fn word_at(&self, at: usize) -> Option<&str> {
+        self.token_at(at, is_word_char)
+    }
+
+    // The file path under the cursor, or the first one after it on the row, like Vim's `gf`
+    // picks: a run of word characters and the ones paths are usually written with, e.g.
+    // `../src/main.rs` or `~/notes-2024.md`. A trailing `.` ends a sentence rather than the path.
+    pub fn path_at(&self, at: usize) -> Option<&str> {
+        let path = self
+            .token_at(at, |c| is_word_char(c) || "/.-+~#$%=".contains(c))?
+            .trim_end_matches('.');
+        (!path.is_empty()).then_some(path)
+    }
+
+    // The run of graphemes made of characters `is_part` accepts that the cursor is on, or the
+    // first one after it on the row.
+    fn token_at<F>(&self, at: usize, is_part: F) -> Option<&str>
+    where
+        F: Fn(char) -> bool,
+    {
+        let graphemes: Vec<(usize, &str)> = self.string[..].grapheme_indices(true).collect();
+        let is_word = |(_, grapheme): &(usize, &str)| grapheme.chars().all(&is_part);
+
+        let mut start = graphemes
+            .iter()
+            .skip(at)
+            .position(is_word)?
+            .saturating_add(at);
+        while start > 0 && is_word(&graphemes[start.saturating_sub(1)]) {
+            start = start.saturating_sub(1);
+        }
+        let end = graphemes[start..]
+            .iter()
+            .position(|grapheme| !is_word(grapheme))
+            .map_or(self.string.len(), |len| {
+                graphemes[start.saturating_add(len)].0
+            });
+
+        Some(&self.string[graphemes[start].0..end])
+    }
+
+    // Where the word before the cursor starts, like Vim's `Ctrl-W` finds it: whitespace before
+    // the cursor is skipped, then a run of word characters or of other non-blank ones.
+    pub fn word_start(&self, at: usize) -> usize {
+        // A grapheme goes by its first character, so a letter with combining accents is a letter.
+        let class = |index: usize| {
+            let grapheme = self.grapheme_at(index).unwrap_or_default();
+            if grapheme.chars().all(char::is_whitespace) {
+                0
+            } else if grapheme.chars().next().is_some_and(is_word_char) {
+                1
+            } else {
+                2
+            }
         };
 
-        let end = if direction == SearchDirection::Forward {
-            self.len
-        } else {
+        let mut start = cmp::min(at, self.len());
+        while start > 0 && class(start.saturating_sub(1)) == 0 {
+            start = start.saturating_sub(1);
+        }
+        if start > 0 {
+            let word = class(start.saturating_sub(1));
+            while start > 0 && class(start.saturating_sub(1)) == word {
+                start = start.saturating_sub(1);
+            }
+        }
+        start
+    }
+
+    // Rendered row with the given grapheme ranges in reverse video, e.g. search matches.
+    pub fn render_marked(&self, marks: &[Range<usize>]) -> String {
+        self.render_with(marks, Whitespace::default())
+    }
+
+    // Rendered row with marks in reverse video and whitespace drawn as dim symbols:
+    // `→` and padding up to the next tab stop for tabs, `·` for trailing spaces and `␣` for
+    // non-breaking spaces. Tabs that aren't visible are drawn as spaces up to the next tab stop.
+    // The string itself is left alone.
+    pub fn render_with(&self, marks: &[Range<usize>], whitespace: Whitespace) -> String {
+        self.render_styled(marks, &[], whitespace)
+    }
+
+    // Like render_with, with the `underlines` ranges underlined too, e.g. misspelled words.
+    pub fn render_styled(
+        &self,
+        marks: &[Range<usize>],
+        underlines: &[Range<usize>],
+        whitespace: Whitespace,
+    ) -> String {
+        if marks.is_empty() && underlines.is_empty() && !whitespace.is_visible() && !self.has_tabs()
+        {
+            return self.highlighting.clone();
+        }
+        let visible_from = self.visible_whitespace_start(whitespace);
+        let trailing_from = self.trailing_whitespace_start();
+
+        let mut rendered = String::new();
+        let mut index = 0;
+        let mut column = 0;
+        let mut rest = &self.highlighting[..];
+        while !rest.is_empty() {
+            // Escape sequences are copied as they are, they don't take up a column.
+            if rest.starts_with('\x1b') {
+                let len = rest
+                    .char_indices()
+                    .skip(2)
+                    .find(|&(_, c)| ('@'..='~').contains(&c))
+                    .map_or(rest.len(), |(end, c)| end.saturating_add(c.len_utf8()));
+                rendered.push_str(&rest[..len]);
+                rest = &rest[len..];
+                continue;
+            }
+
+            let text_len = rest.find('\x1b').unwrap_or(rest.len());
+            for grapheme in rest[..text_len].graphemes(true) {
+                if marks.iter().any(|mark| mark.start == index) {
+                    rendered.push_str(ansi::REVERSE);
+                }
+                if underlines.iter().any(|underline| underline.start == index) {
+                    rendered.push_str(ansi::UNDERLINE);
+                }
+                let width = whitespace.width(grapheme, column);
+                match visible_symbol(grapheme, index >= visible_from, index >= trailing_from) {
+                    Some(symbol) => {
+                        rendered.push_str(ansi::DIM);
+                        rendered.push(symbol);
+                        rendered.push_str(&" ".repeat(width.saturating_sub(1)));
+                        rendered.push_str(ansi::DIM_RESET);
+                    }
+                    None if grapheme == "\t" => rendered.push_str(&" ".repeat(width)),
+                    None => rendered.push_str(grapheme),
+                }
+                index = index.saturating_add(1);
+                column = column.saturating_add(width);
+                if marks.iter().any(|mark| mark.end == index) {
+                    rendered.push_str(ansi::REVERSE_RESET);
+                }
+                if underlines.iter().any(|underline| underline.end == index) {
+                    rendered.push_str(ansi::UNDERLINE_RESET);
+                }
+            }
+            rest = &rest[text_len..];
+        }
+        rendered
+    }
+
+    // Whether the row has tabs, which are drawn expanded rather than as they are.
+    pub fn has_tabs(&self) -> bool {
+        self.string.contains('\t')
+    }
+
+    // Screen column the grapheme at `grapheme_idx` is drawn at, counting tabs up to the next
+    // tab stop, wide characters as two columns and combining marks as none.
+    pub fn visual_column(&self, grapheme_idx: usize, tab_width: usize) -> usize {
+        let x = cmp::min(grapheme_idx, self.len());
+        let prefix = self.slice(0, x);
+        // Printable ASCII takes a column a character, so most rows aren't walked.
+        if prefix.bytes().all(|byte| (b' '..=b'~').contains(&byte)) {
+            return x;
+        }
+        prefix.graphemes(true).fold(0, |column, grapheme| {
+            column.saturating_add(grapheme_width(grapheme, column, tab_width))
+        })
+    }
+
+    // Columns the whole row is drawn in.
+    pub fn visual_width(&self, tab_width: usize) -> usize {
+        self.visual_column(self.len(), tab_width)
+    }
+
+    // Screen lines the row takes up when wrapped at `width` columns. Empty rows take one too.
+    pub fn visual_line_count(&self, width: usize, tab_width: usize) -> usize {
+        let lines = self.visual_width(tab_width).div_ceil(cmp::max(width, 1));
+        cmp::max(lines, 1)
+    }
+
+    // Index of the grapheme drawn at a screen column, the inverse of visual_column, e.g. for a
+    // mouse click. A column inside a tab or a wide character gives that grapheme, and columns
+    // past the end of the row give its length.
+    pub fn grapheme_at_visual_column(&self, visual_col: usize, tab_width: usize) -> usize {
+        let mut column: usize = 0;
+        for (index, grapheme) in self.string.graphemes(true).enumerate() {
+            column = column.saturating_add(grapheme_width(grapheme, column, tab_width));
+            if column > visual_col {
+                return index;
+            }
+        }
+        self.len()
+    }
+
+    // Index of the first grapheme whitespace is drawn visibly from.
+    fn visible_whitespace_start(&self, whitespace: Whitespace) -> usize {
+        if whitespace.list {
             0
-        };
+        } else if whitespace.trailing {
+            self.trailing_whitespace_start()
+        } else {
+            self.len()
+        }
+    }
 
-        let substring: String = self.string[..]
-            .graphemes(true)
-            .skip(start)
-            .take(end - start)
-            .collect();
+    // Index of the first grapheme of the whitespace the row ends with.
+    fn trailing_whitespace_start(&self) -> usize {
+        let end = self.string.trim_end().len();
+        self.boundaries.partition_point(|&index| index < end)
+    }
 
-        let matching_byte_index = if direction == SearchDirection::Forward {
-            substring.find(query)
+    // Renders the plain string again, until the row gets highlighted again.
+    pub fn clear_highlighting(&mut self) {
+        self.highlighting.clone_from(&self.string);
+    }
+
+    pub fn highlight(
+        &mut self,
+        syntax_set: &SyntaxSet,
+        highlighter: &mut HighlightLines,
+        color_support: ColorSupport,
+    ) -> Result<(), ZenError> {
+        let ranges: Vec<(Style, &str)> = highlighter
+            .highlight_line(&self.string, syntax_set)
+            .map_err(|error| ZenError::SyntaxLoad(error.to_string()))?;
+
+        let mut highlighting = String::new();
+        for (style, text) in ranges {
+            highlighting.push_str(&style_to_termion(&style, color_support));
+            highlighting.push_str(text);
+        }
+        highlighting.push_str(ansi::RESET);
+
+        self.highlighting = highlighting;
+        Ok(())
+    }
+
+    // Rewrites the leading whitespace as spaces, or as tabs plus any remaining spaces.
+    // Mixed indentation is measured in columns first, so tabs line up the same afterwards.
+    // Returns whether the row changed.
+    pub fn retab(&mut self, to_spaces: bool, tab_width: usize) -> bool {
+        let tab_width = cmp::max(tab_width, 1);
+        let (indent_len, columns) = self.indent(tab_width);
+
+        let indent = if to_spaces {
+            " ".repeat(columns)
         } else {
-            substring.rfind(query)
+            format!(
+                "{}{}",
+                "\t".repeat(columns / tab_width),
+                " ".repeat(columns % tab_width)
+            )
         };
 
-        if let Some(matching_byte_index) = matching_byte_index {
-            for (grapheme_index, (byte_index, _)) in
-                substring[..].grapheme_indices(true).enumerate()
-            {
-                if matching_byte_index == byte_index {
-                    return Some(start + grapheme_index);
+        if self.string[..indent_len] == indent {
+            return false;
+        }
+
+        self.string = format!("{}{}", indent, &self.string[indent_len..]);
+        self.update_len();
+        true
+    }
+
+    // Changes the case of the graphemes in range, a character at a time so it's Unicode aware,
+    // e.g. "ß" becomes "SS". Returns whether the row changed.
+    pub fn convert_case(&mut self, range: Range<usize>, direction: CaseDirection) -> bool {
+        let mut converted = String::with_capacity(self.string.len());
+        for (index, grapheme) in self.string.graphemes(true).enumerate() {
+            if !range.contains(&index) {
+                converted.push_str(grapheme);
+                continue;
+            }
+            for c in grapheme.chars() {
+                match direction {
+                    CaseDirection::Upper => converted.extend(c.to_uppercase()),
+                    CaseDirection::Lower => converted.extend(c.to_lowercase()),
+                    CaseDirection::Toggle if c.is_lowercase() => converted.extend(c.to_uppercase()),
+                    CaseDirection::Toggle => converted.extend(c.to_lowercase()),
                 }
             }
         }
+        if converted == self.string {
+            return false;
+        }
+        self.string = converted;
+        self.update_len();
+        true
+    }
 
-        None
+    // Index of the start of the next word after `at`, or the row's length if there's none,
+    // skipping whitespace like the cursor's word motions do.
+    pub fn next_word_start(&self, at: usize) -> usize {
+        let graphemes: Vec<&str> = self.string.graphemes(true).collect();
+        let is_space = |index: &usize| graphemes[*index].chars().all(char::is_whitespace);
+        let word_end = (at..graphemes.len())
+            .find(is_space)
+            .unwrap_or(graphemes.len());
+        (word_end..graphemes.len())
+            .find(|index| !is_space(index))
+            .unwrap_or(graphemes.len())
     }
 
-    pub fn highlight(&mut self, syntax_set: &SyntaxSet, highlighter: &mut HighlightLines) {
-        let ranges: Vec<(Style, &str)> = highlighter
-            .highlight_line(&self.string, syntax_set)
-            .unwrap();
+    // Number of indent levels the leading spaces and tabs reach, where a tab goes to the next
+    // tab stop and a level is `tab_width` columns. A partial level doesn't count.
+    pub fn indent_level(&self, tab_width: usize) -> usize {
+        let tab_width = cmp::max(tab_width, 1);
+        self.indent(tab_width).1 / tab_width
+    }
 
-        let escaped = as_24_bit_terminal_escaped(&ranges[..], true);
+    // The leading spaces and tabs as they are, e.g. to indent a new row the same.
+    pub fn indent_string(&self) -> String {
+        let (indent_len, _) = self.indent(1);
+        self.string[..indent_len].to_string()
+    }
 
-        self.highlighting = escaped;
+    // Replaces the leading spaces and tabs with `level` levels of indentation,
+    // as `tab_width` spaces or a tab each.
+    pub fn set_indent(&mut self, level: usize, use_spaces: bool, tab_width: usize) -> &mut Self {
+        let (indent_len, _) = self.indent(1);
+        let indent = if use_spaces {
+            " ".repeat(level.saturating_mul(tab_width))
+        } else {
+            "\t".repeat(level)
+        };
+        self.string.replace_range(..indent_len, &indent);
+        self.update_len();
+        self
+    }
+
+    // Byte length of the leading spaces and tabs, and how many columns they span.
+    fn indent(&self, tab_width: usize) -> (usize, usize) {
+        let mut columns = 0;
+        let mut indent_len = 0;
+        for c in self.string.chars() {
+            match c {
+                ' ' => columns += 1,
+                '\t' => columns += tab_width - columns % tab_width,
+                _ => break,
+            }
+            indent_len += 1;
+        }
+        (indent_len, columns)
     }
 
     pub fn whitespace_len(&self) -> usize {
@@ -195,6 +613,46 @@ impl Row {
     }
 }
 
+// Symbol a whitespace grapheme is drawn as, if it's visible.
+// Spaces only show at the end of the row, tabs and non-breaking spaces wherever they're visible.
+fn visible_symbol(grapheme: &str, visible: bool, trailing: bool) -> Option<char> {
+    match grapheme {
+        "\t" if visible => Some('→'),
+        "\u{a0}" if visible => Some('␣'),
+        " " if visible && trailing => Some('·'),
+        _ => None,
+    }
+}
+
+// Spells out a grapheme for `ga`: the grapheme, then each of its code points in hex and
+// decimal, then its UTF-8 bytes, e.g. `<é> U+00E9 233, UTF-8 c3 a9`.
+// Control characters are shown like `^I`, since they can't be drawn as they are.
+pub fn describe_grapheme(grapheme: &str) -> String {
+    let shown: String = grapheme
+        .chars()
+        .map(|c| match c {
+            '\u{0}'..='\u{1f}' => format!("^{}", char::from(c as u8 ^ 0x40)),
+            '\u{7f}' => "^?".to_string(),
+            c => c.to_string(),
+        })
+        .collect();
+    let code_points: Vec<String> = grapheme
+        .chars()
+        .map(|c| format!("U+{:04X} {}", u32::from(c), u32::from(c)))
+        .collect();
+    let bytes: Vec<String> = grapheme
+        .bytes()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    format!(
+        "<{}> {}, UTF-8 {}",
+        shown,
+        code_points.join(" + "),
+        bytes.join(" ")
+    )
+}
+
 fn is_separator(c: char) -> bool {
     c.is_ascii_punctuation() || c.is_ascii_whitespace()
 }
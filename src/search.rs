@@ -0,0 +1,122 @@
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A search pattern: literal text, which only matches whole words when wrapped in `\<` and `\>`, like in Vim.
+/// `*` and `#` search with such a pattern, typed searches with plain text.
+pub struct Pattern<'a> {
+    text: &'a str,
+    word_start: bool,
+    word_end: bool,
+    ignore_case: bool,
+}
+
+impl<'a> Pattern<'a> {
+    pub fn parse(pattern: &'a str) -> Self {
+        let (text, word_start) = match pattern.strip_prefix("\\<") {
+            Some(text) => (text, true),
+            None => (pattern, false),
+        };
+        let (text, word_end) = match text.strip_suffix("\\>") {
+            Some(text) => (text, true),
+            None => (text, false),
+        };
+
+        Self {
+            text,
+            word_start,
+            word_end,
+            ignore_case: false,
+        }
+    }
+
+    // The same pattern, matching regardless of case.
+    pub fn ignoring_case(self) -> Self {
+        Self {
+            ignore_case: true,
+            ..self
+        }
+    }
+
+    // Pattern matching the word and nothing longer containing it.
+    pub fn whole_word(word: &str) -> String {
+        format!("\\<{}\\>", word)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    // Length of a match in graphemes.
+    pub fn len(&self) -> usize {
+        self.text.graphemes(true).count()
+    }
+
+    // Byte indices of every match in the line, front first.
+    pub fn match_indices(&self, line: &str) -> Vec<usize> {
+        self.match_ranges(line)
+            .into_iter()
+            .map(|range| range.start)
+            .collect()
+    }
+
+    // Byte ranges of every match in the line, front first.
+    // Matches don't overlap; the line is scanned once.
+    pub fn match_ranges(&self, line: &str) -> Vec<Range<usize>> {
+        if self.text.is_empty() {
+            return Vec::new();
+        }
+
+        let matches = if self.ignore_case {
+            self.caseless_matches(line)
+        } else {
+            line.match_indices(self.text)
+                .map(|(index, text)| (index, text.len()))
+                .collect()
+        };
+        matches
+            .into_iter()
+            .filter(|&(index, len)| {
+                let before = line[..index].chars().next_back();
+                let after = line[index.saturating_add(len)..].chars().next();
+                let joins_word_before = self.word_start && before.is_some_and(is_word_char);
+                let joins_word_after = self.word_end && after.is_some_and(is_word_char);
+                !joins_word_before && !joins_word_after
+            })
+            .map(|(index, len)| index..index.saturating_add(len))
+            .collect()
+    }
+
+    // Byte index and length of every match in the line, ignoring case.
+    // Lengths can differ from the pattern's, since case changes can change how long a character is.
+    fn caseless_matches(&self, line: &str) -> Vec<(usize, usize)> {
+        let mut matches = Vec::new();
+        let mut next = 0;
+        for (index, _) in line.char_indices() {
+            if index < next {
+                continue;
+            }
+            if let Some(len) = caseless_prefix_len(&line[index..], self.text) {
+                matches.push((index, len));
+                next = index.saturating_add(len);
+            }
+        }
+        matches
+    }
+}
+
+// Length in bytes of the start of the line that equals the text, ignoring case.
+fn caseless_prefix_len(line: &str, text: &str) -> Option<usize> {
+    let mut chars = line.char_indices();
+    for expected in text.chars() {
+        let (_, c) = chars.next()?;
+        if !c.to_lowercase().eq(expected.to_lowercase()) {
+            return None;
+        }
+    }
+    Some(chars.next().map_or(line.len(), |(index, _)| index))
+}
+
+// Characters words are made of, for `*`, `#` and whole word matching.
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
@@ -0,0 +1,267 @@
+use crate::search::is_word_char;
+
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Common English words, one per line, that every dictionary starts with.
+const BUNDLED_WORDS: &str = include_str!("words.txt");
+
+// File types spelling is checked in. Code has too many names that aren't words to be worth it.
+const PROSE_FILE_TYPES: [&str; 8] = ["txt", "text", "md", "markdown", "rst", "adoc", "org", "tex"];
+
+// Suggestions `z=` offers at most, so each can be picked with a single digit.
+pub const MAX_SUGGESTIONS: usize = 9;
+
+// Edits a suggestion can be away from the word: a letter added, dropped, changed or two swapped.
+const MAX_DISTANCE: usize = 2;
+
+/// Words that are spelled right. Words are kept as the list has them, so `paris` in the list
+/// allows `Paris` but `Paris` doesn't allow `paris`.
+#[derive(Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    // The words zen comes with.
+    pub fn bundled() -> Self {
+        let mut dictionary = Self::default();
+        dictionary.add_words(BUNDLED_WORDS);
+        dictionary
+    }
+
+    // Adds the words of a list, one per line. Blank lines and lines starting with `#` are skipped.
+    pub fn add_words(&mut self, list: &str) {
+        let words = list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        self.words.extend(words.map(String::from));
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    // Whether the word is spelled right. It may be capitalized, as at the start of a sentence,
+    // and end in a possessive `'s`.
+    pub fn contains(&self, word: &str) -> bool {
+        let word = word.replace('’', "'");
+        let stem = word.strip_suffix("'s").unwrap_or(&word);
+        [word.as_str(), stem]
+            .iter()
+            .any(|word| self.words.contains(*word) || self.words.contains(&word.to_lowercase()))
+    }
+
+    // Words from the dictionary the word might have been meant to be, closest first.
+    // A capitalized word gets capitalized suggestions.
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let lower = word.to_lowercase();
+        let target: Vec<char> = lower.chars().collect();
+        let letters = sorted(&target);
+        let mut found: Vec<(usize, bool, bool, &String)> = self
+            .words
+            .iter()
+            .filter_map(|candidate| {
+                let chars: Vec<char> = candidate.to_lowercase().chars().collect();
+                let distance = distance(&target, &chars)?;
+                // Among words as close, ones with the same letters in another order come first,
+                // since swapped letters are the most common typo. First letters are rarely wrong.
+                let other_letters = sorted(&chars) != letters;
+                let other_start = chars.first() != target.first();
+                (distance > 0).then_some((distance, other_letters, other_start, candidate))
+            })
+            .collect();
+        found.sort();
+
+        let capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        found
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, _, _, candidate)| {
+                if capitalized {
+                    capitalize(candidate)
+                } else {
+                    candidate.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+/// Checks rows against a dictionary. What was found on each row is kept along with the text it
+/// was found in, so only rows that changed since are checked again.
+pub struct Checker {
+    dictionary: Dictionary,
+    rows: HashMap<usize, (String, Vec<Range<usize>>)>,
+}
+
+impl Checker {
+    pub fn new(dictionary: Dictionary) -> Self {
+        Self {
+            dictionary,
+            rows: HashMap::new(),
+        }
+    }
+
+    pub fn dictionary(&self) -> &Dictionary {
+        &self.dictionary
+    }
+
+    // The misspelled words on row `y`, which holds `text`.
+    pub fn check(&mut self, y: usize, text: &str) -> &[Range<usize>] {
+        let dictionary = &self.dictionary;
+        let (checked, ranges) = self
+            .rows
+            .entry(y)
+            .or_insert_with(|| (text.to_string(), misspelled(text, dictionary)));
+        if checked != text {
+            *checked = text.to_string();
+            *ranges = misspelled(text, dictionary);
+        }
+        ranges
+    }
+
+    // What was found on row `y` last time it was checked.
+    pub fn checked(&self, y: usize) -> &[Range<usize>] {
+        self.rows.get(&y).map_or(&[], |(_, ranges)| ranges)
+    }
+
+    // Forgets the rows outside the range, e.g. the ones scrolled out of view.
+    pub fn retain(&mut self, rows: &Range<usize>) {
+        self.rows.retain(|y, _| rows.contains(y));
+    }
+}
+
+// Whether spelling is checked in files of the type.
+pub fn is_prose(file_type: &str) -> bool {
+    PROSE_FILE_TYPES.contains(&file_type.to_lowercase().as_str())
+}
+
+// Grapheme ranges of the words in the text that aren't in the dictionary.
+// Anything that looks like code is left alone: names like `camelCase`, `snake_case` or `v2`,
+// links and addresses, and `inline code`. So are single letters and words in capitals.
+pub fn misspelled(text: &str, dictionary: &Dictionary) -> Vec<Range<usize>> {
+    words(text)
+        .into_iter()
+        .filter(|(_, word)| !looks_like_code(word) && !dictionary.contains(word))
+        .map(|(range, _)| range)
+        .collect()
+}
+
+// The word at or right before grapheme `x`, for `z=` to correct.
+pub fn word_at(text: &str, x: usize) -> Option<(Range<usize>, String)> {
+    words(text)
+        .into_iter()
+        .find(|(range, _)| range.start <= x && x <= range.end)
+}
+
+// The words in the text with their grapheme ranges, outside of tokens that look like links,
+// addresses or inline code. A word is a run of word characters, apostrophes included when
+// there's a letter on both sides, like `don't`.
+fn words(text: &str) -> Vec<(Range<usize>, String)> {
+    let graphemes: Vec<&str> = text.graphemes(true).collect();
+    let is_word = |index: usize| {
+        graphemes
+            .get(index)
+            .and_then(|grapheme| grapheme.chars().next())
+            .is_some_and(is_word_char)
+    };
+    let is_blank = |index: usize| graphemes[index].chars().all(char::is_whitespace);
+
+    let mut words = Vec::new();
+    let mut at = 0;
+    while at < graphemes.len() {
+        if is_blank(at) {
+            at = at.saturating_add(1);
+            continue;
+        }
+        let mut end = at;
+        while end < graphemes.len() && !is_blank(end) {
+            end = end.saturating_add(1);
+        }
+        let token = graphemes[at..end].concat();
+        let skipped = token.contains("://")
+            || token.starts_with("www.")
+            || token.contains('@')
+            || token.contains('`');
+
+        while !skipped && at < end {
+            if !is_word(at) {
+                at = at.saturating_add(1);
+                continue;
+            }
+            let start = at;
+            while at < end
+                && (is_word(at)
+                    || (matches!(graphemes[at], "'" | "’")
+                        && is_word(at.saturating_sub(1))
+                        && is_word(at.saturating_add(1))))
+            {
+                at = at.saturating_add(1);
+            }
+            words.push((start..at, graphemes[start..at].concat()));
+        }
+        at = end;
+    }
+    words
+}
+
+// Words spelling leaves alone: single letters, names with digits or underscores in them, and
+// words with capitals after the first letter, like `camelCase` or `HTML`.
+fn looks_like_code(word: &str) -> bool {
+    word.chars().count() < 2
+        || word.chars().any(|c| c == '_' || c.is_numeric())
+        || word.chars().skip(1).any(char::is_uppercase)
+}
+
+fn sorted(chars: &[char]) -> Vec<char> {
+    let mut chars = chars.to_vec();
+    chars.sort_unstable();
+    chars
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+// Edits between the words, where swapping two letters next to each other counts as one,
+// if there are no more than MAX_DISTANCE of them.
+fn distance(a: &[char], b: &[char]) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > MAX_DISTANCE {
+        return None;
+    }
+    // Rows of the edit table for the letters of `a` so far: the one before last, and the last.
+    let mut before: Vec<usize> = Vec::new();
+    let mut last: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut row = vec![i.saturating_add(1)];
+        for (j, b_char) in b.iter().enumerate() {
+            let changed = last[j].saturating_add(usize::from(a_char != b_char));
+            let added = row[j].saturating_add(1);
+            let dropped = last[j.saturating_add(1)].saturating_add(1);
+            let mut best = cmp::min(changed, cmp::min(added, dropped));
+            let swapped = i > 0
+                && j > 0
+                && a.get(i.saturating_sub(1)) == Some(b_char)
+                && b.get(j.saturating_sub(1)) == Some(a_char);
+            if swapped {
+                best = cmp::min(best, before[j.saturating_sub(1)].saturating_add(1));
+            }
+            row.push(best);
+        }
+        before = mem::replace(&mut last, row);
+    }
+    let distance = last[b.len()];
+    (distance <= MAX_DISTANCE).then_some(distance)
+}
@@ -0,0 +1,75 @@
+// ANSI escape sequences shared by every backend.
+// Kept free of any terminal library so rendering works the same everywhere.
+
+pub const CLEAR_ALL: &str = "\x1b[2J";
+pub const CLEAR_CURRENT_LINE: &str = "\x1b[2K";
+pub const CLEAR_UNTIL_NEWLINE: &str = "\x1b[K";
+
+// The alternate screen is drawn on while the editor runs; leaving it brings back the shell's.
+pub const ALTERNATE_SCREEN: &str = "\x1b[?1049h";
+pub const MAIN_SCREEN: &str = "\x1b[?1049l";
+
+// Mouse reporting, in the same modes termion's MouseTerminal turns on.
+pub const MOUSE_ON: &str = "\x1b[?1000h\x1b[?1002h\x1b[?1015h\x1b[?1006h";
+pub const MOUSE_OFF: &str = "\x1b[?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l";
+
+pub const CURSOR_HIDE: &str = "\x1b[?25l";
+pub const CURSOR_SHOW: &str = "\x1b[?25h";
+
+// Cursor shapes (DECSCUSR), steady rather than blinking. The default is the terminal's own.
+pub const CURSOR_BLOCK: &str = "\x1b[2 q";
+pub const CURSOR_UNDERLINE: &str = "\x1b[4 q";
+pub const CURSOR_BAR: &str = "\x1b[6 q";
+pub const CURSOR_DEFAULT: &str = "\x1b[0 q";
+
+pub const RESET: &str = "\x1b[m";
+pub const BOLD: &str = "\x1b[1m";
+pub const ITALIC: &str = "\x1b[3m";
+pub const DIM: &str = "\x1b[2m";
+pub const DIM_RESET: &str = "\x1b[22m";
+pub const REVERSE: &str = "\x1b[7m";
+pub const REVERSE_RESET: &str = "\x1b[27m";
+pub const UNDERLINE: &str = "\x1b[4m";
+pub const UNDERLINE_RESET: &str = "\x1b[24m";
+
+pub const FG_RESET: &str = "\x1b[39m";
+pub const BG_RESET: &str = "\x1b[49m";
+
+// Moves the cursor to the given 1-based column and row.
+pub fn goto(x: u16, y: u16) -> String {
+    format!("\x1b[{};{}H", y, x)
+}
+
+pub fn fg_rgb(r: u8, g: u8, b: u8) -> String {
+    format!("\x1b[38;2;{};{};{}m", r, g, b)
+}
+
+pub fn bg_rgb(r: u8, g: u8, b: u8) -> String {
+    format!("\x1b[48;2;{};{};{}m", r, g, b)
+}
+
+pub fn fg_ansi256(index: u8) -> String {
+    format!("\x1b[38;5;{}m", index)
+}
+
+pub fn bg_ansi256(index: u8) -> String {
+    format!("\x1b[48;5;{}m", index)
+}
+
+// Foreground from the basic 16 colors (30-37, then the bright 90-97).
+pub fn fg_ansi16(index: u8) -> String {
+    ansi16(index, 30, 90)
+}
+
+// Background from the basic 16 colors (40-47, then the bright 100-107).
+pub fn bg_ansi16(index: u8) -> String {
+    ansi16(index, 40, 100)
+}
+
+fn ansi16(index: u8, normal_base: u8, bright_base: u8) -> String {
+    if index < 8 {
+        format!("\x1b[{}m", normal_base + index)
+    } else {
+        format!("\x1b[{}m", bright_base + index - 8)
+    }
+}
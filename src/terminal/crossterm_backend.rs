@@ -0,0 +1,179 @@
+use super::{ColorSupport, KeyEvent, KeySource, MouseButton, MouseEvent, Size, TerminalBackend};
+use crate::error::ZenError;
+
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+    MouseEventKind,
+};
+use crossterm::style::Print;
+use crossterm::{execute, queue, terminal};
+use std::io::{self, stdout, Write};
+
+/// Terminal backed by crossterm, for platforms termion doesn't support (Windows).
+/// Raw mode, mouse capture and the alternate screen are left again when the terminal is dropped.
+pub struct CrosstermTerminal {
+    size: Size,
+    color_support: ColorSupport,
+}
+
+impl CrosstermTerminal {
+    pub fn new() -> Result<Self, ZenError> {
+        let size = terminal::size().map_err(ZenError::Terminal)?;
+        terminal::enable_raw_mode().map_err(ZenError::Terminal)?;
+        execute!(stdout(), EnableMouseCapture).map_err(ZenError::Terminal)?;
+
+        let mut terminal = Self {
+            size: Size {
+                width: size.0,
+                height: size.1.saturating_sub(2),
+            },
+            color_support: ColorSupport::detect(),
+        };
+        terminal.enter_alternate_screen();
+        terminal.flush()?;
+        Ok(terminal)
+    }
+}
+
+impl Drop for CrosstermTerminal {
+    fn drop(&mut self) {
+        self.leave_alternate_screen();
+        let _ = execute!(stdout(), DisableMouseCapture);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl TerminalBackend for CrosstermTerminal {
+    fn size(&self) -> &Size {
+        &self.size
+    }
+
+    fn color_support(&self) -> ColorSupport {
+        self.color_support
+    }
+
+    fn set_color_support(&mut self, color_support: ColorSupport) {
+        self.color_support = color_support;
+    }
+
+    fn keys(&self) -> Box<dyn KeySource> {
+        Box::new(CrosstermKeys)
+    }
+
+    // Goes through crossterm so ANSI processing gets enabled on Windows consoles.
+    fn write(&mut self, text: &str) {
+        let _ = queue!(stdout(), Print(text));
+    }
+
+    fn flush(&mut self) -> Result<(), ZenError> {
+        io::stdout().flush().map_err(ZenError::Terminal)
+    }
+
+    // Goes through crossterm, which switches screens with the console API where ANSI isn't there.
+    fn enter_alternate_screen(&mut self) {
+        let _ = execute!(stdout(), terminal::EnterAlternateScreen);
+    }
+
+    fn leave_alternate_screen(&mut self) {
+        let _ = execute!(stdout(), terminal::LeaveAlternateScreen);
+    }
+
+    // Only Unix shells stop and continue processes.
+    #[cfg(unix)]
+    fn suspend(&mut self) -> Result<(), ZenError> {
+        self.leave_alternate_screen();
+        self.cursor_show();
+        execute!(
+            stdout(),
+            DisableMouseCapture,
+            Print("\r\n[zen suspended]\r\n")
+        )
+        .map_err(ZenError::Terminal)?;
+        terminal::disable_raw_mode().map_err(ZenError::Terminal)?;
+
+        let stopped = super::stop_process();
+
+        self.resume()?;
+        stopped.map_err(|error| ZenError::CommandDispatch(format!("Could not suspend: {}", error)))
+    }
+
+    fn resume(&mut self) -> Result<(), ZenError> {
+        terminal::enable_raw_mode().map_err(ZenError::Terminal)?;
+        let size = terminal::size().map_err(ZenError::Terminal)?;
+        self.size = Size {
+            width: size.0,
+            height: size.1.saturating_sub(2),
+        };
+        execute!(stdout(), EnableMouseCapture).map_err(ZenError::Terminal)?;
+        self.enter_alternate_screen();
+        self.flush()
+    }
+}
+
+/// Keys and mouse presses read through crossterm's event queue.
+struct CrosstermKeys;
+
+impl KeySource for CrosstermKeys {
+    fn read_key(&mut self) -> Result<KeyEvent, ZenError> {
+        loop {
+            match event::read().map_err(ZenError::Terminal)? {
+                // Windows reports releases too; only presses count as keys.
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    return Ok(KeyEvent::from(key));
+                }
+                Event::Mouse(mouse) => {
+                    if let Some(mouse) = mouse_event(mouse) {
+                        return Ok(KeyEvent::Mouse(mouse));
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+// Presses and wheel turns; moves, drags and releases are left out.
+fn mouse_event(mouse: event::MouseEvent) -> Option<MouseEvent> {
+    let button = match mouse.kind {
+        MouseEventKind::Down(event::MouseButton::Left) => MouseButton::Left,
+        MouseEventKind::Down(event::MouseButton::Middle) => MouseButton::Middle,
+        MouseEventKind::ScrollUp => MouseButton::WheelUp,
+        MouseEventKind::ScrollDown => MouseButton::WheelDown,
+        _ => return None,
+    };
+    Some(MouseEvent {
+        button,
+        x: mouse.column,
+        y: mouse.row,
+    })
+}
+
+impl From<event::KeyEvent> for KeyEvent {
+    fn from(key: event::KeyEvent) -> Self {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+        match key.code {
+            KeyCode::Char(c) if ctrl => Self::Ctrl(c.to_ascii_lowercase()),
+            KeyCode::Char(c) if alt => Self::Alt(c),
+            KeyCode::Char(c) => Self::Char(c),
+            KeyCode::Enter => Self::Char('\n'),
+            KeyCode::Tab => Self::Char('\t'),
+            KeyCode::Backspace => Self::Backspace,
+            KeyCode::Left => Self::Left,
+            KeyCode::Right => Self::Right,
+            KeyCode::Up => Self::Up,
+            KeyCode::Down => Self::Down,
+            KeyCode::Home => Self::Home,
+            KeyCode::End => Self::End,
+            KeyCode::PageUp => Self::PageUp,
+            KeyCode::PageDown => Self::PageDown,
+            KeyCode::BackTab => Self::BackTab,
+            KeyCode::Delete => Self::Delete,
+            KeyCode::Insert => Self::Insert,
+            KeyCode::F(n) => Self::F(n),
+            KeyCode::Esc => Self::Esc,
+            _ => Self::Null,
+        }
+    }
+}
@@ -0,0 +1,44 @@
+/// A keypress, independent of the terminal backend it was read from.
+/// Enter is reported as `Char('\n')` and Tab as `Char('\t')`.
+/// Mouse presses come through here too, so they reach the editor in order with the keys.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyEvent {
+    Backspace,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    BackTab,
+    Delete,
+    Insert,
+    F(u8),
+    Char(char),
+    Alt(char),
+    Ctrl(char),
+    Esc,
+    Mouse(MouseEvent),
+
+    // Anything the backend reported that zen doesn't understand.
+    Null,
+}
+
+/// A mouse button pressed, or the wheel turned, over a cell of the screen.
+/// Columns and rows count from zero at the top left, bars included.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MouseEvent {
+    pub button: MouseButton,
+    pub x: u16,
+    pub y: u16,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    WheelUp,
+    WheelDown,
+}
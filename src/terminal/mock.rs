@@ -0,0 +1,283 @@
+use super::{ansi, ColorSupport, KeyEvent, KeySource, Size, TerminalBackend};
+use crate::editor::Position;
+use crate::error::ZenError;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io;
+use std::rc::Rc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+// How long a read waits for keys to be pushed before giving up on an open queue.
+const KEY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// In-memory terminal for tests.
+/// Keys are scripted up front, and everything written is captured both raw
+/// and applied to a plain-text screen so the rendered rows can be asserted on.
+/// Clones share the same state, so a test can keep a handle after giving one to the editor.
+#[derive(Clone)]
+pub struct MockTerminal {
+    size: Size,
+    color_support: ColorSupport,
+    state: Rc<RefCell<MockState>>,
+    keys: Arc<(Mutex<ScriptedKeys>, Condvar)>,
+}
+
+/// Keys returned by read_key, front first.
+/// Shared with the editor's key reader, which runs on another thread.
+#[derive(Default)]
+struct ScriptedKeys {
+    keys: VecDeque<KeyEvent>,
+
+    // Once closed, reads fail as soon as the queue is empty instead of waiting.
+    closed: bool,
+}
+
+/// Reads the scripted keys of a MockTerminal.
+/// Waits a moment for more when they run out, so tests can push keys while the editor runs.
+struct MockKeys {
+    keys: Arc<(Mutex<ScriptedKeys>, Condvar)>,
+}
+
+#[derive(Default)]
+struct MockState {
+    // Every write, escapes included.
+    output: Vec<String>,
+
+    // Plain-text screen lines, covering the text area and both bars.
+    screen: Vec<String>,
+
+    // Snapshot of the screen taken on every flush, i.e. once per frame.
+    frames: Vec<Vec<String>>,
+
+    // Where the next write lands on the screen.
+    cursor: Position,
+
+    // How many times the editor was suspended with Ctrl-Z.
+    suspensions: usize,
+
+    // Size the terminal takes on when the editor resumes, as if resized while stopped.
+    resized: Option<Size>,
+}
+
+impl MockTerminal {
+    // Creates a terminal of the given full size, status and message bars included.
+    pub fn new(width: u16, height: u16) -> Self {
+        let state = MockState {
+            screen: vec![String::new(); height as usize],
+            ..MockState::default()
+        };
+
+        Self {
+            size: Size {
+                width,
+                height: height.saturating_sub(2),
+            },
+            color_support: ColorSupport::TrueColor,
+            state: Rc::new(RefCell::new(state)),
+            keys: Arc::default(),
+        }
+    }
+
+    // Queues keys to be returned by read_key.
+    pub fn push_keys<I>(&self, keys: I)
+    where
+        I: IntoIterator<Item = KeyEvent>,
+    {
+        let (queue, pushed) = &*self.keys;
+        queue.lock().unwrap().keys.extend(keys);
+        pushed.notify_all();
+    }
+
+    // Makes reads fail right away once the pushed keys are used up.
+    pub fn close_keys(&self) {
+        let (queue, pushed) = &*self.keys;
+        queue.lock().unwrap().closed = true;
+        pushed.notify_all();
+    }
+
+    // Everything written so far, escapes included.
+    pub fn output(&self) -> Vec<String> {
+        self.state.borrow().output.clone()
+    }
+
+    // Forgets the captured output, keeping the screen.
+    pub fn clear_output(&self) {
+        self.state.borrow_mut().output.clear();
+    }
+
+    // The screen as currently drawn, without escape sequences.
+    pub fn last_rendered_rows(&self) -> Vec<String> {
+        self.state.borrow().screen.clone()
+    }
+
+    // How many times the editor suspended to the shell and was brought back.
+    pub fn suspensions(&self) -> usize {
+        self.state.borrow().suspensions
+    }
+
+    // Resizes the terminal the next time the editor resumes, as if the window was resized
+    // while it was stopped. Sizes are full sizes, status and message bars included.
+    pub fn resize_on_resume(&self, width: u16, height: u16) {
+        self.state.borrow_mut().resized = Some(Size {
+            width,
+            height: height.saturating_sub(2),
+        });
+    }
+
+    // Every frame flushed so far, oldest first.
+    pub fn frames(&self) -> Vec<Vec<String>> {
+        self.state.borrow().frames.clone()
+    }
+}
+
+impl KeySource for MockKeys {
+    fn read_key(&mut self) -> Result<KeyEvent, ZenError> {
+        let (queue, pushed) = &*self.keys;
+        let mut queue = queue.lock().unwrap();
+        if queue.keys.is_empty() && !queue.closed {
+            queue = pushed
+                .wait_timeout_while(queue, KEY_TIMEOUT, |queue| {
+                    queue.keys.is_empty() && !queue.closed
+                })
+                .unwrap()
+                .0;
+        }
+
+        queue.keys.pop_front().ok_or_else(|| {
+            ZenError::Terminal(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "No more scripted keys",
+            ))
+        })
+    }
+}
+
+impl TerminalBackend for MockTerminal {
+    fn size(&self) -> &Size {
+        &self.size
+    }
+
+    fn color_support(&self) -> ColorSupport {
+        self.color_support
+    }
+
+    fn set_color_support(&mut self, color_support: ColorSupport) {
+        self.color_support = color_support;
+    }
+
+    fn keys(&self) -> Box<dyn KeySource> {
+        Box::new(MockKeys {
+            keys: Arc::clone(&self.keys),
+        })
+    }
+
+    fn write(&mut self, text: &str) {
+        let mut state = self.state.borrow_mut();
+        state.output.push(text.to_string());
+
+        for c in strip_ansi(text).chars() {
+            match c {
+                '\r' => state.cursor.x = 0,
+                '\n' => {
+                    state.cursor.x = 0;
+                    state.cursor.y = state.cursor.y.saturating_add(1);
+                }
+                _ => state.put(c),
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), ZenError> {
+        let mut state = self.state.borrow_mut();
+        let frame = state.screen.clone();
+        state.frames.push(frame);
+        Ok(())
+    }
+
+    // Comes back right away, as if `fg` was typed at once.
+    fn suspend(&mut self) -> Result<(), ZenError> {
+        {
+            let mut state = self.state.borrow_mut();
+            state.suspensions = state.suspensions.saturating_add(1);
+        }
+        self.resume()
+    }
+
+    fn resume(&mut self) -> Result<(), ZenError> {
+        let mut state = self.state.borrow_mut();
+        if let Some(size) = state.resized.take() {
+            let lines = usize::from(size.height).saturating_add(2);
+            state.screen.resize(lines, String::new());
+            self.size = size;
+        }
+        Ok(())
+    }
+
+    fn clear_screen(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.output.push(ansi::CLEAR_ALL.to_string());
+        for line in &mut state.screen {
+            line.clear();
+        }
+    }
+
+    fn cursor_position(&mut self, position: &Position) {
+        let mut state = self.state.borrow_mut();
+        state.output.push(ansi::goto(
+            position.x.saturating_add(1) as u16,
+            position.y.saturating_add(1) as u16,
+        ));
+        state.cursor = position.clone();
+    }
+
+    fn clear_current_line(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.output.push(ansi::CLEAR_CURRENT_LINE.to_string());
+        let y = state.cursor.y;
+        if let Some(line) = state.screen.get_mut(y) {
+            line.clear();
+        }
+    }
+}
+
+impl MockState {
+    // Puts a character at the cursor, padding the line with spaces if needed.
+    fn put(&mut self, c: char) {
+        let Position { x, y } = self.cursor;
+        if let Some(line) = self.screen.get_mut(y) {
+            let mut chars: Vec<char> = line.chars().collect();
+            if chars.len() <= x {
+                chars.resize(x, ' ');
+                chars.push(c);
+            } else {
+                chars[x] = c;
+            }
+            *line = chars.into_iter().collect();
+        }
+        self.cursor.x = x.saturating_add(1);
+    }
+}
+
+// Removes CSI escape sequences (ESC [ ... final byte) from the text.
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+
+    plain
+}
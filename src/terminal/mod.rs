@@ -0,0 +1,231 @@
+use crate::editor::Position;
+use crate::error::ZenError;
+use crate::event::KeyStream;
+use crate::util;
+use serde::Deserialize;
+use std::env;
+use syntect::highlighting::Color;
+
+pub mod ansi;
+pub mod key;
+pub mod mock;
+
+#[cfg(feature = "crossterm")]
+mod crossterm_backend;
+#[cfg(feature = "termion")]
+mod termion_backend;
+
+#[cfg(feature = "crossterm")]
+pub use crossterm_backend::CrosstermTerminal;
+pub use key::{KeyEvent, MouseButton, MouseEvent};
+pub use mock::MockTerminal;
+#[cfg(feature = "termion")]
+pub use termion_backend::Terminal;
+
+/// The backend `Editor::new` draws to: termion where available, crossterm otherwise (e.g. Windows).
+#[cfg(feature = "termion")]
+pub type DefaultTerminal = Terminal;
+#[cfg(all(feature = "crossterm", not(feature = "termion")))]
+pub type DefaultTerminal = CrosstermTerminal;
+
+#[cfg(not(any(feature = "termion", feature = "crossterm")))]
+compile_error!("zen needs a terminal backend: enable the `termion` or `crossterm` feature");
+
+#[derive(Clone)]
+pub struct Size {
+    pub width: u16,
+    pub height: u16,
+}
+
+/// How many colors the terminal can display.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum ColorSupport {
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "16")]
+    Ansi16,
+
+    // No colors at all. Light backgrounds are drawn in reverse video instead.
+    #[serde(rename = "monochrome")]
+    Monochrome,
+}
+
+/// Shapes the terminal can draw the cursor in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CursorStyle {
+    // Whatever the user's terminal is set up to draw, as it was before zen started.
+    Default,
+    Block,
+    Underline,
+    Bar,
+}
+
+impl CursorStyle {
+    pub fn escape(self) -> &'static str {
+        match self {
+            Self::Default => ansi::CURSOR_DEFAULT,
+            Self::Block => ansi::CURSOR_BLOCK,
+            Self::Underline => ansi::CURSOR_UNDERLINE,
+            Self::Bar => ansi::CURSOR_BAR,
+        }
+    }
+}
+
+// $TERM_PROGRAM of terminals known to support true color without setting $COLORTERM.
+const TRUECOLOR_PROGRAMS: &[&str] = &["iTerm.app", "WezTerm", "vscode", "Hyper"];
+
+impl ColorSupport {
+    // Guesses the terminal's color support from $COLORTERM, $TERM and $TERM_PROGRAM.
+    // $NO_COLOR set to anything but "" asks for no color whatever the terminal supports,
+    // see https://no-color.org.
+    pub fn detect() -> Self {
+        if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return Self::Monochrome;
+        }
+        Self::from_env(
+            &env::var("COLORTERM").unwrap_or_default(),
+            &env::var("TERM").unwrap_or_default(),
+            &env::var("TERM_PROGRAM").unwrap_or_default(),
+        )
+    }
+
+    // Color support for the given values of $COLORTERM, $TERM and $TERM_PROGRAM.
+    pub fn from_env(colorterm: &str, term: &str, term_program: &str) -> Self {
+        if colorterm == "truecolor"
+            || colorterm == "24bit"
+            || term.ends_with("-direct")
+            || TRUECOLOR_PROGRAMS.contains(&term_program)
+        {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else if term.is_empty() || term == "dumb" || term.starts_with("vt") {
+            Self::Monochrome
+        } else {
+            Self::Ansi16
+        }
+    }
+}
+
+// Stops the process the way the shell's Ctrl-Z does, returning once it's continued with `fg`.
+// It's SIGSTOP rather than SIGTSTP, since the editor catches SIGTSTP to get here.
+#[cfg(unix)]
+pub fn stop_process() -> std::io::Result<()> {
+    // SAFETY: raise only sends a signal to the calling thread and touches no memory.
+    if unsafe { libc::raise(libc::SIGSTOP) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// Where keypresses come from. Sendable, so reads can block off the editor's task.
+pub trait KeySource: Send {
+    // Blocks until the next keypress.
+    fn read_key(&mut self) -> Result<KeyEvent, ZenError>;
+}
+
+/// Everything the editor needs from a terminal.
+/// Implemented by the termion and crossterm backends, and by `MockTerminal` for tests.
+pub trait TerminalBackend {
+    // Size of the text area; excludes the status and message bars.
+    fn size(&self) -> &Size;
+
+    fn color_support(&self) -> ColorSupport;
+
+    fn set_color_support(&mut self, color_support: ColorSupport);
+
+    // Handle for reading keys, which the editor moves onto a blocking task.
+    fn keys(&self) -> Box<dyn KeySource>;
+
+    // The keys as an async stream, read off the editor's task.
+    fn input_events_stream(&self) -> KeyStream {
+        KeyStream::new(self.keys())
+    }
+
+    // Outputs text, which may contain escape sequences.
+    fn write(&mut self, text: &str);
+
+    fn flush(&mut self) -> Result<(), ZenError>;
+
+    fn clear_screen(&mut self) {
+        self.write(ansi::CLEAR_ALL);
+    }
+
+    // Switches to the alternate screen buffer, leaving what was on the terminal untouched.
+    fn enter_alternate_screen(&mut self) {
+        self.write(ansi::ALTERNATE_SCREEN);
+    }
+
+    // Switches back to the main screen buffer, showing what was there before entering.
+    fn leave_alternate_screen(&mut self) {
+        self.write(ansi::MAIN_SCREEN);
+    }
+
+    // Hands the terminal back to the shell like Ctrl-Z does elsewhere, returning once the user
+    // brings the editor back with `fg`. Not every terminal can.
+    fn suspend(&mut self) -> Result<(), ZenError> {
+        Err(ZenError::CommandDispatch(
+            "Suspending is not supported here".to_string(),
+        ))
+    }
+
+    // Sets the terminal up again after the process was stopped and continued, by `suspend` or
+    // by a signal from elsewhere, since the shell may have changed its modes meanwhile.
+    // The size is looked up again too, in case the window was resized while stopped.
+    fn resume(&mut self) -> Result<(), ZenError> {
+        Ok(())
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn cursor_position(&mut self, position: &Position) {
+        let Position { mut x, mut y } = position;
+        x = x.saturating_add(1);
+        y = y.saturating_add(1);
+
+        let x = x as u16;
+        let y = y as u16;
+
+        self.write(&ansi::goto(x, y));
+    }
+
+    fn cursor_hide(&mut self) {
+        self.write(ansi::CURSOR_HIDE);
+    }
+
+    fn cursor_show(&mut self) {
+        self.write(ansi::CURSOR_SHOW);
+    }
+
+    fn set_cursor_style(&mut self, style: CursorStyle) {
+        self.write(style.escape());
+    }
+
+    fn clear_current_line(&mut self) {
+        self.write(ansi::CLEAR_CURRENT_LINE);
+    }
+
+    fn set_bg_color(&mut self, color: Color) {
+        let escape = util::bg_escape(color, self.color_support());
+        self.write(&escape);
+    }
+
+    fn reset_bg_color(&mut self) {
+        if self.color_support() == ColorSupport::Monochrome {
+            self.write(ansi::REVERSE_RESET);
+        } else {
+            self.write(ansi::BG_RESET);
+        }
+    }
+
+    fn set_fg_color(&mut self, color: Color) {
+        let escape = util::fg_escape(color, self.color_support());
+        self.write(&escape);
+    }
+
+    fn reset_fg_color(&mut self) {
+        self.write(ansi::FG_RESET);
+    }
+}
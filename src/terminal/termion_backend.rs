@@ -0,0 +1,169 @@
+use super::{
+    ansi, ColorSupport, KeyEvent, KeySource, MouseButton, MouseEvent, Size, TerminalBackend,
+};
+use crate::error::ZenError;
+
+use std::io::{self, stdout, Write};
+use termion::event::{self, Event, Key};
+use termion::input::{Events, MouseTerminal, TermRead};
+use termion::raw::{IntoRawMode, RawTerminal};
+
+/// Terminal backed by termion, drawing to stdout in raw mode with mouse reporting on.
+/// It draws on the alternate screen, so the shell's screen is back as it was once it's dropped.
+pub struct Terminal {
+    size: Size,
+    color_support: ColorSupport,
+    _stdout: MouseTerminal<RawTerminal<std::io::Stdout>>,
+}
+
+impl Terminal {
+    pub fn new() -> Result<Self, ZenError> {
+        let size = termion::terminal_size().map_err(ZenError::Terminal)?;
+
+        let mut terminal = Self {
+            size: Size {
+                width: size.0,
+                height: size.1.saturating_sub(2),
+            },
+            color_support: ColorSupport::detect(),
+            _stdout: MouseTerminal::from(stdout().into_raw_mode().map_err(ZenError::Terminal)?),
+        };
+        terminal.enter_alternate_screen();
+        terminal.flush()?;
+        Ok(terminal)
+    }
+}
+
+// Runs before raw mode and mouse reporting are turned off by the fields being dropped.
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        self.leave_alternate_screen();
+        let _ = self.flush();
+    }
+}
+
+impl TerminalBackend for Terminal {
+    fn size(&self) -> &Size {
+        &self.size
+    }
+
+    fn color_support(&self) -> ColorSupport {
+        self.color_support
+    }
+
+    fn set_color_support(&mut self, color_support: ColorSupport) {
+        self.color_support = color_support;
+    }
+
+    fn keys(&self) -> Box<dyn KeySource> {
+        Box::new(StdinKeys {
+            events: io::stdin().events(),
+        })
+    }
+
+    fn write(&mut self, text: &str) {
+        print!("{}", text);
+    }
+
+    fn flush(&mut self) -> Result<(), ZenError> {
+        io::stdout().flush().map_err(ZenError::Terminal)
+    }
+
+    // Leaves the screen, mouse reporting and raw mode as the shell expects them while stopped,
+    // and sets them up again once continued.
+    fn suspend(&mut self) -> Result<(), ZenError> {
+        self.write(ansi::MOUSE_OFF);
+        self.leave_alternate_screen();
+        self.cursor_show();
+        self.write("\r\n[zen suspended]\r\n");
+        self.flush()?;
+        self._stdout
+            .suspend_raw_mode()
+            .map_err(ZenError::Terminal)?;
+
+        let stopped = super::stop_process();
+
+        self.resume()?;
+        stopped.map_err(|error| ZenError::CommandDispatch(format!("Could not suspend: {}", error)))
+    }
+
+    fn resume(&mut self) -> Result<(), ZenError> {
+        self._stdout
+            .activate_raw_mode()
+            .map_err(ZenError::Terminal)?;
+        let size = termion::terminal_size().map_err(ZenError::Terminal)?;
+        self.size = Size {
+            width: size.0,
+            height: size.1.saturating_sub(2),
+        };
+        self.enter_alternate_screen();
+        self.write(ansi::MOUSE_ON);
+        self.flush()
+    }
+}
+
+/// Keys and mouse presses read from stdin, which is in raw mode while the terminal is alive.
+/// The iterator is kept between reads, since it holds on to bytes read ahead
+/// when several keys arrive at once, e.g. when pasting.
+struct StdinKeys {
+    events: Events<io::Stdin>,
+}
+
+impl KeySource for StdinKeys {
+    fn read_key(&mut self) -> Result<KeyEvent, ZenError> {
+        loop {
+            if let Some(event) = self.events.next() {
+                return event.map(KeyEvent::from).map_err(ZenError::Terminal);
+            }
+        }
+    }
+}
+
+impl From<Event> for KeyEvent {
+    fn from(event: Event) -> Self {
+        match event {
+            Event::Key(key) => Self::from(key),
+            Event::Mouse(event::MouseEvent::Press(button, x, y)) => {
+                let button = match button {
+                    event::MouseButton::Left => MouseButton::Left,
+                    event::MouseButton::Middle => MouseButton::Middle,
+                    event::MouseButton::WheelUp => MouseButton::WheelUp,
+                    event::MouseButton::WheelDown => MouseButton::WheelDown,
+                    _ => return Self::Null,
+                };
+                // termion counts from one.
+                Self::Mouse(MouseEvent {
+                    button,
+                    x: x.saturating_sub(1),
+                    y: y.saturating_sub(1),
+                })
+            }
+            _ => Self::Null,
+        }
+    }
+}
+
+impl From<Key> for KeyEvent {
+    fn from(key: Key) -> Self {
+        match key {
+            Key::Backspace => Self::Backspace,
+            Key::Left => Self::Left,
+            Key::Right => Self::Right,
+            Key::Up => Self::Up,
+            Key::Down => Self::Down,
+            Key::Home => Self::Home,
+            Key::End => Self::End,
+            Key::PageUp => Self::PageUp,
+            Key::PageDown => Self::PageDown,
+            Key::BackTab => Self::BackTab,
+            Key::Delete => Self::Delete,
+            Key::Insert => Self::Insert,
+            Key::F(n) => Self::F(n),
+            Key::Char(c) => Self::Char(c),
+            Key::Alt(c) => Self::Alt(c),
+            Key::Ctrl(c) => Self::Ctrl(c),
+            Key::Esc => Self::Esc,
+            _ => Self::Null,
+        }
+    }
+}
@@ -1,39 +1,143 @@
-use syntect::highlighting::Style;
-use termion::color;
-pub fn style_to_termion(style: &Style) -> String {
-    let mut escape_sequence = String::new();
-
-    escape_sequence.push_str(&format!(
-        "{}",
-        color::Fg(color::Rgb(
-            style.foreground.r,
-            style.foreground.g,
-            style.foreground.b
-        ))
-    ));
-
-    escape_sequence.push_str(&format!(
-        "{}",
-        color::Bg(color::Rgb(
-            style.background.r,
-            style.background.g,
-            style.background.b
-        ))
-    ));
+use crate::terminal::{ansi, ColorSupport};
+
+use syntect::highlighting::{Color, Style};
+
+// Levels of each channel in the xterm 256-color cube (indexes 16-231).
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+// Default xterm values of the basic 16 colors.
+const ANSI_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+// Converts a syntect style into terminal escapes.
+// Attributes from a previous style are reset first so bold/italic don't leak between spans.
+pub(crate) fn style_to_termion(style: &Style, color_support: ColorSupport) -> String {
+    let mut escape_sequence = String::from(ansi::RESET);
+
+    escape_sequence.push_str(&fg_escape(style.foreground, color_support));
+    escape_sequence.push_str(&bg_escape(style.background, color_support));
 
     if style
         .font_style
         .contains(syntect::highlighting::FontStyle::BOLD)
     {
-        escape_sequence.push_str(&format!("{}", termion::style::Bold));
+        escape_sequence.push_str(ansi::BOLD);
     }
 
     if style
         .font_style
         .contains(syntect::highlighting::FontStyle::ITALIC)
     {
-        escape_sequence.push_str(&format!("{}", termion::style::Italic));
+        escape_sequence.push_str(ansi::ITALIC);
     }
 
     escape_sequence
 }
+
+// Escape sequence setting the background to the given syntect color.
+pub(crate) fn background_to_termion(color: Color, color_support: ColorSupport) -> String {
+    bg_escape(color, color_support)
+}
+
+// Builds an opaque color from its channels.
+pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color { r, g, b, a: 0xFF }
+}
+
+// Foreground escape for the color, downgraded to what the terminal supports.
+pub(crate) fn fg_escape(color: Color, color_support: ColorSupport) -> String {
+    match color_support {
+        ColorSupport::TrueColor => ansi::fg_rgb(color.r, color.g, color.b),
+        ColorSupport::Ansi256 => ansi::fg_ansi256(color_to_ansi256(color)),
+        ColorSupport::Ansi16 => ansi::fg_ansi16(color_to_ansi16(color)),
+        // The terminal's own foreground is the only one there is.
+        ColorSupport::Monochrome => String::new(),
+    }
+}
+
+// Background escape for the color, downgraded to what the terminal supports.
+pub(crate) fn bg_escape(color: Color, color_support: ColorSupport) -> String {
+    match color_support {
+        ColorSupport::TrueColor => ansi::bg_rgb(color.r, color.g, color.b),
+        ColorSupport::Ansi256 => ansi::bg_ansi256(color_to_ansi256(color)),
+        ColorSupport::Ansi16 => ansi::bg_ansi16(color_to_ansi16(color)),
+        // Light backgrounds, like the status bar's, stand out in reverse video.
+        ColorSupport::Monochrome if is_light(color) => String::from(ansi::REVERSE),
+        ColorSupport::Monochrome => String::new(),
+    }
+}
+
+// Nearest xterm-256 index, picking between the color cube and the grayscale ramp.
+pub fn color_to_ansi256(color: Color) -> u8 {
+    let cube_index = |channel: u8| -> u8 {
+        let mut nearest = 0;
+        for (index, level) in CUBE_LEVELS.iter().enumerate() {
+            if channel.abs_diff(*level) < channel.abs_diff(CUBE_LEVELS[nearest]) {
+                nearest = index;
+            }
+        }
+        nearest as u8
+    };
+
+    let (r, g, b) = (
+        cube_index(color.r),
+        cube_index(color.g),
+        cube_index(color.b),
+    );
+    let cube = (
+        CUBE_LEVELS[r as usize],
+        CUBE_LEVELS[g as usize],
+        CUBE_LEVELS[b as usize],
+    );
+
+    // Grayscale ramp: 232-255 covers 8, 18, ..., 238.
+    let average = (u16::from(color.r) + u16::from(color.g) + u16::from(color.b)) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + gray_index * 10;
+    let gray = (gray_level, gray_level, gray_level);
+
+    if distance(color, gray) < distance(color, cube) {
+        232 + gray_index
+    } else {
+        16 + 36 * r + 6 * g + b
+    }
+}
+
+// Nearest of the basic 16 colors.
+pub fn color_to_ansi16(color: Color) -> u8 {
+    let mut nearest = 0;
+    for (index, candidate) in ANSI_16.iter().enumerate() {
+        if distance(color, *candidate) < distance(color, ANSI_16[nearest]) {
+            nearest = index;
+        }
+    }
+    nearest as u8
+}
+
+// Whether the color is closer to white than to black, by its perceived brightness.
+fn is_light(color: Color) -> bool {
+    let luma = 299 * u32::from(color.r) + 587 * u32::from(color.g) + 114 * u32::from(color.b);
+    luma > 1000 * 127
+}
+
+// Squared euclidean distance in RGB space.
+fn distance(color: Color, (r, g, b): (u8, u8, u8)) -> u32 {
+    let channel = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+    channel(color.r, r) + channel(color.g, g) + channel(color.b, b)
+}
@@ -0,0 +1,92 @@
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::{self, JoinHandle};
+use tokio::time;
+
+/// Tasks working for the editor in the background, like reading the rest of a file or running
+/// a build. Quitting cancels them and gives them a moment to stop.
+pub struct Workers {
+    // Dropped to cancel the workers, which see their channel close.
+    cancel: Option<watch::Sender<()>>,
+    receiver: watch::Receiver<()>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+/// Tells a worker whether the editor is shutting down.
+#[derive(Clone)]
+pub struct Cancellation(watch::Receiver<()>);
+
+impl Cancellation {
+    // For blocking workers, to check between steps.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.has_changed().is_err()
+    }
+
+    // For async workers, to select on. Nothing is ever sent, so this only returns once the
+    // channel closes.
+    pub async fn cancelled(&mut self) {
+        while self.0.changed().await.is_ok() {}
+    }
+}
+
+impl Default for Workers {
+    fn default() -> Self {
+        let (cancel, receiver) = watch::channel(());
+        Self {
+            cancel: Some(cancel),
+            receiver,
+            tasks: Vec::new(),
+        }
+    }
+}
+
+impl Workers {
+    // Runs the future on the runtime. It's expected to stop once its cancellation fires.
+    pub fn spawn<F, Fut>(&mut self, worker: F)
+    where
+        F: FnOnce(Cancellation) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let future = worker(self.cancellation());
+        self.track(task::spawn(future));
+    }
+
+    // Runs the closure on a blocking thread. It's expected to check its cancellation between
+    // steps, as a blocking thread can't be stopped from outside.
+    pub fn spawn_blocking<F>(&mut self, worker: F)
+    where
+        F: FnOnce(Cancellation) + Send + 'static,
+    {
+        let cancellation = self.cancellation();
+        self.track(task::spawn_blocking(move || worker(cancellation)));
+    }
+
+    // Cancels every worker and waits up to `timeout` for them to stop. Async ones still going
+    // after that are aborted; blocking ones are left to finish on their own.
+    // Returns whether they all stopped in time. Workers spawned afterwards start out cancelled.
+    pub async fn shut_down(&mut self, timeout: Duration) -> bool {
+        self.cancel = None;
+        let mut tasks = std::mem::take(&mut self.tasks);
+        let joined = time::timeout(timeout, async {
+            for task in &mut tasks {
+                // A worker that panicked has stopped too.
+                let _ = task.await;
+            }
+        })
+        .await;
+        for task in &tasks {
+            task.abort();
+        }
+        joined.is_ok()
+    }
+
+    fn cancellation(&self) -> Cancellation {
+        Cancellation(self.receiver.clone())
+    }
+
+    fn track(&mut self, task: JoinHandle<()>) {
+        self.tasks.retain(|task| !task.is_finished());
+        self.tasks.push(task);
+    }
+}
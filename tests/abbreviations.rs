@@ -0,0 +1,129 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, type_text, Fixture, HEIGHT};
+use std::collections::HashMap;
+use zen::abbreviation;
+use zen::commands::ex::{self, ExCommand};
+use zen::config::Config;
+use zen::terminal::KeyEvent;
+
+#[test]
+fn only_the_whole_word_before_the_cursor_is_looked_up() {
+    let table = HashMap::from([
+        ("teh".to_string(), "the".to_string()),
+        ("the".to_string(), "THE".to_string()),
+        ("naïve".to_string(), "naive".to_string()),
+    ]);
+
+    assert_eq!(abbreviation::expand("see teh", &table), Some((3, "the")));
+    assert_eq!(abbreviation::expand("(teh", &table), Some((3, "the")));
+    assert_eq!(abbreviation::expand("a naïve", &table), Some((5, "naive")));
+    assert_eq!(abbreviation::expand("theremin", &table), None);
+    assert_eq!(abbreviation::expand("ateh", &table), None);
+    assert_eq!(abbreviation::expand("teh ", &table), None);
+    assert_eq!(abbreviation::expand("", &table), None);
+}
+
+#[test]
+fn abbreviations_expand_after_a_non_word_character() {
+    let fixture = Fixture::new("abbrev.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "iabbrev teh the");
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "see teh.");
+
+    assert_eq!(editor.document.contents(), "see the.\n");
+    assert_eq!(editor.cursor_position.x, 8);
+    assert_eq!(message_bar(&terminal), "Abbreviation expanded");
+}
+
+#[test]
+fn only_whole_words_are_expanded() {
+    let fixture = Fixture::new("words.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "iab teh the");
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "tehx ateh teh_ teh");
+
+    assert_eq!(editor.document.contents(), "tehx ateh teh_ teh\n");
+}
+
+#[test]
+fn one_undo_reverts_the_expansion() {
+    let fixture = Fixture::new("undo.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "iabbrev btw by the way");
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "btw ");
+    assert_eq!(editor.document.contents(), "by the way \n");
+
+    press(&mut editor, &terminal, [KeyEvent::Esc, KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "by the way\n");
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "btw\n");
+}
+
+#[test]
+fn abbreviations_are_read_from_the_config() {
+    let config = Config::parse("[abbreviations]\nteh = \"the\"\n").unwrap();
+
+    assert_eq!(config.abbreviations["teh"], "the");
+}
+
+#[test]
+fn iabbrev_needs_a_word_and_an_expansion() {
+    assert_eq!(
+        ex::parse("iab  ty   thank you ").unwrap(),
+        ExCommand::Abbreviate {
+            word: "ty".to_string(),
+            expansion: "thank you".to_string(),
+        }
+    );
+    assert!(ex::parse("iabbrev teh").is_err());
+}
+
+#[test]
+fn enter_expands_and_ctrl_v_skips() {
+    let fixture = Fixture::new("triggers.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "iab teh the");
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "teh\nteh");
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Ctrl('v'), KeyEvent::Char('.')],
+    );
+
+    assert_eq!(editor.document.contents(), "the\nteh.\n");
+}
+
+#[test]
+fn abbreviations_are_listed_and_removed() {
+    let fixture = Fixture::new("list.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "iabbrev");
+    assert_eq!(message_bar(&terminal), "No abbreviation found");
+
+    ex_command(&mut editor, &terminal, "iab teh the");
+    ex_command(&mut editor, &terminal, "iab btw by the way");
+    ex_command(&mut editor, &terminal, "iabbrev");
+    let rows = terminal.last_rendered_rows();
+    let height = HEIGHT as usize - 2;
+    assert_eq!(rows[height - 3].trim_end(), "i  btw          by the way");
+    assert_eq!(rows[height - 2].trim_end(), "i  teh          the");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+
+    ex_command(&mut editor, &terminal, "unabbreviate teh");
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "teh ");
+    assert_eq!(editor.document.contents(), "teh \n");
+
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    ex_command(&mut editor, &terminal, "una teh");
+    assert_eq!(message_bar(&terminal), "ERR: No such abbreviation: teh");
+    assert_eq!(
+        ex::parse("iuna").unwrap_err().to_string(),
+        "Usage: unabbreviate {word}"
+    );
+}
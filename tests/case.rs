@@ -0,0 +1,85 @@
+mod common;
+
+use common::{press, start, Fixture};
+use zen::row::{CaseDirection, Row};
+use zen::terminal::KeyEvent;
+
+fn convert(text: &str, range: std::ops::Range<usize>, direction: CaseDirection) -> String {
+    let mut row = Row::from(text);
+    row.convert_case(range, direction);
+    row.string
+}
+
+#[test]
+fn rows_convert_case_a_grapheme_at_a_time() {
+    assert_eq!(convert("max_len", 0..7, CaseDirection::Upper), "MAX_LEN");
+    assert_eq!(convert("Straße", 0..6, CaseDirection::Upper), "STRASSE");
+    assert_eq!(convert("ÉCOLE", 1..5, CaseDirection::Lower), "École");
+    assert_eq!(convert("aBc", 0..2, CaseDirection::Toggle), "Abc");
+}
+
+#[test]
+fn g_upper_u_and_gu_follow_a_motion() {
+    let fixture = Fixture::new("case.txt", "const max_len = 3;\nOne\nTwo\nThree\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('l'); 6]);
+    press(
+        &mut editor,
+        &terminal,
+        [
+            KeyEvent::Char('g'),
+            KeyEvent::Char('U'),
+            KeyEvent::Char('w'),
+        ],
+    );
+    assert_eq!(editor.document.row(0).unwrap().string, "const MAX_LEN = 3;");
+    assert_eq!(editor.cursor_position.x, 6);
+
+    press(
+        &mut editor,
+        &terminal,
+        [
+            KeyEvent::Char('g'),
+            KeyEvent::Char('u'),
+            KeyEvent::Char('u'),
+        ],
+    );
+    assert_eq!(editor.document.row(0).unwrap().string, "const max_len = 3;");
+
+    // `k` moves down in zen, so `gUk` takes this line and the next.
+    press(&mut editor, &terminal, [KeyEvent::Char('k')]);
+    press(
+        &mut editor,
+        &terminal,
+        [
+            KeyEvent::Char('g'),
+            KeyEvent::Char('U'),
+            KeyEvent::Char('k'),
+        ],
+    );
+    assert_eq!(
+        editor.document.contents(),
+        "const max_len = 3;\nONE\nTWO\nThree\n"
+    );
+
+    // One undo step.
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(
+        editor.document.contents(),
+        "const max_len = 3;\nOne\nTwo\nThree\n"
+    );
+}
+
+#[test]
+fn tilde_toggles_and_moves_along_the_line() {
+    let fixture = Fixture::new("tilde.txt", "aB\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('~'); 2]);
+    assert_eq!(editor.document.row(0).unwrap().string, "Ab");
+    assert_eq!((editor.cursor_position.x, editor.cursor_position.y), (1, 0));
+
+    // The cursor stays on the last character.
+    press(&mut editor, &terminal, [KeyEvent::Char('~')]);
+    assert_eq!(editor.document.row(0).unwrap().string, "AB");
+    assert_eq!((editor.cursor_position.x, editor.cursor_position.y), (1, 0));
+}
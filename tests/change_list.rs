@@ -0,0 +1,101 @@
+mod common;
+
+use common::{lines, message_bar, press, start, type_text, Fixture};
+use zen::terminal::KeyEvent;
+
+fn position(editor: &zen::editor::Editor) -> (usize, usize) {
+    (editor.cursor_position.x, editor.cursor_position.y)
+}
+
+#[test]
+fn g_semicolon_and_comma_go_through_edited_positions() {
+    let fixture = Fixture::new("changes.txt", &lines(20));
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('g'), KeyEvent::Char(';')],
+    );
+    assert_eq!(message_bar(&terminal), "Change list is empty");
+
+    // Typing on one line leaves a single entry, at the last edit.
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "ab");
+    // Ctrl-K keeps the column, so `H` goes back to the start of the last row.
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Esc, KeyEvent::Ctrl('K'), KeyEvent::Char('H')],
+    );
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "c");
+    press(&mut editor, &terminal, [KeyEvent::Esc, KeyEvent::Ctrl('J')]);
+    assert_eq!(position(&editor), (0, 0));
+
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('g'), KeyEvent::Char(';')],
+    );
+    assert_eq!(position(&editor), (0, 19));
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('g'), KeyEvent::Char(';')],
+    );
+    assert_eq!(position(&editor), (1, 0));
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('g'), KeyEvent::Char(';')],
+    );
+    assert_eq!(message_bar(&terminal), "At start of change list");
+
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('g'), KeyEvent::Char(',')],
+    );
+    assert_eq!(position(&editor), (0, 19));
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('g'), KeyEvent::Char(',')],
+    );
+    assert_eq!(message_bar(&terminal), "At end of change list");
+}
+
+#[test]
+fn edits_far_apart_get_their_own_entries() {
+    let fixture = Fixture::new("far.txt", &lines(20));
+    let (mut editor, terminal) = start(fixture.options());
+    // `k` moves down in zen.
+    for y in [0, 2, 6] {
+        while editor.cursor_position.y < y {
+            press(&mut editor, &terminal, [KeyEvent::Char('k')]);
+        }
+        press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+        type_text(&mut editor, &terminal, "x");
+        press(&mut editor, &terminal, [KeyEvent::Esc]);
+    }
+
+    // The edit on line 2 replaced the one on line 0.
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('g'), KeyEvent::Char(';')],
+    );
+    assert_eq!(editor.cursor_position.y, 6);
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('g'), KeyEvent::Char(';')],
+    );
+    assert_eq!(editor.cursor_position.y, 2);
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('g'), KeyEvent::Char(';')],
+    );
+    assert_eq!(editor.cursor_position.y, 2);
+}
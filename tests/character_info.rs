@@ -0,0 +1,45 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, type_text, Fixture};
+use zen::row::describe_grapheme;
+use zen::terminal::KeyEvent;
+
+#[test]
+fn ascii_characters_show_one_code_point_and_byte() {
+    assert_eq!(describe_grapheme("a"), "<a> U+0061 97, UTF-8 61");
+    assert_eq!(describe_grapheme("\t"), "<^I> U+0009 9, UTF-8 09");
+}
+
+#[test]
+fn composed_and_decomposed_accents_differ_in_code_points() {
+    assert_eq!(describe_grapheme("é"), "<é> U+00E9 233, UTF-8 c3 a9");
+    assert_eq!(
+        describe_grapheme("e\u{301}"),
+        "<e\u{301}> U+0065 101 + U+0301 769, UTF-8 65 cc 81"
+    );
+}
+
+#[test]
+fn zwj_emoji_list_every_code_point() {
+    assert_eq!(
+        describe_grapheme("👩\u{200d}💻"),
+        "<👩\u{200d}💻> U+1F469 128105 + U+200D 8205 + U+1F4BB 128187, \
+         UTF-8 f0 9f 91 a9 e2 80 8d f0 9f 92 bb"
+    );
+}
+
+#[test]
+fn ga_describes_the_grapheme_under_the_cursor() {
+    let fixture = Fixture::new("ga.txt", "ae\u{301}x\n\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('l')]);
+    type_text(&mut editor, &terminal, "ga");
+    assert_eq!(
+        message_bar(&terminal),
+        "<e\u{301}> U+0065 101 + U+0301 769, UTF-8 65 cc 81"
+    );
+
+    press(&mut editor, &terminal, [KeyEvent::Char('k')]);
+    ex_command(&mut editor, &terminal, "ascii");
+    assert_eq!(message_bar(&terminal), "NUL");
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::{ex_command, start, Fixture};
+use syntect::highlighting::Color;
+use zen::cli::LaunchOptions;
+use zen::config::Config;
+use zen::terminal::{ansi, ColorSupport};
+use zen::util::{color_to_ansi16, color_to_ansi256};
+
+fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color { r, g, b, a: 0xFF }
+}
+
+#[test]
+fn maps_rgb_to_xterm_256() {
+    let pairs = [
+        ((0, 0, 0), 16),
+        ((255, 255, 255), 231),
+        ((255, 0, 0), 196),
+        ((0, 255, 0), 46),
+        ((0, 0, 255), 21),
+        ((95, 135, 175), 67),
+        ((128, 128, 128), 244),
+        ((238, 238, 238), 255),
+        ((43, 48, 59), 236),
+    ];
+
+    for ((r, g, b), expected) in pairs {
+        assert_eq!(
+            color_to_ansi256(rgb(r, g, b)),
+            expected,
+            "rgb({r}, {g}, {b})"
+        );
+    }
+}
+
+#[test]
+fn maps_rgb_to_basic_16() {
+    let pairs = [
+        ((0, 0, 0), 0),
+        ((200, 10, 10), 1),
+        ((255, 0, 0), 9),
+        ((250, 250, 250), 15),
+        ((120, 120, 130), 8),
+    ];
+
+    for ((r, g, b), expected) in pairs {
+        assert_eq!(
+            color_to_ansi16(rgb(r, g, b)),
+            expected,
+            "rgb({r}, {g}, {b})"
+        );
+    }
+}
+
+#[test]
+fn color_support_is_detected_from_the_environment() {
+    let cases = [
+        (("truecolor", "xterm-256color", ""), ColorSupport::TrueColor),
+        (("", "xterm-direct", ""), ColorSupport::TrueColor),
+        (("", "xterm-256color", "iTerm.app"), ColorSupport::TrueColor),
+        (("", "screen-256color", ""), ColorSupport::Ansi256),
+        (("", "xterm", ""), ColorSupport::Ansi16),
+        (("", "dumb", ""), ColorSupport::Monochrome),
+        (("", "vt100", ""), ColorSupport::Monochrome),
+    ];
+
+    for ((colorterm, term, term_program), expected) in cases {
+        assert_eq!(
+            ColorSupport::from_env(colorterm, term, term_program),
+            expected,
+            "{colorterm} {term} {term_program}"
+        );
+    }
+}
+
+#[test]
+fn monochrome_terminals_get_reverse_video_instead_of_colors() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    editor.set_config(Config::parse("colors = \"monochrome\"\n").unwrap());
+    terminal.clear_output();
+    editor.refresh_screen().unwrap();
+
+    let output = terminal.output().concat();
+    assert!(output.contains(&format!("{}-- NORMAL -- [No Name]", ansi::REVERSE)));
+    assert!(!output.contains("\x1b[38;") && !output.contains("\x1b[48;"));
+}
+
+#[test]
+fn notermcolors_draws_without_any_color_until_turned_back_on() {
+    let fixture = Fixture::new("main.rs", "fn main() {}\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "set notermcolors");
+
+    // The frame drawn once the command ran; the ones while typing it were still in color.
+    let output = terminal.output().concat();
+    let output = output.rsplit("\x1b[?25l").next().unwrap();
+    assert!(!output.contains("\x1b[38;") && !output.contains("\x1b[48;"));
+    assert!(output.contains(&format!("{}-- NORMAL --", ansi::REVERSE)));
+    assert!(output.contains("fn main() {}"));
+
+    terminal.clear_output();
+    ex_command(&mut editor, &terminal, "set termcolors");
+    assert!(terminal.output().concat().contains("\x1b[48;2;"));
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::{block_on, message_bar, start, Fixture};
+use zen::commands::{Command, CommandContext, CommandOutcome};
+use zen::document::Document;
+use zen::editor::Position;
+use zen::mode::EditorMode;
+use zen::terminal::ansi;
+
+fn run(document: &mut Document, cursor_position: Position, command: &Command) -> CommandOutcome {
+    let mut ctx = CommandContext {
+        document,
+        cursor_position,
+        view_height: 10,
+        view_top: 0,
+        text_width: 80,
+        mode: EditorMode::Normal,
+    };
+    block_on(command.execute(&mut ctx)).unwrap()
+}
+
+#[test]
+fn cursor_commands_ask_for_a_cursor_move() {
+    let fixture = Fixture::new("moves.txt", "abc\ndef\n");
+    let mut document = block_on(Document::open(fixture.path())).unwrap();
+
+    assert_eq!(
+        run(&mut document, Position::default(), &Command::CursorMoveDown),
+        CommandOutcome::MoveCursor(Position { x: 0, y: 1 })
+    );
+    assert_eq!(
+        run(
+            &mut document,
+            Position { x: 3, y: 0 },
+            &Command::CursorMoveRight
+        ),
+        CommandOutcome::MoveCursor(Position { x: 0, y: 1 })
+    );
+    assert_eq!(
+        run(
+            &mut document,
+            Position { x: 1, y: 1 },
+            &Command::DocumentMoveStart
+        ),
+        CommandOutcome::MoveCursor(Position::default())
+    );
+}
+
+#[test]
+fn goto_line_keeps_the_cursor_on_the_document() {
+    let fixture = Fixture::new("goto.txt", "long line\nab\nlast row\n");
+    let mut document = block_on(Document::open(fixture.path())).unwrap();
+    let from = Position { x: 5, y: 0 };
+
+    assert_eq!(
+        run(
+            &mut document,
+            from.clone(),
+            &Command::CursorGotoLine { line: 1 }
+        ),
+        CommandOutcome::MoveCursor(Position { x: 1, y: 1 })
+    );
+    assert_eq!(
+        run(
+            &mut document,
+            from.clone(),
+            &Command::CursorGotoLine { line: 99 }
+        ),
+        CommandOutcome::MoveCursor(Position { x: 5, y: 2 })
+    );
+    // The end of the document is the last line, as far along as the cursor was.
+    assert_eq!(
+        run(&mut document, from, &Command::DocumentMoveEnd),
+        CommandOutcome::MoveCursor(Position { x: 5, y: 2 })
+    );
+}
+
+#[test]
+fn insert_changes_the_document_and_moves_past_the_character() {
+    let mut document = Document::default();
+    let outcome = run(
+        &mut document,
+        Position::default(),
+        &Command::DocumentInsert('x'),
+    );
+
+    assert_eq!(outcome, CommandOutcome::MoveCursor(Position { x: 1, y: 0 }));
+    assert_eq!(document.contents(), "x\n");
+}
+
+#[test]
+fn mode_quit_and_editor_bound_commands() {
+    let mut document = Document::default();
+
+    assert_eq!(
+        run(
+            &mut document,
+            Position::default(),
+            &Command::EditorSwitchMode(EditorMode::Insert)
+        ),
+        CommandOutcome::SwitchMode(EditorMode::Insert)
+    );
+    assert_eq!(
+        run(&mut document, Position::default(), &Command::DocumentQuit),
+        CommandOutcome::Quit
+    );
+    assert_eq!(
+        run(&mut document, Position::default(), &Command::DocumentSave),
+        CommandOutcome::NeedsEditor
+    );
+}
+
+#[test]
+fn outcomes_are_applied_to_the_editor() {
+    let fixture = Fixture::new("apply.txt", "one\ntwo\n");
+    let (mut editor, terminal) = start(fixture.options());
+
+    editor.apply(CommandOutcome::MoveCursor(Position { x: 2, y: 1 }));
+    assert_eq!(editor.cursor_position, Position { x: 2, y: 1 });
+
+    editor.apply(CommandOutcome::SwitchMode(EditorMode::Insert));
+    assert_eq!(editor.mode(), EditorMode::Insert);
+
+    editor.apply(CommandOutcome::Message("Hello".to_string()));
+    editor.refresh_screen().unwrap();
+    // Lined up behind the help shown at startup.
+    assert!(message_bar(&terminal).ends_with("quit (1 more)"));
+
+    terminal.clear_output();
+    editor.apply(CommandOutcome::Quit);
+    editor.refresh_screen().unwrap();
+    // The shell's screen is restored by leaving the alternate screen, not by clearing this one.
+    let output = terminal.output().concat();
+    assert!(output.contains(ansi::CURSOR_DEFAULT));
+    assert!(!output.contains(ansi::CLEAR_ALL));
+}
@@ -0,0 +1,152 @@
+// Helpers shared by the integration tests, driving an Editor on a MockTerminal.
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::runtime::{self, Runtime};
+use zen::cli::LaunchOptions;
+use zen::clock::MockClock;
+use zen::document::Document;
+use zen::editor::Editor;
+use zen::terminal::{KeyEvent, MockTerminal};
+
+pub const WIDTH: u16 = 60;
+pub const HEIGHT: u16 = 12;
+
+// Starts an editor on a mock terminal and draws the first frame.
+pub fn start(options: LaunchOptions) -> (Editor, MockTerminal) {
+    let terminal = MockTerminal::new(WIDTH, HEIGHT);
+    let mut editor = Editor::with_terminal(terminal.clone(), options);
+    editor.refresh_screen().unwrap();
+    (editor, terminal)
+}
+
+// Like start, but the editor's time only moves when the returned clock is advanced.
+pub fn start_with_clock(options: LaunchOptions) -> (Editor, MockTerminal, MockClock) {
+    let terminal = MockTerminal::new(WIDTH, HEIGHT);
+    let clock = MockClock::new();
+    let mut editor = Editor::with_terminal(terminal.clone(), options);
+    editor.set_clock(clock.clone());
+    editor.refresh_screen().unwrap();
+    (editor, terminal, clock)
+}
+
+thread_local! {
+    static RUNTIME: Runtime = runtime::Builder::new_current_thread()
+        .enable_io()
+        .enable_time()
+        .build()
+        .unwrap();
+}
+
+// Drives one of the editor's futures to completion on this test's runtime.
+pub fn block_on<F>(future: F) -> F::Output
+where
+    F: Future,
+{
+    RUNTIME.with(|runtime| runtime.block_on(future))
+}
+
+// Feeds keys to the editor one at a time, redrawing after each like the run loop does.
+pub fn press<I>(editor: &mut Editor, terminal: &MockTerminal, keys: I)
+where
+    I: IntoIterator<Item = KeyEvent>,
+{
+    for key in keys {
+        terminal.push_keys([key]);
+        block_on(editor.process_keypress()).unwrap();
+        editor.refresh_screen().unwrap();
+    }
+}
+
+// Types a `:` command and submits it. The prompt reads all of its keys within one keypress.
+pub fn ex_command(editor: &mut Editor, terminal: &MockTerminal, command: &str) {
+    terminal.push_keys([KeyEvent::Char(':')]);
+    terminal.push_keys(keys(command));
+    terminal.push_keys([KeyEvent::Char('\n')]);
+    block_on(editor.process_keypress()).unwrap();
+    editor.refresh_screen().unwrap();
+}
+
+pub fn type_text(editor: &mut Editor, terminal: &MockTerminal, text: &str) {
+    press(editor, terminal, text.chars().map(KeyEvent::Char));
+}
+
+// Keys for typing the text, for scripting a whole session up front.
+pub fn keys(text: &str) -> Vec<KeyEvent> {
+    text.chars().map(KeyEvent::Char).collect()
+}
+
+// Text of `count` lines, each holding the number of its row, from "line 0".
+pub fn lines(count: usize) -> String {
+    (0..count).map(|n| format!("line {}\n", n)).collect()
+}
+
+// Opens a document with the contents, along with the fixture holding its file.
+pub fn document(contents: &str) -> (Document, Fixture) {
+    let fixture = Fixture::new("document.txt", contents);
+    (block_on(Document::open(fixture.path())).unwrap(), fixture)
+}
+
+pub fn status_bar(terminal: &MockTerminal) -> String {
+    terminal.last_rendered_rows()[HEIGHT as usize - 2].clone()
+}
+
+pub fn message_bar(terminal: &MockTerminal) -> String {
+    terminal.last_rendered_rows()[HEIGHT as usize - 1].clone()
+}
+
+/// A file in its own temporary directory, removed again when dropped.
+/// Created either from inline contents or by copying one of `tests/fixtures`.
+pub struct Fixture {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl Fixture {
+    pub fn new(name: &str, contents: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let dir = env::temp_dir().join(format!("zen-tests-{}-{}", process::id(), id));
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        Self { dir, path }
+    }
+
+    // Copies tests/fixtures/{name}, so tests can modify it freely.
+    pub fn copy(name: &str) -> Self {
+        let source = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join(name);
+        Self::new(name, &fs::read_to_string(source).unwrap())
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.to_str().unwrap()
+    }
+
+    pub fn contents(&self) -> String {
+        fs::read_to_string(&self.path).unwrap()
+    }
+
+    // Arguments opening this fixture.
+    pub fn options(&self) -> LaunchOptions {
+        LaunchOptions {
+            file_name: Some(self.path().to_string()),
+            ..LaunchOptions::default()
+        }
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
@@ -0,0 +1,210 @@
+mod common;
+
+use common::{press, start, type_text, Fixture};
+use zen::editor::Position;
+use zen::row::Row;
+use zen::terminal::{ansi, KeyEvent, MouseButton, MouseEvent};
+
+fn cursor(x: usize, y: usize) -> (usize, usize) {
+    (x, y)
+}
+
+fn position(position: &Position) -> (usize, usize) {
+    (position.x, position.y)
+}
+
+#[test]
+fn l_and_h_move_within_the_row() {
+    let fixture = Fixture::new("cursor_lh.txt", "abc");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "ll");
+    assert_eq!(position(&editor.cursor_position), cursor(2, 0));
+
+    type_text(&mut editor, &terminal, "h");
+    assert_eq!(position(&editor.cursor_position), cursor(1, 0));
+}
+
+#[test]
+fn l_at_the_end_of_a_row_wraps_to_the_next() {
+    let fixture = Fixture::new("cursor_wrap_next.txt", "ab\ncd");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "ll");
+
+    assert_eq!(position(&editor.cursor_position), cursor(0, 1));
+}
+
+#[test]
+fn h_at_the_start_of_a_row_wraps_to_the_previous_end() {
+    let fixture = Fixture::new("cursor_wrap_prev.txt", "abc\nd");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "kh");
+
+    assert_eq!(position(&editor.cursor_position), cursor(2, 0));
+}
+
+#[test]
+fn k_and_j_move_between_rows() {
+    let fixture = Fixture::new("cursor_jk.txt", "one\ntwo\nthree");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "kk");
+    assert_eq!(editor.cursor_position.y, 2);
+
+    type_text(&mut editor, &terminal, "j");
+    assert_eq!(editor.cursor_position.y, 1);
+}
+
+#[test]
+fn column_is_clamped_on_shorter_rows() {
+    let fixture = Fixture::new("cursor_clamp.txt", "long line\nab");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "Lk");
+    assert_eq!(position(&editor.cursor_position), cursor(1, 1));
+
+    type_text(&mut editor, &terminal, "h");
+    assert_eq!(position(&editor.cursor_position), cursor(0, 1));
+}
+
+#[test]
+fn shift_h_and_shift_l_jump_to_row_edges() {
+    let fixture = Fixture::new("cursor_edges.txt", "hello world");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "L");
+    assert_eq!(editor.cursor_position.x, 10);
+
+    type_text(&mut editor, &terminal, "H");
+    assert_eq!(editor.cursor_position.x, 0);
+}
+
+#[test]
+fn arrows_move_by_word_in_normal_mode() {
+    let fixture = Fixture::new("cursor_words.txt", "foo bar baz");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Right]);
+    assert_eq!(editor.cursor_position.x, 3);
+
+    type_text(&mut editor, &terminal, "L");
+    press(&mut editor, &terminal, [KeyEvent::Left]);
+    assert_eq!(editor.cursor_position.x, 8);
+}
+
+#[test]
+fn terminal_cursor_follows_the_document_cursor() {
+    let fixture = Fixture::new("cursor_terminal.txt", "abc\ndef");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "kl");
+
+    let output = terminal.output().concat();
+    assert!(output.ends_with("\x1b[2;2H\x1b[?25h"));
+}
+
+#[test]
+fn normal_mode_keeps_the_cursor_on_a_character() {
+    let fixture = Fixture::new("cursor_rows.txt", "x\n\nlast\n");
+    let (mut editor, terminal) = start(fixture.options());
+    // A single character row has nowhere to go but the next row.
+    type_text(&mut editor, &terminal, "L");
+    assert_eq!(position(&editor.cursor_position), cursor(0, 0));
+
+    // An empty row only has its start.
+    type_text(&mut editor, &terminal, "kL");
+    assert_eq!(position(&editor.cursor_position), cursor(0, 1));
+
+    // The last row is as far down as it goes.
+    type_text(&mut editor, &terminal, "kkkL");
+    assert_eq!(position(&editor.cursor_position), cursor(3, 2));
+}
+
+#[test]
+fn insert_mode_reaches_the_end_and_esc_steps_back() {
+    let fixture = Fixture::new("cursor_insert.txt", "ab\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i'), KeyEvent::End]);
+    assert_eq!(position(&editor.cursor_position), cursor(2, 0));
+
+    type_text(&mut editor, &terminal, "c");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    assert_eq!(editor.document.contents(), "abc\n");
+    assert_eq!(position(&editor.cursor_position), cursor(2, 0));
+
+    // At the start of a row there's nowhere to step back to.
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('H'), KeyEvent::Char('i')],
+    );
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    assert_eq!(position(&editor.cursor_position), cursor(0, 0));
+}
+
+#[test]
+fn goto_line_scrolls_the_line_into_view() {
+    let contents: String = (1..=40).map(|n| format!("line {}\n", n)).collect();
+    let fixture = Fixture::new("cursor_goto.txt", &contents);
+    let (mut editor, terminal) = start(fixture.options());
+    editor.cursor_position.x = 5;
+
+    editor.goto_line(29);
+    editor.refresh_screen().unwrap();
+    assert_eq!(position(&editor.cursor_position), cursor(5, 29));
+    assert!(terminal
+        .last_rendered_rows()
+        .iter()
+        .any(|row| row == "line 30"));
+
+    editor.goto_line(100);
+    assert_eq!(position(&editor.cursor_position), cursor(5, 39));
+    editor.goto_line(0);
+    editor.refresh_screen().unwrap();
+    assert_eq!(terminal.last_rendered_rows()[0], "line 1");
+}
+
+#[test]
+fn wide_characters_take_two_columns_and_combining_marks_none() {
+    let row = Row::from("日本x");
+    assert_eq!(row.visual_column(1, 4), 2);
+    assert_eq!(row.visual_column(2, 4), 4);
+    assert_eq!(row.grapheme_at_visual_column(1, 4), 0);
+    assert_eq!(row.grapheme_at_visual_column(3, 4), 1);
+    assert_eq!(row.grapheme_at_visual_column(4, 4), 2);
+
+    let row = Row::from("cafe\u{301}\tx");
+    assert_eq!(row.visual_column(4, 4), 4);
+    assert_eq!(row.visual_column(5, 4), 8);
+    assert_eq!(row.grapheme_at_visual_column(6, 4), 4);
+}
+
+#[test]
+fn the_terminal_cursor_sits_where_the_grapheme_is_drawn() {
+    let fixture = Fixture::new("cursor_wide.txt", "日本語x\n\tab\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('L')]);
+    assert_eq!(position(&editor.cursor_position), cursor(3, 0));
+    assert!(terminal.output().contains(&ansi::goto(7, 1)));
+
+    // Clicking the second half of a wide character lands on it.
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Mouse(MouseEvent {
+            button: MouseButton::Left,
+            x: 3,
+            y: 0,
+        })],
+    );
+    assert_eq!(position(&editor.cursor_position), cursor(1, 0));
+    assert!(terminal.output().contains(&ansi::goto(3, 1)));
+
+    // Tabs are drawn up to the next tab stop even when they aren't visible.
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Mouse(MouseEvent {
+            button: MouseButton::Left,
+            x: 5,
+            y: 1,
+        })],
+    );
+    assert_eq!(position(&editor.cursor_position), cursor(2, 1));
+    assert_eq!(terminal.last_rendered_rows()[1], "    ab");
+    assert!(terminal.output().contains(&ansi::goto(6, 2)));
+}
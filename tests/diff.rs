@@ -0,0 +1,236 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, type_text, Fixture, HEIGHT};
+use zen::diff::{self, Diff, DiffTag};
+use zen::terminal::KeyEvent;
+
+fn tags(diff: &Diff) -> Vec<DiffTag> {
+    diff.lines().iter().map(|line| line.tag).collect()
+}
+
+#[test]
+fn identical_text_has_no_hunks() {
+    let diff = Diff::new("a\nb\n", "a\nb\n");
+
+    assert_eq!(tags(&diff), [DiffTag::Equal, DiffTag::Equal]);
+    assert_eq!(diff.hunk_count(), 0);
+}
+
+#[test]
+fn changes_are_aligned_and_grouped_into_hunks() {
+    let diff = Diff::new("a\nb\nc\nd\ne\n", "a\nB\nc\nd\ne\nf\n");
+
+    assert_eq!(
+        tags(&diff),
+        [
+            DiffTag::Equal,
+            DiffTag::Removed,
+            DiffTag::Added,
+            DiffTag::Equal,
+            DiffTag::Equal,
+            DiffTag::Equal,
+            DiffTag::Added,
+        ]
+    );
+    assert_eq!(diff.hunk_count(), 2);
+    assert_eq!(diff.next_hunk(0), Some(1));
+    assert_eq!(diff.next_hunk(1), Some(5));
+    assert_eq!(diff.prev_hunk(5), Some(1));
+    assert_eq!(diff.prev_hunk(1), None);
+    assert_eq!(diff.row_of(2), 3);
+}
+
+#[test]
+fn removed_lines_start_a_hunk_on_the_following_buffer_line() {
+    let diff = Diff::new("a\nb\nc\n", "a\nc\n");
+
+    assert_eq!(diff.lines()[1].tag, DiffTag::Removed);
+    assert_eq!(diff.lines()[1].buffer_line, None);
+    assert_eq!(diff.next_hunk(0), Some(1));
+}
+
+#[test]
+fn diff_command_shows_buffer_and_disk_side_by_side() {
+    let fixture = Fixture::new("diff.txt", "one\ntwo\nthree\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "new ");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    ex_command(&mut editor, &terminal, "diff");
+
+    let rows = terminal.last_rendered_rows();
+    assert_eq!(message_bar(&terminal), "1 hunks");
+    assert_eq!(rows[0].trim_end(), format!("{:29}│one", ""));
+    assert_eq!(rows[1].trim_end(), format!("{:29}│", "new one"));
+    assert_eq!(rows[2].trim_end(), format!("{:29}│two", "two"));
+
+    // Edits update the view straight away.
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('H'), KeyEvent::Char('i')],
+    );
+    press(&mut editor, &terminal, [KeyEvent::Delete; 4]);
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    assert_eq!(
+        terminal.last_rendered_rows()[0].trim_end(),
+        format!("{:29}│one", "one")
+    );
+
+    ex_command(&mut editor, &terminal, "diffoff");
+    assert_eq!(terminal.last_rendered_rows()[0], "one");
+}
+
+#[test]
+fn bracket_c_jumps_between_hunks() {
+    let fixture = Fixture::new("hunks.txt", "a\nb\nc\nd\ne\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "diff");
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char(']'), KeyEvent::Char('c')],
+    );
+    assert_eq!(message_bar(&terminal), "No more hunks.");
+
+    // Change "b" and "e", then walk between the two hunks.
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('k'), KeyEvent::Char('i')],
+    );
+    type_text(&mut editor, &terminal, "x");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    press(&mut editor, &terminal, [KeyEvent::Char('k'); 3]);
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "y");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+
+    editor.cursor_position.y = 0;
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char(']'), KeyEvent::Char('c')],
+    );
+    assert_eq!(editor.cursor_position.y, 1);
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char(']'), KeyEvent::Char('c')],
+    );
+    assert_eq!(editor.cursor_position.y, 4);
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('['), KeyEvent::Char('c')],
+    );
+    assert_eq!(editor.cursor_position.y, 1);
+}
+
+#[test]
+fn unknown_commands_are_reported() {
+    let (mut editor, terminal) = start(Default::default());
+    ex_command(&mut editor, &terminal, "frobnicate");
+
+    assert_eq!(
+        message_bar(&terminal),
+        "ERR: Not an editor command: frobnicate"
+    );
+}
+
+#[test]
+fn unified_diffs_keep_three_lines_of_context() {
+    let disk: String = (0..10).map(|n| format!("{}\n", n)).collect();
+    let buffer = disk.replace("5\n", "five\n");
+
+    assert_eq!(
+        diff::unified(&disk, &buffer),
+        [
+            (DiffTag::Equal, "@@ -3,7 +3,7 @@".to_string()),
+            (DiffTag::Equal, " 2".to_string()),
+            (DiffTag::Equal, " 3".to_string()),
+            (DiffTag::Equal, " 4".to_string()),
+            (DiffTag::Removed, "-5".to_string()),
+            (DiffTag::Added, "+five".to_string()),
+            (DiffTag::Equal, " 6".to_string()),
+            (DiffTag::Equal, " 7".to_string()),
+            (DiffTag::Equal, " 8".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn unsaved_shows_the_changes_colored_until_a_key_is_pressed() {
+    let fixture = Fixture::new("unsaved.txt", "one\ntwo\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "new ");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    terminal.clear_output();
+    ex_command(&mut editor, &terminal, "unsaved");
+
+    let rows = terminal.last_rendered_rows();
+    let changes = &rows[HEIGHT as usize - 8..HEIGHT as usize - 2];
+    assert!(changes[0].starts_with("--- "));
+    assert!(changes[1].starts_with("+++ "));
+    assert_eq!(
+        changes[2..],
+        ["@@ -1,2 +1,2 @@", "-one", "+new one", " two"]
+    );
+    assert!(terminal
+        .output()
+        .contains(&"\x1b[38;2;152;195;121m+new one\x1b[39m".to_string()));
+
+    press(&mut editor, &terminal, [KeyEvent::Char('j')]);
+    assert_eq!(terminal.last_rendered_rows()[0], "new one");
+}
+
+#[test]
+fn a_file_not_saved_yet_shows_as_all_added_and_scrolls() {
+    let dir = Fixture::new("existing.txt", "");
+    let path = std::path::Path::new(dir.path()).with_file_name("not_yet.txt");
+    let mut options = dir.options();
+    options.file_name = Some(path.to_str().unwrap().to_string());
+    let (mut editor, terminal) = start(options);
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    for n in 0..20 {
+        type_text(&mut editor, &terminal, &format!("{}\n", n));
+    }
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    ex_command(&mut editor, &terminal, "unsaved");
+
+    let rows = terminal.last_rendered_rows();
+    assert_eq!(rows[0], "--- /dev/null");
+    assert_eq!(rows[2], "@@ -0,0 +1,21 @@");
+    assert_eq!(rows[3], "+0");
+
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('j'), KeyEvent::Char('j')],
+    );
+    assert_eq!(terminal.last_rendered_rows()[0], "@@ -0,0 +1,21 @@");
+    press(&mut editor, &terminal, [KeyEvent::Char(' ')]);
+    assert_eq!(terminal.last_rendered_rows()[0], "+9");
+    press(&mut editor, &terminal, [KeyEvent::Char('q')]);
+    assert_eq!(terminal.last_rendered_rows()[0], "11");
+}
+
+#[test]
+fn d_at_the_quit_warning_shows_the_changes() {
+    let fixture = Fixture::new("quit.txt", "text\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Char('x'), KeyEvent::Esc],
+    );
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('q')]);
+    assert!(message_bar(&terminal).starts_with("WARNING!"));
+
+    press(&mut editor, &terminal, [KeyEvent::Char('d')]);
+    assert!(terminal
+        .last_rendered_rows()
+        .contains(&"+xtext".to_string()));
+    assert!(!editor.document.contents().starts_with("text"));
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{message_bar, press, start, type_text, Fixture};
+use std::collections::HashMap;
+use zen::config::Config;
+use zen::digraph::{self, Digraphs};
+use zen::terminal::KeyEvent;
+
+#[test]
+fn built_in_digraphs_are_looked_up_in_order() {
+    assert_eq!(digraph::lookup('a', ':'), Some('ä'));
+    assert_eq!(digraph::lookup('-', '>'), Some('→'));
+    assert_eq!(digraph::lookup('p', '*'), Some('π'));
+    assert_eq!(digraph::lookup('E', 'u'), Some('€'));
+    assert_eq!(digraph::lookup(':', 'a'), None);
+}
+
+#[test]
+fn digraphs_can_be_typed_the_other_way_around() {
+    let digraphs = Digraphs::default();
+    assert_eq!(digraphs.get(':', 'a'), Some('ä'));
+    // `<-` is a digraph of its own, so it isn't `-<` turned around.
+    assert_eq!(digraphs.get('<', '-'), Some('←'));
+    assert_eq!(digraphs.get('q', 'q'), None);
+}
+
+#[test]
+fn digraphs_from_the_config_come_first() {
+    let custom = HashMap::from([("qq".to_string(), 'ℚ'), ("a:".to_string(), 'ӓ')]);
+    let digraphs = Digraphs::new(&custom).unwrap();
+    assert_eq!(digraphs.get('q', 'q'), Some('ℚ'));
+    assert_eq!(digraphs.get('a', ':'), Some('ӓ'));
+
+    let custom = HashMap::from([("abc".to_string(), 'x')]);
+    assert!(Digraphs::new(&custom).is_err());
+}
+
+#[test]
+fn ctrl_k_inserts_a_digraph_shown_while_typed() {
+    let fixture = Fixture::new("digraph.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "ix");
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Ctrl('k'), KeyEvent::Char('a')],
+    );
+    assert_eq!(editor.pending_input().keys(), "^Ka");
+    assert!(message_bar(&terminal).ends_with("^Ka"));
+
+    press(&mut editor, &terminal, [KeyEvent::Char(':')]);
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('k')]);
+    type_text(&mut editor, &terminal, "->");
+    assert_eq!(editor.document.contents(), "xä→\n");
+    assert!(editor.pending_input().is_empty());
+    assert!(editor.document.is_dirty());
+}
+
+#[test]
+fn unknown_digraphs_insert_nothing() {
+    let fixture = Fixture::new("unknown.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('k')],
+    );
+    type_text(&mut editor, &terminal, "qq");
+
+    assert_eq!(editor.document.contents(), "");
+    assert_eq!(message_bar(&terminal), "ERR: Unknown digraph: qq");
+}
+
+#[test]
+fn config_digraphs_are_typed_with_ctrl_k() {
+    let fixture = Fixture::new("config.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.set_config(Config::parse("[digraphs]\nqq = \"ℚ\"\n").unwrap());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('k')],
+    );
+    type_text(&mut editor, &terminal, "qq");
+
+    assert_eq!(editor.document.contents(), "ℚ\n");
+}
+
+#[test]
+fn ctrl_v_u_inserts_a_code_point() {
+    let fixture = Fixture::new("codepoint.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('v')],
+    );
+    type_text(&mut editor, &terminal, "u00e9");
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('v')]);
+    type_text(&mut editor, &terminal, "u21D2");
+    assert_eq!(editor.document.contents(), "é⇒\n");
+
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('v')]);
+    type_text(&mut editor, &terminal, "ud800");
+    assert_eq!(message_bar(&terminal), "ERR: Invalid code point: d800");
+    assert_eq!(editor.document.contents(), "é⇒\n");
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{block_on, ex_command, message_bar, press, start, status_bar, Fixture};
+use std::fs;
+use std::path::PathBuf;
+use zen::cli::LaunchOptions;
+use zen::document::{Document, DocumentOrigin};
+use zen::terminal::KeyEvent;
+
+#[test]
+fn documents_know_where_they_came_from() {
+    let fixture = Fixture::new("origin.txt", "text\n");
+    let document = block_on(Document::open(fixture.path())).unwrap();
+    assert_eq!(
+        document.origin(),
+        &DocumentOrigin::OpenedFromDisk {
+            path: PathBuf::from(fixture.path())
+        }
+    );
+    assert!(!document.is_new_file());
+    assert!(Document::default().is_new_file());
+}
+
+#[test]
+fn new_documents_are_marked_until_saved() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    assert!(status_bar(&terminal).contains("[No Name] - 0 lines [New File]"));
+
+    let fixture = Fixture::new("existing.txt", "");
+    let path = format!("{}.new", fixture.path());
+    ex_command(&mut editor, &terminal, &format!("tabnew {}", path));
+    assert!(editor.document.is_new_file());
+    assert!(status_bar(&terminal).contains("[New File]"));
+
+    // A named new document is written without asking for a name.
+    press(
+        &mut editor,
+        &terminal,
+        [
+            KeyEvent::Char('i'),
+            KeyEvent::Char('x'),
+            KeyEvent::Ctrl('s'),
+        ],
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), "x\n");
+    assert!(!editor.document.is_new_file());
+    assert!(!status_bar(&terminal).contains("[New File]"));
+}
+
+#[test]
+fn files_that_cant_be_read_are_never_written_over() {
+    // A directory opens but can't be read.
+    let fixture = Fixture::new("unreadable", "");
+    fs::remove_file(fixture.path()).unwrap();
+    fs::create_dir(fixture.path()).unwrap();
+    let (mut editor, terminal) = start(fixture.options());
+
+    assert!(matches!(
+        editor.document.origin(),
+        DocumentOrigin::OpenFailed { error, .. } if error.contains("directory")
+    ));
+    assert!(!editor.document.is_new_file());
+    assert!(status_bar(&terminal).contains("[Open failed]"));
+
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Char('x')],
+    );
+    // Saving asks for a name, and nothing is written when that's cancelled.
+    terminal.push_keys([KeyEvent::Ctrl('s'), KeyEvent::Esc]);
+    block_on(editor.process_keypress()).unwrap();
+    editor.refresh_screen().unwrap();
+
+    assert_eq!(message_bar(&terminal), "Save aborted.");
+    assert!(fs::metadata(fixture.path()).unwrap().is_dir());
+}
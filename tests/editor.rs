@@ -0,0 +1,355 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, status_bar, type_text, Fixture, HEIGHT};
+use zen::cli::LaunchOptions;
+use zen::config::Config;
+use zen::mode::EditorMode;
+use zen::terminal::{ansi, CursorStyle, KeyEvent};
+
+#[test]
+fn empty_document_shows_welcome_message() {
+    let (_, terminal) = start(LaunchOptions::default());
+    let rows = terminal.last_rendered_rows();
+
+    let welcome = format!("Zen {}", env!("CARGO_PKG_VERSION"));
+    assert!(rows[(HEIGHT as usize - 2) / 3].contains(&welcome));
+    assert_eq!(rows[0], "~");
+    assert!(rows[(HEIGHT as usize - 2) / 3 + 1].contains("HELP: i = insert"));
+}
+
+#[test]
+fn the_welcome_message_is_configurable() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    let config = Config::parse("welcome_message = \"Hi\\nfrom\\nzen {version}\"\n").unwrap();
+    editor.set_config(config);
+    editor.refresh_screen().unwrap();
+
+    // Centered on the row the single line message would be on.
+    let rows = terminal.last_rendered_rows();
+    let middle = (HEIGHT as usize - 2) / 3;
+    assert_eq!(rows[middle - 2], "~");
+    assert_eq!(rows[middle - 1], format!("~{}Hi", " ".repeat(28)));
+    assert_eq!(rows[middle], format!("~{}from", " ".repeat(27)));
+    assert!(rows[middle + 1].ends_with(&format!("zen {}", env!("CARGO_PKG_VERSION"))));
+
+    editor.set_config(Config::parse("welcome_message = \"\"\n").unwrap());
+    editor.refresh_screen().unwrap();
+    let rows = terminal.last_rendered_rows();
+    assert!(rows[..HEIGHT as usize - 2].iter().all(|row| row == "~"));
+}
+
+#[test]
+fn lines_past_the_end_are_drawn_with_a_muted_filler() {
+    let fixture = Fixture::new("filler.txt", "only line\n");
+    let (mut editor, terminal) = start(fixture.options());
+    let rows = terminal.last_rendered_rows();
+    assert_eq!(rows[1], "~");
+    // The bundled theme has no gutter color, so it's dimmed.
+    assert!(terminal
+        .output()
+        .concat()
+        .contains(&format!("{}~{}", ansi::DIM, ansi::DIM_RESET)));
+
+    editor.set_config(Config::parse("filler = \"\"\n").unwrap());
+    editor.refresh_screen().unwrap();
+    let rows = terminal.last_rendered_rows();
+    assert_eq!(rows[0], "only line");
+    assert!(rows[1..HEIGHT as usize - 2]
+        .iter()
+        .all(|row| row.is_empty()));
+
+    // The welcome message stays centered without a filler before it.
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    editor.set_config(Config::parse("filler = \"\"\n").unwrap());
+    editor.refresh_screen().unwrap();
+    let rows = terminal.last_rendered_rows();
+    let welcome = format!("Zen {}", env!("CARGO_PKG_VERSION"));
+    let middle = (HEIGHT as usize - 2) / 3;
+    assert_eq!(rows[middle].trim_start(), welcome);
+    assert_eq!(rows[middle].len() - welcome.len(), (60 - welcome.len()) / 2);
+}
+
+#[test]
+fn opened_file_contents_are_drawn() {
+    let fixture = Fixture::new("open.txt", "first line\nsecond line");
+    let (_, terminal) = start(fixture.options());
+    let rows = terminal.last_rendered_rows();
+
+    assert_eq!(rows[0], "first line");
+    assert_eq!(rows[1], "second line");
+    assert_eq!(rows[2], "~");
+}
+
+#[test]
+fn typing_in_insert_mode_draws_text() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "hello");
+
+    assert_eq!(terminal.last_rendered_rows()[0], "hello");
+    assert_eq!(editor.cursor_position.x, 5);
+}
+
+#[test]
+fn normal_mode_keys_do_not_insert() {
+    let fixture = Fixture::new("normal.txt", "abc");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "xyz");
+
+    assert_eq!(terminal.last_rendered_rows()[0], "abc");
+    assert!(!editor.document.is_dirty());
+}
+
+#[test]
+fn enter_splits_the_line() {
+    let fixture = Fixture::new("split.txt", "hello world");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "lllll");
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Char('\n')],
+    );
+    let rows = terminal.last_rendered_rows();
+
+    assert_eq!(rows[0], "hello");
+    assert_eq!(rows[1], " world");
+    assert_eq!(editor.document.len(), 2);
+}
+
+#[test]
+fn backspace_joins_lines() {
+    let fixture = Fixture::new("join.txt", "foo\nbar");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Down, KeyEvent::Backspace],
+    );
+
+    assert_eq!(terminal.last_rendered_rows()[0], "foobar");
+    assert_eq!(editor.document.len(), 1);
+}
+
+#[test]
+fn status_bar_shows_file_and_modified_state() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    assert!(status_bar(&terminal).starts_with("-- NORMAL -- [No Name] - 0 lines"));
+
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Char('a')],
+    );
+    assert!(status_bar(&terminal).contains("(modified)"));
+}
+
+#[test]
+fn the_mode_shows_in_the_status_bar_and_the_cursor_shape() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    assert!(terminal.output().concat().contains(ansi::CURSOR_BLOCK));
+
+    terminal.clear_output();
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    assert!(status_bar(&terminal).starts_with("-- INSERT -- "));
+    assert!(terminal.output().concat().contains(ansi::CURSOR_BAR));
+
+    // The shape is only written again when it changes.
+    terminal.clear_output();
+    press(&mut editor, &terminal, [KeyEvent::Char('a')]);
+    assert!(!terminal.output().concat().contains(ansi::CURSOR_BAR));
+
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    assert!(status_bar(&terminal).starts_with("-- NORMAL -- "));
+    assert!(terminal.output().concat().contains(ansi::CURSOR_BLOCK));
+    assert_eq!(EditorMode::Command.to_string(), "COMMAND");
+}
+
+#[test]
+fn cursor_shapes_can_be_turned_off() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    editor.set_config(Config::parse("cursor_shape = false\n").unwrap());
+
+    terminal.clear_output();
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    assert!(!terminal.output().concat().contains(ansi::CURSOR_BAR));
+
+    // The block drawn before it was turned off is still undone on quitting.
+    press(&mut editor, &terminal, [KeyEvent::Esc, KeyEvent::Ctrl('q')]);
+    assert!(terminal.output().concat().ends_with(&format!(
+        "{}{}",
+        ansi::CURSOR_DEFAULT,
+        ansi::CURSOR_SHOW
+    )));
+    assert_eq!(CursorStyle::Underline.escape(), ansi::CURSOR_UNDERLINE);
+}
+
+#[test]
+fn the_cursor_is_hidden_while_an_overlay_is_up() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    let last_frame = || {
+        let output = terminal.output().concat();
+        let start = output.rfind(ansi::CURSOR_HIDE).unwrap();
+        output[start..].to_string()
+    };
+
+    ex_command(&mut editor, &terminal, "messages");
+    assert!(!last_frame().contains(ansi::CURSOR_SHOW));
+
+    press(&mut editor, &terminal, [KeyEvent::Char('x')]);
+    assert!(last_frame().contains(ansi::CURSOR_SHOW));
+}
+
+#[test]
+fn status_bar_tracks_cursor_line() {
+    let fixture = Fixture::new("lines.txt", "one\ntwo\nthree");
+    let (mut editor, terminal) = start(fixture.options());
+    assert!(status_bar(&terminal).ends_with("Plain Text | 1/3"));
+
+    press(&mut editor, &terminal, [KeyEvent::Char('k')]);
+    assert!(status_bar(&terminal).ends_with("Plain Text | 2/3"));
+}
+
+#[test]
+fn line_argument_positions_the_cursor() {
+    let fixture = Fixture::new("line_arg.txt", "a\nb\nc\nd");
+    let options = LaunchOptions {
+        line: Some(3),
+        ..fixture.options()
+    };
+    let (editor, terminal) = start(options);
+
+    assert_eq!(editor.cursor_position.y, 2);
+    assert!(status_bar(&terminal).ends_with("3/4"));
+}
+
+#[test]
+fn read_only_document_refuses_edits() {
+    let fixture = Fixture::new("readonly.txt", "locked");
+    let options = LaunchOptions {
+        readonly: true,
+        ..fixture.options()
+    };
+    let (mut editor, terminal) = start(options);
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Char('x')],
+    );
+
+    assert_eq!(terminal.last_rendered_rows()[0], "locked");
+    assert_eq!(message_bar(&terminal), "File is read-only.");
+    assert!(!editor.document.is_dirty());
+}
+
+#[test]
+fn saving_writes_the_document() {
+    let fixture = Fixture::new("save.txt", "draft");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i'), KeyEvent::End]);
+    type_text(&mut editor, &terminal, " two");
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('s')]);
+
+    assert_eq!(fixture.contents(), "draft two\n");
+    assert_eq!(message_bar(&terminal), "File saved successfully.");
+}
+
+#[test]
+fn cursor_movement_redraws_no_rows() {
+    let fixture = Fixture::new("redraw.txt", "alpha\nbeta\ngamma");
+    let (mut editor, terminal) = start(fixture.options());
+    terminal.clear_output();
+    press(&mut editor, &terminal, [KeyEvent::Char('l')]);
+
+    let output = terminal.output().concat();
+    assert!(!output.contains("alpha"));
+    assert!(!output.contains("beta"));
+}
+
+#[test]
+fn ctrl_g_shows_file_info() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "one two");
+    press(&mut editor, &terminal, [KeyEvent::Esc, KeyEvent::Ctrl('g')]);
+
+    // The message bar cuts the message off at the terminal width.
+    assert_eq!(
+        message_bar(&terminal),
+        "\"[No Name]\" [modified] line 1 of 1, col 7 -- 100% -- (1, 7) "
+    );
+    assert_eq!(editor.document.byte_count(), 8);
+}
+
+#[test]
+fn undo_and_redo_edits() {
+    let fixture = Fixture::new("undo.txt", "ab\ncd");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Char('x')],
+    );
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('\n'), KeyEvent::Esc],
+    );
+    assert_eq!(editor.document.contents(), "x\nab\ncd\n");
+
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "xab\ncd\n");
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "ab\ncd\n");
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(message_bar(&terminal), "Already at oldest change");
+
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Ctrl('r'), KeyEvent::Ctrl('r')],
+    );
+    assert_eq!(editor.document.contents(), "x\nab\ncd\n");
+    assert_eq!(terminal.last_rendered_rows()[0], "x");
+}
+
+#[test]
+fn characters_typed_in_a_row_are_undone_together() {
+    let fixture = Fixture::new("undo.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "hello");
+    press(&mut editor, &terminal, [KeyEvent::Left]);
+    type_text(&mut editor, &terminal, "XY");
+    press(&mut editor, &terminal, [KeyEvent::Esc, KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "!");
+    assert_eq!(editor.document.contents(), "hellX!Yo\n");
+
+    press(&mut editor, &terminal, [KeyEvent::Esc, KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "hellXYo\n");
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "hello\n");
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "");
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('r')]);
+    assert_eq!(editor.document.contents(), "hello\n");
+}
+
+#[test]
+fn deleting_ends_the_typed_undo_step() {
+    let fixture = Fixture::new("undo.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "abc");
+    press(&mut editor, &terminal, [KeyEvent::Backspace]);
+    type_text(&mut editor, &terminal, "de");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    assert_eq!(editor.document.contents(), "abde\n");
+
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "ab\n");
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "abc\n");
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "");
+}
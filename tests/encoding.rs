@@ -0,0 +1,127 @@
+mod common;
+
+use common::{block_on, ex_command, message_bar, press, start, type_text, Fixture};
+use std::fs;
+use zen::commands::ex::{self, ExCommand};
+use zen::document::Document;
+use zen::encoding::Encoding;
+use zen::terminal::KeyEvent;
+
+#[test]
+fn text_is_encoded_with_question_marks_for_missing_characters() {
+    let cases = [
+        (Encoding::Utf8, "é€", "é€".as_bytes().to_vec(), 0),
+        (Encoding::Latin1, "café", b"caf\xE9".to_vec(), 0),
+        (Encoding::Latin1, "5€ ✓", b"5? ?".to_vec(), 2),
+        (
+            Encoding::Windows1252,
+            "5€ “x”",
+            b"5\x80 \x93x\x94".to_vec(),
+            0,
+        ),
+        (Encoding::Windows1252, "\u{85}", b"?".to_vec(), 1),
+    ];
+
+    for (encoding, text, bytes, unencodable) in cases {
+        assert_eq!(encoding.encode(text), (bytes, unencodable), "{text}");
+    }
+}
+
+#[test]
+fn bytes_that_arent_utf8_are_read_in_a_single_byte_encoding() {
+    assert_eq!(
+        Encoding::decode_single_byte(b"\x93caf\xE9\x94"),
+        ("“café”".to_string(), Encoding::Windows1252)
+    );
+    assert_eq!(Encoding::Utf8.decode(b"caf\xE9"), None);
+}
+
+#[test]
+fn every_byte_survives_a_single_byte_round_trip() {
+    let bytes: Vec<u8> = (0..=u8::MAX).collect();
+    for encoding in [Encoding::Latin1, Encoding::Windows1252] {
+        let text = encoding.decode(&bytes).unwrap();
+        assert_eq!(encoding.encode(&text), (bytes.clone(), 0), "{encoding:?}");
+    }
+    // The bytes Windows-1252 leaves undefined are read as the C1 controls, like Latin-1's.
+    for byte in [0x81, 0x8D, 0x8F, 0x90, 0x9D] {
+        assert_eq!(
+            Encoding::Windows1252.decode(&[byte]),
+            Some(char::from(byte).to_string())
+        );
+    }
+}
+
+#[test]
+fn files_that_arent_utf8_are_saved_back_unchanged() {
+    let fixture = Fixture::new("cp1252.txt", "");
+    fs::write(fixture.path(), b"\x93caf\xE9\x94\n").unwrap();
+    let (mut editor, terminal) = start(fixture.options());
+    assert_eq!(editor.document.contents(), "“café”\n");
+    assert_eq!(editor.document.encoding(), Encoding::Windows1252);
+
+    ex_command(&mut editor, &terminal, "w");
+    assert_eq!(fs::read(fixture.path()).unwrap(), b"\x93caf\xE9\x94\n");
+}
+
+#[test]
+fn big_files_that_arent_utf8_are_saved_back_unchanged() {
+    // Big enough to be read a chunk at a time.
+    let fixture = Fixture::new("big_cp1252.txt", "");
+    let mut contents = b"\x93caf\xE9\x94\n".to_vec();
+    contents.extend(b"line\n".repeat(1024 * 1024));
+    fs::write(fixture.path(), &contents).unwrap();
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "w");
+
+    assert_eq!(editor.document.row_strings(0..1), ["“café”"]);
+    assert_eq!(editor.document.encoding(), Encoding::Windows1252);
+    assert!(fs::read(fixture.path()).unwrap() == contents);
+}
+
+#[test]
+fn documents_are_read_and_written_on_the_runtime() {
+    let fixture = Fixture::new("async_save.txt", "café\n");
+    let mut document = block_on(Document::open(fixture.path())).unwrap();
+    assert_eq!(
+        block_on(document.save_as_encoding(Encoding::Latin1)).unwrap(),
+        0
+    );
+    assert_eq!(fs::read(fixture.path()).unwrap(), b"caf\xE9\n");
+    assert_eq!(document.encoding(), Encoding::Latin1);
+
+    assert!(block_on(Document::open("missing/async_save.txt")).is_err());
+}
+
+#[test]
+fn enc_is_parsed_on_write() {
+    assert_eq!(
+        ex::parse("w ++enc=Latin1").unwrap(),
+        ExCommand::WriteEncoded(Encoding::Latin1)
+    );
+    assert_eq!(
+        ex::parse("write ++enc=utf-8").unwrap(),
+        ExCommand::WriteEncoded(Encoding::Utf8)
+    );
+    assert!(ex::parse("w ++enc=ebcdic").is_err());
+    assert!(ex::parse("w other.txt").is_err());
+}
+
+#[test]
+fn later_writes_keep_the_encoding() {
+    let fixture = Fixture::new("legacy.ini", "name=café €\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "w ++enc=latin1");
+    assert_eq!(
+        message_bar(&terminal),
+        "1 characters could not be encoded, replaced with '?'"
+    );
+    assert_eq!(fs::read(fixture.path()).unwrap(), b"name=caf\xE9 ?\n");
+    assert_eq!(editor.document.encoding(), Encoding::Latin1);
+
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "é");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    ex_command(&mut editor, &terminal, "w");
+    assert_eq!(fs::read(fixture.path()).unwrap()[0], 0xE9);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::{block_on, message_bar, press, start, Fixture};
+use zen::cli::LaunchOptions;
+use zen::error::ZenError;
+use zen::terminal::KeyEvent;
+
+#[test]
+fn failed_save_is_reported_in_the_message_bar() {
+    let fixture = Fixture::new("unsaved.txt", "text");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.document.file_name = Some(format!("{}.d/unsaved.txt", fixture.path()));
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Char('x')],
+    );
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('s')]);
+
+    assert!(message_bar(&terminal).starts_with("ERR: /"));
+    assert!(editor.document.is_dirty());
+}
+
+#[test]
+fn missing_file_is_reported_in_the_message_bar() {
+    let options = LaunchOptions {
+        file_name: Some("/nonexistent/zen/missing.txt".to_string()),
+        ..LaunchOptions::default()
+    };
+    let (editor, terminal) = start(options);
+
+    assert!(message_bar(&terminal).starts_with("ERR: Could not open file /nonexistent/zen"));
+    assert!(editor.document.is_empty());
+}
+
+#[test]
+fn terminal_errors_end_the_run_loop() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    terminal.push_keys([KeyEvent::Char('i'), KeyEvent::Char('x')]);
+    terminal.close_keys();
+
+    let error = block_on(editor.run()).unwrap_err();
+    assert!(matches!(error, ZenError::Terminal(_)));
+    assert!(error.is_fatal());
+    assert!(terminal.last_rendered_rows().iter().all(String::is_empty));
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::{block_on, keys, start, Fixture, HEIGHT};
+use std::time::Duration;
+use tokio::time;
+use tokio_stream::StreamExt;
+use zen::cli::LaunchOptions;
+use zen::commands::Command;
+use zen::event::COMMAND_QUEUE_CAPACITY;
+use zen::mode::EditorMode;
+use zen::terminal::{KeyEvent, MockTerminal, TerminalBackend};
+
+const COUNT: usize = 300;
+
+#[test]
+fn keys_and_queued_commands_interleave_without_loss() {
+    let fixture = Fixture::new("stress.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    let queue = editor.command_queue();
+
+    terminal.push_keys([KeyEvent::Char('i')]);
+    terminal.push_keys(keys(&"a".repeat(COUNT)));
+
+    let producer = async {
+        // More than the queue holds, so sending has to wait for the editor to catch up.
+        for _ in 0..COUNT {
+            queue.send(Command::DocumentInsert('b')).await.unwrap();
+        }
+        // Queued before these keys, so handled before them too.
+        terminal.push_keys([KeyEvent::Esc, KeyEvent::Ctrl('s'), KeyEvent::Char('i')]);
+        terminal.push_keys([KeyEvent::Ctrl('s'), KeyEvent::Esc, KeyEvent::Ctrl('q')]);
+    };
+
+    let (result, ()) = block_on(async { tokio::join!(editor.run(), producer) });
+    result.unwrap();
+
+    let contents = fixture.contents();
+    assert_eq!(contents.matches('a').count(), COUNT);
+    assert_eq!(contents.matches('b').count(), COUNT);
+    assert_eq!(contents.len(), COUNT * 2 + 1);
+}
+
+#[test]
+fn shutdown_stops_the_event_loop() {
+    let (mut editor, _terminal) = start(LaunchOptions::default());
+    let queue = editor.command_queue();
+    queue.shutdown();
+
+    block_on(editor.run()).unwrap();
+}
+
+#[test]
+fn queued_commands_run_while_no_key_is_pressed() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    let queue = editor.command_queue();
+
+    let producer = async {
+        queue.send(Command::DocumentInsert('z')).await.unwrap();
+        queue.send(Command::DocumentQuit).await.unwrap();
+    };
+    let (result, ()) = block_on(async { tokio::join!(editor.run(), producer) });
+    result.unwrap();
+
+    assert_eq!(editor.document.contents(), "z\n");
+    let frames = terminal.frames();
+    assert!(frames.iter().any(|frame| frame[0] == "z"));
+}
+
+#[test]
+fn commands_pushed_to_a_full_queue_run_in_order_once_it_drains() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    let queue = editor.command_queue();
+
+    // Nothing is draining the queue yet, so half of these have to wait for room.
+    let text: String = (0..COMMAND_QUEUE_CAPACITY * 3 / 2)
+        .map(|i| char::from(b'a' + (i % 26) as u8))
+        .collect();
+    queue
+        .push(Command::EditorSwitchMode(EditorMode::Insert))
+        .unwrap();
+    for c in text.chars() {
+        queue.push(Command::DocumentInsert(c)).unwrap();
+    }
+    block_on(async {
+        queue.send(Command::DocumentInsert('!')).await.unwrap();
+    });
+    queue.push(Command::DocumentQuit).unwrap();
+
+    block_on(editor.run()).unwrap();
+
+    assert_eq!(editor.document.contents(), format!("{}!\n", text));
+    let message_bar = HEIGHT as usize - 1;
+    let frames = terminal.frames();
+    assert!(frames
+        .iter()
+        .any(|frame| frame[message_bar].starts_with("Editor busy, ")));
+}
+
+#[test]
+fn the_key_stream_keeps_a_read_that_was_given_up_on() {
+    let terminal = MockTerminal::new(60, 12);
+    let mut stream = terminal.input_events_stream();
+
+    block_on(async {
+        // Stops waiting before a key comes, with the read still going on its task.
+        let waited = time::timeout(Duration::from_millis(50), stream.next()).await;
+        assert!(waited.is_err());
+
+        terminal.push_keys([KeyEvent::Char('a'), KeyEvent::Char('b')]);
+        assert_eq!(stream.next().await.unwrap().unwrap(), KeyEvent::Char('a'));
+        assert_eq!(stream.next().await.unwrap().unwrap(), KeyEvent::Char('b'));
+    });
+}
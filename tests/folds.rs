@@ -0,0 +1,132 @@
+mod common;
+
+use common::{message_bar, press, start, Fixture, HEIGHT};
+use zen::document::RowSplice;
+use zen::editor::SearchDirection;
+use zen::fold::Folds;
+use zen::terminal::KeyEvent;
+
+const SOURCE: &str = "fn a() {\n    one\n\n    two\n}\nfn b() {\n    three\n}\n";
+
+fn fold_line(text: &str) -> String {
+    let width = common::WIDTH as usize;
+    format!("{}{}", text, "·".repeat(width - text.chars().count()))
+}
+
+#[test]
+fn za_folds_the_rows_indented_further_and_opens_them_again() {
+    let fixture = Fixture::new("fold.rs", SOURCE);
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('z'), KeyEvent::Char('a')],
+    );
+
+    let rows = terminal.last_rendered_rows();
+    assert_eq!(rows[0], fold_line("+--   4 lines: fn a() { "));
+    assert_eq!(rows[1], "}");
+    assert_eq!(rows[2], "fn b() {");
+
+    // `k` moves down in zen, over the fold.
+    press(&mut editor, &terminal, [KeyEvent::Char('k')]);
+    assert_eq!(editor.cursor_position.y, 4);
+    press(&mut editor, &terminal, [KeyEvent::Char('j')]);
+    assert_eq!(editor.cursor_position.y, 0);
+
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('z'), KeyEvent::Char('a')],
+    );
+    assert_eq!(terminal.last_rendered_rows()[1], "    one");
+}
+
+#[test]
+fn za_without_indented_rows_below_finds_no_fold() {
+    let fixture = Fixture::new("flat.txt", "one\ntwo\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('z'), KeyEvent::Char('a')],
+    );
+
+    assert_eq!(message_bar(&terminal), "ERR: No fold found");
+}
+
+#[test]
+fn searching_into_a_fold_opens_it() {
+    let fixture = Fixture::new("search.rs", SOURCE);
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('z'), KeyEvent::Char('a')],
+    );
+    editor.search_set_pattern("two".to_string(), SearchDirection::Forward);
+    editor.refresh_screen().unwrap();
+
+    assert_eq!(editor.cursor_position.y, 3);
+    assert_eq!(terminal.last_rendered_rows()[3], "    two");
+}
+
+#[test]
+fn closed_folds_count_as_one_line_when_scrolling() {
+    let mut contents = String::from("top\n");
+    for n in 1..20 {
+        contents.push_str(&format!("    line {}\n", n));
+    }
+    for n in 20..30 {
+        contents.push_str(&format!("line {}\n", n));
+    }
+    let fixture = Fixture::new("long.txt", &contents);
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('z'), KeyEvent::Char('a')],
+    );
+    let rows = terminal.last_rendered_rows();
+    assert_eq!(rows[1], "line 20");
+    assert_eq!(rows[HEIGHT as usize - 3], "line 28");
+
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('K')]);
+    let rows = terminal.last_rendered_rows();
+    assert_eq!(editor.cursor_position.y, 29);
+    assert_eq!(rows[0], "line 20");
+    assert_eq!(rows[HEIGHT as usize - 3], "line 29");
+}
+
+#[test]
+fn edits_move_folds_along_or_open_them() {
+    let mut folds = Folds::default();
+    folds.close(4..8);
+    folds.close(10..12);
+
+    // A line added above moves both down.
+    folds.splice(RowSplice {
+        at: 1,
+        removed: 1,
+        inserted: 2,
+    });
+    assert_eq!(folds.at(5), Some(5..9));
+    assert_eq!(folds.at(11), Some(11..13));
+
+    // An edit within the first opens it, the second moves up a line.
+    folds.splice(RowSplice {
+        at: 6,
+        removed: 2,
+        inserted: 1,
+    });
+    assert_eq!(folds.at(5), None);
+    assert_eq!(folds.at(10), Some(10..12));
+
+    // Closing around a fold takes it in.
+    folds.close(9..13);
+    assert_eq!(folds.at(10), Some(9..13));
+    assert_eq!(
+        folds.lines(0, 15).collect::<Vec<_>>(),
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 13, 14]
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::{message_bar, press, start, type_text, Fixture};
+use std::fs;
+use std::path::Path;
+use zen::cli::LaunchOptions;
+use zen::editor::Editor;
+use zen::row::Row;
+use zen::terminal::{KeyEvent, MockTerminal};
+
+// A project with a README, and src/main.rs opened in the editor, naming files on its lines.
+fn project(name: &str, main: &str) -> (Fixture, Editor, MockTerminal) {
+    let readme = Fixture::new(&format!("{}.md", name), "readme\n");
+    let src = Path::new(readme.path()).with_file_name("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(src.join("lib.rs"), "pub fn lib() {}\n").unwrap();
+    fs::write(src.join("main.rs"), main).unwrap();
+    let (editor, terminal) = start(LaunchOptions {
+        file_name: Some(src.join("main.rs").display().to_string()),
+        ..LaunchOptions::default()
+    });
+    (readme, editor, terminal)
+}
+
+#[test]
+fn paths_take_in_the_characters_file_names_are_written_with() {
+    let row = Row::from("see ../src/main-2.rs. or ~/notes.md");
+    assert_eq!(row.path_at(4), Some("../src/main-2.rs"));
+    assert_eq!(row.path_at(10), Some("../src/main-2.rs"));
+    assert_eq!(row.path_at(21), Some("or"));
+    assert_eq!(row.path_at(26), Some("~/notes.md"));
+    assert_eq!(row.word_at(8), Some("src"));
+    assert_eq!(Row::from("...").path_at(0), None);
+}
+
+#[test]
+fn gf_opens_files_relative_to_the_document_or_its_parents() {
+    let (readme, mut editor, terminal) = project("gf_relative", "../gf_relative.md\n");
+    type_text(&mut editor, &terminal, "gf");
+    assert_eq!(editor.document.contents(), "readme\n");
+    assert_eq!(
+        editor.document.file_name.as_deref(),
+        fs::canonicalize(readme.path()).unwrap().to_str()
+    );
+
+    // Found from the project's directory, a parent of the document's.
+    let (_readme, mut editor, terminal) = project("gf_parent", "mod lib;\nsrc/lib.rs\n");
+    type_text(&mut editor, &terminal, "kgf");
+    assert_eq!(editor.document.contents(), "pub fn lib() {}\n");
+}
+
+#[test]
+fn gf_reports_missing_files_and_keeps_unsaved_changes() {
+    let (_readme, mut editor, terminal) = project("gf_missing", "missing.rs\nsrc/lib.rs\n");
+    type_text(&mut editor, &terminal, "gf");
+    assert_eq!(message_bar(&terminal), "ERR: No such file: missing.rs");
+    assert_eq!(editor.document.contents(), "missing.rs\nsrc/lib.rs\n");
+
+    // Changed, so the file opens in a tab of its own.
+    press(
+        &mut editor,
+        &terminal,
+        [
+            KeyEvent::Char('i'),
+            KeyEvent::Char('x'),
+            KeyEvent::Esc,
+            KeyEvent::Char('k'),
+        ],
+    );
+    type_text(&mut editor, &terminal, "gf");
+    assert_eq!(editor.document.contents(), "pub fn lib() {}\n");
+    type_text(&mut editor, &terminal, "gT");
+    assert_eq!(editor.document.contents(), "xmissing.rs\nsrc/lib.rs\n");
+    assert!(editor.document.is_dirty());
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, status_bar, Fixture};
+use zen::editor::{Position, SearchDirection};
+use zen::hex::{self, HexView};
+use zen::terminal::KeyEvent;
+
+#[test]
+fn bytes_are_dumped_sixteen_to_a_line_with_their_ascii() {
+    let view = HexView::new(b"Hello\0world, this is zen!".to_vec());
+    let rows: Vec<String> = view.rows().iter().map(|row| row.string.clone()).collect();
+
+    assert_eq!(
+        rows,
+        [
+            "00000000  48 65 6c 6c 6f 00 77 6f 72 6c 64 2c 20 74 68 69  |Hello.world, thi|",
+            "00000010  73 20 69 73 20 7a 65 6e 21                       |s is zen!|",
+        ]
+    );
+}
+
+#[test]
+fn cursor_positions_map_to_byte_offsets() {
+    let view = HexView::new((0..40).collect());
+
+    assert_eq!(view.position_of(17), Position { x: 13, y: 1 });
+    assert_eq!(view.offset_at(&Position { x: 13, y: 1 }), 17);
+    // The space after a byte's digits and its character in the gutter are the same byte.
+    assert_eq!(view.offset_at(&Position { x: 15, y: 1 }), 17);
+    assert_eq!(view.offset_at(&Position { x: 61, y: 1 }), 17);
+    assert_eq!(
+        view.snap(&Position { x: 15, y: 1 }),
+        Position { x: 13, y: 1 }
+    );
+    // Past the last byte is the last byte.
+    assert_eq!(view.offset_at(&Position { x: 50, y: 2 }), 39);
+}
+
+#[test]
+fn hex_queries_are_read_as_bytes() {
+    assert_eq!(
+        hex::parse_bytes("de ad be ef"),
+        Some(vec![0xde, 0xad, 0xbe, 0xef])
+    );
+    assert_eq!(
+        hex::parse_bytes("DEADbeef"),
+        Some(vec![0xde, 0xad, 0xbe, 0xef])
+    );
+    assert_eq!(hex::parse_bytes("dea"), None);
+    assert_eq!(hex::parse_bytes("hello"), None);
+    assert!(hex::is_binary(b"ab\0cd"));
+    assert!(!hex::is_binary(b"abcd\n"));
+}
+
+#[test]
+fn binary_files_open_as_a_read_only_hex_dump() {
+    let fixture = Fixture::new("data.bin", "\0\u{1}abcdefghijklmnopqrstuvwxyz");
+    let (mut editor, terminal) = start(fixture.options());
+
+    assert!(editor.document.hex().is_some());
+    assert!(editor.document.is_read_only());
+    assert_eq!(editor.document.len(), 2);
+    assert_eq!(
+        terminal.last_rendered_rows()[0].trim_end(),
+        "00000000  00 01 61 62 63 64 65 66 67 68 69 6a 6b 6c 6d 6e  |"
+    );
+    assert_eq!(editor.cursor_position, Position { x: 10, y: 0 });
+    assert!(status_bar(&terminal).contains("[byte 0x0]"));
+
+    // `l` and `h` go from byte to byte, `k` keeps to the same column a line down.
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('l'), KeyEvent::Char('l')],
+    );
+    assert_eq!(editor.cursor_position, Position { x: 16, y: 0 });
+    press(&mut editor, &terminal, [KeyEvent::Char('h')]);
+    assert_eq!(editor.cursor_position, Position { x: 13, y: 0 });
+    press(&mut editor, &terminal, [KeyEvent::Char('k')]);
+    assert_eq!(editor.cursor_position, Position { x: 13, y: 1 });
+    assert!(status_bar(&terminal).contains("[byte 0x11]"));
+
+    // The second line stops at its last byte.
+    press(&mut editor, &terminal, [KeyEvent::Char('L')]);
+    assert_eq!(editor.cursor_position, Position { x: 71, y: 1 });
+    assert!(status_bar(&terminal).contains("[byte 0x1b]"));
+
+    press(&mut editor, &terminal, [KeyEvent::Char('x')]);
+    assert_eq!(fixture.contents(), "\0\u{1}abcdefghijklmnopqrstuvwxyz");
+}
+
+#[test]
+fn hex_searches_find_bytes_across_lines() {
+    let mut contents = "\0".repeat(14);
+    contents.push_str("\u{7f}\u{1}\u{7f}\u{1}");
+    contents.push_str(&"\0".repeat(8));
+    contents.push_str("\u{7f}\u{1}\u{7f}\u{1}");
+    let fixture = Fixture::new("search.bin", &contents);
+    let (mut editor, terminal) = start(fixture.options());
+
+    editor.search_set_pattern("7f 01 7f 01".to_string(), SearchDirection::Forward);
+    assert_eq!(editor.cursor_position, Position { x: 52, y: 0 });
+
+    press(&mut editor, &terminal, [KeyEvent::Char('n')]);
+    assert_eq!(editor.cursor_position, Position { x: 40, y: 1 });
+    press(&mut editor, &terminal, [KeyEvent::Char('n')]);
+    assert_eq!(editor.cursor_position, Position { x: 52, y: 0 });
+    assert_eq!(
+        message_bar(&terminal),
+        "search hit BOTTOM, continuing at TOP"
+    );
+}
+
+#[test]
+fn hex_shows_a_text_file_as_a_dump_and_back() {
+    let fixture = Fixture::new("text.txt", "hi\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Char('o'), KeyEvent::Esc],
+    );
+    ex_command(&mut editor, &terminal, "hex");
+
+    // The dump is of the text as it would be saved, edits included.
+    assert_eq!(
+        editor.document.row(0).unwrap().string,
+        "00000000  6f 68 69 0a                                      |ohi.|"
+    );
+    assert!(editor.document.is_read_only());
+
+    ex_command(&mut editor, &terminal, "hex");
+    assert_eq!(editor.document.contents(), "ohi\n");
+    assert!(editor.document.is_dirty());
+    assert!(!editor.document.is_read_only());
+    assert_eq!(editor.cursor_position, Position::default());
+}
+
+#[test]
+fn binary_files_have_no_text_to_go_back_to() {
+    let fixture = Fixture::new("only.bin", "\0\0");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "hex");
+
+    assert_eq!(
+        message_bar(&terminal),
+        "ERR: Binary files can only be shown as hex"
+    );
+    assert!(editor.document.hex().is_some());
+}
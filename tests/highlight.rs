@@ -0,0 +1,85 @@
+mod common;
+
+use common::{block_on, ex_command, message_bar, start, status_bar, Fixture};
+use zen::document::Document;
+use zen::editor::Position;
+use zen::terminal::ColorSupport;
+
+const TRUE_COLOR: &str = "\x1b[38;2;";
+const ANSI_256: &str = "\x1b[38;5;";
+
+fn rendered(document: &Document, row: usize) -> String {
+    document.row(row).unwrap().render().to_string()
+}
+
+#[test]
+fn edited_rows_are_highlighted_again() {
+    let fixture = Fixture::new("edit.rs", "fn main() {\n    let x = 1;\n}\n");
+    let mut document = block_on(Document::open(fixture.path())).unwrap();
+    document.highlight(0..3, ColorSupport::TrueColor).unwrap();
+    assert!(rendered(&document, 1).contains(TRUE_COLOR));
+
+    document.insert(&Position { x: 4, y: 1 }, 'm');
+    assert_eq!(rendered(&document, 1), "    mlet x = 1;");
+
+    document.highlight(0..3, ColorSupport::TrueColor).unwrap();
+    let row = rendered(&document, 1);
+    assert!(row.contains(TRUE_COLOR) && row.contains("mlet"));
+    assert!(rendered(&document, 0).contains(TRUE_COLOR));
+}
+
+#[test]
+fn rows_scrolled_into_view_are_highlighted() {
+    let fixture = Fixture::copy("hello.rs");
+    let mut document = block_on(Document::open(fixture.path())).unwrap();
+    document.highlight(0..1, ColorSupport::TrueColor).unwrap();
+    assert_eq!(rendered(&document, 2), "}");
+
+    document.highlight(1..3, ColorSupport::TrueColor).unwrap();
+    assert!(rendered(&document, 2).contains(TRUE_COLOR));
+}
+
+#[test]
+fn changing_colors_highlights_the_view_again() {
+    let fixture = Fixture::copy("hello.rs");
+    let mut document = block_on(Document::open(fixture.path())).unwrap();
+    document.highlight(0..3, ColorSupport::TrueColor).unwrap();
+    document.highlight(0..3, ColorSupport::Ansi256).unwrap();
+
+    for row in 0..3 {
+        let rendered = rendered(&document, row);
+        assert!(rendered.contains(ANSI_256) && !rendered.contains(TRUE_COLOR));
+    }
+}
+
+#[test]
+fn a_syntax_set_by_name_wins_over_the_extension() {
+    let fixture = Fixture::new("script", "def main():\n    pass\n");
+    let mut document = block_on(Document::open(fixture.path())).unwrap();
+    document.highlight(0..2, ColorSupport::TrueColor).unwrap();
+    assert_eq!(rendered(&document, 0), "def main():");
+    assert_eq!(document.syntax_name(), "Unknown");
+
+    document.set_syntax("python").unwrap();
+    document.highlight(0..2, ColorSupport::TrueColor).unwrap();
+    assert!(rendered(&document, 0).contains(TRUE_COLOR));
+    assert_eq!(document.syntax_name(), "Python");
+
+    document.set_syntax("off").unwrap();
+    document.highlight(0..2, ColorSupport::TrueColor).unwrap();
+    assert_eq!(rendered(&document, 0), "def main():");
+
+    assert!(document.set_syntax("klingon").is_err());
+}
+
+#[test]
+fn set_syntax_changes_the_status_bar() {
+    let fixture = Fixture::new("notes.conf", "x = 1\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "set syntax=python");
+    assert!(status_bar(&terminal).ends_with("Python | 1/1"));
+
+    ex_command(&mut editor, &terminal, "set syntax=klingon");
+    assert!(message_bar(&terminal).ends_with("Unknown syntax: klingon"));
+    assert!(status_bar(&terminal).ends_with("Python | 1/1"));
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, start_with_clock, Fixture};
+use std::time::Duration;
+use zen::config::Config;
+use zen::terminal::KeyEvent;
+
+fn hooks(hooks: &[(&str, &str)]) -> Config {
+    let toml: String = hooks
+        .iter()
+        .map(|(event, run)| format!("[[hooks]]\nevent = \"{}\"\nrun = \"{}\"\n", event, run))
+        .collect();
+    Config::parse(&toml).unwrap()
+}
+
+#[test]
+fn hooks_run_in_the_order_they_are_listed() {
+    let fixture = Fixture::new("order.txt", "a  \n");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.set_config(hooks(&[
+        ("BufWritePre", ":s/a/b/"),
+        ("BufWritePre", ":s/b/c/"),
+        ("BufWritePre", "trim_trailing_whitespace"),
+    ]));
+    ex_command(&mut editor, &terminal, "w");
+
+    assert_eq!(fixture.contents(), "c\n");
+    assert!(!editor.document.is_dirty());
+}
+
+#[test]
+fn a_failing_hook_is_reported_without_blocking_the_write() {
+    let fixture = Fixture::new("failing.txt", "one\n");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.set_config(hooks(&[
+        ("BufWritePre", ":s/zzz/x/"),
+        ("BufWritePre", ":s/one/two/"),
+    ]));
+    ex_command(&mut editor, &terminal, "w");
+
+    assert_eq!(fixture.contents(), "two\n");
+    assert_eq!(
+        message_bar(&terminal),
+        "ERR: Pattern not found: zzz (2 more)"
+    );
+}
+
+#[test]
+fn a_required_pre_hook_that_fails_cancels_the_write() {
+    let fixture = Fixture::new("required.txt", "one\n");
+    let (mut editor, terminal) = start(fixture.options());
+    let config = "[[hooks]]\nevent = \"BufWritePre\"\nrun = \":s/zzz/x/\"\nrequired = true\n";
+    editor.set_config(Config::parse(config).unwrap());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Char('x'), KeyEvent::Esc],
+    );
+    ex_command(&mut editor, &terminal, "w");
+
+    assert_eq!(fixture.contents(), "one\n");
+    assert!(editor.document.is_dirty());
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    assert_eq!(
+        message_bar(&terminal),
+        "Write cancelled by a BufWritePre hook"
+    );
+}
+
+#[test]
+fn post_hooks_run_after_the_write() {
+    let fixture = Fixture::new("post.txt", "one\n");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.set_config(hooks(&[("BufWritePost", ":s/one/two/")]));
+    ex_command(&mut editor, &terminal, "w");
+
+    assert_eq!(fixture.contents(), "one\n");
+    assert_eq!(editor.document.contents(), "two\n");
+}
+
+#[test]
+fn mode_changes_and_cursor_moves_run_hooks() {
+    let fixture = Fixture::new("events.txt", "ab \n");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.set_config(hooks(&[("ModeChanged", ":set list")]));
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    assert_eq!(terminal.last_rendered_rows()[0], "ab·");
+
+    let (mut editor, terminal, clock) = start_with_clock(fixture.options());
+    editor.set_config(hooks(&[("CursorMoved", ":set list")]));
+    press(&mut editor, &terminal, [KeyEvent::Char('l')]);
+    assert_eq!(terminal.last_rendered_rows()[0], "ab ");
+
+    // Only once the cursor rested.
+    clock.advance(Duration::from_millis(200));
+    press(&mut editor, &terminal, [KeyEvent::Char('l')]);
+    assert_eq!(terminal.last_rendered_rows()[0], "ab·");
+}
+
+#[test]
+fn unknown_hook_actions_are_config_errors() {
+    assert!(Config::parse("[[hooks]]\nevent = \"BufWritePre\"\nrun = \"explode\"\n").is_err());
+    assert!(Config::parse("[[hooks]]\nevent = \"BufWritePre\"\nrun = \":bogus\"\n").is_err());
+    assert!(Config::parse("[[hooks]]\nevent = \"BufClose\"\nrun = \":retab\"\n").is_err());
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::{press, start, type_text, Fixture};
+use zen::editor::Position;
+use zen::row::Row;
+use zen::terminal::KeyEvent;
+
+#[test]
+fn word_start_skips_blanks_then_one_kind_of_word() {
+    let row = Row::from("let x = foo.bar(baz)  ");
+    assert_eq!(row.word_start(22), 19);
+    assert_eq!(row.word_start(19), 16);
+    assert_eq!(row.word_start(16), 15);
+    assert_eq!(row.word_start(15), 12);
+    assert_eq!(row.word_start(8), 6);
+    assert_eq!(row.word_start(2), 0);
+    assert_eq!(row.word_start(0), 0);
+}
+
+#[test]
+fn ctrl_w_deletes_the_word_before_the_cursor_as_one_undo_step() {
+    let fixture = Fixture::new("words.txt", "hello brave world\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i'), KeyEvent::End]);
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('w')]);
+    assert_eq!(editor.document.contents(), "hello brave \n");
+    assert_eq!(editor.cursor_position.x, 12);
+
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('w')]);
+    assert_eq!(editor.document.contents(), "hello \n");
+
+    press(&mut editor, &terminal, [KeyEvent::Esc, KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "hello brave \n");
+}
+
+#[test]
+fn ctrl_w_at_the_start_of_a_row_does_nothing() {
+    let fixture = Fixture::new("join.txt", "one\ntwo\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('k'), KeyEvent::Char('i')],
+    );
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('w')]);
+    assert_eq!(editor.document.contents(), "one\ntwo\n");
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 1 });
+}
+
+#[test]
+fn ctrl_w_deletes_whole_graphemes() {
+    let fixture = Fixture::new("graphemes.txt", "cafe\u{301} 👍🏽\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i'), KeyEvent::End]);
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('w')]);
+    assert_eq!(editor.document.contents(), "cafe\u{301} \n");
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('w')]);
+    assert_eq!(editor.document.contents(), "\n");
+}
+
+#[test]
+fn ctrl_u_keeps_the_text_from_before_the_insert() {
+    let fixture = Fixture::new("line.txt", "keep\nnext\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i'), KeyEvent::End]);
+    type_text(&mut editor, &terminal, " typo here");
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('u')]);
+    assert_eq!(editor.document.contents(), "keep\nnext\n");
+    assert_eq!(editor.cursor_position.x, 4);
+
+    // There's nothing typed left to delete.
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('u')]);
+    assert_eq!(editor.document.contents(), "keep\nnext\n");
+
+    // What was deleted isn't part of the text inserted again with `Ctrl-A`.
+    type_text(&mut editor, &terminal, "new");
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Esc, KeyEvent::Char('k'), KeyEvent::Char('H')],
+    );
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('a')],
+    );
+    assert_eq!(editor.document.contents(), "keepnew\nnewnext\n");
+}
+
+#[test]
+fn ctrl_u_on_a_row_typed_in_this_insert_deletes_back_to_its_start() {
+    let fixture = Fixture::new("rows.txt", "old\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i'), KeyEvent::End]);
+    type_text(&mut editor, &terminal, " one\ntwo");
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('u')]);
+    assert_eq!(editor.document.contents(), "old one\n\n");
+
+    // It doesn't go on to the row above.
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('u')]);
+    assert_eq!(editor.document.contents(), "old one\n\n");
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 1 });
+}
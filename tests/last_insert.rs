@@ -0,0 +1,44 @@
+mod common;
+
+use common::{message_bar, press, start, type_text, Fixture};
+use zen::terminal::KeyEvent;
+
+#[test]
+fn ctrl_a_inserts_the_text_of_the_last_insert_again() {
+    let fixture = Fixture::new("boilerplate.txt", "a\nb\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "// xy");
+    press(&mut editor, &terminal, [KeyEvent::Backspace]);
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Esc, KeyEvent::Char('k'), KeyEvent::Char('H')],
+    );
+
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('a')],
+    );
+    assert_eq!(editor.document.contents(), "// xa\n// xb\n");
+    assert_eq!(editor.cursor_position.x, 4);
+
+    // One undo takes it out again.
+    press(&mut editor, &terminal, [KeyEvent::Esc, KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "// xa\nb\n");
+}
+
+#[test]
+fn ctrl_a_before_any_insert_warns() {
+    let fixture = Fixture::new("empty.txt", "a\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('a')],
+    );
+
+    assert_eq!(editor.document.contents(), "a\n");
+    assert_eq!(message_bar(&terminal), "No inserted text yet");
+}
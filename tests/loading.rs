@@ -0,0 +1,83 @@
+mod common;
+
+use common::{block_on, ex_command, press, start, status_bar, type_text, Fixture};
+use std::fmt::Write;
+use zen::document::Document;
+use zen::terminal::KeyEvent;
+
+const LINES: usize = 1_000_000;
+
+// A million numbered lines, about 13 MB, so it's opened incrementally.
+fn huge_log() -> String {
+    let mut contents = String::with_capacity(LINES * 14);
+    for line in 1..=LINES {
+        let _ = writeln!(contents, "line {}", line);
+    }
+    contents
+}
+
+#[test]
+fn big_files_open_with_their_first_chunk_and_load_the_rest() {
+    let fixture = Fixture::new("huge.log", &huge_log());
+    let (mut document, loader) = Document::open_incremental(fixture.path()).unwrap();
+    let mut loader = loader.unwrap();
+    assert_eq!(document.len(), 10_000);
+    assert_eq!(document.load_progress(), Some(0));
+
+    let mut chunks = 1;
+    while document.is_loading() {
+        let chunk = loader.next_chunk().unwrap();
+        assert!(chunk.percent <= 100);
+        document.append_loaded(chunk);
+        chunks += 1;
+    }
+    assert_eq!(chunks, 101);
+    assert_eq!(document.len(), LINES);
+    assert_eq!(document.row(LINES - 1).unwrap().string, "line 1000000");
+    // Loading isn't an edit.
+    assert!(!document.is_dirty());
+    assert!(document.undo().is_none());
+}
+
+#[test]
+fn small_files_are_read_whole() {
+    let fixture = Fixture::new("small.log", "one\r\ntwo\n");
+    let (document, loader) = Document::open_incremental(fixture.path()).unwrap();
+    assert!(loader.is_none());
+    assert!(!document.is_loading());
+    assert_eq!(document.contents(), "one\ntwo\n");
+}
+
+#[test]
+fn the_loaded_start_is_editable_and_going_to_the_end_waits_for_the_rest() {
+    let fixture = Fixture::new("edit.log", &huge_log());
+    let (mut editor, terminal) = start(fixture.options());
+    assert!(status_bar(&terminal).contains("[loading… 0%]"));
+
+    type_text(&mut editor, &terminal, "ix");
+    press(&mut editor, &terminal, [KeyEvent::Esc, KeyEvent::Ctrl('K')]);
+    assert_eq!(editor.cursor_position.y, LINES - 1);
+    assert_eq!(editor.document.len(), LINES);
+    assert_eq!(editor.document.row(0).unwrap().string, "xline 1");
+    assert!(!status_bar(&terminal).contains("loading"));
+
+    ex_command(&mut editor, &terminal, "w");
+    assert!(fixture.contents().starts_with("xline 1\nline 2\n"));
+}
+
+#[test]
+fn the_rest_loads_in_the_background_while_the_editor_runs() {
+    let fixture = Fixture::new("background.log", &huge_log());
+    let (mut editor, terminal) = start(fixture.options());
+
+    terminal.push_keys([KeyEvent::Ctrl('K'), KeyEvent::Ctrl('q')]);
+    block_on(editor.run()).unwrap();
+
+    assert_eq!(editor.cursor_position.y, LINES - 1);
+    assert!(!editor.document.is_loading());
+    let loading_shown = terminal
+        .frames()
+        .iter()
+        .any(|frame| frame.iter().any(|line| line.contains("[loading… ")));
+    assert!(loading_shown);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{block_on, ex_command, message_bar, start, Fixture};
+use zen::config::{Config, LocalConfig};
+use zen::document::Document;
+
+#[test]
+fn modelines_are_read_in_both_forms() {
+    assert_eq!(
+        LocalConfig::from_modeline("# vim: ts=2 tw=72"),
+        Some(LocalConfig {
+            tab_width: Some(2),
+            text_width: Some(72),
+            ..LocalConfig::default()
+        })
+    );
+    assert_eq!(
+        LocalConfig::from_modeline("/* vim: set tabstop=8 list: ts=3 */"),
+        Some(LocalConfig {
+            tab_width: Some(8),
+            list: Some(true),
+            ..LocalConfig::default()
+        })
+    );
+    // Options zen doesn't have are skipped.
+    assert_eq!(
+        LocalConfig::from_modeline("vi:ft=python:sw=4:ts=3"),
+        Some(LocalConfig {
+            tab_width: Some(3),
+            ..LocalConfig::default()
+        })
+    );
+    // The marker has to start the line or follow whitespace.
+    assert_eq!(LocalConfig::from_modeline("let avim: ts=2"), None);
+    assert_eq!(LocalConfig::from_modeline("let x = 1;"), None);
+}
+
+#[test]
+fn modelines_at_the_end_of_a_file_override_the_config() {
+    let fixture = Fixture::new("modeline.py", "\tx = 1\n\n# vim: set ts=2 tw=40:\n");
+    let document = block_on(Document::open(fixture.path())).unwrap();
+    let config = Config::default();
+
+    assert_eq!(document.effective_tab_width(&config), 2);
+    assert_eq!(document.effective_text_width(&config), 40);
+
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "retab");
+    assert_eq!(editor.document.row(0).unwrap().string, "  x = 1");
+}
+
+#[test]
+fn setlocal_only_changes_the_current_document() {
+    let fixture = Fixture::new("setlocal.go", "\tfmt.Println()\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "setlocal tabstop=2 nolist");
+
+    assert_eq!(editor.document.local_config.tab_width, Some(2));
+    assert_eq!(editor.document.local_config.list, Some(false));
+    assert_eq!(editor.document.effective_tab_width(&Config::default()), 2);
+
+    ex_command(&mut editor, &terminal, "tabnew");
+    assert_eq!(editor.document.local_config, LocalConfig::default());
+    ex_command(&mut editor, &terminal, "tabprev");
+    assert_eq!(editor.document.local_config.tab_width, Some(2));
+
+    ex_command(&mut editor, &terminal, "setl ts=x");
+    assert_eq!(message_bar(&terminal), "ERR: Invalid number: ts=x");
+    ex_command(&mut editor, &terminal, "setlocal wrap");
+    assert_eq!(message_bar(&terminal), "ERR: Unknown option: wrap");
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::{block_on, press, start, status_bar, Fixture};
+use std::time::Instant;
+use zen::document::Document;
+use zen::row::Row;
+use zen::terminal::{ColorSupport, KeyEvent};
+
+// About 5 MB on one line, like minified code.
+fn minified() -> String {
+    let mut line = "var a=function(b){return b+1};".repeat(170_000);
+    line.push_str("END");
+    line
+}
+
+#[test]
+fn rows_are_sliced_without_walking_them() {
+    let row = Row::from("ab\tcd\t");
+    assert_eq!(row.slice(2, 3), "\tcd");
+    assert_eq!(row.slice(5, 9), "\t");
+}
+
+#[test]
+fn long_rows_are_left_plain_until_the_limit_is_lifted() {
+    let contents = format!("let x = 1;\n{}\nlet y = 2;\n", minified());
+    let fixture = Fixture::new("bundle.rs", &contents);
+    let mut document = block_on(Document::open(fixture.path())).unwrap();
+    assert!(document.is_long_line(1));
+
+    document.highlight(0..3, ColorSupport::TrueColor).unwrap();
+    let row = document.row(1).unwrap();
+    assert_eq!(row.render(), row.string);
+    assert!(document.row(2).unwrap().render().contains("\x1b[38;2;"));
+
+    document.set_long_line_length(0);
+    assert!(!document.is_long_line(1));
+    document.highlight(0..1, ColorSupport::TrueColor).unwrap();
+    assert!(document.row(0).unwrap().render().contains("\x1b[38;2;"));
+}
+
+#[test]
+fn a_five_megabyte_line_opens_and_scrolls_with_bounded_frames() {
+    let fixture = Fixture::new("bundle.js", &minified());
+    let (mut editor, terminal) = start(fixture.options());
+    // The fixture's path leaves only room for the start of the note.
+    assert!(status_bar(&terminal).contains("[long line, highlighting"));
+
+    // Going to the end only draws the part of the row in view.
+    terminal.clear_output();
+    let moved = Instant::now();
+    press(&mut editor, &terminal, [KeyEvent::Char('L')]);
+    assert!(terminal.last_rendered_rows()[0].contains("END"));
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('h'), KeyEvent::Char('h')],
+    );
+    let written: usize = terminal.output().iter().map(String::len).sum();
+    assert!(written < 4_096, "{} bytes written", written);
+    // Generous, only to catch frames that walk the whole row again.
+    assert!(moved.elapsed().as_secs() < 10);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, start_with_clock, type_text, Fixture, HEIGHT};
+use std::time::Duration;
+use zen::commands::ex::{self, ExCommand};
+use zen::config::Config;
+use zen::keymap::MapMode;
+use zen::terminal::KeyEvent;
+
+#[test]
+fn mappings_from_the_config_use_the_leader() {
+    let fixture = Fixture::new("leader.txt", "one\n");
+    let (mut editor, terminal) = start(fixture.options());
+    let config = "leader = \",\"\n[nmap]\n\"<leader>w\" = \":s/one/two/<CR>:w<CR>\"\n";
+    editor.set_config(Config::parse(config).unwrap());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char(','), KeyEvent::Char('w')],
+    );
+
+    assert_eq!(fixture.contents(), "two\n");
+    assert!(!editor.document.is_dirty());
+}
+
+#[test]
+fn insert_mappings_take_precedence_over_typing() {
+    let fixture = Fixture::new("insert.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "imap jk <Esc>");
+    type_text(&mut editor, &terminal, "ijxjk");
+
+    assert_eq!(editor.document.contents(), "jx\n");
+    // Back in Normal mode on the `x`, where `i` inserts again.
+    type_text(&mut editor, &terminal, "iy");
+    assert_eq!(editor.document.contents(), "jyx\n");
+}
+
+#[test]
+fn held_keys_are_typed_once_the_mapping_times_out() {
+    let fixture = Fixture::new("timeout.txt", "");
+    let (mut editor, terminal, clock) = start_with_clock(fixture.options());
+    ex_command(&mut editor, &terminal, "imap jk <Esc>");
+    type_text(&mut editor, &terminal, "ij");
+    assert_eq!(editor.document.contents(), "");
+
+    clock.advance(Duration::from_secs(2));
+    type_text(&mut editor, &terminal, "k");
+    assert_eq!(editor.document.contents(), "jk\n");
+}
+
+#[test]
+fn mappings_expand_into_other_mappings_until_they_recurse() {
+    let fixture = Fixture::new("recursive.txt", "abc\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "nmap Q L");
+    ex_command(&mut editor, &terminal, "nmap W Q");
+    press(&mut editor, &terminal, [KeyEvent::Char('W')]);
+    assert_eq!(editor.cursor_position.x, 2);
+
+    ex_command(&mut editor, &terminal, "nmap R R");
+    press(&mut editor, &terminal, [KeyEvent::Char('R')]);
+    assert_eq!(message_bar(&terminal), "ERR: Recursive mapping");
+}
+
+#[test]
+fn map_without_arguments_lists_the_mappings() {
+    let fixture = Fixture::new("list.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "map");
+    assert_eq!(message_bar(&terminal), "No mapping found");
+
+    ex_command(&mut editor, &terminal, "nmap <leader>w :w<CR>");
+    ex_command(&mut editor, &terminal, "imap jk <Esc>");
+    ex_command(&mut editor, &terminal, "imap");
+
+    let rows = terminal.last_rendered_rows();
+    let height = HEIGHT as usize - 2;
+    assert_eq!(rows[height - 2].trim_end(), "i  jk           <Esc>");
+    assert_eq!(rows[height - 1], "Press any key to continue");
+}
+
+#[test]
+fn unknown_key_names_are_errors() {
+    let fixture = Fixture::new("unknown.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "nmap <Hyper-x> L");
+
+    assert_eq!(message_bar(&terminal), "ERR: Unknown key: <Hyper-x>");
+}
+
+#[test]
+fn map_commands_need_keys_and_what_they_map_to() {
+    assert_eq!(
+        ex::parse("imap  jk   <Esc> ").unwrap(),
+        ExCommand::Map {
+            mode: MapMode::Insert,
+            lhs: "jk".to_string(),
+            rhs: "<Esc>".to_string(),
+        }
+    );
+    assert_eq!(ex::parse("map").unwrap(), ExCommand::ListMaps(None));
+    assert_eq!(
+        ex::parse("nmap").unwrap(),
+        ExCommand::ListMaps(Some(MapMode::Normal))
+    );
+    assert!(ex::parse("nmap ZZ").is_err());
+}
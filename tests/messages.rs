@@ -0,0 +1,172 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start_with_clock, Fixture, HEIGHT};
+use std::time::{Duration, Instant};
+use zen::config::Config;
+use zen::message::{Level, Messages};
+use zen::terminal::KeyEvent;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn current(messages: &mut Messages, now: Instant) -> Option<(String, usize)> {
+    messages
+        .current(now)
+        .map(|(message, waiting)| (message.to_string(), waiting))
+}
+
+#[test]
+fn messages_sent_together_are_shown_one_after_another() {
+    let start = Instant::now();
+    let mut messages = Messages::new(TIMEOUT);
+    messages.push(Level::Info, "one".to_string(), start);
+    messages.push(Level::Warning, "two".to_string(), start);
+    messages.push(Level::Info, "three".to_string(), start);
+
+    assert_eq!(current(&mut messages, start), Some(("one".to_string(), 2)));
+    assert_eq!(
+        current(&mut messages, start + TIMEOUT),
+        Some(("two".to_string(), 1))
+    );
+    assert_eq!(
+        current(&mut messages, start + TIMEOUT * 2),
+        Some(("three".to_string(), 0))
+    );
+    assert_eq!(current(&mut messages, start + TIMEOUT * 3), None);
+}
+
+#[test]
+fn errors_stay_until_a_key_is_pressed() {
+    let start = Instant::now();
+    let mut messages = Messages::new(TIMEOUT);
+    messages.push(Level::Error, "broken".to_string(), start);
+
+    let later = start + TIMEOUT * 10;
+    assert_eq!(
+        current(&mut messages, later),
+        Some(("ERR: broken".to_string(), 0))
+    );
+    messages.key_pressed(later);
+    assert_eq!(current(&mut messages, later), None);
+}
+
+#[test]
+fn a_keypress_lets_waiting_messages_through() {
+    let start = Instant::now();
+    let mut messages = Messages::new(TIMEOUT);
+    messages.push(Level::Info, "one".to_string(), start);
+    messages.push(Level::Info, "two".to_string(), start);
+
+    messages.key_pressed(start);
+    assert_eq!(current(&mut messages, start), Some(("two".to_string(), 0)));
+
+    // Seen across a keypress, so the next message takes its place.
+    messages.key_pressed(start);
+    messages.push(Level::Info, "three".to_string(), start);
+    assert_eq!(
+        current(&mut messages, start),
+        Some(("three".to_string(), 0))
+    );
+}
+
+#[test]
+fn a_message_taking_a_seen_ones_place_lines_up_the_next() {
+    let start = Instant::now();
+    let mut messages = Messages::new(TIMEOUT);
+    messages.push(Level::Info, "one".to_string(), start);
+    messages.key_pressed(start);
+
+    messages.push(Level::Warning, "two".to_string(), start);
+    messages.push(Level::Info, "three".to_string(), start);
+    assert_eq!(current(&mut messages, start), Some(("two".to_string(), 1)));
+}
+
+#[test]
+fn repeated_messages_collapse_and_history_keeps_everything() {
+    let start = Instant::now();
+    let mut messages = Messages::new(TIMEOUT);
+    messages.push(Level::Info, "saved".to_string(), start);
+    messages.push(Level::Info, "saved".to_string(), start);
+    messages.push(Level::Error, "failed".to_string(), start);
+
+    assert_eq!(
+        current(&mut messages, start),
+        Some(("saved".to_string(), 1))
+    );
+    let history: Vec<String> = messages.history().map(ToString::to_string).collect();
+    assert_eq!(history, ["saved", "saved", "ERR: failed"]);
+}
+
+#[test]
+fn history_keeps_the_last_two_hundred_messages() {
+    let start = Instant::now();
+    let mut messages = Messages::new(TIMEOUT);
+    for n in 0..250 {
+        messages.push(Level::Info, n.to_string(), start);
+    }
+
+    let history: Vec<String> = messages.history().map(ToString::to_string).collect();
+    assert_eq!(history.len(), 200);
+    assert_eq!(history[0], "50");
+    assert_eq!(
+        messages
+            .history()
+            .next_back()
+            .unwrap()
+            .age(start + TIMEOUT * 30),
+        "2m"
+    );
+}
+
+#[test]
+fn errors_are_drawn_red_and_outlast_the_timeout() {
+    let (mut editor, terminal, clock) = start_with_clock(Default::default());
+    terminal.clear_output();
+    ex_command(&mut editor, &terminal, "frobnicate");
+    assert!(terminal
+        .output()
+        .concat()
+        .contains("\x1b[38;2;224;108;117mERR: Not an editor command"));
+
+    clock.advance(TIMEOUT * 2);
+    editor.refresh_screen().unwrap();
+    assert_eq!(
+        message_bar(&terminal),
+        "ERR: Not an editor command: frobnicate"
+    );
+
+    press(&mut editor, &terminal, [KeyEvent::Char('h')]);
+    assert_eq!(message_bar(&terminal), "");
+}
+
+#[test]
+fn messages_command_shows_the_history_until_a_key_is_pressed() {
+    let fixture = Fixture::new("messages.txt", "text\n");
+    let (mut editor, terminal, clock) = start_with_clock(fixture.options());
+    clock.advance(Duration::from_secs(90));
+    press(&mut editor, &terminal, [KeyEvent::Char('n')]);
+    clock.advance(Duration::from_secs(5));
+    ex_command(&mut editor, &terminal, "messages");
+
+    // Newest first, with how long ago each was sent.
+    let rows = terminal.last_rendered_rows();
+    let height = HEIGHT as usize - 2;
+    assert_eq!(rows[height - 3], " 5s ago  ERR: No previous search");
+    assert_eq!(
+        rows[height - 2],
+        " 1m ago  HELP: Ctrl-F = find | Ctrl-S = save | Ctrl-Q = quit"
+    );
+    assert_eq!(rows[height - 1], "Press any key to continue");
+
+    // The key only closes the overlay.
+    press(&mut editor, &terminal, [KeyEvent::Char('l')]);
+    assert_eq!(terminal.last_rendered_rows()[0], "text");
+    assert_eq!(editor.cursor_position.x, 0);
+}
+
+#[test]
+fn the_timeout_is_configurable() {
+    let config = Config::parse("message_timeout = 2\n").unwrap();
+
+    assert_eq!(config.message_timeout, 2);
+    assert_eq!(Config::default().message_timeout, 5);
+}
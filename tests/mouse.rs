@@ -0,0 +1,66 @@
+mod common;
+
+use common::{press, start, Fixture, HEIGHT};
+use zen::terminal::{KeyEvent, MouseButton, MouseEvent};
+
+fn mouse(button: MouseButton, x: u16, y: u16) -> KeyEvent {
+    KeyEvent::Mouse(MouseEvent { button, x, y })
+}
+
+fn position(editor: &zen::editor::Editor) -> (usize, usize) {
+    (editor.cursor_position.x, editor.cursor_position.y)
+}
+
+#[test]
+fn clicking_moves_the_cursor_there() {
+    let fixture = Fixture::new("click.txt", "first\nsecond line\nthird\n");
+    let (mut editor, terminal) = start(fixture.options());
+
+    press(&mut editor, &terminal, [mouse(MouseButton::Left, 3, 1)]);
+    assert_eq!(position(&editor), (3, 1));
+
+    // Past the end of a line, or below the last one, the cursor stops on the last character.
+    press(&mut editor, &terminal, [mouse(MouseButton::Left, 40, 2)]);
+    assert_eq!(position(&editor), (4, 2));
+    press(&mut editor, &terminal, [mouse(MouseButton::Left, 1, 8)]);
+    assert_eq!(position(&editor), (1, 2));
+}
+
+#[test]
+fn clicks_on_the_bars_are_ignored() {
+    let fixture = Fixture::new("bars.txt", "one\ntwo\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [mouse(MouseButton::Left, 2, 1)]);
+
+    press(
+        &mut editor,
+        &terminal,
+        [
+            mouse(MouseButton::Left, 0, HEIGHT - 2),
+            mouse(MouseButton::Left, 0, HEIGHT - 1),
+        ],
+    );
+    assert_eq!(position(&editor), (2, 1));
+}
+
+#[test]
+fn the_wheel_scrolls_and_clicks_account_for_the_scroll() {
+    let contents: String = (0..40).map(|n| format!("line {}\n", n)).collect();
+    let fixture = Fixture::new("wheel.txt", &contents);
+    let (mut editor, terminal) = start(fixture.options());
+
+    press(
+        &mut editor,
+        &terminal,
+        [mouse(MouseButton::WheelDown, 0, 0); 5],
+    );
+    assert_eq!(position(&editor), (0, 15));
+    let top = terminal.last_rendered_rows()[0].clone();
+    assert_eq!(top, "line 6");
+
+    press(&mut editor, &terminal, [mouse(MouseButton::Left, 5, 0)]);
+    assert_eq!(position(&editor), (5, 6));
+
+    press(&mut editor, &terminal, [mouse(MouseButton::WheelUp, 0, 0)]);
+    assert_eq!(position(&editor), (5, 3));
+}
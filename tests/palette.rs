@@ -0,0 +1,134 @@
+mod common;
+
+use common::{block_on, ex_command, keys, message_bar, press, start, status_bar, Fixture};
+use std::fs;
+use std::path::Path;
+use zen::keymap::{self, Keymap, MapMode};
+use zen::picker::{Picker, PickerAction, PickerItem};
+use zen::terminal::KeyEvent;
+
+fn item(label: &str) -> PickerItem {
+    PickerItem {
+        label: label.to_string(),
+        detail: String::new(),
+    }
+}
+
+#[test]
+fn the_picker_narrows_to_fuzzy_matches_best_first() {
+    let mut picker = Picker::new(vec![
+        item("Show Unsaved Changes"),
+        item("Search Word Forward"),
+        item("Undo"),
+    ]);
+    for c in "sw".chars() {
+        assert_eq!(picker.handle_key(KeyEvent::Char(c)), PickerAction::Continue);
+    }
+
+    // Both match, but the query starts words of the second.
+    let labels: Vec<&str> = picker.matches().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, ["Search Word Forward", "Show Unsaved Changes"]);
+    assert_eq!(
+        picker.lines(5)[0],
+        ("> Search Word Forward".to_string(), true)
+    );
+
+    picker.handle_key(KeyEvent::Down);
+    assert_eq!(
+        picker.handle_key(KeyEvent::Char('\n')),
+        PickerAction::Submit(Some(0))
+    );
+
+    picker.handle_key(KeyEvent::Char('x'));
+    assert_eq!(
+        picker.handle_key(KeyEvent::Char('\n')),
+        PickerAction::Submit(None)
+    );
+    assert_eq!(picker.handle_key(KeyEvent::Esc), PickerAction::Cancel);
+}
+
+#[test]
+fn keys_are_bound_to_registered_commands() {
+    let keymap = Keymap::default();
+    let save = keymap
+        .binding(MapMode::Insert, KeyEvent::Ctrl('s'))
+        .unwrap();
+    assert_eq!(save.name, "Save");
+    assert!(save.is_valid_in(MapMode::Normal));
+    assert!(keymap
+        .binding(MapMode::Normal, KeyEvent::Ctrl('s'))
+        .is_none());
+
+    assert_eq!(
+        keymap.keys_for(MapMode::Insert, "Normal Mode"),
+        ["<Esc>", "<C-c>"]
+    );
+    assert_eq!(keymap::key_name(KeyEvent::Char(' ')), "<Space>");
+}
+
+#[test]
+fn the_palette_runs_the_command_picked() {
+    let fixture = Fixture::new("palette.txt", "some text\n");
+    let (mut editor, terminal) = start(fixture.options());
+    terminal.push_keys([KeyEvent::Ctrl('p')]);
+    terminal.push_keys(keys("end of l"));
+    terminal.push_keys([KeyEvent::Esc]);
+    block_on(editor.process_keypress()).unwrap();
+
+    // The commands of Normal mode are listed with what they do and their keys.
+    let listing = terminal.frames().concat().concat();
+    assert!(listing.contains("End of Line"));
+    assert!(listing.contains("Move the cursor to the end of its line (L)"));
+    assert!(listing.contains("Command: end of l"));
+    assert_eq!(editor.cursor_position.x, 0);
+
+    terminal.push_keys([KeyEvent::Ctrl('p')]);
+    terminal.push_keys(keys("end of l\n"));
+    block_on(editor.process_keypress()).unwrap();
+    editor.refresh_screen().unwrap();
+    assert_eq!(editor.cursor_position.x, 8);
+    assert!(!message_bar(&terminal).starts_with("Command:"));
+}
+
+#[test]
+fn mappings_are_listed_and_run_as_typed() {
+    let fixture = Fixture::new("palette_mapping.txt", "some text\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "nmap Q L");
+
+    terminal.push_keys(keys(":commands\nmapping q\n"));
+    block_on(editor.process_keypress()).unwrap();
+    assert_eq!(editor.cursor_position.x, 8);
+}
+
+#[test]
+fn only_commands_of_the_mode_are_listed() {
+    let fixture = Fixture::new("palette_insert.txt", "text\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    terminal.clear_output();
+    terminal.push_keys([KeyEvent::Ctrl('p')]);
+    terminal.push_keys(keys("normal\n"));
+    block_on(editor.process_keypress()).unwrap();
+    editor.refresh_screen().unwrap();
+
+    let listing = terminal.frames().concat().concat();
+    assert!(listing.contains("Normal Mode"));
+    assert!(!listing.contains("Undo"));
+    assert!(status_bar(&terminal).starts_with("-- NORMAL --"));
+    assert_eq!(editor.document.contents(), "text\n");
+}
+
+#[test]
+fn commands_taking_an_argument_ask_for_it() {
+    let fixture = Fixture::new("palette_rename.txt", "text\n");
+    let renamed = Path::new(fixture.path()).with_file_name("renamed.txt");
+    let (mut editor, terminal) = start(fixture.options());
+    terminal.push_keys([KeyEvent::Ctrl('p')]);
+    terminal.push_keys(keys("rename\n"));
+    terminal.push_keys(keys(&format!("{}\n", renamed.display())));
+    block_on(editor.process_keypress()).unwrap();
+
+    assert_eq!(fs::read_to_string(&renamed).unwrap(), "text\n");
+    assert!(!Path::new(fixture.path()).exists());
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::{document, press, start, Fixture};
+use zen::editor::Position;
+use zen::terminal::KeyEvent;
+
+const TEXT: &str = "one\ntwo\n\n\nthree\nfour\n\nfive\n";
+
+#[test]
+fn braces_move_between_empty_rows() {
+    let fixture = Fixture::new("braces.txt", TEXT);
+    let (mut editor, terminal) = start(fixture.options());
+
+    press(&mut editor, &terminal, [KeyEvent::Char('}')]);
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 2 });
+    press(&mut editor, &terminal, [KeyEvent::Char('}')]);
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 6 });
+    press(&mut editor, &terminal, [KeyEvent::Char('}')]);
+    assert_eq!(editor.cursor_position, Position { x: 3, y: 7 });
+
+    press(&mut editor, &terminal, [KeyEvent::Char('{')]);
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 6 });
+    press(&mut editor, &terminal, [KeyEvent::Char('{')]);
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 3 });
+    press(&mut editor, &terminal, [KeyEvent::Char('{')]);
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 0 });
+}
+
+#[test]
+fn paragraph_objects_cover_the_paragraph_and_the_empty_rows_after_it() {
+    let (document, _fixture) = document(TEXT);
+
+    assert_eq!(document.paragraph_object(1, false), 0..2);
+    assert_eq!(document.paragraph_object(1, true), 0..4);
+    assert_eq!(document.paragraph_object(2, false), 2..4);
+    assert_eq!(document.paragraph_object(2, true), 2..6);
+    // The last paragraph takes the empty rows before it instead.
+    assert_eq!(document.paragraph_object(7, true), 6..8);
+}
+
+#[test]
+fn gq_and_gu_take_paragraph_objects() {
+    let fixture = Fixture::new("objects.txt", TEXT);
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [
+            KeyEvent::Char('g'),
+            KeyEvent::Char('q'),
+            KeyEvent::Char('i'),
+            KeyEvent::Char('p'),
+        ],
+    );
+    assert_eq!(
+        editor.document.contents(),
+        "one two\n\n\nthree\nfour\n\nfive\n"
+    );
+
+    press(
+        &mut editor,
+        &terminal,
+        [
+            KeyEvent::Char('}'),
+            KeyEvent::Char('g'),
+            KeyEvent::Char('U'),
+            KeyEvent::Char('a'),
+            KeyEvent::Char('p'),
+        ],
+    );
+    assert_eq!(
+        editor.document.contents(),
+        "one two\n\n\nTHREE\nFOUR\n\nfive\n"
+    );
+}
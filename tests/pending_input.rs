@@ -0,0 +1,36 @@
+mod common;
+
+use common::{message_bar, press, start, Fixture, WIDTH};
+use zen::terminal::KeyEvent;
+
+#[test]
+fn unfinished_sequences_show_at_the_right_of_the_message_bar() {
+    let fixture = Fixture::new("pending.txt", "a\nb\nc\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('g'), KeyEvent::Char('q')],
+    );
+
+    assert_eq!(editor.pending_input().keys(), "gq");
+    let bar = message_bar(&terminal);
+    let help = "HELP: Ctrl-F = find | Ctrl-S = save | Ctrl-Q = quit";
+    let message_width = WIDTH as usize - 12;
+    assert_eq!(bar, format!("{} gq", &help[..message_width]));
+
+    press(&mut editor, &terminal, [KeyEvent::Char('q')]);
+    assert!(editor.pending_input().is_empty());
+    assert!(!message_bar(&terminal).ends_with("gq"));
+}
+
+#[test]
+fn esc_abandons_the_sequence() {
+    let fixture = Fixture::new("esc.txt", "a\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char(']'), KeyEvent::Esc]);
+
+    assert!(editor.pending_input().is_empty());
+    assert!(!message_bar(&terminal).ends_with(']'));
+    assert_eq!(editor.document.contents(), "a\n");
+}
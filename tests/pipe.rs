@@ -0,0 +1,47 @@
+use zen::cli::{self, Action};
+
+fn pipe(input: &str, commands: &[&str]) -> Result<String, String> {
+    let commands: Vec<String> = commands.iter().map(|command| command.to_string()).collect();
+    zen::pipe::run(input, &commands).map_err(|error| error.to_string())
+}
+
+#[test]
+fn commands_run_over_the_input_in_order() {
+    assert_eq!(
+        pipe("hello world\n", &["s/hello/goodbye/g"]).unwrap(),
+        "goodbye world\n"
+    );
+    // The cursor starts on the first line, so `%` is needed to reach every one.
+    assert_eq!(
+        pipe("hello\nhello\n", &["s/hello/bye/"]).unwrap(),
+        "bye\nhello\n"
+    );
+    assert_eq!(
+        pipe("one\ntwo\nthree\n", &["%s/o/0/g", "2d"]).unwrap(),
+        "0ne\nthree\n"
+    );
+    assert_eq!(pipe("", &[]).unwrap(), "");
+}
+
+#[test]
+fn the_first_failing_command_stops_the_run() {
+    assert_eq!(
+        pipe("text\n", &["s/z/y/", "d"]).unwrap_err(),
+        "s/z/y/: Pattern not found: z"
+    );
+    assert_eq!(
+        pipe("text\n", &["w"]).unwrap_err(),
+        "w: Not available with --pipe"
+    );
+}
+
+#[test]
+fn pipe_commands_are_gathered_from_the_arguments() {
+    let arguments = ["--pipe", "s/a/b/", "--pipe=%d"].map(String::from);
+    match cli::parse(arguments) {
+        Ok(Action::Pipe(commands)) => assert_eq!(commands, ["s/a/b/", "%d"]),
+        _ => panic!("expected pipe mode"),
+    }
+    assert!(cli::parse(["--pipe", "d", "file.txt"].map(String::from)).is_err());
+    assert!(cli::parse(["--pipe"].map(String::from)).is_err());
+}
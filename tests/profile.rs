@@ -0,0 +1,76 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, type_text, Fixture};
+use std::time::Duration;
+use zen::commands::ex::{self, ExCommand};
+use zen::profile::{Phase, Profiler, SAMPLES};
+use zen::terminal::KeyEvent;
+
+#[test]
+fn percentiles_are_taken_over_the_latest_timings() {
+    let mut profiler = Profiler::default();
+    assert_eq!(profiler.percentile(Phase::Input, 50), None);
+    for micros in 1..=100 {
+        profiler.record(Phase::Input, Duration::from_micros(micros));
+    }
+    assert_eq!(
+        profiler.percentile(Phase::Input, 50),
+        Some(Duration::from_micros(50))
+    );
+    assert_eq!(
+        profiler.percentile(Phase::Input, 99),
+        Some(Duration::from_micros(99))
+    );
+    assert_eq!(
+        profiler.percentile(Phase::Input, 100),
+        Some(Duration::from_micros(100))
+    );
+    assert_eq!(profiler.count(Phase::Render), 0);
+
+    // Only the latest are kept, so old slow ones age out.
+    for _ in 0..SAMPLES {
+        profiler.record(Phase::Input, Duration::from_micros(7));
+    }
+    assert_eq!(profiler.count(Phase::Input), SAMPLES);
+    assert_eq!(
+        profiler.percentile(Phase::Input, 100),
+        Some(Duration::from_micros(7))
+    );
+
+    let report = profiler.report();
+    assert_eq!(report.len(), 2);
+    assert!(report[1].starts_with("input"));
+    assert!(report[1].ends_with("7µs"));
+}
+
+#[test]
+fn profiling_times_the_event_loop_once_turned_on() {
+    assert_eq!(ex::parse("profile").unwrap(), ExCommand::ProfileReport);
+    assert_eq!(ex::parse("profile reset").unwrap(), ExCommand::ProfileReset);
+    assert!(ex::parse("profile all").is_err());
+
+    let fixture = Fixture::new("profiled.rs", "fn main() {}\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "profile");
+    assert_eq!(
+        message_bar(&terminal),
+        "Nothing timed, turn profiling on with :set profiling"
+    );
+
+    ex_command(&mut editor, &terminal, "set profiling");
+    type_text(&mut editor, &terminal, "lll");
+    ex_command(&mut editor, &terminal, "profile");
+    let rows = terminal.last_rendered_rows();
+    // Listed at the bottom of the text area.
+    assert_eq!(rows[9], "Press any key to continue");
+    assert!(rows.iter().any(|row| row.starts_with("phase")));
+    for phase in ["input", "command", "highlight", "render"] {
+        assert!(rows.iter().any(|row| row.starts_with(phase)), "{phase}");
+    }
+
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    ex_command(&mut editor, &terminal, "profile reset");
+    ex_command(&mut editor, &terminal, "set noprofiling");
+    ex_command(&mut editor, &terminal, "profile");
+    assert!(message_bar(&terminal).starts_with("Nothing timed"));
+}
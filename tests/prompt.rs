@@ -0,0 +1,226 @@
+mod common;
+
+use common::{block_on, keys, message_bar, press, start, Fixture, HEIGHT};
+use std::fs;
+use std::path::Path;
+use zen::cli::LaunchOptions;
+use zen::prompt::{LineEditor, PromptAction, PromptHistory, PromptKind};
+use zen::terminal::{ansi, KeyEvent};
+
+fn edit<I>(line: &mut LineEditor, kind: PromptKind, history: &[String], keys: I)
+where
+    I: IntoIterator<Item = KeyEvent>,
+{
+    for key in keys {
+        assert_eq!(line.handle_key(key, kind, history), PromptAction::Continue);
+    }
+}
+
+#[test]
+fn the_line_can_be_edited_anywhere() {
+    let mut line = LineEditor::default();
+    edit(&mut line, PromptKind::Search, &[], keys("wrld"));
+    edit(
+        &mut line,
+        PromptKind::Search,
+        &[],
+        [KeyEvent::Left, KeyEvent::Left, KeyEvent::Left],
+    );
+    edit(&mut line, PromptKind::Search, &[], keys("o"));
+    assert_eq!((line.text().as_str(), line.cursor()), ("world", 2));
+
+    edit(
+        &mut line,
+        PromptKind::Search,
+        &[],
+        [KeyEvent::Ctrl('a'), KeyEvent::Delete, KeyEvent::End],
+    );
+    edit(&mut line, PromptKind::Search, &[], keys("s"));
+    assert_eq!((line.text().as_str(), line.cursor()), ("orlds", 5));
+}
+
+#[test]
+fn ctrl_w_deletes_the_previous_word() {
+    let mut line = LineEditor::default();
+    edit(&mut line, PromptKind::Command, &[], keys("iab teh  the"));
+    edit(&mut line, PromptKind::Command, &[], [KeyEvent::Ctrl('w')]);
+    assert_eq!(line.text(), "iab teh  ");
+
+    edit(&mut line, PromptKind::Command, &[], [KeyEvent::Ctrl('w')]);
+    assert_eq!(line.text(), "iab ");
+}
+
+#[test]
+fn history_is_kept_per_kind_and_recalled_with_up_and_down() {
+    let mut history = PromptHistory::default();
+    history.push(PromptKind::Command, "write".to_string());
+    history.push(PromptKind::Search, "needle".to_string());
+    history.push(PromptKind::Command, "retab".to_string());
+    history.push(PromptKind::Command, "write".to_string());
+    assert_eq!(history.get(PromptKind::Command), ["retab", "write"]);
+
+    let commands = history.get(PromptKind::Command);
+    let mut line = LineEditor::default();
+    edit(&mut line, PromptKind::Command, commands, keys("dr"));
+    edit(&mut line, PromptKind::Command, commands, [KeyEvent::Up]);
+    assert_eq!(line.text(), "write");
+    edit(
+        &mut line,
+        PromptKind::Command,
+        commands,
+        [KeyEvent::Up, KeyEvent::Up],
+    );
+    assert_eq!(line.text(), "retab");
+    edit(&mut line, PromptKind::Command, commands, [KeyEvent::Down]);
+    assert_eq!(line.text(), "write");
+
+    // Past the newest line, the draft comes back.
+    edit(&mut line, PromptKind::Command, commands, [KeyEvent::Down]);
+    assert_eq!((line.text().as_str(), line.cursor()), ("dr", 2));
+}
+
+#[test]
+fn tab_completes_command_names_and_cycles_through_matches() {
+    let mut line = LineEditor::default();
+    edit(&mut line, PromptKind::Command, &[], keys("diffu"));
+    edit(&mut line, PromptKind::Command, &[], [KeyEvent::Char('\t')]);
+    assert_eq!(line.text(), "diffupdate");
+
+    let mut line = LineEditor::default();
+    edit(&mut line, PromptKind::Command, &[], keys("diff"));
+    edit(&mut line, PromptKind::Command, &[], [KeyEvent::Char('\t')]);
+    assert_eq!(line.text(), "diff");
+    edit(&mut line, PromptKind::Command, &[], [KeyEvent::Char('\t')]);
+    assert_eq!(line.text(), "diffoff");
+    edit(&mut line, PromptKind::Command, &[], [KeyEvent::Char('\t')]);
+    assert_eq!(line.text(), "diffthis");
+
+    // Option names after `:set`.
+    let mut line = LineEditor::default();
+    edit(&mut line, PromptKind::Command, &[], keys("set nol"));
+    edit(&mut line, PromptKind::Command, &[], [KeyEvent::Char('\t')]);
+    assert_eq!(line.text(), "set nolist");
+}
+
+#[test]
+fn tab_completes_file_paths() {
+    let fixture = Fixture::new("completion.txt", "");
+    let dir = Path::new(fixture.path())
+        .parent()
+        .unwrap()
+        .to_str()
+        .unwrap();
+
+    let mut line = LineEditor::default();
+    edit(
+        &mut line,
+        PromptKind::FileName,
+        &[],
+        keys(&format!("{}/comp", dir)),
+    );
+    edit(&mut line, PromptKind::FileName, &[], [KeyEvent::Char('\t')]);
+    assert_eq!(line.text(), format!("{}/completion.txt", dir));
+
+    // Command arguments complete as paths too.
+    let mut line = LineEditor::default();
+    edit(
+        &mut line,
+        PromptKind::Command,
+        &[],
+        keys(&format!("w {}/c", dir)),
+    );
+    edit(&mut line, PromptKind::Command, &[], [KeyEvent::Char('\t')]);
+    assert_eq!(line.text(), format!("w {}/completion.txt", dir));
+}
+
+#[test]
+fn file_names_complete_as_far_as_the_matches_agree_then_cycle() {
+    let fixture = Fixture::new("notes_a.txt", "");
+    let dir = Path::new(fixture.path()).parent().unwrap();
+    fs::write(dir.join("notes_b.txt"), "").unwrap();
+    let dir = dir.to_str().unwrap();
+
+    let mut line = LineEditor::default();
+    edit(
+        &mut line,
+        PromptKind::FileName,
+        &[],
+        keys(&format!("{}/n", dir)),
+    );
+    edit(&mut line, PromptKind::FileName, &[], [KeyEvent::Char('\t')]);
+    assert_eq!(line.text(), format!("{}/notes_", dir));
+    assert_eq!(
+        line.matches(),
+        Some(
+            &[
+                format!("{}/notes_a.txt", dir),
+                format!("{}/notes_b.txt", dir)
+            ][..]
+        )
+    );
+
+    edit(&mut line, PromptKind::FileName, &[], [KeyEvent::Char('\t')]);
+    assert_eq!(line.text(), format!("{}/notes_a.txt", dir));
+    edit(&mut line, PromptKind::FileName, &[], [KeyEvent::Char('\t')]);
+    assert_eq!(line.text(), format!("{}/notes_b.txt", dir));
+    edit(&mut line, PromptKind::FileName, &[], keys("x"));
+    assert_eq!(line.matches(), None);
+}
+
+#[test]
+fn save_as_lists_the_file_names_tab_could_complete_to() {
+    let fixture = Fixture::new("draft_a.txt", "");
+    let dir = Path::new(fixture.path()).parent().unwrap();
+    fs::write(dir.join("draft_b.txt"), "").unwrap();
+    let dir = dir.to_str().unwrap();
+
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Char('x')],
+    );
+    terminal.push_keys([KeyEvent::Ctrl('s')]);
+    terminal.push_keys(keys(&format!("{}/d", dir)));
+    terminal.push_keys([KeyEvent::Char('\t')]);
+    terminal.push_keys(keys("c.txt"));
+    terminal.push_keys([KeyEvent::Char('\n')]);
+    block_on(editor.process_keypress()).unwrap();
+    editor.refresh_screen().unwrap();
+
+    let listed = terminal.frames().into_iter().find(|frame| {
+        frame[HEIGHT as usize - 1].ends_with("/draft_")
+            && frame.contains(&"draft_a.txt  draft_b.txt".to_string())
+    });
+    assert!(listed.is_some());
+    // The list goes once a name is typed, and the document is back after saving.
+    assert_eq!(terminal.last_rendered_rows()[0], "x");
+    assert_eq!(
+        fs::read_to_string(format!("{}/draft_c.txt", dir)).unwrap(),
+        "x\n"
+    );
+    assert!(!message_bar(&terminal).starts_with("Save as"));
+}
+
+#[test]
+fn the_prompt_draws_its_cursor_in_the_message_bar() {
+    let fixture = Fixture::new("prompt.txt", "hello world\n");
+    let (mut editor, terminal) = start(fixture.options());
+    terminal.push_keys([KeyEvent::Char('i'), KeyEvent::Ctrl('f')]);
+    terminal.push_keys(keys("wrld"));
+    terminal.push_keys([KeyEvent::Left, KeyEvent::Left, KeyEvent::Left]);
+    terminal.push_keys(keys("o"));
+    terminal.push_keys([KeyEvent::Char('\n')]);
+    block_on(editor.process_keypress()).unwrap();
+    terminal.clear_output();
+    block_on(editor.process_keypress()).unwrap();
+
+    // "Search ...: wo|rld", in the last row of the screen.
+    let prompt = "Search (ESC to cancel, Ctrl-G/Ctrl-T to navigate): ";
+    let frames = terminal.frames();
+    let last = &frames[frames.len() - 1];
+    assert_eq!(last[HEIGHT as usize - 1], format!("{}world", prompt));
+    let column = prompt.len() as u16 + 2;
+    assert!(terminal.output().contains(&ansi::goto(column + 1, HEIGHT)));
+    assert_eq!(editor.cursor_position.x, 6);
+}
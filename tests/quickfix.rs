@@ -0,0 +1,128 @@
+mod common;
+
+use common::{block_on, keys, start, Fixture};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+use zen::config::Config;
+use zen::editor::Position;
+use zen::quickfix::{self, Entry, Severity};
+use zen::terminal::KeyEvent;
+
+fn entry(file: &str, line: usize, column: usize, severity: Severity, message: &str) -> Entry {
+    Entry {
+        file: file.to_string(),
+        line,
+        column,
+        severity,
+        message: message.to_string(),
+    }
+}
+
+#[test]
+fn gcc_and_short_rustc_lines_are_parsed() {
+    let output = "\
+main.c:3:5: error: 'x' undeclared
+main.c: In function 'main':
+main.c:7: warning: unused variable 'y'
+main.c:8:1: note: declared here
+src/lib.rs:12:9: error[E0425]: cannot find value `z` in this scope
+";
+
+    assert_eq!(
+        quickfix::parse(output),
+        vec![
+            entry("main.c", 3, 5, Severity::Error, "'x' undeclared"),
+            entry("main.c", 7, 1, Severity::Warning, "unused variable 'y'"),
+            entry(
+                "src/lib.rs",
+                12,
+                9,
+                Severity::Error,
+                "cannot find value `z` in this scope"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn rustc_messages_take_the_location_on_the_next_line() {
+    let output = "\
+warning: unused variable: `a`
+  --> src/main.rs:2:9
+   |
+error[E0308]: mismatched types
+ --> src/main.rs:5:18
+warning: `demo` (bin \"demo\") generated 1 warning
+error: could not compile `demo`
+";
+
+    assert_eq!(
+        quickfix::parse(output),
+        vec![
+            entry(
+                "src/main.rs",
+                2,
+                9,
+                Severity::Warning,
+                "unused variable: `a`"
+            ),
+            entry("src/main.rs", 5, 18, Severity::Error, "mismatched types"),
+        ]
+    );
+}
+
+#[test]
+fn make_jumps_to_the_first_error_and_cnext_goes_on() {
+    let fixture = Fixture::new("build.c", "int a;\nint b;\nint c;\n");
+    let (mut editor, terminal) = start(fixture.options());
+    let output = format!(
+        "{0}:1:1: warning: first\\n{0}:3:5: error: second\\n{0}:2:2: error: third\\n",
+        fixture.path()
+    );
+    let config = format!("[makeprg]\nc = \"printf '{}'; exit 1\"\n", output);
+    editor.set_config(Config::parse(&config).unwrap());
+
+    terminal.push_keys([KeyEvent::Char(':')]);
+    terminal.push_keys(keys("make\n"));
+    let build = async {
+        // The editor keeps reading keys while the build runs.
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        terminal.push_keys([KeyEvent::Char(':')]);
+        terminal.push_keys(keys("cn\n"));
+        terminal.push_keys([KeyEvent::Ctrl('q')]);
+    };
+    let (result, ()) = block_on(async { tokio::join!(editor.run(), build) });
+    result.unwrap();
+
+    assert_eq!(editor.cursor_position, Position { x: 1, y: 1 });
+    let frames = terminal.frames();
+    let shown = |text: &str| {
+        frames
+            .iter()
+            .any(|frame| frame.iter().any(|line| line.contains(text)))
+    };
+    assert!(shown("[building…]"));
+    assert!(shown("[2 errors, 1 warning]"));
+    assert!(shown("(2 of 3) error: second"));
+}
+
+#[test]
+fn quitting_kills_a_running_build() {
+    let fixture = Fixture::new("slow_build.c", "int a;\n");
+    let marker = Path::new(fixture.path()).with_file_name("slow_build.done");
+    let (mut editor, terminal) = start(fixture.options());
+    let config = format!("[makeprg]\nc = \"sleep 1; touch {}\"\n", marker.display());
+    editor.set_config(Config::parse(&config).unwrap());
+
+    terminal.push_keys([KeyEvent::Char(':')]);
+    terminal.push_keys(keys("make\n"));
+    terminal.push_keys([KeyEvent::Ctrl('q')]);
+    let started = Instant::now();
+    block_on(editor.run()).unwrap();
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    // The shell was killed before it got to the second command.
+    thread::sleep(Duration::from_millis(1500));
+    assert!(!marker.exists());
+}
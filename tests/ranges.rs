@@ -0,0 +1,192 @@
+mod common;
+
+use common::{ex_command, lines, message_bar, press, start, Fixture};
+use zen::commands::ex::{self, Address, CommandRange, ExCommand};
+use zen::terminal::KeyEvent;
+
+#[test]
+fn ranges_are_parsed_before_the_command() {
+    assert_eq!(
+        ex::parse("%d").unwrap(),
+        ExCommand::Delete(CommandRange::Percent, None)
+    );
+    assert_eq!(
+        ex::parse("1,5d").unwrap(),
+        ExCommand::Delete(
+            CommandRange::Range(Address::Line(1), Address::Line(5)),
+            None
+        )
+    );
+    assert_eq!(
+        ex::parse(".,+3y").unwrap(),
+        ExCommand::Yank(
+            CommandRange::Range(Address::Current(0), Address::Current(3)),
+            None
+        )
+    );
+    assert_eq!(
+        ex::parse("$y").unwrap(),
+        ExCommand::Yank(CommandRange::LastLine, None)
+    );
+    assert_eq!(
+        ex::parse("y").unwrap(),
+        ExCommand::Yank(CommandRange::CurrentLine, None)
+    );
+    assert_eq!(
+        ex::parse("%s/foo/bar/g").unwrap(),
+        ExCommand::Substitute {
+            range: CommandRange::Percent,
+            pattern: "foo".to_string(),
+            replacement: "bar".to_string(),
+            global: true,
+            count_only: false,
+        }
+    );
+    assert!(ex::parse("%q").is_err());
+    assert!(ex::parse("s/a/b/x").is_err());
+}
+
+#[test]
+fn ranges_resolve_to_rows() {
+    // Range, cursor row, rows covered in a ten line document, and whether they were clamped.
+    let table = [
+        ("%", 4, 0..10, false),
+        ("", 4, 4..5, false),
+        (".", 4, 4..5, false),
+        ("$", 4, 9..10, false),
+        ("3", 4, 2..3, false),
+        ("10,20", 0, 9..10, true),
+        ("3,11", 0, 2..10, true),
+        ("0", 0, 0..1, true),
+        ("2,$-1", 4, 1..9, false),
+        ("$-3,$", 0, 6..10, false),
+        (".-1,+2", 4, 3..7, false),
+        (".,+5", 2, 2..8, false),
+        ("+,++", 2, 3..5, false),
+        ("-3,.", 1, 0..2, true),
+        ("5,2", 0, 1..5, false),
+        ("1+2,1+4", 0, 2..5, false),
+    ];
+
+    for (range, current, expected, clamped) in table {
+        let parsed = match ex::parse(&format!("{}d", range)).unwrap() {
+            ExCommand::Delete(range, _) => range,
+            other => panic!("{} parsed as {:?}", range, other),
+        };
+        assert_eq!(
+            parsed.rows(current, 10),
+            (expected, clamped),
+            "{:?} from row {}",
+            range,
+            current
+        );
+    }
+}
+
+#[test]
+fn malformed_ranges_and_marks_are_errors() {
+    let table = [
+        ("1,d", "Invalid range"),
+        ("'<,'>d", "Mark not set: '<"),
+        ("'ay", "Mark not set: 'a"),
+        ("%w", "No range allowed"),
+    ];
+
+    for (input, expected) in table {
+        let error = ex::parse(input).unwrap_err();
+        assert_eq!(error.to_string(), expected, "{:?}", input);
+    }
+}
+
+#[test]
+fn d_deletes_lines_in_one_undo_step() {
+    let fixture = Fixture::new("delete.txt", &lines(6));
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "2,4d");
+
+    assert_eq!(editor.document.contents(), "line 0\nline 4\nline 5\n");
+    assert_eq!(editor.yanked(), ["line 1", "line 2", "line 3"]);
+    assert_eq!(message_bar(&terminal), "3 fewer lines");
+    assert_eq!(editor.cursor_position.y, 1);
+
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), lines(6));
+
+    ex_command(&mut editor, &terminal, "%d");
+    assert_eq!(editor.document.contents(), "");
+}
+
+#[test]
+fn y_copies_lines_relative_to_the_cursor() {
+    let fixture = Fixture::new("yank.txt", &lines(6));
+    let (mut editor, terminal) = start(fixture.options());
+    // `k` moves down in zen.
+    press(&mut editor, &terminal, [KeyEvent::Char('k')]);
+    ex_command(&mut editor, &terminal, ".,+3y");
+
+    assert_eq!(editor.yanked(), ["line 1", "line 2", "line 3", "line 4"]);
+    assert_eq!(message_bar(&terminal), "4 lines yanked");
+    assert!(!editor.document.is_dirty());
+
+    // Past the end, the range stops at the last line.
+    ex_command(&mut editor, &terminal, ".,+9y");
+    assert_eq!(message_bar(&terminal), "Range clamped to 2,6 (1 more)");
+    press(&mut editor, &terminal, [KeyEvent::Char('l')]);
+    assert_eq!(message_bar(&terminal), "5 lines yanked");
+}
+
+#[test]
+fn s_replaces_the_first_match_or_all_with_g() {
+    let fixture = Fixture::new("substitute.txt", "a a\nb\na\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "%s/a/x/");
+    assert_eq!(editor.document.contents(), "x a\nb\nx\n");
+    assert_eq!(message_bar(&terminal), "2 substitutions on 2 lines");
+
+    ex_command(&mut editor, &terminal, "1s#a#yy#g");
+    assert_eq!(editor.document.contents(), "x yy\nb\nx\n");
+
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "a a\nb\na\n");
+
+    ex_command(&mut editor, &terminal, "%s/z/x/");
+    assert_eq!(message_bar(&terminal), "ERR: Pattern not found: z");
+}
+
+#[test]
+fn the_n_flag_and_count_only_count_matches() {
+    assert_eq!(
+        ex::parse("count a b").unwrap(),
+        ExCommand::Substitute {
+            range: CommandRange::Percent,
+            pattern: "a b".to_string(),
+            replacement: String::new(),
+            global: true,
+            count_only: true,
+        }
+    );
+    assert!(ex::parse("count").is_err());
+
+    let fixture = Fixture::new("count.txt", "a a\nb\na\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "%s/a//n");
+    assert_eq!(message_bar(&terminal), "2 matches on 2 lines");
+    ex_command(&mut editor, &terminal, "%s/a/x/gn");
+    assert_eq!(message_bar(&terminal), "3 matches on 2 lines");
+    ex_command(&mut editor, &terminal, "count a");
+    assert_eq!(message_bar(&terminal), "3 matches on 2 lines");
+    ex_command(&mut editor, &terminal, "count z");
+    assert_eq!(message_bar(&terminal), "ERR: Pattern not found: z");
+
+    assert_eq!(editor.document.contents(), "a a\nb\na\n");
+    assert!(!editor.document.is_dirty());
+
+    // Counted a chunk of rows at a time, which adds up the same.
+    let fixture = Fixture::new("count_long.txt", &"a b a\n".repeat(25_000));
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "count a");
+    assert_eq!(message_bar(&terminal), "50000 matches on 25000 lines");
+    ex_command(&mut editor, &terminal, "%s/b//n");
+    assert_eq!(message_bar(&terminal), "25000 matches on 25000 lines");
+}
@@ -0,0 +1,177 @@
+mod common;
+
+use common::{block_on, keys, start_with_clock, Fixture};
+use std::cell::Cell;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+use tokio::time;
+use zen::editor::Editor;
+use zen::recovery::{self, RecoveryChoice};
+use zen::terminal::{KeyEvent, MockTerminal};
+
+fn recovery_dir(fixture: &Fixture) -> PathBuf {
+    Path::new(fixture.path()).with_file_name("recovery")
+}
+
+// An editor on the fixture's file, with a recovery file for it left behind holding `contents`.
+fn editor_with_recovery(fixture: &Fixture, contents: &str) -> (Editor, MockTerminal, PathBuf) {
+    let dir = recovery_dir(fixture);
+    let path = recovery::path_for(&dir, fixture.path());
+    // The file was saved a while before the recovery file was written.
+    let saved = SystemTime::now() - Duration::from_secs(60);
+    File::options()
+        .write(true)
+        .open(fixture.path())
+        .unwrap()
+        .set_modified(saved)
+        .unwrap();
+    recovery::write(&path, contents).unwrap();
+
+    let terminal = MockTerminal::new(60, 12);
+    let mut editor = Editor::with_terminal(terminal.clone(), fixture.options());
+    editor.set_recovery_dir(dir);
+    (editor, terminal, path)
+}
+
+#[test]
+fn keys_at_the_recovery_prompt_pick_what_to_do() {
+    let choice = |c| RecoveryChoice::from_key(KeyEvent::Char(c));
+    assert_eq!(choice('r'), Some(RecoveryChoice::Recover));
+    assert_eq!(choice('R'), Some(RecoveryChoice::Recover));
+    assert_eq!(choice('d'), Some(RecoveryChoice::Delete));
+    assert_eq!(choice('o'), Some(RecoveryChoice::Open));
+    assert_eq!(
+        RecoveryChoice::from_key(KeyEvent::Esc),
+        Some(RecoveryChoice::Open)
+    );
+    assert_eq!(choice('x'), None);
+    assert_eq!(RecoveryChoice::from_key(KeyEvent::Ctrl('r')), None);
+}
+
+#[test]
+fn recovery_files_are_named_after_the_absolute_path() {
+    let dir = Path::new("/state/recovery");
+    let path = recovery::path_for(dir, "/home/me/notes.txt");
+    assert_eq!(path, dir.join("%home%me%notes.txt.recovery"));
+    assert_eq!(recovery::path_for(dir, "notes.txt").parent(), Some(dir));
+}
+
+#[test]
+fn recovering_loads_changes_newer_than_the_file_as_unsaved() {
+    let fixture = Fixture::new("recover.txt", "on disk\n");
+    let (mut editor, terminal, path) = editor_with_recovery(&fixture, "on disk\nnever saved\n");
+    assert!(recovery::is_newer(&path, Path::new(fixture.path())));
+
+    terminal.push_keys([KeyEvent::Char('x'), KeyEvent::Char('r')]);
+    terminal.push_keys(keys(":q!\n"));
+    block_on(editor.run()).unwrap();
+
+    let frames = terminal.frames();
+    assert!(frames
+        .iter()
+        .any(|frame| frame
+            .contains(&"(R)ecover, (D)elete it, or (O)pen the file anyway".to_string())));
+    assert_eq!(editor.document.contents(), "on disk\nnever saved\n");
+    assert!(editor.document.is_dirty());
+    assert_eq!(fixture.contents(), "on disk\n");
+    // Quitting throws the changes away, and the recovery file with them.
+    assert!(!path.exists());
+}
+
+#[test]
+fn deleting_or_opening_anyway_leave_the_file_as_it_is() {
+    let fixture = Fixture::new("recover_delete.txt", "on disk\n");
+    let (mut editor, terminal, path) = editor_with_recovery(&fixture, "stale\n");
+    terminal.push_keys([KeyEvent::Char('d')]);
+    terminal.push_keys(keys(":q\n"));
+    block_on(editor.run()).unwrap();
+    assert_eq!(editor.document.contents(), "on disk\n");
+    assert!(!path.exists());
+
+    let (mut editor, terminal, path) = editor_with_recovery(&fixture, "stale\n");
+    terminal.push_keys([KeyEvent::Esc]);
+    terminal.push_keys([KeyEvent::Ctrl('q')]);
+    block_on(editor.run()).unwrap();
+    assert_eq!(editor.document.contents(), "on disk\n");
+    assert!(!editor.document.is_dirty());
+    assert!(!path.exists());
+}
+
+#[test]
+fn changes_are_written_once_left_alone_and_removed_once_saved() {
+    let fixture = Fixture::new("journal.txt", "saved\n");
+    let dir = recovery_dir(&fixture);
+    let path = recovery::path_for(&dir, fixture.path());
+    let (mut editor, terminal, clock) = start_with_clock(fixture.options());
+    editor.set_recovery_dir(dir);
+    terminal.push_keys([KeyEvent::Char('i'), KeyEvent::Char('x'), KeyEvent::Esc]);
+
+    let session = async {
+        time::sleep(Duration::from_millis(600)).await;
+        assert!(!path.exists());
+
+        clock.advance(recovery::WRITE_DELAY);
+        // Keeps the scripted keys coming while the editor waits.
+        terminal.push_keys([KeyEvent::Char('H')]);
+        time::sleep(Duration::from_millis(600)).await;
+        assert_eq!(fs::read_to_string(&path).unwrap(), "xsaved\n");
+
+        terminal.push_keys(keys(":w\n"));
+        time::sleep(Duration::from_millis(100)).await;
+        assert!(!path.exists());
+        terminal.push_keys(keys(":q\n"));
+    };
+    let (result, ()) = block_on(async { tokio::join!(editor.run(), session) });
+    result.unwrap();
+    assert_eq!(fixture.contents(), "xsaved\n");
+}
+
+#[test]
+fn quitting_waits_for_a_write_still_going() {
+    let fixture = Fixture::new("slow_write.txt", "saved\n");
+    let dir = recovery_dir(&fixture);
+    let path = recovery::path_for(&dir, fixture.path());
+    // Writing blocks until something reads from the other end.
+    let temporary = path.with_extension("recovery.tmp");
+    fs::create_dir_all(&dir).unwrap();
+    let _ = fs::remove_file(&temporary);
+    assert!(Command::new("mkfifo")
+        .arg(&temporary)
+        .status()
+        .unwrap()
+        .success());
+
+    let (mut editor, terminal, clock) = start_with_clock(fixture.options());
+    editor.set_recovery_dir(dir);
+    terminal.push_keys([KeyEvent::Char('i'), KeyEvent::Char('x'), KeyEvent::Esc]);
+
+    let finished = Cell::new(false);
+    let run = async {
+        let result = editor.run().await;
+        finished.set(true);
+        result
+    };
+    let session = async {
+        time::sleep(Duration::from_millis(600)).await;
+        clock.advance(recovery::WRITE_DELAY);
+        terminal.push_keys([KeyEvent::Char('H')]);
+        time::sleep(Duration::from_millis(600)).await;
+
+        terminal.push_keys(keys(":q!\n"));
+        time::sleep(Duration::from_millis(300)).await;
+        assert!(!finished.get());
+        let frames = terminal.frames();
+        assert!(frames
+            .iter()
+            .any(|frame| frame.iter().any(|line| line.contains("[saving…]"))));
+
+        assert_eq!(fs::read_to_string(&temporary).unwrap(), "xsaved\n");
+        time::sleep(Duration::from_millis(300)).await;
+        assert!(finished.get());
+    };
+    let (result, ()) = block_on(async { tokio::join!(run, session) });
+    result.unwrap();
+    fs::remove_file(&temporary).unwrap();
+}
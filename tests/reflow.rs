@@ -0,0 +1,95 @@
+mod common;
+
+use common::{document, press, start, Fixture};
+use zen::terminal::KeyEvent;
+
+#[test]
+fn long_lines_are_split_and_short_ones_joined() {
+    let (mut document, _fixture) = document("one two three four five six\nseven\neight nine\n");
+    let len = document.reflow_paragraph(0..3, 14);
+
+    assert_eq!(len, 4);
+    assert_eq!(
+        document.contents(),
+        "one two three\nfour five six\nseven eight\nnine\n"
+    );
+    assert!(document.is_dirty());
+}
+
+#[test]
+fn indentation_and_paragraph_breaks_are_kept() {
+    let (mut document, _fixture) = document("  a b c d\n  e\n\nf g\nh\n");
+    document.reflow_paragraph(0..5, 7);
+
+    assert_eq!(document.contents(), "  a b c\n  d e\n\nf g h\n");
+}
+
+#[test]
+fn words_wider_than_the_width_get_their_own_line() {
+    let (mut document, _fixture) = document("a extraordinarily b\n");
+    document.reflow_paragraph(0..1, 5);
+
+    assert_eq!(document.contents(), "a\nextraordinarily\nb\n");
+}
+
+#[test]
+fn reflowing_is_undone_in_one_step() {
+    let (mut document, _fixture) = document("a\nb\nc\n");
+    document.reflow_paragraph(0..3, 80);
+    assert_eq!(document.contents(), "a b c\n");
+
+    document.undo();
+    assert_eq!(document.contents(), "a\nb\nc\n");
+}
+
+#[test]
+fn paragraph_range_stops_at_blank_lines() {
+    let (document, _fixture) = document("a\n\nb\nc\n  \nd\n");
+
+    assert_eq!(document.paragraph_range(3), 2..4);
+    assert_eq!(document.paragraph_range(0), 0..1);
+    assert_eq!(document.paragraph_range(1), 1..1);
+}
+
+#[test]
+fn gqq_reflows_the_paragraph_under_the_cursor() {
+    let words = "word ".repeat(20);
+    let fixture = Fixture::new(
+        "gqq.txt",
+        &format!("{}\nshort\n\nnext\nline\n", words.trim_end()),
+    );
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [
+            KeyEvent::Char('g'),
+            KeyEvent::Char('q'),
+            KeyEvent::Char('q'),
+        ],
+    );
+
+    let first = format!("{}word", "word ".repeat(15));
+    assert_eq!(
+        editor.document.contents(),
+        format!("{}\nword word word word short\n\nnext\nline\n", first)
+    );
+    assert_eq!(editor.cursor_position.y, 1);
+}
+
+#[test]
+fn gq_with_a_line_motion_only_reflows_those_lines() {
+    let fixture = Fixture::new("gqk.txt", "a\nb\nc\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [
+            KeyEvent::Char('g'),
+            KeyEvent::Char('q'),
+            KeyEvent::Char('k'),
+        ],
+    );
+
+    assert_eq!(editor.document.contents(), "a b\nc\n");
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, status_bar, type_text, Fixture};
+use std::env;
+use zen::commands::ex::{self, CommandRange, ExCommand};
+use zen::editor::Position;
+use zen::terminal::KeyEvent;
+
+#[test]
+fn yank_and_delete_take_a_register_name() {
+    assert_eq!(
+        ex::parse("y a").unwrap(),
+        ExCommand::Yank(CommandRange::CurrentLine, Some('a'))
+    );
+    assert_eq!(
+        ex::parse("%delete B").unwrap(),
+        ExCommand::Delete(CommandRange::Percent, Some('B'))
+    );
+    assert_eq!(
+        ex::parse("y 1").unwrap_err().to_string(),
+        "Invalid register name: 1"
+    );
+}
+
+#[test]
+fn ctrl_r_quote_inserts_the_last_yank() {
+    let fixture = Fixture::new("quote.txt", "one\ntwo\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "y");
+    // `k` moves down in zen.
+    press(&mut editor, &terminal, [KeyEvent::Char('k')]);
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('r')],
+    );
+    assert!(status_bar(&terminal).starts_with("-- INSERT (^R) -- "));
+
+    press(&mut editor, &terminal, [KeyEvent::Char('"')]);
+    assert_eq!(editor.document.contents(), "one\none\ntwo\n");
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 2 });
+    assert!(status_bar(&terminal).starts_with("-- INSERT -- "));
+
+    // The whole paste is a single undo step.
+    press(&mut editor, &terminal, [KeyEvent::Esc, KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "one\ntwo\n");
+}
+
+#[test]
+fn named_registers_keep_their_lines_and_uppercase_appends() {
+    let fixture = Fixture::new("named.txt", "one\ntwo\nthree\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "1y a");
+    ex_command(&mut editor, &terminal, "3y A");
+    ex_command(&mut editor, &terminal, "2y");
+    assert_eq!(editor.yanked(), ["two"]);
+
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('r')],
+    );
+    type_text(&mut editor, &terminal, "a");
+    assert_eq!(editor.document.contents(), "one\nthree\none\ntwo\nthree\n");
+}
+
+#[test]
+fn empty_registers_insert_nothing() {
+    let fixture = Fixture::new("empty.txt", "one\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('r')],
+    );
+    type_text(&mut editor, &terminal, "x");
+
+    assert_eq!(message_bar(&terminal), "ERR: Nothing in register x");
+    assert_eq!(editor.document.contents(), "one\n");
+    assert!(editor.pending_input().is_empty());
+}
+
+#[test]
+fn ctrl_r_zero_inserts_the_last_yank_even_after_a_delete() {
+    let fixture = Fixture::new("zero.txt", "one\ntwo\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "1y");
+    ex_command(&mut editor, &terminal, "2d");
+    assert_eq!(editor.yanked(), ["two"]);
+
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('r')],
+    );
+    type_text(&mut editor, &terminal, "0");
+    assert_eq!(editor.document.contents(), "one\none\n");
+}
+
+#[test]
+fn ctrl_r_percent_inserts_the_file_name() {
+    let fixture = Fixture::new("name.txt", "");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('r')],
+    );
+    type_text(&mut editor, &terminal, "%");
+
+    assert_eq!(editor.document.contents(), format!("{}\n", fixture.path()));
+    assert_eq!(editor.cursor_position.y, 0);
+}
+
+#[test]
+fn ctrl_r_with_an_invalid_register_name_inserts_nothing() {
+    let fixture = Fixture::new("invalid.txt", "one\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('r')],
+    );
+    type_text(&mut editor, &terminal, "!");
+
+    assert_eq!(message_bar(&terminal), "ERR: Invalid register name: !");
+    assert_eq!(editor.document.contents(), "one\n");
+    assert!(editor.pending_input().is_empty());
+}
+
+#[test]
+fn ctrl_r_is_undone_along_with_what_was_typed_before_it() {
+    let fixture = Fixture::new("typed.txt", "one\ntwo\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "2y");
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "ab");
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('r')]);
+    type_text(&mut editor, &terminal, "\"cd");
+    assert_eq!(editor.document.contents(), "abtwo\ncdone\ntwo\n");
+    assert_eq!(editor.cursor_position, Position { x: 2, y: 1 });
+
+    press(&mut editor, &terminal, [KeyEvent::Esc, KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "one\ntwo\n");
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('r')]);
+    assert_eq!(editor.document.contents(), "abtwo\ncdone\ntwo\n");
+}
+
+#[test]
+fn ctrl_r_plus_without_a_clipboard_tool_inserts_nothing() {
+    let fixture = Fixture::new("plus.txt", "one\n");
+    // None of the clipboard tools can be found on a PATH without any programs.
+    env::set_var("PATH", fixture.path());
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('r')],
+    );
+    type_text(&mut editor, &terminal, "+");
+
+    assert_eq!(message_bar(&terminal), "ERR: Nothing in register +");
+    assert_eq!(editor.document.contents(), "one\n");
+}
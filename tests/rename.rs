@@ -0,0 +1,72 @@
+mod common;
+
+use common::{block_on, ex_command, message_bar, start, type_text, Fixture};
+use std::fs;
+use std::path::Path;
+use zen::cli::LaunchOptions;
+use zen::terminal::KeyEvent;
+
+#[test]
+fn rename_moves_the_file_and_follows_its_extension() {
+    let fixture = Fixture::new("notes.txt", "fn main() {}\n");
+    let dir = Path::new(fixture.path()).parent().unwrap();
+    let target = dir.join("main.rs").to_str().unwrap().to_string();
+    let (mut editor, terminal) = start(fixture.options());
+    assert_eq!(editor.document.file_type(), "txt");
+
+    ex_command(&mut editor, &terminal, &format!("rename {}", target));
+    assert_eq!(message_bar(&terminal), format!("Renamed to \"{}\"", target));
+    assert!(!Path::new(fixture.path()).exists());
+    assert_eq!(fs::read_to_string(&target).unwrap(), "fn main() {}\n");
+    assert_eq!(editor.document.file_name.as_deref(), Some(target.as_str()));
+    assert_eq!(editor.document.file_type(), "rs");
+}
+
+#[test]
+fn rename_only_replaces_an_existing_file_with_a_bang() {
+    let fixture = Fixture::new("first.txt", "first\n");
+    let other = Path::new(fixture.path()).with_file_name("second.txt");
+    fs::write(&other, "second\n").unwrap();
+    let other = other.to_str().unwrap();
+    let (mut editor, terminal) = start(fixture.options());
+
+    ex_command(&mut editor, &terminal, &format!("rename {}", other));
+    assert_eq!(
+        message_bar(&terminal),
+        "ERR: File exists (add ! to override)"
+    );
+    assert_eq!(fixture.contents(), "first\n");
+
+    ex_command(&mut editor, &terminal, &format!("rename! {}", other));
+    assert_eq!(fs::read_to_string(other).unwrap(), "first\n");
+}
+
+#[test]
+fn unnamed_buffers_are_saved_first() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    ex_command(&mut editor, &terminal, "rename new.txt");
+
+    assert_eq!(
+        message_bar(&terminal),
+        "ERR: No file name, save it with :w first"
+    );
+    assert_eq!(editor.document.file_name, None);
+}
+
+#[test]
+fn save_as_picks_the_file_type_from_the_new_name() {
+    let fixture = Fixture::new("placeholder.txt", "");
+    let target = Path::new(fixture.path()).with_file_name("script.py");
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    terminal.push_keys([KeyEvent::Char('i')]);
+    block_on(editor.process_keypress()).unwrap();
+    type_text(&mut editor, &terminal, "print()");
+
+    terminal.push_keys([KeyEvent::Ctrl('s')]);
+    terminal.push_keys(common::keys(target.to_str().unwrap()));
+    terminal.push_keys([KeyEvent::Char('\n')]);
+    block_on(editor.process_keypress()).unwrap();
+
+    assert_eq!(fs::read_to_string(&target).unwrap(), "print()\n");
+    assert_eq!(editor.document.file_type(), "py");
+}
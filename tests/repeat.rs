@@ -0,0 +1,78 @@
+mod common;
+
+use common::{message_bar, press, start, status_bar, type_text, Fixture};
+use zen::editor::{Position, SearchDirection};
+use zen::terminal::KeyEvent;
+
+#[test]
+fn cgn_changes_the_match_and_dot_changes_the_next_one() {
+    let fixture = Fixture::new("cgn.txt", "foo bar foo\nfoo\n");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.search_set_pattern("foo".to_string(), SearchDirection::Forward);
+    editor.cursor_position = Position::default();
+
+    // The match under the cursor is the one changed.
+    type_text(&mut editor, &terminal, "cgn");
+    assert_eq!(editor.document.contents(), " bar foo\nfoo\n");
+    assert!(status_bar(&terminal).starts_with("-- INSERT -- "));
+    type_text(&mut editor, &terminal, "baz");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    assert_eq!(editor.document.contents(), "baz bar foo\nfoo\n");
+
+    press(&mut editor, &terminal, [KeyEvent::Char('.')]);
+    assert_eq!(editor.document.contents(), "baz bar baz\nfoo\n");
+    assert_eq!(editor.cursor_position, Position { x: 10, y: 0 });
+    assert!(status_bar(&terminal).starts_with("-- NORMAL -- "));
+
+    press(&mut editor, &terminal, [KeyEvent::Char('.')]);
+    assert_eq!(editor.document.contents(), "baz bar baz\nbaz\n");
+    press(&mut editor, &terminal, [KeyEvent::Char('.')]);
+    assert_eq!(message_bar(&terminal), "ERR: Pattern not found: foo");
+
+    // Each repeat is undone as a whole.
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "baz bar baz\nfoo\n");
+}
+
+#[test]
+fn cgn_changes_the_whole_match_past_wide_characters() {
+    let fixture = Fixture::new("cgn_wide.txt", "日本 café crème\n");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.search_set_pattern("crème".to_string(), SearchDirection::Forward);
+    editor.cursor_position = Position::default();
+
+    type_text(&mut editor, &terminal, "cgn");
+    assert_eq!(editor.document.contents(), "日本 café \n");
+    assert_eq!(editor.cursor_position, Position { x: 8, y: 0 });
+}
+
+#[test]
+fn dot_wraps_around_to_the_first_match() {
+    let fixture = Fixture::new("cgn_wrap.txt", "x = old;\ny = old;\n");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.search_set_pattern("old".to_string(), SearchDirection::Forward);
+    // `k` moves down in zen.
+    press(&mut editor, &terminal, [KeyEvent::Char('k')]);
+    type_text(&mut editor, &terminal, "cgnnew");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+
+    press(&mut editor, &terminal, [KeyEvent::Char('.')]);
+    assert_eq!(editor.document.contents(), "x = new;\ny = new;\n");
+    assert_eq!(
+        message_bar(&terminal),
+        "search hit BOTTOM, continuing at TOP"
+    );
+}
+
+#[test]
+fn cgn_needs_a_search() {
+    let fixture = Fixture::new("cgn_none.txt", "foo\n");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "cgn");
+
+    assert_eq!(message_bar(&terminal), "ERR: No previous search");
+    assert!(status_bar(&terminal).starts_with("-- NORMAL -- "));
+    // Nothing was changed, so there's nothing to repeat.
+    press(&mut editor, &terminal, [KeyEvent::Char('.')]);
+    assert_eq!(editor.document.contents(), "foo\n");
+}
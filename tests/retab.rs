@@ -0,0 +1,65 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, Fixture};
+use zen::row::Row;
+use zen::terminal::KeyEvent;
+
+fn retab(text: &str, to_spaces: bool) -> (bool, String) {
+    let mut row = Row::from(text);
+    let changed = row.retab(to_spaces, 4);
+    (changed, row.string)
+}
+
+#[test]
+fn tabs_become_spaces() {
+    assert_eq!(retab("\t\tx", true), (true, "        x".to_string()));
+    assert_eq!(retab("    x\ty", true), (false, "    x\ty".to_string()));
+}
+
+#[test]
+fn spaces_become_tabs_where_they_fill_a_tab_stop() {
+    assert_eq!(retab("      x", false), (true, "\t  x".to_string()));
+    assert_eq!(retab("  x", false), (false, "  x".to_string()));
+}
+
+#[test]
+fn mixed_indentation_keeps_its_columns() {
+    // Two spaces then a tab still reach column 4, not 6.
+    assert_eq!(retab("  \tx", true), (true, "    x".to_string()));
+    assert_eq!(retab(" \t  x", false), (true, "\t  x".to_string()));
+}
+
+#[test]
+fn indent_levels_count_whole_tab_stops() {
+    assert_eq!(Row::from("\t  \tx").indent_level(4), 2);
+    assert_eq!(Row::from("      x").indent_level(4), 1);
+    assert_eq!(Row::from("x  ").indent_level(4), 0);
+    assert_eq!(Row::from(" \t x").indent_string(), " \t ");
+}
+
+#[test]
+fn set_indent_replaces_the_leading_whitespace() {
+    let mut row = Row::from(" \t x y");
+    row.set_indent(2, true, 4);
+    assert_eq!(row.string, "        x y");
+    assert_eq!(row.set_indent(1, false, 4).string, "\tx y");
+    assert_eq!(row.set_indent(0, false, 4).string, "x y");
+    assert_eq!(row.len(), 3);
+}
+
+#[test]
+fn retab_command_converts_the_document_and_undoes_at_once() {
+    let fixture = Fixture::new("retab.txt", "\tone\n\t\ttwo\nthree\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "retab");
+
+    assert_eq!(message_bar(&terminal), "2 lines changed");
+    assert_eq!(editor.document.contents(), "    one\n        two\nthree\n");
+    assert!(editor.document.is_dirty());
+
+    ex_command(&mut editor, &terminal, "retab!");
+    assert_eq!(editor.document.contents(), "\tone\n\t\ttwo\nthree\n");
+
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "    one\n        two\nthree\n");
+}
@@ -0,0 +1,211 @@
+use proptest::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+use zen::row::Row;
+
+// Grapheme clusters the generated rows are built from: ASCII, combining diacritics,
+// emoji sequences (ZWJ, flags, skin tones), RTL scripts, CJK and Devanagari.
+const CLUSTERS: &[&str] = &[
+    "a",
+    "Z",
+    "0",
+    " ",
+    "\t",
+    "_",
+    "{",
+    "é",
+    "e\u{301}",
+    "n\u{303}\u{308}",
+    "👨\u{200d}👩\u{200d}👧",
+    "🇯🇵",
+    "👍🏽",
+    "😀",
+    "ש",
+    "ל",
+    "و",
+    "م",
+    "ر",
+    "漢",
+    "字",
+    "न",
+    "स्",
+    "ते",
+];
+
+// Characters that always form a cluster of their own, so inserting one never merges with a neighbour.
+const STANDALONE: &[char] = &['a', 'Q', '7', ' ', '-', '(', 'é', 'ש', 'ع', '中', 'ß'];
+
+fn row_string() -> impl Strategy<Value = String> {
+    prop::collection::vec(prop::sample::select(CLUSTERS), 0..40).prop_map(|parts| parts.concat())
+}
+
+// A generated string along with a grapheme index within it (inclusive of the end).
+fn string_and_index() -> impl Strategy<Value = (String, usize)> {
+    row_string().prop_flat_map(|string| {
+        let len = string.graphemes(true).count();
+        (Just(string), 0..=len)
+    })
+}
+
+fn grapheme_len(string: &str) -> usize {
+    string.graphemes(true).count()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10_000))]
+
+    #[test]
+    fn insert_then_delete_restores_the_row(
+        (string, at) in string_and_index(),
+        c in prop::sample::select(STANDALONE),
+    ) {
+        let mut row = Row::from(string.as_str());
+        row.insert(at, c);
+        prop_assert_eq!(row.len(), grapheme_len(&string) + 1);
+        prop_assert_eq!(row.len(), grapheme_len(&row.string));
+
+        row.delete(at);
+        prop_assert_eq!(&row.string, &string);
+        prop_assert_eq!(row.len(), grapheme_len(&string));
+    }
+
+    #[test]
+    fn split_then_append_restores_the_row((string, at) in string_and_index()) {
+        let mut row = Row::from(string.as_str());
+        let tail = row.split(at);
+        prop_assert_eq!(row.len(), at);
+        prop_assert_eq!(row.len() + tail.len(), grapheme_len(&string));
+
+        row.append(&tail);
+        prop_assert_eq!(&row.string, &string);
+        prop_assert_eq!(row.len(), grapheme_len(&string));
+    }
+
+    #[test]
+    fn split_then_append_restores_arbitrary_strings(string in any::<String>(), at in 0usize..64) {
+        let mut row = Row::from(string.as_str());
+        let tail = row.split(at);
+        row.append(&tail);
+
+        prop_assert_eq!(&row.string, &string);
+        prop_assert_eq!(row.len(), grapheme_len(&string));
+    }
+
+    #[test]
+    fn insert_str_then_deletes_restore_the_row(
+        (string, at) in string_and_index(),
+        inserted in prop::collection::vec(prop::sample::select(STANDALONE), 1..8),
+    ) {
+        let inserted: String = inserted.into_iter().collect();
+        let mut row = Row::from(string.as_str());
+        row.insert_str(at, &inserted);
+        prop_assert_eq!(row.len(), grapheme_len(&string) + grapheme_len(&inserted));
+
+        for _ in 0..grapheme_len(&inserted) {
+            row.delete(at);
+        }
+        prop_assert_eq!(&row.string, &string);
+    }
+
+    #[test]
+    fn replace_str_matches_splicing_the_graphemes(
+        (string, at) in string_and_index(),
+        len in 0usize..6,
+        replacement in row_string(),
+    ) {
+        let graphemes: Vec<&str> = string.graphemes(true).collect();
+        let end = (at + len).min(graphemes.len());
+        let expected = [
+            graphemes[..at].concat(),
+            replacement.clone(),
+            graphemes[end..].concat(),
+        ].concat();
+
+        let mut row = Row::from(string.as_str());
+        row.replace_str(at, at + len, &replacement);
+        prop_assert_eq!(&row.string, &expected);
+        prop_assert_eq!(row.len(), grapheme_len(&expected));
+    }
+
+    #[test]
+    fn edits_keep_every_grapheme_in_place(
+        (string, at) in string_and_index(),
+        inserted in row_string(),
+        removed in 0usize..4,
+    ) {
+        // Clusters may join with their neighbours here, e.g. an accent after an `e`.
+        let mut row = Row::from(string.as_str());
+        row.insert_str(at, &inserted);
+        row.replace_str(at.saturating_sub(1), at + removed, "\u{301}");
+        row.delete(at);
+
+        let graphemes: Vec<&str> = row.string.graphemes(true).collect();
+        prop_assert_eq!(row.len(), graphemes.len());
+        for (index, grapheme) in graphemes.iter().enumerate() {
+            prop_assert_eq!(row.grapheme_at(index), Some(*grapheme));
+        }
+        prop_assert_eq!(row.grapheme_at(graphemes.len()), None);
+    }
+
+    #[test]
+    fn deleting_out_of_bounds_is_a_no_op((string, _) in string_and_index(), extra in 0usize..4) {
+        let mut row = Row::from(string.as_str());
+        row.delete(grapheme_len(&string) + extra);
+
+        prop_assert_eq!(&row.string, &string);
+        prop_assert_eq!(row.len(), grapheme_len(&string));
+    }
+}
+
+#[test]
+fn replace_str_replaces_a_range_of_graphemes() {
+    // Row, range, replacement, result.
+    let cases = [
+        ("hello world", 0..5, "howdy", "howdy world"),
+        ("hello world", 6..11, "there", "hello there"),
+        ("hello world", 4..7, "", "hellorld"),
+        ("hello world", 5..5, ",", "hello, world"),
+        ("hello world", 0..11, "hi", "hi"),
+        ("ab", 1..2, "long replacement", "along replacement"),
+        ("e\u{301}🇯🇵x", 1..2, "y", "e\u{301}yx"),
+        ("abc", 2..10, "Z", "abZ"),
+        ("abc", 7..9, "!", "abc!"),
+    ];
+
+    for (string, range, replacement, expected) in cases {
+        let mut row = Row::from(string);
+        row.replace_str(range.start, range.end, replacement);
+        assert_eq!(row.string, expected, "{string} {range:?}");
+        assert_eq!(row.len(), grapheme_len(expected));
+    }
+}
+
+#[test]
+fn combining_marks_join_the_grapheme_before_them() {
+    let mut row = Row::from("cafe!");
+    row.insert(4, '\u{301}');
+    assert_eq!(row.len(), 5);
+    assert_eq!(row.grapheme_at(3), Some("e\u{301}"));
+    assert_eq!(row.grapheme_at(4), Some("!"));
+
+    // Deleting the accent's grapheme takes the whole cluster with it.
+    row.delete(3);
+    assert_eq!(row.string, "caf!");
+    assert_eq!(row.len(), 4);
+}
+
+#[test]
+fn long_rows_stay_in_step_after_many_edits() {
+    let mut row = Row::from("ab".repeat(5_000).as_str());
+    for at in (0..10_000).step_by(7) {
+        row.insert(at, 'é');
+        row.delete(at + 1);
+    }
+    let tail = row.split(5_000);
+    assert_eq!(row.len(), 5_000);
+    assert_eq!(tail.len(), 5_000);
+    assert_eq!(row.len(), grapheme_len(&row.string));
+    assert_eq!(
+        tail.grapheme_at(4_999),
+        tail.string.graphemes(true).next_back()
+    );
+}
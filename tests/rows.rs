@@ -0,0 +1,172 @@
+mod common;
+
+use common::document;
+use zen::document::{Edit, Selection};
+use zen::editor::Position;
+
+#[test]
+fn rows_are_inserted_before_the_index_or_at_the_end() {
+    let (mut document, _fixture) = document("a\nc\n");
+    document.insert_row_at(1, "b".to_string());
+    document.insert_row_at(3, "d".to_string());
+
+    assert_eq!(document.contents(), "a\nb\nc\nd\n");
+    assert!(document.is_dirty());
+
+    document.insert_row_at(9, "z".to_string());
+    assert_eq!(document.len(), 4);
+}
+
+#[test]
+fn removed_rows_are_returned() {
+    let (mut document, _fixture) = document("a\nb\nc\n");
+    let row = document.remove_row(1);
+
+    assert_eq!(row.map(|row| row.string), Some("b".to_string()));
+    assert_eq!(document.contents(), "a\nc\n");
+    assert!(document.remove_row(2).is_none());
+}
+
+#[test]
+fn inserting_and_removing_rows_are_undone_one_at_a_time() {
+    let (mut document, _fixture) = document("a\nb\n");
+    document.insert_row_at(0, "new".to_string());
+    document.remove_row(2);
+    assert_eq!(document.contents(), "new\na\n");
+
+    document.undo();
+    assert_eq!(document.contents(), "new\na\nb\n");
+    document.undo();
+    assert_eq!(document.contents(), "a\nb\n");
+    document.redo();
+    assert_eq!(document.contents(), "new\na\nb\n");
+}
+
+#[test]
+fn read_only_documents_keep_their_rows() {
+    let (mut document, _fixture) = document("a\n");
+    document.set_read_only(true);
+    document.insert_row_at(0, "b".to_string());
+
+    assert!(document.remove_row(0).is_none());
+    assert_eq!(document.contents(), "a\n");
+    assert!(!document.is_dirty());
+}
+
+#[test]
+fn text_ranges_span_rows_and_count_graphemes() {
+    let (document, _fixture) = document("héllo\nwörld\nend\n");
+    let at = |x, y| Position { x, y };
+
+    assert_eq!(document.get_text_range(&at(1, 0), &at(4, 0)), "éll");
+    assert_eq!(document.get_text_range(&at(3, 0), &at(2, 1)), "lo\nwö");
+    assert_eq!(document.get_text_range(&at(4, 0), &at(1, 2)), "o\nwörld\ne");
+    assert_eq!(
+        document.get_text_range(&at(0, 1), &at(0, 3)),
+        "wörld\nend\n"
+    );
+}
+
+#[test]
+fn empty_and_backwards_text_ranges_are_empty() {
+    let (document, _fixture) = document("abc\n");
+    let at = |x, y| Position { x, y };
+
+    assert_eq!(document.get_text_range(&at(1, 0), &at(1, 0)), "");
+    assert_eq!(document.get_text_range(&at(2, 0), &at(0, 0)), "");
+    assert_eq!(document.get_text_range(&at(0, 5), &at(0, 6)), "");
+}
+
+#[test]
+fn characters_are_read_by_grapheme() {
+    let (document, _fixture) = document("ae\u{301}b\n\n");
+    let at = |x, y| Position { x, y };
+
+    assert_eq!(document.char_at(&at(0, 0)), Some('a'));
+    // The first code point of a decomposed é.
+    assert_eq!(document.char_at(&at(1, 0)), Some('e'));
+    assert_eq!(document.char_at(&at(2, 0)), Some('b'));
+    assert_eq!(document.char_at(&at(3, 0)), None);
+    assert_eq!(document.char_at(&at(0, 1)), None);
+    assert_eq!(document.char_at(&at(0, 5)), None);
+}
+
+#[test]
+fn characters_around_a_position_stay_on_its_row() {
+    let (document, _fixture) = document("ab\ncd\n");
+    let at = |x, y| Position { x, y };
+
+    assert_eq!(document.char_before(&at(1, 0)), Some('a'));
+    assert_eq!(document.char_after(&at(0, 0)), Some('b'));
+    assert_eq!(document.char_before(&at(0, 1)), None);
+    assert_eq!(document.char_after(&at(1, 0)), None);
+    assert_eq!(document.char_before(&at(2, 1)), Some('d'));
+}
+
+fn edit(start: (usize, usize), end: (usize, usize), text: &str) -> Edit {
+    Edit {
+        range: Selection {
+            start: Position {
+                x: start.1,
+                y: start.0,
+            },
+            end: Position { x: end.1, y: end.0 },
+        },
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn edits_refer_to_the_document_before_any_of_them() {
+    let (mut document, _fixture) = document("let a = a + 1;\nprint(a)\n");
+    // Given in any order, with one adding a row and one joining two.
+    let edits = vec![
+        edit((1, 6), (1, 7), "count"),
+        edit((0, 4), (0, 5), "count"),
+        edit((0, 8), (0, 9), "count"),
+        edit((0, 14), (1, 0), "\n\n"),
+    ];
+    document.apply_edits_transactional(edits).unwrap();
+    assert_eq!(
+        document.contents(),
+        "let count = count + 1;\n\nprint(count)\n"
+    );
+
+    // They're undone and redone in one step.
+    document.undo();
+    assert_eq!(document.contents(), "let a = a + 1;\nprint(a)\n");
+    document.redo();
+    assert_eq!(document.len(), 3);
+}
+
+#[test]
+fn overlapping_or_out_of_range_edits_change_nothing() {
+    let (mut document, _fixture) = document("abc\ndef\n");
+    let overlapping = vec![edit((0, 0), (0, 2), "x"), edit((0, 1), (0, 3), "y")];
+    assert!(document.apply_edits_transactional(overlapping).is_err());
+
+    let out_of_range = vec![edit((0, 0), (0, 1), "x"), edit((5, 0), (5, 1), "y")];
+    assert!(document.apply_edits_transactional(out_of_range).is_err());
+    assert_eq!(document.contents(), "abc\ndef\n");
+    assert!(!document.is_dirty());
+
+    // Appending at the end of the document and deleting past it are in range.
+    let edits = vec![edit((1, 1), (9, 0), ""), edit((0, 3), (0, 3), "!")];
+    document.apply_edits_transactional(edits).unwrap();
+    assert_eq!(document.contents(), "abc!\nd\n");
+}
+
+#[test]
+fn wrapped_rows_count_the_screen_lines_they_take_up() {
+    let (document, _fixture) = document("short\n\n\tab\n日本語日本語\n");
+    let row = |y| document.row(y).unwrap();
+    assert_eq!(row(0).visual_line_count(4, 4), 2);
+    assert_eq!(row(1).visual_line_count(4, 4), 1);
+    assert_eq!(row(2).visual_width(4), 6);
+    assert_eq!(row(2).visual_line_count(4, 4), 2);
+    assert_eq!(row(3).visual_width(4), 12);
+    assert_eq!(row(3).visual_line_count(4, 4), 3);
+
+    assert_eq!(document.visual_line_count(4, 4), 8);
+    assert_eq!(document.visual_line_count(80, 4), document.len());
+}
@@ -0,0 +1,88 @@
+// End-to-end sessions: keys are scripted up front and the editor's run loop
+// plays them back until it quits, like a user at a real terminal would.
+mod common;
+
+use common::{block_on, keys, start_with_clock, Fixture, HEIGHT};
+use std::time::Duration;
+use zen::terminal::KeyEvent;
+
+// The frame drawn right before quitting; the very last one leaves the screen as it was.
+fn frame_before_quit(frames: &[Vec<String>]) -> &Vec<String> {
+    &frames[frames.len() - 2]
+}
+
+#[test]
+fn edit_save_and_quit() {
+    let fixture = Fixture::copy("lines.txt");
+    let (mut editor, terminal, _) = start_with_clock(fixture.options());
+
+    terminal.push_keys(keys("iabc"));
+    terminal.push_keys([KeyEvent::Ctrl('s'), KeyEvent::Esc, KeyEvent::Ctrl('q')]);
+    block_on(editor.run()).unwrap();
+
+    assert_eq!(
+        fixture.contents(),
+        "abcfirst line\nsecond line\nthird line\n"
+    );
+
+    let frames = terminal.frames();
+    let frame = frame_before_quit(&frames);
+    assert!(frame[HEIGHT as usize - 2].contains(" - 3 lines"));
+    assert!(!frame[HEIGHT as usize - 2].contains("(modified)"));
+    assert_eq!(frame[HEIGHT as usize - 1], "File saved successfully.");
+    // The shell's screen is restored by leaving the alternate screen, not by clearing it.
+    assert_eq!(frames.last().unwrap(), frame);
+}
+
+#[test]
+fn quitting_a_dirty_document_needs_confirmation() {
+    let fixture = Fixture::copy("hello.rs");
+    let (mut editor, terminal, _) = start_with_clock(fixture.options());
+
+    terminal.push_keys([KeyEvent::Char('i'), KeyEvent::Char('x'), KeyEvent::Esc]);
+    terminal.push_keys([KeyEvent::Ctrl('q'); 4]);
+    block_on(editor.run()).unwrap();
+
+    let frames = terminal.frames();
+    let warnings: Vec<&String> = frames[..frames.len() - 1]
+        .iter()
+        .map(|frame| &frame[HEIGHT as usize - 1])
+        .filter(|message| message.starts_with("WARNING!"))
+        .collect();
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings[0].contains("Press Ctrl-Q 3 more times"));
+    assert_eq!(
+        fixture.contents(),
+        "fn main() {\n    println!(\"Hello, world!\");\n}\n"
+    );
+}
+
+#[test]
+fn status_messages_expire_after_five_seconds() {
+    let fixture = Fixture::copy("lines.txt");
+    let (mut editor, terminal, clock) = start_with_clock(fixture.options());
+    let message_bar = |terminal: &zen::terminal::MockTerminal| {
+        terminal.last_rendered_rows()[HEIGHT as usize - 1].clone()
+    };
+    assert!(message_bar(&terminal).starts_with("HELP:"));
+
+    clock.advance(Duration::from_millis(4_900));
+    editor.refresh_screen().unwrap();
+    assert!(message_bar(&terminal).starts_with("HELP:"));
+
+    clock.advance(Duration::from_millis(200));
+    editor.refresh_screen().unwrap();
+    assert_eq!(message_bar(&terminal), "");
+}
+
+#[test]
+fn highlighted_fixture_renders_plain_text() {
+    let fixture = Fixture::copy("hello.rs");
+    let (_, terminal, _) = start_with_clock(fixture.options());
+    let rows = terminal.last_rendered_rows();
+
+    assert_eq!(rows[0], "fn main() {");
+    assert_eq!(rows[1], "    println!(\"Hello, world!\");");
+    assert_eq!(rows[2], "}");
+    assert!(rows[HEIGHT as usize - 2].ends_with("Rust | 1/3"));
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{block_on, message_bar, press, start, start_with_clock, type_text, Fixture};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use zen::cli::LaunchOptions;
+use zen::terminal::KeyEvent;
+
+#[test]
+fn scripts_run_their_commands_in_name_order() {
+    let fixture = Fixture::new("b.zen", "iabbrev teh the\n\" a comment\n\nset list\n");
+    let dir = Path::new(fixture.path()).parent().unwrap();
+    fs::write(dir.join("a.zen"), "iabbrev teh then\nset nolist\n").unwrap();
+    fs::write(dir.join("notes.txt"), "set scrollbar\n").unwrap();
+
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    block_on(editor.run_scripts(dir));
+    editor.refresh_screen().unwrap();
+
+    // Only the startup help is shown, nothing failed.
+    assert!(!message_bar(&terminal).contains("more"));
+
+    // b.zen ran last, and notes.txt isn't a script.
+    press(&mut editor, &terminal, [KeyEvent::Char('i')]);
+    type_text(&mut editor, &terminal, "teh ");
+    assert_eq!(terminal.last_rendered_rows()[0], "the·");
+}
+
+#[test]
+fn failing_lines_are_reported_and_the_rest_still_run() {
+    let fixture = Fixture::new("startup.zen", "set number\nset list\ncolorscheme desert\n");
+    let dir = Path::new(fixture.path()).parent().unwrap();
+
+    let (mut editor, terminal, clock) = start_with_clock(LaunchOptions::default());
+    block_on(editor.run_scripts(dir));
+    clock.advance(Duration::from_secs(10));
+    editor.refresh_screen().unwrap();
+
+    assert_eq!(
+        message_bar(&terminal),
+        "ERR: startup.zen:1: Unknown option: number (1 more)"
+    );
+}
+
+#[test]
+fn a_missing_directory_runs_nothing() {
+    let (mut editor, terminal) = start(LaunchOptions::default());
+    block_on(editor.run_scripts(Path::new("/nonexistent/zen/after")));
+    editor.refresh_screen().unwrap();
+
+    assert!(!message_bar(&terminal).contains("more"));
+}
@@ -0,0 +1,166 @@
+mod common;
+
+use common::{block_on, ex_command, keys, lines, press, start, Fixture};
+use zen::commands::{Command, CommandContext, CommandOutcome};
+use zen::document::Document;
+use zen::editor::Position;
+use zen::mode::EditorMode;
+use zen::terminal::KeyEvent;
+
+// Runs the command with the view starting at `view_top` and the cursor on row `y`.
+// Returns the new top row and the row the cursor ends up on.
+fn scroll(document: &mut Document, view_top: usize, y: usize, command: Command) -> (usize, usize) {
+    let mut ctx = CommandContext {
+        document,
+        cursor_position: Position { x: 0, y },
+        view_height: 10,
+        view_top,
+        text_width: 80,
+        mode: EditorMode::Normal,
+    };
+    match block_on(command.execute(&mut ctx)).unwrap() {
+        CommandOutcome::ScrollTo { top, cursor } => (top, cursor.y),
+        _ => panic!("{} didn't scroll", y),
+    }
+}
+
+#[test]
+fn z_commands_ask_for_a_top_row_within_the_document() {
+    let fixture = Fixture::new("scroll.txt", &lines(30));
+    let mut document = block_on(Document::open(fixture.path())).unwrap();
+
+    let center = || Command::ScrollCursorCenter;
+    let top = || Command::ScrollCursorTop;
+    let bottom = || Command::ScrollCursorBottom;
+    assert_eq!(scroll(&mut document, 0, 20, center()), (16, 20));
+    assert_eq!(scroll(&mut document, 0, 20, top()), (20, 20));
+    assert_eq!(scroll(&mut document, 0, 20, bottom()), (11, 20));
+
+    // Near the edges of the document, the view stops at them.
+    assert_eq!(scroll(&mut document, 0, 2, center()), (0, 2));
+    assert_eq!(scroll(&mut document, 0, 3, bottom()), (0, 3));
+    assert_eq!(scroll(&mut document, 0, 29, top()), (29, 29));
+}
+
+#[test]
+fn ctrl_e_and_ctrl_y_scroll_a_line_and_keep_the_cursor_in_view() {
+    let fixture = Fixture::new("lines.txt", &lines(30));
+    let mut document = block_on(Document::open(fixture.path())).unwrap();
+
+    assert_eq!(
+        scroll(&mut document, 0, 5, Command::ScrollViewportDown),
+        (1, 5)
+    );
+    assert_eq!(
+        scroll(&mut document, 0, 0, Command::ScrollViewportDown),
+        (1, 1)
+    );
+    assert_eq!(
+        scroll(&mut document, 29, 29, Command::ScrollViewportDown),
+        (29, 29)
+    );
+    assert_eq!(
+        scroll(&mut document, 5, 14, Command::ScrollViewportUp),
+        (4, 13)
+    );
+    assert_eq!(
+        scroll(&mut document, 0, 3, Command::ScrollViewportUp),
+        (0, 3)
+    );
+}
+
+#[test]
+fn ctrl_d_and_ctrl_u_move_the_view_and_cursor_half_a_page() {
+    let fixture = Fixture::new("half.txt", &lines(30));
+    let mut document = block_on(Document::open(fixture.path())).unwrap();
+
+    assert_eq!(
+        scroll(&mut document, 0, 2, Command::ScrollHalfPageDown),
+        (5, 7)
+    );
+    assert_eq!(
+        scroll(&mut document, 10, 12, Command::ScrollHalfPageUp),
+        (5, 7)
+    );
+
+    // Once the last row is in view, only the cursor keeps going.
+    assert_eq!(
+        scroll(&mut document, 18, 20, Command::ScrollHalfPageDown),
+        (20, 25)
+    );
+    assert_eq!(
+        scroll(&mut document, 20, 27, Command::ScrollHalfPageDown),
+        (20, 29)
+    );
+    assert_eq!(
+        scroll(&mut document, 2, 4, Command::ScrollHalfPageUp),
+        (0, 0)
+    );
+}
+
+#[test]
+fn z_commands_move_the_view_but_not_the_cursor() {
+    let fixture = Fixture::new("z.txt", &lines(40));
+    let (mut editor, terminal) = start(fixture.options());
+    // `k` moves down in zen.
+    press(&mut editor, &terminal, [KeyEvent::Char('k'); 20]);
+
+    press(&mut editor, &terminal, keys("zt"));
+    assert_eq!(terminal.last_rendered_rows()[0], "line 20");
+    press(&mut editor, &terminal, keys("zb"));
+    assert_eq!(terminal.last_rendered_rows()[0], "line 11");
+    press(&mut editor, &terminal, keys("zz"));
+    assert_eq!(terminal.last_rendered_rows()[0], "line 16");
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 20 });
+}
+
+#[test]
+fn ctrl_e_scrolls_the_editor_view() {
+    let fixture = Fixture::new("ctrl_e.txt", &lines(40));
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('e'); 3]);
+
+    assert_eq!(terminal.last_rendered_rows()[0], "line 3");
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 3 });
+
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('d')]);
+    assert_eq!(terminal.last_rendered_rows()[0], "line 8");
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 8 });
+}
+
+#[test]
+fn the_scrollbar_follows_the_view_and_narrows_the_text_area() {
+    let mut contents = lines(40);
+    contents.insert_str(0, &format!("{}\n", "x".repeat(70)));
+    let fixture = Fixture::new("scrollbar.txt", &contents);
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "set scrollbar");
+
+    // 41 lines in a view of 10 make a thumb of two rows.
+    let bar = |terminal: &zen::terminal::MockTerminal| -> String {
+        terminal.last_rendered_rows()[..10]
+            .iter()
+            .map(|row| row.chars().last().unwrap())
+            .collect()
+    };
+    assert_eq!(bar(&terminal), "██││││││││");
+    assert_eq!(
+        terminal.last_rendered_rows()[0],
+        format!("{}█", "x".repeat(59))
+    );
+
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('K')]);
+    assert_eq!(bar(&terminal), "││││││││██");
+
+    ex_command(&mut editor, &terminal, "set noscrollbar");
+    assert_eq!(terminal.last_rendered_rows()[9], "line 39");
+}
+
+#[test]
+fn short_documents_get_a_full_scrollbar() {
+    let (mut editor, terminal) = start(Default::default());
+    ex_command(&mut editor, &terminal, "set scrollbar");
+
+    let rows = terminal.last_rendered_rows();
+    assert!(rows[..10].iter().all(|row| row.ends_with('█')));
+}
@@ -0,0 +1,277 @@
+mod common;
+
+use common::{block_on, ex_command, message_bar, press, start, Fixture};
+use zen::config::Config;
+use zen::document::{Document, Selection};
+use zen::editor::{Position, SearchDirection};
+use zen::row::Row;
+use zen::search::Pattern;
+use zen::terminal::{ansi, KeyEvent};
+
+#[test]
+fn whole_word_patterns_skip_longer_words() {
+    let row = Row::from("foo foobar _foo foo.");
+
+    assert_eq!(row.find_all("foo"), [0, 4, 12, 16]);
+    assert_eq!(row.find_all("\\<foo\\>"), [0, 16]);
+    assert_eq!(row.find("\\<foo\\>", 1, SearchDirection::Forward), Some(16));
+    assert_eq!(
+        row.find("\\<foo\\>", 16, SearchDirection::Backward),
+        Some(0)
+    );
+    assert_eq!(row.find("\\<foo\\>", 0, SearchDirection::Backward), None);
+}
+
+#[test]
+fn word_at_picks_the_word_under_or_after_the_cursor() {
+    let row = Row::from("  let x_1 = y;");
+
+    assert_eq!(row.word_at(0), Some("let"));
+    assert_eq!(row.word_at(3), Some("let"));
+    assert_eq!(row.word_at(5), Some("x_1"));
+    assert_eq!(row.word_at(8), Some("x_1"));
+    assert_eq!(row.word_at(13), None);
+}
+
+#[test]
+fn star_searches_the_word_under_the_cursor_and_n_repeats_it() {
+    let fixture = Fixture::new("star.txt", "foo bar\nfoobar foo\nbaz\nfoo\n");
+    let (mut editor, terminal) = start(fixture.options());
+
+    press(&mut editor, &terminal, [KeyEvent::Char('*')]);
+    assert_eq!((editor.cursor_position.x, editor.cursor_position.y), (7, 1));
+
+    press(&mut editor, &terminal, [KeyEvent::Char('n')]);
+    assert_eq!((editor.cursor_position.x, editor.cursor_position.y), (0, 3));
+
+    press(&mut editor, &terminal, [KeyEvent::Char('n')]);
+    assert_eq!((editor.cursor_position.x, editor.cursor_position.y), (0, 0));
+    assert_eq!(
+        message_bar(&terminal),
+        "search hit BOTTOM, continuing at TOP"
+    );
+
+    press(&mut editor, &terminal, [KeyEvent::Char('N')]);
+    assert_eq!((editor.cursor_position.x, editor.cursor_position.y), (0, 3));
+}
+
+#[test]
+fn hash_searches_backward() {
+    let fixture = Fixture::new("hash.txt", "bar\nfoo bar\nbar\n");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.cursor_position.y = 2;
+
+    press(&mut editor, &terminal, [KeyEvent::Char('#')]);
+    assert_eq!((editor.cursor_position.x, editor.cursor_position.y), (4, 1));
+
+    // `n` keeps going the way `#` went, `N` turns around.
+    press(&mut editor, &terminal, [KeyEvent::Char('n')]);
+    assert_eq!((editor.cursor_position.x, editor.cursor_position.y), (0, 0));
+    press(&mut editor, &terminal, [KeyEvent::Char('N')]);
+    assert_eq!((editor.cursor_position.x, editor.cursor_position.y), (4, 1));
+}
+
+#[test]
+fn matches_in_view_are_highlighted_until_noh() {
+    let fixture = Fixture::new("highlight.txt", "one two\ntwo one\n");
+    let (mut editor, terminal) = start(fixture.options());
+
+    terminal.clear_output();
+    press(&mut editor, &terminal, [KeyEvent::Char('*')]);
+    let output = terminal.output().concat();
+    assert!(output.contains(&format!("{}one{}", ansi::REVERSE, ansi::REVERSE_RESET)));
+    assert_eq!(terminal.last_rendered_rows()[1], "two one");
+
+    terminal.clear_output();
+    ex_command(&mut editor, &terminal, "noh");
+    assert!(!terminal.output().concat().contains(ansi::REVERSE));
+}
+
+#[test]
+fn ctrl_l_clears_the_highlight_and_redraws_every_line() {
+    let fixture = Fixture::new("ctrl_l.txt", "one two\ntwo one\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('*')]);
+
+    terminal.clear_output();
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('l')]);
+    let output = terminal.output().concat();
+    assert!(!output.contains(ansi::REVERSE));
+    assert!(output.contains("two one"));
+
+    // `n` highlights the matches again.
+    terminal.clear_output();
+    press(&mut editor, &terminal, [KeyEvent::Char('n')]);
+    assert!(terminal.output().concat().contains(ansi::REVERSE));
+}
+
+#[test]
+fn nohlsearch_option_turns_highlighting_off_for_good() {
+    let fixture = Fixture::new("hls.txt", "one two\ntwo one\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "set nohlsearch");
+
+    terminal.clear_output();
+    press(&mut editor, &terminal, [KeyEvent::Char('*')]);
+    assert!(!terminal.output().concat().contains(ansi::REVERSE));
+    assert_eq!(editor.cursor_position, Position { x: 4, y: 1 });
+
+    ex_command(&mut editor, &terminal, "set hls");
+    assert!(terminal.output().concat().contains(ansi::REVERSE));
+}
+
+#[test]
+fn find_with_wrap_goes_on_from_the_other_end() {
+    let fixture = Fixture::new("wrap.txt", "foo\nbar\nfoo bar\n");
+    let document = block_on(Document::open(fixture.path())).unwrap();
+    let at = |x, y| Position { x, y };
+
+    assert_eq!(
+        document.find_with_wrap("bar", &at(0, 1), SearchDirection::Forward, None),
+        (Some(at(0, 1)), false)
+    );
+    assert_eq!(
+        document.find_with_wrap("foo", &at(1, 2), SearchDirection::Forward, None),
+        (Some(at(0, 0)), true)
+    );
+    assert_eq!(
+        document.find_with_wrap("bar", &at(0, 0), SearchDirection::Backward, None),
+        (Some(at(4, 2)), true)
+    );
+    assert_eq!(
+        document.find_with_wrap("baz", &at(0, 0), SearchDirection::Forward, None),
+        (None, false)
+    );
+}
+
+#[test]
+fn bounded_searches_only_find_matches_inside_the_bounds() {
+    let fixture = Fixture::new("bounds.txt", "foo\nfoo bar foo\nfoo\nfoo\n");
+    let document = block_on(Document::open(fixture.path())).unwrap();
+    let at = |x, y| Position { x, y };
+    let bounds = Selection {
+        start: at(4, 1),
+        end: at(0, 3),
+    };
+    let find = |x, y, direction| document.find("foo", &at(x, y), direction, Some(&bounds));
+
+    // Searches from before the bounds start at them, and matches after them aren't found.
+    assert_eq!(find(0, 0, SearchDirection::Forward), Some(at(8, 1)));
+    assert_eq!(find(9, 1, SearchDirection::Forward), Some(at(0, 2)));
+    assert_eq!(find(1, 2, SearchDirection::Forward), None);
+    assert_eq!(find(0, 3, SearchDirection::Backward), Some(at(0, 2)));
+    assert_eq!(find(8, 1, SearchDirection::Backward), None);
+    assert_eq!(find(0, 0, SearchDirection::Backward), None);
+}
+
+#[test]
+fn bounded_searches_wrap_around_within_the_bounds() {
+    let fixture = Fixture::new("bounds_wrap.txt", "foo\nfoo bar foo\nfoo\nfoo\n");
+    let document = block_on(Document::open(fixture.path())).unwrap();
+    let at = |x, y| Position { x, y };
+    let bounds = Selection {
+        start: at(4, 1),
+        end: at(0, 3),
+    };
+
+    assert_eq!(
+        document.find_with_wrap("foo", &at(1, 2), SearchDirection::Forward, Some(&bounds)),
+        (Some(at(8, 1)), true)
+    );
+    assert_eq!(
+        document.find_with_wrap("foo", &at(8, 1), SearchDirection::Backward, Some(&bounds)),
+        (Some(at(0, 2)), true)
+    );
+    // Searching backward from before the bounds wraps to their end too.
+    assert_eq!(
+        document.find_with_wrap("foo", &at(0, 0), SearchDirection::Backward, Some(&bounds)),
+        (Some(at(0, 2)), true)
+    );
+    assert_eq!(
+        document.find_with_wrap("bar", &at(0, 2), SearchDirection::Forward, Some(&bounds)),
+        (Some(at(4, 1)), true)
+    );
+
+    // Bounds taking in the last row whole end past it.
+    let rows = Selection::rows(2..4);
+    assert_eq!(
+        document.find_with_wrap("foo", &at(0, 2), SearchDirection::Backward, Some(&rows)),
+        (Some(at(0, 3)), true)
+    );
+    assert_eq!(
+        document.find_with_wrap("bar", &at(0, 2), SearchDirection::Forward, Some(&rows)),
+        (None, false)
+    );
+}
+
+#[test]
+fn searches_stop_at_the_end_without_wrap_search() {
+    let fixture = Fixture::new("nowrap.txt", "foo\nfoo\n");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.set_config(Config::parse("wrap_search = false\n").unwrap());
+
+    press(&mut editor, &terminal, [KeyEvent::Char('*')]);
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 1 });
+    press(&mut editor, &terminal, [KeyEvent::Char('n')]);
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 1 });
+    assert_eq!(
+        message_bar(&terminal),
+        "ERR: search hit BOTTOM without match for: \\<foo\\>"
+    );
+}
+
+#[test]
+fn n_without_a_search_says_so() {
+    let fixture = Fixture::new("none.txt", "text\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('n')]);
+
+    assert_eq!(message_bar(&terminal), "ERR: No previous search");
+}
+
+#[test]
+fn find_all_returns_every_match_in_document_order() {
+    let fixture = Fixture::new("find_all.txt", "Foo foo\nbar\nfoofoo FOO\n");
+    let document = block_on(Document::open(fixture.path())).unwrap();
+    let positions = |matches: Vec<Position>| -> Vec<(usize, usize)> {
+        matches
+            .into_iter()
+            .map(|Position { x, y }| (x, y))
+            .collect()
+    };
+
+    assert_eq!(
+        positions(document.find_all("foo", true)),
+        [(4, 0), (0, 2), (3, 2)]
+    );
+    assert_eq!(
+        positions(document.find_all("foo", false)),
+        [(0, 0), (4, 0), (0, 2), (3, 2), (7, 2)]
+    );
+    assert_eq!(
+        positions(document.find_all("\\<FOO\\>", false)),
+        [(0, 0), (4, 0), (7, 2)]
+    );
+    assert_eq!(
+        positions(document.find_all_in_range("foo", 1..3, false)),
+        [(0, 2), (3, 2), (7, 2)]
+    );
+}
+
+#[test]
+fn caseless_matches_survive_case_changes_of_different_lengths() {
+    // 'ẞ' is three bytes, its lowercase 'ß' two.
+    let row = Row::from("aẞc ßC");
+
+    assert_eq!(row.find_all("ßC"), [4]);
+    assert_eq!(
+        row.find_matches(&Pattern::parse("ßc").ignoring_case()),
+        [1, 4]
+    );
+    // The ranges are the matches' own, in graphemes.
+    assert_eq!(
+        row.find_match_ranges(&Pattern::parse("ßc").ignoring_case()),
+        [1..3, 4..6]
+    );
+    assert_eq!(row.find_match_ranges(&Pattern::parse("ẞc ß")), [1..5]);
+}
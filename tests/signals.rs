@@ -0,0 +1,70 @@
+mod common;
+
+use common::{block_on, ex_command, message_bar, press, start, status_bar, Fixture};
+use std::fs;
+use std::path::Path;
+use zen::event::ProcessSignal;
+use zen::recovery;
+use zen::terminal::KeyEvent;
+
+#[test]
+fn ctrl_c_leaves_insert_mode_and_says_how_to_quit() {
+    let fixture = Fixture::new("interrupt.txt", "text\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Ctrl('c')],
+    );
+    assert!(status_bar(&terminal).starts_with("-- NORMAL --"));
+
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('c')]);
+    assert_eq!(message_bar(&terminal), "Type :q and press Enter to quit");
+    assert_eq!(editor.document.contents(), "text\n");
+}
+
+#[test]
+fn an_interrupt_from_elsewhere_is_taken_as_ctrl_c_and_can_be_mapped() {
+    let fixture = Fixture::new("interrupt_mapped.txt", "some text\n");
+    let (mut editor, terminal) = start(fixture.options());
+    block_on(editor.handle_signal(ProcessSignal::Interrupt)).unwrap();
+    editor.refresh_screen().unwrap();
+    assert_eq!(message_bar(&terminal), "Type :q and press Enter to quit");
+
+    ex_command(&mut editor, &terminal, "nmap <C-c> L");
+    block_on(editor.handle_signal(ProcessSignal::Interrupt)).unwrap();
+    assert_eq!(editor.cursor_position.x, 8);
+}
+
+#[test]
+fn terminating_keeps_unsaved_changes_in_recovery_files() {
+    let fixture = Fixture::new("terminate.txt", "saved\n");
+    let dir = Path::new(fixture.path()).with_file_name("recovery");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.set_recovery_dir(dir.clone());
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Char('i'), KeyEvent::Char('x')],
+    );
+
+    block_on(editor.handle_signal(ProcessSignal::Terminate)).unwrap();
+    // The editor quits at once, without asking about the changes, and keeps them.
+    block_on(editor.run()).unwrap();
+
+    let recovery = recovery::path_for(&dir, fixture.path());
+    assert_eq!(fs::read_to_string(&recovery).unwrap(), "xsaved\n");
+    assert_eq!(fs::read_to_string(fixture.path()).unwrap(), "saved\n");
+}
+
+#[test]
+fn documents_without_changes_get_no_recovery_file() {
+    let fixture = Fixture::new("terminate_clean.txt", "saved\n");
+    let dir = Path::new(fixture.path()).with_file_name("recovery");
+    let (mut editor, _terminal) = start(fixture.options());
+    editor.set_recovery_dir(dir.clone());
+    block_on(editor.handle_signal(ProcessSignal::Terminate)).unwrap();
+    block_on(editor.run()).unwrap();
+
+    assert!(!recovery::path_for(&dir, fixture.path()).exists());
+}
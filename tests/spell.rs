@@ -0,0 +1,134 @@
+mod common;
+
+use common::{block_on, ex_command, message_bar, press, start, type_text, Fixture};
+use zen::editor::Position;
+use zen::spell::{self, Dictionary};
+use zen::terminal::{ansi, KeyEvent};
+
+fn dictionary(words: &str) -> Dictionary {
+    let mut dictionary = Dictionary::default();
+    dictionary.add_words(words);
+    dictionary
+}
+
+#[test]
+fn words_are_checked_capitalized_and_possessive() {
+    let dictionary = dictionary("# Comments and blank lines are skipped\n\nthe\nwriter\nParis\n");
+    assert_eq!(dictionary.len(), 3);
+
+    for word in ["the", "The", "writer's", "Paris"] {
+        assert!(dictionary.contains(word), "{}", word);
+    }
+    for word in ["teh", "paris", "writers"] {
+        assert!(!dictionary.contains(word), "{}", word);
+    }
+    assert!(Dictionary::bundled().contains("don't"));
+}
+
+#[test]
+fn code_links_and_capitals_are_left_alone() {
+    let dictionary = dictionary("the\nand\nis\nsee\nnot\n");
+    let text = "teh camelCase and snake_case v2 HTML see https://exampel.com `codez` nto";
+    let found: Vec<&str> = spell::misspelled(text, &dictionary)
+        .into_iter()
+        .map(|range| &text[range])
+        .collect();
+    assert_eq!(found, ["teh", "nto"]);
+
+    // Ranges count graphemes, not bytes.
+    let ranges = spell::misspelled("é teh", &dictionary);
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(ranges.first(), Some(&(2..5)));
+}
+
+#[test]
+fn suggestions_are_the_closest_words_first() {
+    let dictionary = dictionary("word\nwords\nworld\nsword\nwood\ncat\n");
+    assert_eq!(
+        dictionary.suggest("wrod"),
+        ["word", "wood", "words", "world", "sword"]
+    );
+    assert_eq!(dictionary.suggest("Wrod")[0], "Word");
+    assert!(dictionary.suggest("xylophone").is_empty());
+}
+
+#[test]
+fn only_prose_is_checked() {
+    assert!(spell::is_prose("md"));
+    assert!(spell::is_prose("txt"));
+    assert!(!spell::is_prose("rs"));
+}
+
+#[test]
+fn misspelled_words_are_underlined_with_set_spell() {
+    let fixture = Fixture::new("notes.md", "the wrod is here\n");
+    let (mut editor, terminal) = start(fixture.options());
+    let underlined = format!("{}wrod{}", ansi::UNDERLINE, ansi::UNDERLINE_RESET);
+    assert!(!terminal.output().concat().contains(&underlined));
+
+    terminal.clear_output();
+    ex_command(&mut editor, &terminal, "set spell");
+    assert!(terminal.output().concat().contains(&underlined));
+    assert_eq!(terminal.last_rendered_rows()[0], "the wrod is here");
+
+    // Fixing the word takes the underline away.
+    terminal.clear_output();
+    ex_command(&mut editor, &terminal, "s/wrod/word/");
+    assert_eq!(editor.document.row(0).unwrap().string, "the word is here");
+    assert!(!terminal.output().concat().contains(ansi::UNDERLINE));
+}
+
+#[test]
+fn brackets_s_jump_between_misspellings() {
+    let fixture = Fixture::new("jump.txt", "teh one\nfine\nthe nto wrod\n");
+    let (mut editor, terminal) = start(fixture.options());
+
+    type_text(&mut editor, &terminal, "]s");
+    assert_eq!(
+        message_bar(&terminal),
+        "ERR: Spell checking is off, turn it on with :set spell"
+    );
+
+    ex_command(&mut editor, &terminal, "set spell");
+    type_text(&mut editor, &terminal, "]s");
+    assert_eq!(editor.cursor_position, Position { x: 4, y: 2 });
+    type_text(&mut editor, &terminal, "]s");
+    assert_eq!(editor.cursor_position, Position { x: 8, y: 2 });
+    type_text(&mut editor, &terminal, "]s");
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 0 });
+    assert_eq!(
+        message_bar(&terminal),
+        "search hit BOTTOM, continuing at TOP"
+    );
+    type_text(&mut editor, &terminal, "[s");
+    assert_eq!(editor.cursor_position, Position { x: 8, y: 2 });
+}
+
+#[test]
+fn z_equals_replaces_the_word_with_the_picked_suggestion() {
+    let fixture = Fixture::new("fix.txt", "a wrod here\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "set spell");
+    editor.cursor_position = Position { x: 3, y: 0 };
+
+    // Any key other than a suggestion's number keeps the word.
+    type_text(&mut editor, &terminal, "z");
+    terminal.push_keys([KeyEvent::Char('='), KeyEvent::Esc]);
+    block_on(editor.process_keypress()).unwrap();
+    assert!(terminal
+        .last_rendered_rows()
+        .iter()
+        .any(|row| row.contains("Change \"wrod\" to:")));
+    editor.refresh_screen().unwrap();
+    assert_eq!(editor.document.row(0).unwrap().string, "a wrod here");
+
+    type_text(&mut editor, &terminal, "z");
+    terminal.push_keys([KeyEvent::Char('='), KeyEvent::Char('1')]);
+    block_on(editor.process_keypress()).unwrap();
+    editor.refresh_screen().unwrap();
+    assert_eq!(editor.document.row(0).unwrap().string, "a word here");
+    assert_eq!(editor.cursor_position, Position { x: 2, y: 0 });
+
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.row(0).unwrap().string, "a wrod here");
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{press, start, Fixture};
+use zen::terminal::{ansi, KeyEvent};
+
+#[test]
+fn ctrl_z_suspends_and_redraws_everything_once_back() {
+    let fixture = Fixture::new("suspend.txt", "first\nsecond\n");
+    let (mut editor, terminal) = start(fixture.options());
+    terminal.clear_output();
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('z')]);
+
+    assert_eq!(terminal.suspensions(), 1);
+    // Rows that didn't change are written again, since the shell may have drawn over them.
+    let output = terminal.output().concat();
+    assert!(output.contains("first"));
+    assert!(output.contains("second"));
+}
+
+#[test]
+fn resuming_sets_the_terminal_up_again_at_its_new_size() {
+    let contents: String = (1..=30).map(|n| format!("line {}\n", n)).collect();
+    let fixture = Fixture::new("resume.txt", &contents);
+    let (mut editor, terminal) = start(fixture.options());
+    editor.goto_line(9);
+    editor.refresh_screen().unwrap();
+    terminal.clear_output();
+
+    // Stopped from elsewhere and continued in a smaller window.
+    terminal.resize_on_resume(60, 6);
+    editor.resume().unwrap();
+    editor.refresh_screen().unwrap();
+
+    let rows = terminal.last_rendered_rows();
+    assert_eq!(rows.len(), 6);
+    assert_eq!(rows[3], "line 10");
+    assert!(rows[4].contains("10/30"));
+    // The cursor shape is set again, in case the shell changed it.
+    assert!(terminal.output().concat().contains(ansi::CURSOR_BLOCK));
+}
+
+#[test]
+fn ctrl_z_picks_up_a_resize_while_suspended() {
+    let fixture = Fixture::new("suspend_resize.txt", "first\nsecond\n");
+    let (mut editor, terminal) = start(fixture.options());
+    terminal.resize_on_resume(40, 8);
+    press(&mut editor, &terminal, [KeyEvent::Ctrl('z')]);
+
+    let rows = terminal.last_rendered_rows();
+    assert_eq!(rows.len(), 8);
+    assert_eq!(rows[1], "second");
+    assert!(rows[6].contains("2 lines"));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, type_text, Fixture};
+use zen::commands::ex::{self, ExCommand};
+use zen::terminal::KeyEvent;
+
+#[test]
+fn tabnew_opens_the_file_in_a_tab_shown_at_the_top() {
+    let first = Fixture::new("first.txt", "one\n");
+    let second = Fixture::new("second.txt", "two\n");
+    let (mut editor, terminal) = start(first.options());
+    ex_command(&mut editor, &terminal, &format!("tabnew {}", second.path()));
+
+    assert_eq!(editor.document.contents(), "two\n");
+    let rows = terminal.last_rendered_rows();
+    assert!(rows[0].starts_with(" 1 first.txt  2 second.txt "));
+    // The text area starts below the tab bar.
+    assert_eq!(rows[1].trim_end(), "two");
+}
+
+#[test]
+fn gt_and_shifted_gt_cycle_through_the_tabs_keeping_each_cursor() {
+    let fixture = Fixture::new("cycle.txt", "abc\n");
+    let (mut editor, terminal) = start(fixture.options());
+    press(&mut editor, &terminal, [KeyEvent::Char('L')]);
+    ex_command(&mut editor, &terminal, "tabnew");
+    ex_command(&mut editor, &terminal, "tabnew");
+    assert_eq!(editor.cursor_position.x, 0);
+
+    type_text(&mut editor, &terminal, "gt");
+    assert_eq!(editor.document.contents(), "abc\n");
+    assert_eq!(editor.cursor_position.x, 2);
+
+    type_text(&mut editor, &terminal, "gT");
+    assert!(editor.document.is_empty());
+    ex_command(&mut editor, &terminal, "tabprev");
+    ex_command(&mut editor, &terminal, "tabprev");
+    assert_eq!(editor.document.contents(), "abc\n");
+}
+
+#[test]
+fn dirty_tabs_are_only_closed_with_a_bang() {
+    let fixture = Fixture::new("close.txt", "abc\n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "tabc");
+    assert_eq!(message_bar(&terminal), "ERR: Cannot close last tab");
+
+    ex_command(&mut editor, &terminal, "tabnew");
+    type_text(&mut editor, &terminal, "ix");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    ex_command(&mut editor, &terminal, "tabclose");
+    assert_eq!(editor.document.contents(), "x\n");
+
+    ex_command(&mut editor, &terminal, "q!");
+    assert_eq!(editor.document.contents(), "abc\n");
+    // Back to a single tab, the text area takes the top line again.
+    assert_eq!(terminal.last_rendered_rows()[0].trim_end(), "abc");
+}
+
+#[test]
+fn tab_commands_are_parsed() {
+    assert_eq!(
+        ex::parse("tabnew notes.md").unwrap(),
+        ExCommand::TabNew(Some("notes.md".to_string()))
+    );
+    assert_eq!(ex::parse("tabnew").unwrap(), ExCommand::TabNew(None));
+    assert_eq!(ex::parse("tabn").unwrap(), ExCommand::TabNext);
+    assert_eq!(ex::parse("tabprev").unwrap(), ExCommand::TabPrev);
+    assert_eq!(
+        ex::parse("tabc!").unwrap(),
+        ExCommand::TabClose { force: true }
+    );
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::{ex_command, message_bar, press, start, Fixture};
+use zen::row::{Row, Whitespace};
+use zen::terminal::{ansi, KeyEvent, MouseButton, MouseEvent};
+
+const LIST: Whitespace = Whitespace {
+    list: true,
+    trailing: false,
+    tab_width: 4,
+};
+const TRAILING: Whitespace = Whitespace {
+    list: false,
+    trailing: true,
+    tab_width: 4,
+};
+
+fn dim(symbol: &str) -> String {
+    format!("{}{}{}", ansi::DIM, symbol, ansi::DIM_RESET)
+}
+
+#[test]
+fn list_draws_tabs_trailing_and_non_breaking_spaces() {
+    let row = Row::from("\tx\u{a0}y z \t");
+    assert_eq!(
+        row.render_with(&[], LIST),
+        format!("{}x{}y z{}{}", dim("→   "), dim("␣"), dim("·"), dim("→ "))
+    );
+    // The string itself is untouched.
+    assert_eq!(row.string, "\tx\u{a0}y z \t");
+    // Without it, tabs are still drawn up to the next tab stop.
+    let plain = Whitespace {
+        tab_width: 4,
+        ..Whitespace::default()
+    };
+    assert_eq!(row.render_with(&[], plain), "    x\u{a0}y z   ");
+}
+
+#[test]
+fn trailing_only_draws_the_whitespace_at_the_end() {
+    let row = Row::from("\ta b  ");
+    assert_eq!(
+        row.render_with(&[], TRAILING),
+        format!("    a b{}{}", dim("·"), dim("·"))
+    );
+}
+
+#[test]
+fn columns_count_tabs_up_to_the_next_stop() {
+    let row = Row::from("a\tb\tc");
+    assert_eq!(row.visual_column(2, 4), 4);
+    assert_eq!(row.visual_column(4, 4), 8);
+    assert_eq!(row.visual_column(4, 8), 16);
+
+    assert_eq!(row.grapheme_at_visual_column(2, 4), 1);
+    assert_eq!(row.grapheme_at_visual_column(4, 4), 2);
+    assert_eq!(row.grapheme_at_visual_column(40, 4), 5);
+}
+
+#[test]
+fn set_list_redraws_rows_and_moves_the_cursor_with_them() {
+    let fixture = Fixture::new("list.txt", "\tindented  \n");
+    let (mut editor, terminal) = start(fixture.options());
+    ex_command(&mut editor, &terminal, "set list");
+    press(&mut editor, &terminal, [KeyEvent::Char('l')]);
+
+    assert_eq!(terminal.last_rendered_rows()[0], "→   indented··");
+    assert!(terminal.output().contains(&ansi::goto(5, 1)));
+
+    // Clicks land on the grapheme drawn there.
+    press(
+        &mut editor,
+        &terminal,
+        [KeyEvent::Mouse(MouseEvent {
+            button: MouseButton::Left,
+            x: 6,
+            y: 0,
+        })],
+    );
+    assert_eq!(editor.cursor_position.x, 3);
+
+    ex_command(&mut editor, &terminal, "set nolist");
+    assert_eq!(terminal.last_rendered_rows()[0], "    indented  ");
+    assert!(terminal.output().contains(&ansi::goto(5, 1)));
+    ex_command(&mut editor, &terminal, "set tabs");
+    assert_eq!(message_bar(&terminal), "ERR: Unknown option: tabs");
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{press, start, type_text, Fixture};
+use zen::editor::Position;
+use zen::motion::Motion;
+use zen::terminal::KeyEvent;
+
+#[test]
+fn words_and_big_words_end_at_different_places() {
+    let fixture = Fixture::new("words.rs", "foo.bar(baz) qux\n\n  end\n");
+    let (mut editor, terminal) = start(fixture.options());
+
+    type_text(&mut editor, &terminal, "w");
+    assert_eq!(editor.cursor_position, Position { x: 3, y: 0 });
+    type_text(&mut editor, &terminal, "W");
+    assert_eq!(editor.cursor_position, Position { x: 13, y: 0 });
+    // An empty row is a word to `w`, but not to `e`.
+    type_text(&mut editor, &terminal, "w");
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 1 });
+    type_text(&mut editor, &terminal, "e");
+    assert_eq!(editor.cursor_position, Position { x: 4, y: 2 });
+    type_text(&mut editor, &terminal, "b");
+    assert_eq!(editor.cursor_position, Position { x: 2, y: 2 });
+    type_text(&mut editor, &terminal, "bB");
+    assert_eq!(editor.cursor_position, Position { x: 13, y: 0 });
+    type_text(&mut editor, &terminal, "B");
+    assert_eq!(editor.cursor_position, Position { x: 0, y: 0 });
+    type_text(&mut editor, &terminal, "E");
+    assert_eq!(editor.cursor_position, Position { x: 11, y: 0 });
+}
+
+#[test]
+fn only_e_and_big_e_are_inclusive() {
+    for (key, inclusive) in [
+        ('w', false),
+        ('b', false),
+        ('e', true),
+        ('W', false),
+        ('B', false),
+        ('E', true),
+    ] {
+        assert_eq!(Motion::from_key(key).unwrap().is_inclusive(), inclusive);
+    }
+    assert_eq!(Motion::from_key('x'), None);
+}
+
+#[test]
+fn deletes_take_what_each_motion_covers() {
+    // Row, cursor column, keys, and the row left after.
+    let cases = [
+        ("foo.bar(baz) qux", 0, "dw", ".bar(baz) qux"),
+        ("foo.bar(baz) qux", 0, "dW", "qux"),
+        ("foo.bar(baz) qux", 0, "de", ".bar(baz) qux"),
+        ("foo.bar(baz) qux", 0, "dE", " qux"),
+        ("foo.bar(baz) qux", 3, "dw", "foobar(baz) qux"),
+        ("foo.bar(baz) qux", 5, "dW", "foo.bqux"),
+        ("foo.bar(baz) qux", 5, "de", "foo.b(baz) qux"),
+        ("foo.bar(baz) qux", 13, "db", "foo.bar(bazqux"),
+        ("foo.bar(baz) qux", 13, "dB", "qux"),
+        ("foo.bar(baz) qux", 6, "dB", "r(baz) qux"),
+        ("foo.bar(baz) qux", 6, "db", "foo.r(baz) qux"),
+        // On blanks, `w` only goes over them.
+        ("a   b", 1, "dw", "ab"),
+        ("a   b", 1, "de", "a"),
+    ];
+    for (row, x, keys, expected) in cases {
+        let fixture = Fixture::new("delete.txt", &format!("{}\n", row));
+        let (mut editor, terminal) = start(fixture.options());
+        editor.cursor_position = Position { x, y: 0 };
+        type_text(&mut editor, &terminal, keys);
+
+        assert_eq!(
+            editor.document.row(0).unwrap().string,
+            expected,
+            "{} at {} of {:?}",
+            keys,
+            x,
+            row
+        );
+    }
+}
+
+#[test]
+fn dw_on_the_last_word_of_a_row_keeps_the_line_break() {
+    let fixture = Fixture::new("last_word.txt", "one two\n  three\n");
+    let (mut editor, terminal) = start(fixture.options());
+    editor.cursor_position = Position { x: 4, y: 0 };
+    type_text(&mut editor, &terminal, "dW");
+
+    assert_eq!(editor.document.contents(), "one \n  three\n");
+    // The cursor steps back onto the row's last character.
+    assert_eq!(editor.cursor_position, Position { x: 3, y: 0 });
+
+    // `de` goes on to the end of the next word, joining the rows.
+    editor.cursor_position = Position { x: 3, y: 0 };
+    type_text(&mut editor, &terminal, "de");
+    assert_eq!(editor.document.contents(), "one\n");
+
+    // The delete is a single undo step.
+    press(&mut editor, &terminal, [KeyEvent::Char('u')]);
+    assert_eq!(editor.document.contents(), "one \n  three\n");
+}
+
+#[test]
+fn cw_changes_to_the_end_of_the_word() {
+    let fixture = Fixture::new("change.txt", "foo.bar baz\n");
+    let (mut editor, terminal) = start(fixture.options());
+    type_text(&mut editor, &terminal, "cWqux");
+    press(&mut editor, &terminal, [KeyEvent::Esc]);
+    assert_eq!(editor.document.contents(), "qux baz\n");
+
+    type_text(&mut editor, &terminal, "bcwx");
+    assert_eq!(editor.document.contents(), "x baz\n");
+}